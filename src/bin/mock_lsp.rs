@@ -0,0 +1,826 @@
+//! Minimal language server used by the LSP client tests.
+//!
+//! Besides `initialize`/`shutdown`/`exit` it understands a few `mock/*`
+//! methods that exercise the framing and dispatch paths of the client:
+//!
+//! - `mock/echo`: answers with its params
+//! - `mock/error`: answers with an error
+//! - `mock/notify`: sends a `mock/notification` with its params, then answers `null`
+//! - `mock/batch`: writes a notification and the response in a single write
+//! - `mock/split`: writes the response in small chunks with pauses in between
+//! - `mock/ask`: sends a `mock/question` request and answers with the client's reply
+//! - `mock/document`: answers with the text it has for `params.uri`, as
+//!   rebuilt from `didOpen`/`didChange`
+//! - `mock/slow`: only answers (with a cancellation error) once cancelled
+//! - `mock/cancelled`: answers with the ids of all cancelled requests
+//! - `mock/env`: answers with the environment variable `params.name` and the
+//!   working directory
+//! - `mock/crash`: exits with status 1 without answering
+//!
+//! `textDocument/completion` offers a fixed set of items for the word before
+//! the cursor (or fields after a `.` trigger). `textDocument/definition`
+//! points at the first `fn`/`let` naming the word under the cursor and
+//! `textDocument/references` at every occurrence of it. `textDocument/hover`
+//! shows the word as a code block and `textDocument/signatureHelp` a fixed
+//! two-parameter signature for any open call. `textDocument/rename` renames
+//! the word in every open document. `textDocument/codeAction` offers, for
+//! each diagnostic in the context, a preferred fix removing its range, an
+//! uppercase action whose edit comes from `codeAction/resolve`, and a
+//! `mock.insertTodo` command; `workspace/executeCommand` runs the command
+//! through a `workspace/applyEdit` request to the client and answers with
+//! the client's reply. `textDocument/formatting` and
+//! `textDocument/rangeFormatting` strip trailing whitespace from every line
+//! (of the range). `textDocument/inlayHint` shows `: i32` after the name of
+//! every `let` with a number in the range; `textDocument/semanticTokens/full`
+//! marks `fn`/`let` as keywords, the word after `fn` as a function and other
+//! words as variables, and `.../full/delta` answers with one edit to the
+//! previous result. `textDocument/documentSymbol` lists every `fn` as a
+//! function with the `let` bindings of its body as variables.
+//! `workspace/symbol` finds the `fn`s of all open documents whose name
+//! contains the query and, given a `partialResultToken`, streams them one
+//! `$/progress` notification at a time before answering with `[]`, the last
+//! one written together with the answer.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+#[path = "../core/plugins/lsp/transport.rs"]
+mod transport;
+
+#[allow(dead_code)]
+#[path = "../core/plugins/lsp/protocol.rs"]
+mod protocol;
+
+use protocol::{
+    Position, Range, offset_to_position, offsets_to_range, position_to_offset, range_to_offsets,
+};
+use transport::{FrameDecoder, encode};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_CANCELLED: i64 = -32800;
+const QUESTION_ID: &str = "mock-question";
+const APPLY_EDIT_ID: &str = "mock-apply-edit";
+
+fn main() {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    let mut decoder = FrameDecoder::new();
+    let mut chunk = [0u8; 4096];
+    // id of the `mock/ask` request waiting for the client's reply
+    let mut asking: Option<Value> = None;
+    // id of the `workspace/executeCommand` request waiting for `applyEdit`
+    let mut executing: Option<Value> = None;
+    let mut documents: HashMap<String, String> = HashMap::new();
+    // id of the `mock/slow` request waiting to be cancelled
+    let mut slow: Option<Value> = None;
+    let mut cancelled: Vec<Value> = Vec::new();
+    // last semantic token data sent per result id
+    let mut token_results: HashMap<String, Vec<u32>> = HashMap::new();
+
+    loop {
+        let n = match stdin.read(&mut chunk) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        decoder.extend(&chunk[..n]);
+
+        while let Ok(Some(message)) = decoder.next_message() {
+            let id = message.get("id").cloned();
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            let Some(method) = message["method"].as_str() else {
+                // reply to our own `mock/question`
+                if id == Some(json!(QUESTION_ID))
+                    && let Some(ask_id) = asking.take()
+                {
+                    let answer = message.get("result").cloned().unwrap_or(Value::Null);
+                    write(&mut stdout, &[response(ask_id, answer)]);
+                }
+                if id == Some(json!(APPLY_EDIT_ID))
+                    && let Some(execute_id) = executing.take()
+                {
+                    let applied = message["result"]["applied"].clone();
+                    write(&mut stdout, &[response(execute_id, applied)]);
+                }
+                continue;
+            };
+
+            let Some(id) = id else {
+                match method {
+                    "exit" => return,
+                    "$/cancelRequest" => {
+                        let cancelled_id = params["id"].clone();
+                        if slow.as_ref() == Some(&cancelled_id) {
+                            slow = None;
+                            write(
+                                &mut stdout,
+                                &[json!({
+                                    "jsonrpc": "2.0",
+                                    "id": cancelled_id,
+                                    "error": { "code": REQUEST_CANCELLED, "message": "cancelled" }
+                                })],
+                            );
+                        }
+                        cancelled.push(cancelled_id);
+                    }
+                    "textDocument/didOpen" => {
+                        let document = &params["textDocument"];
+                        documents.insert(
+                            document["uri"].as_str().unwrap_or_default().to_string(),
+                            document["text"].as_str().unwrap_or_default().to_string(),
+                        );
+                    }
+                    "textDocument/didChange" => {
+                        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                        if let Some(text) = documents.get_mut(uri) {
+                            for change in params["contentChanges"].as_array().into_iter().flatten()
+                            {
+                                apply_change(text, change);
+                            }
+                        }
+                    }
+                    "textDocument/didClose" => {
+                        documents
+                            .remove(params["textDocument"]["uri"].as_str().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+                continue;
+            };
+
+            match method {
+                "initialize" => write(
+                    &mut stdout,
+                    &[response(
+                        id,
+                        json!({
+                            "capabilities": {
+                                "textDocumentSync": { "openClose": true, "change": 2, "save": {} },
+                                "completionProvider": { "triggerCharacters": ["."] },
+                                "definitionProvider": true,
+                                "hoverProvider": true,
+                                "renameProvider": { "prepareProvider": true },
+                                "signatureHelpProvider": { "triggerCharacters": ["(", ","] },
+                                "referencesProvider": true,
+                                "codeActionProvider": { "resolveProvider": true },
+                                "documentFormattingProvider": true,
+                                "inlayHintProvider": true,
+                                "documentSymbolProvider": true,
+                                "workspaceSymbolProvider": true,
+                                "semanticTokensProvider": {
+                                    "legend": {
+                                        "tokenTypes": ["keyword", "function", "variable"],
+                                        "tokenModifiers": ["declaration"]
+                                    },
+                                    "full": { "delta": true }
+                                },
+                                "documentRangeFormattingProvider": true,
+                                "executeCommandProvider": { "commands": ["mock.insertTodo"] }
+                            },
+                            "serverInfo": { "name": "mock-lsp" }
+                        }),
+                    )],
+                ),
+                "shutdown" => write(&mut stdout, &[response(id, Value::Null)]),
+                "mock/echo" => write(&mut stdout, &[response(id, params)]),
+                "mock/document" => {
+                    let text = documents.get(params["uri"].as_str().unwrap_or_default());
+                    write(&mut stdout, &[response(id, json!(text))]);
+                }
+                "textDocument/completion" => {
+                    let text = documents
+                        .get(params["textDocument"]["uri"].as_str().unwrap_or_default())
+                        .map_or("", String::as_str);
+                    let position: Position = serde_json::from_value(params["position"].clone())
+                        .unwrap_or(Position {
+                            line: 0,
+                            character: 0,
+                        });
+                    let trigger = params["context"]["triggerCharacter"].as_str();
+                    write(
+                        &mut stdout,
+                        &[response(id, completions(text, position, trigger))],
+                    );
+                }
+                "textDocument/definition" | "textDocument/references" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let position: Position = serde_json::from_value(params["position"].clone())
+                        .unwrap_or(Position {
+                            line: 0,
+                            character: 0,
+                        });
+                    let result = if method == "textDocument/definition" {
+                        definition(uri, text, position)
+                    } else {
+                        references(uri, text, position)
+                    };
+                    write(&mut stdout, &[response(id, result)]);
+                }
+                "textDocument/hover" | "textDocument/signatureHelp" => {
+                    let text = documents
+                        .get(params["textDocument"]["uri"].as_str().unwrap_or_default())
+                        .map_or("", String::as_str);
+                    let position: Position = serde_json::from_value(params["position"].clone())
+                        .unwrap_or(Position {
+                            line: 0,
+                            character: 0,
+                        });
+                    let result = if method == "textDocument/hover" {
+                        hover(text, position)
+                    } else {
+                        signature_help(text, position)
+                    };
+                    write(&mut stdout, &[response(id, result)]);
+                }
+                "textDocument/prepareRename" | "textDocument/rename" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let position: Position = serde_json::from_value(params["position"].clone())
+                        .unwrap_or(Position {
+                            line: 0,
+                            character: 0,
+                        });
+                    let result = if method == "textDocument/prepareRename" {
+                        prepare_rename(text, position)
+                    } else {
+                        let new_name = params["newName"].as_str().unwrap_or_default();
+                        rename(&documents, text, position, new_name)
+                    };
+                    write(&mut stdout, &[response(id, result)]);
+                }
+                "textDocument/codeAction" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let result = code_actions(uri, text, &params["context"]["diagnostics"]);
+                    write(&mut stdout, &[response(id, result)]);
+                }
+                "textDocument/formatting" | "textDocument/rangeFormatting" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let lines = serde_json::from_value::<Range>(params["range"].clone())
+                        .map_or(0..u32::MAX, |range| range.start.line..range.end.line + 1);
+                    write(&mut stdout, &[response(id, trim_lines(text, lines))]);
+                }
+                "textDocument/inlayHint" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let range: Range = serde_json::from_value(params["range"].clone())
+                        .unwrap_or_else(|_| offsets_to_range(text, 0..text.len()));
+                    write(&mut stdout, &[response(id, inlay_hints(text, range))]);
+                }
+                "textDocument/documentSymbol" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    write(&mut stdout, &[response(id, document_symbols(text))]);
+                }
+                "workspace/symbol" => {
+                    let query = params["query"].as_str().unwrap_or_default();
+                    let mut uris: Vec<&String> = documents.keys().collect();
+                    uris.sort();
+                    let symbols: Vec<Value> = uris
+                        .into_iter()
+                        .flat_map(|uri| workspace_symbols(uri, &documents[uri], query))
+                        .collect();
+                    match params.get("partialResultToken") {
+                        Some(token) => {
+                            let mut messages: Vec<Value> = symbols
+                                .into_iter()
+                                .map(|symbol| {
+                                    let value = json!({ "token": token, "value": [symbol] });
+                                    notification("$/progress", value)
+                                })
+                                .collect();
+                            messages.push(response(id, json!([])));
+                            // the last notification goes out with the answer
+                            let together = messages.len().saturating_sub(2);
+                            for message in &messages[..together] {
+                                write(&mut stdout, std::slice::from_ref(message));
+                            }
+                            write(&mut stdout, &messages[together..]);
+                        }
+                        None => write(&mut stdout, &[response(id, Value::Array(symbols))]),
+                    }
+                }
+                "textDocument/semanticTokens/full" | "textDocument/semanticTokens/full/delta" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let data = semantic_tokens(text);
+                    let result_id = (token_results.len() + 1).to_string();
+                    let previous = params["previousResultId"]
+                        .as_str()
+                        .and_then(|id| token_results.get(id));
+                    let result = match previous {
+                        Some(previous) => {
+                            let prefix = previous
+                                .iter()
+                                .zip(&data)
+                                .take_while(|(a, b)| a == b)
+                                .count();
+                            json!({
+                                "resultId": result_id,
+                                "edits": [{
+                                    "start": prefix,
+                                    "deleteCount": previous.len() - prefix,
+                                    "data": data[prefix..]
+                                }]
+                            })
+                        }
+                        None => json!({ "resultId": result_id, "data": data }),
+                    };
+                    token_results.insert(result_id, data);
+                    write(&mut stdout, &[response(id, result)]);
+                }
+                "codeAction/resolve" => {
+                    let mut action = params;
+                    let uri = action["data"]["uri"].as_str().unwrap_or_default();
+                    let text = documents.get(uri).map_or("", String::as_str);
+                    let range: Range = serde_json::from_value(action["data"]["range"].clone())
+                        .unwrap_or_else(|_| offsets_to_range(text, 0..0));
+                    let word = text
+                        .get(range_to_offsets(text, range))
+                        .unwrap_or_default()
+                        .to_uppercase();
+                    action["edit"] = json!({
+                        "changes": { uri: [{ "range": range, "newText": word }] }
+                    });
+                    write(&mut stdout, &[response(id, action)]);
+                }
+                "workspace/executeCommand" => {
+                    let uri = params["arguments"][0].as_str().unwrap_or_default();
+                    let start = offsets_to_range("", 0..0);
+                    executing = Some(id);
+                    write(
+                        &mut stdout,
+                        &[json!({
+                            "jsonrpc": "2.0",
+                            "id": APPLY_EDIT_ID,
+                            "method": "workspace/applyEdit",
+                            "params": {
+                                "label": "Insert TODO",
+                                "edit": {
+                                    "changes": { uri: [{ "range": start, "newText": "// TODO\n" }] }
+                                }
+                            }
+                        })],
+                    );
+                }
+                "mock/slow" => slow = Some(id),
+                "mock/env" => {
+                    let value = std::env::var(params["name"].as_str().unwrap_or_default()).ok();
+                    let cwd = std::env::current_dir().ok();
+                    write(
+                        &mut stdout,
+                        &[response(id, json!({ "value": value, "cwd": cwd }))],
+                    );
+                }
+                "mock/crash" => std::process::exit(1),
+                "mock/cancelled" => write(&mut stdout, &[response(id, json!(cancelled))]),
+                "mock/error" => write(
+                    &mut stdout,
+                    &[json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": 1, "message": "mock failure" }
+                    })],
+                ),
+                "mock/notify" => {
+                    write(&mut stdout, &[notification("mock/notification", params)]);
+                    write(&mut stdout, &[response(id, Value::Null)]);
+                }
+                "mock/batch" => write(
+                    &mut stdout,
+                    &[
+                        notification("mock/notification", params.clone()),
+                        response(id, params),
+                    ],
+                ),
+                "mock/split" => {
+                    for piece in encode(&response(id, params)).chunks(7) {
+                        let _ = stdout.write_all(piece);
+                        let _ = stdout.flush();
+                        thread::sleep(Duration::from_millis(2));
+                    }
+                }
+                "mock/ask" => {
+                    asking = Some(id);
+                    write(
+                        &mut stdout,
+                        &[json!({
+                            "jsonrpc": "2.0",
+                            "id": QUESTION_ID,
+                            "method": "mock/question",
+                            "params": params
+                        })],
+                    );
+                }
+                _ => write(
+                    &mut stdout,
+                    &[json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": METHOD_NOT_FOUND, "message": format!("unknown method {}", method) }
+                    })],
+                ),
+            }
+        }
+    }
+}
+
+fn apply_change(text: &mut String, change: &Value) {
+    let new_text = change["text"].as_str().unwrap_or_default();
+    match serde_json::from_value::<Range>(change["range"].clone()) {
+        Ok(range) => {
+            let offsets = range_to_offsets(text, range);
+            text.replace_range(offsets, new_text);
+        }
+        Err(_) => *text = new_text.to_string(),
+    }
+}
+
+fn completions(text: &str, position: Position, trigger: Option<&str>) -> Value {
+    if trigger == Some(".") {
+        return json!([{ "label": "len", "kind": 2, "insertText": "len()" }]);
+    }
+
+    let cursor = position_to_offset(text, position);
+    let word_start = text[..cursor]
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let word = offsets_to_range(text, word_start..cursor);
+    let top = offsets_to_range(text, 0..0);
+
+    json!({
+        "isIncomplete": false,
+        "items": [
+            {
+                "label": "println!",
+                "kind": 3,
+                "detail": "macro_rules! println",
+                "documentation": { "kind": "markdown", "value": "Prints to the standard output, with a newline." },
+                "insertTextFormat": 2,
+                "textEdit": { "range": word, "newText": "println!(\"${1:text}\")$0" }
+            },
+            { "label": "print!", "kind": 3, "insertText": "print!" },
+            {
+                "label": "HashMap",
+                "kind": 22,
+                "detail": "std::collections::HashMap",
+                "textEdit": { "range": word, "newText": "HashMap" },
+                "additionalTextEdits": [
+                    { "range": top, "newText": "use std::collections::HashMap;\n" }
+                ]
+            }
+        ]
+    })
+}
+
+/// The word after the whitespace following `offset`.
+fn word_after(text: &str, offset: usize) -> std::ops::Range<usize> {
+    let rest = &text[offset..];
+    let start = offset + rest.len() - rest.trim_start().len();
+    let len = text[start..]
+        .find(|c: char| !is_word_char(c))
+        .unwrap_or(text.len() - start);
+    start..start + len
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whole-word occurrences of the word under `position`.
+fn occurrences(text: &str, position: Position) -> Vec<std::ops::Range<usize>> {
+    let offset = position_to_offset(text, position);
+    let start = text[..offset]
+        .rfind(|c: char| !is_word_char(c))
+        .map_or(0, |i| i + 1);
+    let end = text[offset..]
+        .find(|c: char| !is_word_char(c))
+        .map_or(text.len(), |i| offset + i);
+    whole_words(text, &text[start..end])
+}
+
+fn whole_words(text: &str, word: &str) -> Vec<std::ops::Range<usize>> {
+    if word.is_empty() {
+        return vec![];
+    }
+    text.match_indices(word)
+        .map(|(i, _)| i..i + word.len())
+        .filter(|range| {
+            !text[..range.start].ends_with(is_word_char)
+                && !text[range.end..].starts_with(is_word_char)
+        })
+        .collect()
+}
+
+fn definition(uri: &str, text: &str, position: Position) -> Value {
+    let found = occurrences(text, position).into_iter().find(|range| {
+        let before = text[..range.start].trim_end();
+        before.ends_with("fn") || before.ends_with("let")
+    });
+    match found {
+        Some(range) => json!([{
+            "targetUri": uri,
+            "targetRange": offsets_to_range(text, 0..text.len()),
+            "targetSelectionRange": offsets_to_range(text, range),
+        }]),
+        None => Value::Null,
+    }
+}
+
+fn references(uri: &str, text: &str, position: Position) -> Value {
+    occurrences(text, position)
+        .into_iter()
+        .map(|range| json!({ "uri": uri, "range": offsets_to_range(text, range) }))
+        .collect()
+}
+
+fn prepare_rename(text: &str, position: Position) -> Value {
+    let offset = position_to_offset(text, position);
+    let under_cursor = occurrences(text, position)
+        .into_iter()
+        .find(|range| range.start <= offset && offset <= range.end);
+    match under_cursor {
+        // keywords can't be renamed
+        Some(range) if !matches!(&text[range.clone()], "fn" | "let") => {
+            json!({ "range": offsets_to_range(text, range.clone()), "placeholder": &text[range] })
+        }
+        _ => Value::Null,
+    }
+}
+
+/// Every whole-word occurrence of the word under `position`, in every open
+/// document.
+fn rename(
+    documents: &HashMap<String, String>,
+    text: &str,
+    position: Position,
+    new_name: &str,
+) -> Value {
+    let Some(range) = occurrences(text, position).into_iter().next() else {
+        return Value::Null;
+    };
+    let word = &text[range];
+
+    let changes: serde_json::Map<String, Value> = documents
+        .iter()
+        .filter_map(|(uri, text)| {
+            let edits: Vec<Value> = whole_words(text, word)
+                .into_iter()
+                .map(|range| json!({ "range": offsets_to_range(text, range), "newText": new_name }))
+                .collect();
+            (!edits.is_empty()).then(|| (uri.clone(), Value::Array(edits)))
+        })
+        .collect();
+    json!({ "changes": changes })
+}
+
+/// Per diagnostic: a preferred fix removing its range, an action to
+/// uppercase it that needs resolving, and a bare command.
+fn code_actions(uri: &str, text: &str, diagnostics: &Value) -> Value {
+    let mut actions = Vec::new();
+    for diagnostic in diagnostics.as_array().into_iter().flatten() {
+        let Ok(range) = serde_json::from_value::<Range>(diagnostic["range"].clone()) else {
+            continue;
+        };
+        let word = text.get(range_to_offsets(text, range)).unwrap_or_default();
+        actions.push(json!({
+            "title": format!("Uppercase `{}`", word),
+            "kind": "refactor.rewrite",
+            "data": { "uri": uri, "range": range }
+        }));
+        actions.push(json!({
+            "title": format!("Remove `{}`", word),
+            "kind": "quickfix",
+            "isPreferred": true,
+            "diagnostics": [diagnostic],
+            "edit": { "changes": { uri: [{ "range": range, "newText": "" }] } }
+        }));
+        actions.push(json!({
+            "title": "Insert TODO",
+            "command": "mock.insertTodo",
+            "arguments": [uri]
+        }));
+    }
+    Value::Array(actions)
+}
+
+/// Edits removing trailing whitespace from the lines in `lines`.
+fn trim_lines(text: &str, lines: std::ops::Range<u32>) -> Value {
+    let mut edits = Vec::new();
+    let mut start = 0;
+    for (line, content) in text.split('\n').enumerate() {
+        let trimmed = content.trim_end().len();
+        if lines.contains(&(line as u32)) && trimmed < content.len() {
+            let range = offsets_to_range(text, start + trimmed..start + content.len());
+            edits.push(json!({ "range": range, "newText": "" }));
+        }
+        start += content.len() + 1;
+    }
+    Value::Array(edits)
+}
+
+/// `: i32` after the name of each `let name = <digits>` starting in `range`.
+fn inlay_hints(text: &str, range: Range) -> Value {
+    let range = range_to_offsets(text, range);
+    let mut hints = Vec::new();
+    for start in whole_words(text, "let") {
+        let rest = &text[start.end..];
+        let name = rest.trim_start();
+        let name_len = name.find(|c: char| !is_word_char(c)).unwrap_or(name.len());
+        let after = name[name_len..].trim_start();
+        let is_number = after
+            .strip_prefix('=')
+            .is_some_and(|value| value.trim_start().starts_with(|c: char| c.is_ascii_digit()));
+        if range.contains(&start.start) && name_len > 0 && is_number {
+            let end = start.end + (rest.len() - name.len()) + name_len;
+            hints.push(json!({
+                "position": offset_to_position(text, end),
+                "label": [{ "value": ": " }, { "value": "i32" }],
+                "kind": 1
+            }));
+        }
+    }
+    Value::Array(hints)
+}
+
+/// A function symbol from each `fn` to the `}` closing its body, with a
+/// variable for each `let` in between.
+fn document_symbols(text: &str) -> Value {
+    let symbol = |kind: u32, name: std::ops::Range<usize>, range: std::ops::Range<usize>| {
+        json!({
+            "name": &text[name.clone()],
+            "kind": kind,
+            "range": offsets_to_range(text, range),
+            "selectionRange": offsets_to_range(text, name),
+        })
+    };
+
+    let mut symbols = Vec::new();
+    for keyword in whole_words(text, "fn") {
+        let name = word_after(text, keyword.end);
+        let Some(open) = text[name.end..].find('{').map(|i| name.end + i) else {
+            continue;
+        };
+        let mut depth = 0;
+        let mut end = text.len();
+        for (i, c) in text[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => continue,
+            }
+            if depth == 0 {
+                end = open + i + 1;
+                break;
+            }
+        }
+        let mut function = symbol(12, name, keyword.start..end);
+        function["children"] = whole_words(&text[..end], "let")
+            .into_iter()
+            .filter(|binding| binding.start > open)
+            .map(|binding| {
+                let name = word_after(text, binding.end);
+                let end = text[name.end..].find(';').map_or(end, |i| name.end + i + 1);
+                symbol(13, name, binding.start..end)
+            })
+            .collect();
+        symbols.push(function);
+    }
+    Value::Array(symbols)
+}
+
+/// `SymbolInformation` for each `fn` of `text` whose name contains `query`,
+/// ignoring case, contained in the file's stem.
+fn workspace_symbols(uri: &str, text: &str, query: &str) -> Vec<Value> {
+    let container = uri
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default();
+    whole_words(text, "fn")
+        .into_iter()
+        .filter_map(|keyword| {
+            let name = word_after(text, keyword.end);
+            let matches = text[name.clone()]
+                .to_lowercase()
+                .contains(&query.to_lowercase());
+            (!name.is_empty() && matches).then(|| {
+                json!({
+                    "name": &text[name.clone()],
+                    "kind": 12,
+                    "containerName": container,
+                    "location": { "uri": uri, "range": offsets_to_range(text, name) }
+                })
+            })
+        })
+        .collect()
+}
+
+/// Relative token data: `fn`/`let` are keywords (0), the word after `fn` a
+/// function (1) declared here, other words variables (2).
+fn semantic_tokens(text: &str) -> Vec<u32> {
+    let mut data = Vec::new();
+    let mut previous = Position {
+        line: 0,
+        character: 0,
+    };
+    let mut after_fn = false;
+    let mut word_start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if is_word_char(c) {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        let Some(start) = word_start.take() else {
+            continue;
+        };
+        let word = &text[start..i];
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        let (token_type, modifiers) = match word {
+            "fn" | "let" => (0, 0),
+            _ if after_fn => (1, 1),
+            _ => (2, 0),
+        };
+        after_fn = word == "fn";
+
+        let position = offset_to_position(text, start);
+        let delta_start = if position.line == previous.line {
+            position.character - previous.character
+        } else {
+            position.character
+        };
+        data.extend([
+            position.line - previous.line,
+            delta_start,
+            word.encode_utf16().count() as u32,
+            token_type,
+            modifiers,
+        ]);
+        previous = position;
+    }
+    data
+}
+
+fn hover(text: &str, position: Position) -> Value {
+    match occurrences(text, position).first() {
+        Some(range) => json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("```rust\nfn {}()\n```\n---\nA mock function.", &text[range.clone()])
+            }
+        }),
+        None => Value::Null,
+    }
+}
+
+/// `fn helper(a: i32, b: &str)` for the innermost unclosed `(` before the
+/// cursor, with the parameter the cursor is in active.
+fn signature_help(text: &str, position: Position) -> Value {
+    let cursor = position_to_offset(text, position);
+    let mut depth = 0;
+    let mut commas = 0;
+    for c in text[..cursor].chars().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => {
+                return json!({
+                    "signatures": [{
+                        "label": "fn helper(a: i32, b: &str)",
+                        "documentation": "Helps.",
+                        "parameters": [{ "label": [10, 16] }, { "label": "b: &str" }]
+                    }],
+                    "activeSignature": 0,
+                    "activeParameter": commas
+                });
+            }
+            '(' => depth -= 1,
+            ',' if depth == 0 => commas += 1,
+            '\n' => break,
+            _ => {}
+        }
+    }
+    Value::Null
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// Writes all messages with a single `write_all`.
+fn write(stdout: &mut impl Write, messages: &[Value]) {
+    let bytes: Vec<u8> = messages.iter().flat_map(encode).collect();
+    let _ = stdout.write_all(&bytes);
+    let _ = stdout.flush();
+}
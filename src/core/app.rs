@@ -5,6 +5,7 @@ use std::rc::Rc;
 use eframe::egui;
 
 use crate::core::enums::enums::{Hotkeys, UiAction};
+use crate::core::plugins::lsp::lsp_manager;
 use crate::core::stores::app_name_store::AppNameStore;
 use crate::core::stores::global_store::{global_store, GlobalStore};
 use crate::core::stores::icons::IconsInteractionsStore;
@@ -15,6 +16,7 @@ use crate::modules::editor::components::App;
 use crate::modules::editor::stores::hotkeys::HotkeysInteractionsStore;
 use crate::modules::editor::stores::{
     EditorInteractionsStore, FileActionsStore, FileInteractionsStore, ThemeInteractionsStore,
+    editor_interactions_store, file_finder_store, project_search_store,
 };
 
 pub struct MyApp {
@@ -40,6 +42,10 @@ impl MyApp {
     ) -> Self {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         let files = read_current_folder(&current_dir);
+        lsp_manager().set_root(current_dir.clone());
+        project_search_store().set_root(current_dir.clone());
+        editor_interactions_store().set_root(current_dir.clone());
+        file_finder_store().set_root(current_dir.clone());
 
         let file_actions = Rc::new(RefCell::new(FileActionsStore::new()));
         let file_interactions = Rc::new(RefCell::new(FileInteractionsStore::new()));
@@ -115,6 +121,71 @@ impl eframe::App for MyApp {
             editor_interactions_store().open_tab(ctx, path);
         }
 
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S)) {
+            editor_interactions_store().save_current_tab(ctx);
+        }
+
+        lsp_manager().poll(ctx);
+
+        // first, so the arrows and Enter reach the open overlay
+        use crate::modules::editor::stores::workspace_symbols_store;
+        workspace_symbols_store().handle_keys(ctx);
+        workspace_symbols_store().poll(ctx);
+        file_finder_store().handle_keys(ctx);
+        file_finder_store().poll(ctx);
+
+        // before completion, so Enter and Escape reach the rename box first
+        use crate::modules::editor::stores::rename_store;
+        rename_store().poll(ctx);
+        rename_store().handle_keys(ctx);
+
+        // before completion, so the arrows and Enter reach an open menu first
+        use crate::modules::editor::stores::code_actions_store;
+        code_actions_store().poll(ctx);
+        code_actions_store().handle_keys(ctx);
+
+        use crate::modules::editor::stores::completion_store;
+        completion_store().poll(ctx);
+        completion_store().handle_keys(ctx);
+
+        use crate::modules::editor::stores::formatting_store;
+        formatting_store().poll(ctx);
+        formatting_store().handle_keys(ctx);
+
+        use crate::modules::editor::stores::decorations_store;
+        decorations_store().poll(ctx);
+
+        use crate::modules::editor::stores::outline_store;
+        outline_store().poll(ctx);
+
+        // before the find bar, whose Cmd+F would take Cmd+Shift+F too
+        project_search_store().handle_keys(ctx);
+        project_search_store().poll(ctx);
+
+        // before the folds, so a match being selected is unfolded first
+        use crate::modules::editor::stores::find_store;
+        find_store().handle_keys(ctx);
+        find_store().poll(ctx);
+
+        use crate::modules::editor::stores::folding_store;
+        folding_store().poll(ctx);
+        folding_store().handle_keys(ctx);
+
+        use crate::modules::editor::stores::gutter_store;
+        gutter_store().poll(ctx);
+
+        use crate::modules::editor::stores::{hover_store, signature_help_store};
+        hover_store().poll(ctx);
+        signature_help_store().poll(ctx);
+        signature_help_store().handle_keys(ctx);
+
+        use crate::modules::editor::stores::navigation_store;
+        navigation_store().poll(ctx);
+        navigation_store().handle_keys(ctx);
+
+        // before the editor, whose own undo would take Cmd+Z
+        editor_interactions_store().handle_keys(ctx);
+
         use crate::modules::editor::stores::context::{set_all_stores, AppStores};
 
         let files_rc = Rc::new(RefCell::new(self.files.clone()));
@@ -133,4 +204,8 @@ impl eframe::App for MyApp {
 
         render_app(ctx, app);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        lsp_manager().shutdown();
+    }
 }
@@ -0,0 +1,75 @@
+use std::ops::Range;
+
+/// The folded regions of a text, as the byte ranges they hide. A range
+/// spans whole lines: from the start of the line after the one left
+/// visible to the start of the first line shown again.
+///
+/// Folds nest; unfolding the outer one of two leaves the inner one folded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Folds {
+    // sorted by start, the outer of two starting together first
+    ranges: Vec<Range<usize>>,
+}
+
+impl Folds {
+    pub fn iter(&self) -> impl Iterator<Item = &Range<usize>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, range: &Range<usize>) -> bool {
+        self.ranges.contains(range)
+    }
+
+    /// Folds `range`; returns whether it wasn't folded already.
+    pub fn add(&mut self, range: Range<usize>) -> bool {
+        if range.is_empty() || self.contains(&range) {
+            return false;
+        }
+        self.ranges.push(range);
+        self.ranges
+            .sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        true
+    }
+
+    /// Unfolds `range`; returns whether it was folded.
+    pub fn remove(&mut self, range: &Range<usize>) -> bool {
+        let len = self.ranges.len();
+        self.ranges.retain(|folded| folded != range);
+        self.ranges.len() != len
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// The text out of sight: the folds merged where they nest or touch.
+    pub fn hidden(&self) -> Vec<Range<usize>> {
+        let mut hidden: Vec<Range<usize>> = vec![];
+        for range in &self.ranges {
+            match hidden.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => hidden.push(range.clone()),
+            }
+        }
+        hidden
+    }
+
+    /// Follows the replacement of `old_len` bytes at `offset` with
+    /// `new_len` bytes. A fold the edit reaches into is unfolded: what it
+    /// hid is no longer what the user folded.
+    pub fn map(&mut self, offset: usize, old_len: usize, new_len: usize) {
+        let end = offset + old_len;
+        self.ranges.retain_mut(|range| {
+            if end < range.start {
+                *range = range.start - old_len + new_len..range.end - old_len + new_len;
+                true
+            } else {
+                offset >= range.end
+            }
+        });
+    }
+}
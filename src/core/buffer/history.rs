@@ -0,0 +1,220 @@
+use std::ops::Range;
+
+use crate::core::buffer::selection::{Selection, Selections};
+
+/// Undo steps kept per buffer; older ones are dropped.
+const MAX_UNDO: usize = 1000;
+
+/// One replacement: `old` at `offset` became `new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub offset: usize,
+    pub old: String,
+    pub new: String,
+}
+
+impl Change {
+    /// Where `new` sits once the change is applied.
+    pub fn new_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.new.len()
+    }
+
+    /// Where `old` sits before the change is applied.
+    pub fn old_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.old.len()
+    }
+}
+
+/// What one undo takes back.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    id: u64,
+    pub changes: Vec<Change>,
+    /// Selections to restore on undo.
+    pub selections_before: Selections,
+    /// Selections to restore on redo.
+    pub selections_after: Selections,
+}
+
+/// How the last transaction may still grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Growth {
+    Typing,
+    Deleting,
+    #[default]
+    Closed,
+}
+
+/// Where the last transaction left a cursor, and so where typing may
+/// continue it.
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    offset: usize,
+    // the char typed or deleted last was whitespace
+    space: bool,
+}
+
+/// Edits of a [`TextBuffer`](super::TextBuffer), grouped into undo steps.
+///
+/// Typing a character right after the previous one and deleting one next
+/// to the previous deletion grow the same step until a word ends (the first
+/// whitespace after a word starts a new one). With several cursors this
+/// holds at each of them, so typing at all cursors at once undoes the same
+/// way. Anything else, such as a paste or a line break, is a step of its
+/// own.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Transaction>,
+    redo: Vec<Transaction>,
+    growth: Growth,
+    points: Vec<Point>,
+    // inside `begin`..`end`: whether the group already has its transaction
+    group: Option<bool>,
+    // whether the group stays open for typing after `end`
+    group_growth: bool,
+    next_id: u64,
+}
+
+impl History {
+    /// Records `change`; `selections` are the ones before it.
+    pub fn record(&mut self, change: Change, selections: &Selections) {
+        self.redo.clear();
+        let growth = growth_of(&change);
+        let edited = if growth == Growth::Deleting {
+            &change.old
+        } else {
+            &change.new
+        };
+        // the point the change starts or ends at
+        let mut at = self.points.iter().position(|point| {
+            (change.offset..=change.offset + change.old.len()).contains(&point.offset)
+        });
+        let grows = at.is_some_and(|index| {
+            self.growth == growth
+                && growth != Growth::Closed
+                && !word_ends(self.points[index].space, edited)
+        });
+
+        let extend = match self.group {
+            Some(true) => true,
+            // a transaction starts a step of its own
+            Some(false) if !self.group_growth => false,
+            _ => grows,
+        };
+        if !extend {
+            self.next_id += 1;
+            self.undo.push(Transaction {
+                id: self.next_id,
+                changes: vec![],
+                selections_before: selections.clone(),
+                selections_after: selections.clone(),
+            });
+            if self.undo.len() > MAX_UNDO {
+                self.undo.remove(0);
+            }
+            if let Some(started) = &mut self.group {
+                *started = true;
+            }
+            self.points.clear();
+            at = None;
+        }
+
+        for point in &mut self.points {
+            if point.offset > change.offset {
+                point.offset = (point.offset + change.new.len())
+                    .saturating_sub(change.old.len())
+                    .max(change.offset);
+            }
+        }
+        let point = Point {
+            offset: change.offset + change.new.len(),
+            space: is_space(edited),
+        };
+        match at {
+            Some(index) => self.points[index] = point,
+            None => self.points.push(point),
+        }
+
+        let transaction = self.undo.last_mut().unwrap();
+        let cursors = self
+            .points
+            .iter()
+            .map(|point| Selection::cursor(point.offset));
+        transaction.selections_after = Selections::new(cursors.collect(), self.points.len() - 1);
+        transaction.changes.push(change);
+        self.growth = growth;
+    }
+
+    /// Makes the changes until [`History::end`] one step. With `grow`, the
+    /// step may still take in the typing that follows, as when typing over
+    /// a selection deletes it first.
+    pub fn begin(&mut self, grow: bool) {
+        self.group = Some(false);
+        self.group_growth = grow;
+    }
+
+    pub fn end(&mut self) {
+        if self.group.take() == Some(true) && !self.group_growth {
+            self.close();
+        }
+    }
+
+    /// Starts a new step with the next change.
+    pub fn close(&mut self) {
+        self.growth = Growth::Closed;
+    }
+
+    /// Identifies the text the history leads to: equal states mean equal
+    /// texts.
+    pub fn state(&self) -> u64 {
+        self.undo.last().map_or(0, |transaction| transaction.id)
+    }
+
+    /// Identifies the step [`History::undo`] would take back.
+    pub fn next_undo(&self) -> Option<u64> {
+        self.undo.last().map(|transaction| transaction.id)
+    }
+
+    /// Identifies the step [`History::redo`] would apply again.
+    pub fn next_redo(&self) -> Option<u64> {
+        self.redo.last().map(|transaction| transaction.id)
+    }
+
+    /// Takes the last step off the undo stack and files it for redo; the
+    /// caller reverts its changes.
+    pub fn undo(&mut self) -> Option<Transaction> {
+        let transaction = self.undo.pop()?;
+        self.redo.push(transaction.clone());
+        self.close();
+        Some(transaction)
+    }
+
+    /// Takes the last undone step back onto the undo stack; the caller
+    /// applies its changes again.
+    pub fn redo(&mut self) -> Option<Transaction> {
+        let transaction = self.redo.pop()?;
+        self.undo.push(transaction.clone());
+        self.close();
+        Some(transaction)
+    }
+}
+
+fn growth_of(change: &Change) -> Growth {
+    let single = |text: &str| text.chars().count() == 1 && text != "\n";
+    if change.old.is_empty() && single(&change.new) {
+        Growth::Typing
+    } else if change.new.is_empty() && single(&change.old) {
+        Growth::Deleting
+    } else {
+        Growth::Closed
+    }
+}
+
+fn is_space(text: &str) -> bool {
+    text.chars().all(char::is_whitespace)
+}
+
+/// Whether `next` is the first whitespace after a word.
+fn word_ends(last_was_space: bool, next: &str) -> bool {
+    is_space(next) && !last_was_space
+}
@@ -0,0 +1,10 @@
+pub mod folds;
+pub mod history;
+pub mod selection;
+#[cfg(test)]
+mod tests;
+pub mod text_buffer;
+
+pub use folds::Folds;
+pub use selection::{Selection, Selections};
+pub use text_buffer::{FlatEdit, TextBuffer, TextChange};
@@ -0,0 +1,272 @@
+use std::ops::Range;
+
+/// A selected byte range; an empty one is a plain cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    /// The end that stays put while the selection is extended.
+    pub anchor: usize,
+    /// The end that moves, where the cursor is drawn.
+    pub head: usize,
+}
+
+impl Selection {
+    pub fn new(anchor: usize, head: usize) -> Self {
+        Selection { anchor, head }
+    }
+
+    pub fn cursor(offset: usize) -> Self {
+        Selection::new(offset, offset)
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+impl From<Range<usize>> for Selection {
+    fn from(range: Range<usize>) -> Self {
+        Selection::new(range.start, range.end)
+    }
+}
+
+/// The cursors of an editor: at least one, sorted by position and never
+/// overlapping. One of them is the primary, the one the view follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selections {
+    items: Vec<Selection>,
+    primary: usize,
+}
+
+impl Default for Selections {
+    fn default() -> Self {
+        Selections::single(Selection::default())
+    }
+}
+
+impl Selections {
+    pub fn single(selection: Selection) -> Self {
+        Selections {
+            items: vec![selection],
+            primary: 0,
+        }
+    }
+
+    /// `selections` in any order; the one at `primary` becomes the primary.
+    pub fn new(selections: Vec<Selection>, primary: usize) -> Self {
+        if selections.is_empty() {
+            return Selections::default();
+        }
+        let mut selections = Selections {
+            primary: primary.min(selections.len() - 1),
+            items: selections,
+        };
+        selections.normalize();
+        selections
+    }
+
+    pub fn primary(&self) -> Selection {
+        self.items[self.primary]
+    }
+
+    pub fn primary_index(&self) -> usize {
+        self.primary
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Selection> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Adds `selection` as the new primary.
+    pub fn add(&mut self, selection: Selection) {
+        self.items.push(selection);
+        self.primary = self.items.len() - 1;
+        self.normalize();
+    }
+
+    /// Removes the cursor at `offset` unless it is the only one; returns
+    /// whether there was one.
+    pub fn remove_cursor(&mut self, offset: usize) -> bool {
+        let Some(index) = self
+            .items
+            .iter()
+            .position(|selection| *selection == Selection::cursor(offset))
+        else {
+            return false;
+        };
+        if self.items.len() == 1 {
+            return false;
+        }
+        self.items.remove(index);
+        if self.primary >= index && self.primary > 0 {
+            self.primary -= 1;
+        }
+        true
+    }
+
+    /// Drops every selection but the primary.
+    pub fn collapse(&mut self) {
+        *self = Selections::single(self.primary());
+    }
+
+    /// Keeps every selection inside a text of `len` bytes.
+    pub fn clamp(&mut self, len: usize) {
+        for selection in &mut self.items {
+            selection.anchor = selection.anchor.min(len);
+            selection.head = selection.head.min(len);
+        }
+        self.normalize();
+    }
+
+    /// Moves both ends of every selection to where `map` puts them.
+    pub fn map_offsets(&mut self, map: impl Fn(usize) -> usize) {
+        for selection in &mut self.items {
+            selection.anchor = map(selection.anchor);
+            selection.head = map(selection.head);
+        }
+        self.normalize();
+    }
+
+    /// Moves the selections along with `old_len` bytes at `offset` being
+    /// replaced by `new_len` bytes; ends inside the replaced text go to the
+    /// end of the new one.
+    pub fn map(&mut self, offset: usize, old_len: usize, new_len: usize) {
+        let map = |position: usize| {
+            if position <= offset {
+                position
+            } else if position >= offset + old_len {
+                position - old_len + new_len
+            } else {
+                offset + new_len
+            }
+        };
+        for selection in &mut self.items {
+            selection.anchor = map(selection.anchor);
+            selection.head = map(selection.head);
+        }
+        self.normalize();
+    }
+
+    /// Cmd+D: selects the word under an empty primary cursor, otherwise
+    /// adds the next occurrence of the primary's text after it, wrapping
+    /// around, as the new primary. Returns whether anything changed.
+    pub fn add_next_occurrence(&mut self, text: &str) -> bool {
+        let primary = self.primary();
+        if primary.is_empty() {
+            let word = word_at(text, primary.head);
+            if word.is_empty() {
+                return false;
+            }
+            self.items[self.primary] = Selection::from(word);
+            self.normalize();
+            return true;
+        }
+
+        let needle = &text[primary.range()];
+        let after = primary.range().end;
+        let found = text[after..]
+            .match_indices(needle)
+            .map(|(start, _)| after + start)
+            .chain(text.match_indices(needle).map(|(start, _)| start))
+            .find(|start| {
+                let range = *start..start + needle.len();
+                !self
+                    .items
+                    .iter()
+                    .any(|selection| selection.range() == range)
+            });
+        match found {
+            Some(start) => {
+                self.add(Selection::new(start, start + needle.len()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cmd+Shift+L: selects every occurrence of the primary's text, or of
+    /// the word under an empty primary cursor.
+    pub fn select_all_occurrences(&mut self, text: &str) {
+        let primary = self.primary();
+        let range = if primary.is_empty() {
+            word_at(text, primary.head)
+        } else {
+            primary.range()
+        };
+        if range.is_empty() {
+            return;
+        }
+        let needle = &text[range.clone()];
+        let items: Vec<Selection> = text
+            .match_indices(needle)
+            .map(|(start, _)| Selection::new(start, start + needle.len()))
+            .collect();
+        let primary = items
+            .iter()
+            .position(|selection| selection.range() == range)
+            .unwrap_or(0);
+        *self = Selections::new(items, primary);
+    }
+
+    // sorts the selections and merges the ones that overlap
+    fn normalize(&mut self) {
+        let primary = self.items[self.primary];
+        self.items.sort_by_key(|selection| selection.range().start);
+
+        let mut merged: Vec<Selection> = Vec::with_capacity(self.items.len());
+        let mut primary_index = 0;
+        for selection in self.items.drain(..) {
+            let is_primary = selection == primary;
+            match merged.last_mut() {
+                Some(last) if overlaps(last, &selection) => {
+                    let range = last.range().start..last.range().end.max(selection.range().end);
+                    // keep the direction of the one that came first
+                    *last = if last.head >= last.anchor {
+                        Selection::new(range.start, range.end)
+                    } else {
+                        Selection::new(range.end, range.start)
+                    };
+                }
+                _ => merged.push(selection),
+            }
+            if is_primary {
+                primary_index = merged.len() - 1;
+            }
+        }
+        self.items = merged;
+        self.primary = primary_index;
+    }
+}
+
+fn overlaps(a: &Selection, b: &Selection) -> bool {
+    let (a, b) = (a.range(), b.range());
+    // touching cursors are the same cursor, touching selections are not
+    b.start < a.end || b.start == a.start || ((a.is_empty() || b.is_empty()) && b.start == a.end)
+}
+
+/// The identifier chars around `offset`; empty outside a word.
+pub fn word_at(text: &str, offset: usize) -> Range<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(text.len(), |(i, _)| offset + i);
+    start..end
+}
@@ -0,0 +1,310 @@
+use std::ops::Range;
+
+use crate::core::buffer::{FlatEdit, Selection, Selections, TextBuffer};
+
+#[test]
+fn test_edits_reach_the_rope() {
+    let mut buffer = TextBuffer::from("fn main() {\n}\n");
+    buffer.insert(11, "\n    let é = 1;");
+    buffer.replace(20..22, "x");
+    buffer.delete(0..3);
+
+    assert_eq!(buffer, "main() {\n    let x = 1;\n}\n");
+    assert_eq!(buffer.len_chars(), buffer.to_string().chars().count());
+    assert_eq!(buffer.line(1), "    let x = 1;\n");
+    assert_eq!(buffer.slice(4..8), "() {");
+    assert_eq!(buffer.len_lines(), 4);
+    assert_eq!(buffer.line_to_byte(1), "main() {\n".len());
+}
+
+#[test]
+fn test_offset_conversions() {
+    // "é" is two bytes and one UTF-16 unit, "𝕏" four bytes and two units
+    let buffer = TextBuffer::from("aé\n𝕏b\n");
+    let b = "aé\n𝕏".len();
+
+    assert_eq!(buffer.byte_to_char(b), 4);
+    assert_eq!(buffer.char_to_byte(4), b);
+    assert_eq!(buffer.byte_to_utf16(b), 5);
+    assert_eq!(buffer.utf16_to_byte(5), b);
+    assert_eq!(buffer.line_col(b), (1, 1));
+    assert_eq!(buffer.line_col_to_byte(1, 1), b);
+    // columns past the end of a line stop before its line break
+    assert_eq!(buffer.line_col_to_byte(0, 10), "aé".len());
+    assert_eq!(buffer.line_to_byte(10), buffer.len());
+}
+
+#[test]
+fn test_revision_tracks_dirty_state() {
+    let mut buffer = TextBuffer::from("hello");
+    assert!(!buffer.is_dirty());
+
+    buffer.insert(5, " world");
+    assert!(buffer.is_dirty());
+    buffer.mark_saved();
+    assert!(!buffer.is_dirty());
+
+    // setting the same text is not an edit
+    let revision = buffer.revision();
+    buffer.set_text("hello world");
+    assert_eq!(buffer.revision(), revision);
+
+    buffer.set_text("hello there");
+    assert_eq!(buffer, "hello there");
+    assert!(buffer.is_dirty());
+}
+
+#[test]
+fn test_egui_edits_by_char_index() {
+    use eframe::egui::TextBuffer as _;
+
+    let mut buffer = TextBuffer::from("añb");
+    let mut flat = FlatEdit::new(&mut buffer);
+    assert_eq!(flat.insert_text("ü", 2), 1);
+    assert_eq!(flat.as_str(), "añüb");
+    flat.delete_char_range(1..3);
+    assert_eq!(flat.as_str(), "ab");
+    assert_eq!(buffer, "ab");
+    assert_eq!(buffer.len_chars(), 2);
+}
+
+#[test]
+fn test_changes_since_a_revision() {
+    let mut buffer = TextBuffer::from("one\ntwo\n");
+    let start = buffer.revision();
+    buffer.insert(4, "2: ");
+    let middle = buffer.revision();
+    buffer.replace(0..3, "1");
+
+    let changes: Vec<_> = buffer.changes_since(start).unwrap().collect();
+    assert_eq!(changes.len(), 2);
+    assert_eq!((changes[0].start, changes[0].end), ((1, 0), (1, 0)));
+    assert_eq!((changes[1].range.clone(), changes[1].text.as_str()), (0..3, "1"));
+    assert_eq!(buffer.changes_since(middle).unwrap().count(), 1);
+    assert_eq!(buffer.changes_since(buffer.revision()).unwrap().count(), 0);
+    // revisions of other buffers are unknown
+    assert!(buffer.changes_since(TextBuffer::from("x").revision()).is_none());
+
+    // one edit covering both, from the first text to the last
+    let edit = buffer.edit_since(start).unwrap().unwrap();
+    assert_eq!((edit.range, edit.new_len), (0..4, "1\n2: ".len()));
+    assert_eq!(buffer.edit_since(buffer.revision()), Some(None));
+}
+
+#[test]
+fn test_contents_are_shared_until_an_edit() {
+    let mut buffer = TextBuffer::from("one\n");
+    let contents = buffer.contents();
+    assert_eq!(&*contents, "one\n");
+    assert!(std::sync::Arc::ptr_eq(&contents, &buffer.contents()));
+
+    buffer.insert(0, "zero ");
+    assert_eq!(&*buffer.contents(), "zero one\n");
+    buffer.undo();
+    assert_eq!(&*buffer.contents(), "one\n");
+    assert!(!std::sync::Arc::ptr_eq(&contents, &buffer.contents()));
+}
+
+/// The primary selection restored by an undo or redo.
+fn primary(selections: Option<&Selections>) -> Option<Range<usize>> {
+    selections.map(|selections| selections.primary().range())
+}
+
+/// Types `text` a char at a time, as the editor does, one frame per char.
+fn type_text(buffer: &mut TextBuffer, offset: usize, text: &str) {
+    let mut offset = offset;
+    for c in text.chars() {
+        buffer.begin_input();
+        buffer.insert(offset, &c.to_string());
+        buffer.end_input();
+        offset += c.len_utf8();
+        buffer.set_selection(offset..offset);
+    }
+}
+
+#[test]
+fn test_typing_undoes_word_by_word() {
+    let mut buffer = TextBuffer::new();
+    type_text(&mut buffer, 0, "let answer = 42");
+
+    assert_eq!(primary(buffer.undo()), Some(12..12));
+    assert_eq!(buffer, "let answer =");
+    buffer.undo();
+    buffer.undo();
+    assert_eq!(buffer, "let");
+    assert_eq!(primary(buffer.undo()), Some(0..0));
+    assert_eq!(buffer, "");
+    assert_eq!(primary(buffer.undo()), None);
+
+    assert_eq!(primary(buffer.redo()), Some(3..3));
+    assert_eq!(primary(buffer.redo()), Some(10..10));
+    assert_eq!(buffer, "let answer");
+}
+
+#[test]
+fn test_typing_over_a_selection_is_one_step() {
+    let mut buffer = TextBuffer::from("let x = 1;");
+    buffer.set_selection(4..5);
+    buffer.begin_input();
+    buffer.delete(4..5);
+    buffer.insert(4, "y");
+    buffer.end_input();
+    buffer.set_selection(5..5);
+    type_text(&mut buffer, 5, "z");
+
+    assert_eq!(buffer, "let yz = 1;");
+    // the selection comes back with the text
+    assert_eq!(primary(buffer.undo()), Some(4..5));
+    assert_eq!(buffer, "let x = 1;");
+}
+
+#[test]
+fn test_transaction_is_one_step_and_undo_restores_clean_state() {
+    let mut buffer = TextBuffer::from("a b a");
+    type_text(&mut buffer, 5, "!");
+    buffer.mark_saved();
+
+    buffer.transaction(|buffer| {
+        buffer.replace(4..5, "c");
+        buffer.replace(0..1, "c");
+    });
+    // typing right after a transaction starts a step of its own
+    type_text(&mut buffer, 6, "?");
+    assert_eq!(buffer, "c b c!?");
+    assert!(buffer.is_dirty());
+
+    buffer.undo();
+    buffer.undo();
+    assert_eq!(buffer, "a b a!");
+    assert!(!buffer.is_dirty());
+
+    // a new edit drops what could be redone
+    buffer.insert(0, "-");
+    assert!(buffer.redo().is_none());
+}
+
+#[test]
+fn test_steps_keep_their_id_through_undo_and_redo() {
+    let mut buffer = TextBuffer::from("a b");
+    assert_eq!(buffer.next_undo(), None);
+    buffer.transaction(|buffer| buffer.replace(0..1, "c"));
+    let step = buffer.next_undo();
+    assert!(step.is_some());
+
+    // typing and undoing it gets back to the same step
+    type_text(&mut buffer, 3, "!");
+    assert_ne!(buffer.next_undo(), step);
+    buffer.undo();
+    assert_eq!(buffer.next_undo(), step);
+
+    buffer.undo();
+    assert_eq!(buffer.next_undo(), None);
+    assert_eq!(buffer.next_redo(), step);
+    buffer.redo();
+    assert_eq!(buffer.next_undo(), step);
+    assert_eq!(buffer, "c b");
+}
+
+#[test]
+fn test_next_occurrence_wraps_and_select_all() {
+    let text = "foo bar foo baz foo";
+    let mut selections = Selections::single(Selection::cursor(9));
+
+    // a bare cursor selects its word first
+    assert!(selections.add_next_occurrence(text));
+    assert_eq!(selections.primary().range(), 8..11);
+    assert!(selections.add_next_occurrence(text));
+    assert!(selections.add_next_occurrence(text));
+    let ranges: Vec<_> = selections.iter().map(Selection::range).collect();
+    assert_eq!(ranges, vec![0..3, 8..11, 16..19]);
+    assert_eq!(selections.primary().range(), 0..3);
+    // every occurrence is taken
+    assert!(!selections.add_next_occurrence(text));
+
+    let mut selections = Selections::single(Selection::new(4, 5));
+    selections.select_all_occurrences(text);
+    let ranges: Vec<_> = selections.iter().map(Selection::range).collect();
+    assert_eq!(ranges, vec![4..5, 12..13]);
+    assert_eq!(selections.primary_index(), 0);
+}
+
+#[test]
+fn test_selections_merge_and_follow_edits() {
+    let mut selections = Selections::new(
+        vec![
+            Selection::new(6, 8),
+            Selection::cursor(2),
+            Selection::new(7, 10),
+        ],
+        1,
+    );
+    let ranges: Vec<_> = selections.iter().map(Selection::range).collect();
+    assert_eq!(ranges, vec![2..2, 6..10]);
+    assert_eq!(selections.primary().range(), 2..2);
+
+    // "abc" replaced by "x" at 1: the cursor inside goes after it
+    selections.map(1, 3, 1);
+    let ranges: Vec<_> = selections.iter().map(Selection::range).collect();
+    assert_eq!(ranges, vec![2..2, 4..8]);
+
+    assert!(selections.remove_cursor(2));
+    assert!(!selections.remove_cursor(4));
+    assert_eq!(selections.len(), 1);
+}
+
+#[test]
+fn test_typing_at_several_cursors_undoes_as_one() {
+    let mut buffer = TextBuffer::from("a\nb\n");
+    let cursors = Selections::new(vec![Selection::cursor(1), Selection::cursor(3)], 0);
+    buffer.set_selections(cursors.clone());
+
+    // what the editor does for each typed char: the last cursor first
+    let mut offsets = [1, 3];
+    for c in ["x", "y"] {
+        buffer.begin_input();
+        buffer.insert(offsets[1], c);
+        buffer.insert(offsets[0], c);
+        buffer.end_input();
+        offsets = [offsets[0] + 1, offsets[1] + 2];
+        buffer.set_selections(Selections::new(
+            offsets
+                .iter()
+                .map(|offset| Selection::cursor(*offset))
+                .collect(),
+            0,
+        ));
+    }
+    assert_eq!(buffer, "axy\nbxy\n");
+
+    assert_eq!(buffer.undo(), Some(&cursors));
+    assert_eq!(buffer, "a\nb\n");
+    let after: Vec<_> = buffer.redo().unwrap().iter().map(|s| s.head).collect();
+    assert_eq!(after, vec![3, 7]);
+}
+
+#[test]
+fn test_folds_follow_edits_and_keep_cursors_out() {
+    let mut buffer = TextBuffer::from("fn a() {\n    one\n    two\n}\nfn b() {\n    three\n}\n");
+    buffer.set_selection(12..12);
+    buffer.fold(0..3);
+    buffer.fold(4..6);
+    assert_eq!(buffer.folded_lines(), vec![0..3, 4..6]);
+    assert_eq!(buffer.folds().hidden(), vec![9..25, 36..46]);
+    // the cursor inside left for the end of the first line
+    assert_eq!(buffer.selections().primary().head, 8);
+
+    assert_eq!(buffer.skip_folded(20, true), 25);
+    assert_eq!(buffer.skip_folded(20, false), 8);
+    assert_eq!(buffer.skip_folded(25, false), 25);
+
+    // an edit before a fold moves it, one into it unfolds it
+    buffer.insert(0, "\n");
+    assert_eq!(buffer.folded_lines(), vec![1..4, 5..7]);
+    buffer.insert(40, "x");
+    assert_eq!(buffer.folded_lines(), vec![1..4]);
+    buffer.undo();
+    assert!(buffer.is_folded(1..4));
+
+    buffer.unfold(1..4);
+    assert!(buffer.folds().is_empty());
+}
@@ -0,0 +1,587 @@
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ropey::Rope;
+
+use crate::core::buffer::folds::Folds;
+use crate::core::buffer::history::{Change, History};
+use crate::core::buffer::selection::{Selections, word_at};
+use crate::core::lexer::Edit;
+use crate::core::plugins::lsp::protocol::{LineIndex, Position};
+
+/// Changes kept for [`TextBuffer::changes_since`]; whoever falls further
+/// behind looks at the whole text again.
+const MAX_CHANGES: usize = 256;
+
+/// Revisions are unique across buffers, so a new buffer in place of another
+/// never passes for it.
+fn next_revision() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// One edit of a [`TextBuffer`], located in the text before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChange {
+    /// The bytes replaced.
+    pub range: Range<usize>,
+    /// Line and column of the start of `range`, in UTF-16 code units as the
+    /// Language Server Protocol counts.
+    pub start: (usize, usize),
+    /// Same for the end of `range`.
+    pub end: (usize, usize),
+    pub text: String,
+}
+
+impl TextChange {
+    pub fn edit(&self) -> Edit {
+        Edit {
+            range: self.range.clone(),
+            new_len: self.text.len(),
+        }
+    }
+}
+
+/// The text of a document.
+///
+/// Edits, line lookups and offset conversions go through a rope and take
+/// O(log n). Read the text in [`TextBuffer::chunks`] or
+/// [`TextBuffer::slice`]s; what needs it in one piece (a lexer, a regex)
+/// shares [`TextBuffer::contents`], which is put together at most once per
+/// revision. egui edits it through a [`FlatEdit`].
+///
+/// Every edit gives the buffer a new [`TextBuffer::revision`], which makes
+/// "did it change since ..." a comparison of two numbers instead of two
+/// texts, and the last edits can be replayed with
+/// [`TextBuffer::changes_since`]. Edits are also recorded in a [`History`]
+/// for undo and redo.
+#[derive(Debug, Clone, Default)]
+pub struct TextBuffer {
+    rope: Rope,
+    revision: u64,
+    // the last edits, with the revision each applies to
+    changes: VecDeque<(u64, TextChange)>,
+    history: History,
+    // history state of the text on disk
+    saved_state: u64,
+    // cursors of the editor showing the buffer; edits move them along and
+    // undo restores them
+    selections: Selections,
+    // regions the editor shows folded; edits into one unfold it
+    folds: Folds,
+    // the text in one piece, once asked for; edits drop it
+    flat: OnceCell<Arc<str>>,
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Length in bytes.
+    pub fn len(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The bytes in `range`, copied only when they span chunks of the rope.
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, str> {
+        let slice = self.rope.byte_slice(range);
+        match slice.as_str() {
+            Some(text) => Cow::Borrowed(text),
+            None => Cow::Owned(slice.to_string()),
+        }
+    }
+
+    /// The whole text in one piece, shared by everyone asking until the
+    /// next edit.
+    pub fn contents(&self) -> Arc<str> {
+        self.flat.get_or_init(|| self.to_string().into()).clone()
+    }
+
+    /// The text in order, a piece at a time.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.rope.chunks()
+    }
+
+    /// The zero-based `line` with its line break, if it has one.
+    pub fn line(&self, line: usize) -> Cow<'_, str> {
+        self.slice(self.line_to_byte(line)..self.line_to_byte(line + 1))
+    }
+
+    /// The identifier chars around byte `offset`, see [`word_at`]; words
+    /// don't span lines, so only the line of `offset` is looked at.
+    pub fn word_at(&self, offset: usize) -> Range<usize> {
+        let line = self.byte_to_line(offset);
+        let line_start = self.line_to_byte(line);
+        let word = word_at(&self.line(line), offset - line_start);
+        word.start + line_start..word.end + line_start
+    }
+
+    /// The char starting at byte `offset`, if one does.
+    pub fn char_at(&self, offset: usize) -> Option<char> {
+        let index = self.char_index(offset)?;
+        (index < self.rope.len_chars()).then(|| self.rope.char(index))
+    }
+
+    /// The char ending at byte `offset`, if one does.
+    pub fn char_before(&self, offset: usize) -> Option<char> {
+        let index = self.char_index(offset)?;
+        (index > 0).then(|| self.rope.char(index - 1))
+    }
+
+    // the char index of a char boundary
+    fn char_index(&self, offset: usize) -> Option<usize> {
+        if offset > self.len() {
+            return None;
+        }
+        let index = self.rope.byte_to_char(offset);
+        (self.rope.char_to_byte(index) == offset).then_some(index)
+    }
+
+    /// Changes with every edit, and differs between buffers with different
+    /// texts.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The edits that took the text from `revision` to the current one, in
+    /// order; `None` when they are no longer kept (or `revision` is not one
+    /// of this buffer's).
+    pub fn changes_since(&self, revision: u64) -> Option<impl Iterator<Item = &TextChange>> {
+        let first = if revision == self.revision {
+            self.changes.len()
+        } else {
+            self.changes
+                .iter()
+                .rposition(|(base, _)| *base == revision)?
+        };
+        Some(self.changes.range(first..).map(|(_, change)| change))
+    }
+
+    /// The single edit covering every change since `revision`, see
+    /// [`TextBuffer::changes_since`]; `Some(None)` when nothing changed.
+    pub fn edit_since(&self, revision: u64) -> Option<Option<Edit>> {
+        let mut changes = self.changes_since(revision)?;
+        let first = changes.next().map(TextChange::edit);
+        Some(first.map(|first| changes.fold(first, |edit, change| edit.then(&change.edit()))))
+    }
+
+    /// Remembers the current text as the one on disk.
+    pub fn mark_saved(&mut self) {
+        self.saved_state = self.history.state();
+        // typing after a save is undone separately
+        self.history.close();
+    }
+
+    /// Whether the text differs from the one loaded or last saved; undoing
+    /// back to it makes the buffer clean again.
+    pub fn is_dirty(&self) -> bool {
+        self.history.state() != self.saved_state
+    }
+
+    pub fn selections(&self) -> &Selections {
+        &self.selections
+    }
+
+    /// Sets the cursors, which the next undo step starts from.
+    pub fn set_selections(&mut self, mut selections: Selections) {
+        selections.clamp(self.len());
+        self.selections = selections;
+    }
+
+    /// Sets a single selection.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        self.set_selections(Selections::single(selection.into()));
+    }
+
+    pub fn folds(&self) -> &Folds {
+        &self.folds
+    }
+
+    /// Folds the zero-based `lines` after the first one up to the last one
+    /// before `lines.end`; the cursors in there move to the end of the
+    /// first line.
+    pub fn fold(&mut self, lines: Range<usize>) {
+        if !self.folds.add(self.fold_bytes(lines)) {
+            return;
+        }
+        let mut selections = self.selections.clone();
+        selections.map_offsets(|offset| self.skip_folded(offset, false));
+        self.set_selections(selections);
+    }
+
+    pub fn unfold(&mut self, lines: Range<usize>) {
+        self.folds.remove(&self.fold_bytes(lines));
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folds.clear();
+    }
+
+    /// Unfolds the regions hiding any of `range`, e.g. a match about to be
+    /// selected.
+    pub fn unfold_around(&mut self, range: Range<usize>) {
+        let hiding: Vec<Range<usize>> = self
+            .folds
+            .iter()
+            .filter(|fold| fold.start < range.end && range.start < fold.end)
+            .cloned()
+            .collect();
+        for fold in hiding {
+            self.folds.remove(&fold);
+        }
+    }
+
+    pub fn is_folded(&self, lines: Range<usize>) -> bool {
+        self.folds.contains(&self.fold_bytes(lines))
+    }
+
+    /// The folded regions as the lines given to [`TextBuffer::fold`].
+    pub fn folded_lines(&self) -> Vec<Range<usize>> {
+        self.folds
+            .iter()
+            .map(|range| self.byte_to_line(range.start) - 1..self.byte_to_line(range.end))
+            .collect()
+    }
+
+    /// `offset` if it is in sight, otherwise moved out of the folded text
+    /// around it: `forward` to the line shown after it, or back to the end
+    /// of the line shown before it.
+    pub fn skip_folded(&self, offset: usize, forward: bool) -> usize {
+        // the end of the text is hidden with the last line when that has
+        // no line break
+        let open_end = self.char_before(self.len()) != Some('\n');
+        let hidden = self.folds.hidden().into_iter().find(|hidden| {
+            hidden.contains(&offset) || (open_end && hidden.end == offset && offset == self.len())
+        });
+        match hidden {
+            Some(hidden) if forward && !(open_end && hidden.end == self.len()) => hidden.end,
+            Some(hidden) => hidden.start - 1,
+            None => offset,
+        }
+    }
+
+    fn fold_bytes(&self, lines: Range<usize>) -> Range<usize> {
+        let start = self.line_to_byte(lines.start + 1);
+        start..self.line_to_byte(lines.end).max(start)
+    }
+
+    /// Makes every edit `edit` does a single undo step, e.g. all the
+    /// changes of a rename or a format.
+    pub fn transaction<R>(&mut self, edit: impl FnOnce(&mut Self) -> R) -> R {
+        self.history.begin(false);
+        let result = edit(self);
+        self.history.end();
+        result
+    }
+
+    /// Starts a step like [`TextBuffer::transaction`] that typing right
+    /// after may still join. The editor wraps each frame's input in
+    /// `begin_input`..`end_input`, so typing over a selection is undone
+    /// together with the deletion of the selection.
+    pub fn begin_input(&mut self) {
+        self.history.begin(true);
+    }
+
+    pub fn end_input(&mut self) {
+        self.history.end();
+    }
+
+    /// Reverts the last undo step and restores the selections from before
+    /// it.
+    pub fn undo(&mut self) -> Option<&Selections> {
+        let transaction = self.history.undo()?;
+        for change in transaction.changes.iter().rev() {
+            self.apply(change.new_range(), &change.old);
+        }
+        self.set_selections(transaction.selections_before);
+        Some(&self.selections)
+    }
+
+    /// Applies the last undone step again and restores the selections from
+    /// after it.
+    pub fn redo(&mut self) -> Option<&Selections> {
+        let transaction = self.history.redo()?;
+        for change in &transaction.changes {
+            self.apply(change.old_range(), &change.new);
+        }
+        self.set_selections(transaction.selections_after);
+        Some(&self.selections)
+    }
+
+    /// Identifies the step [`TextBuffer::undo`] would revert, e.g. to tell
+    /// whether it is still the one a rename made.
+    pub fn next_undo(&self) -> Option<u64> {
+        self.history.next_undo()
+    }
+
+    /// Identifies the step [`TextBuffer::redo`] would apply again.
+    pub fn next_redo(&self) -> Option<u64> {
+        self.history.next_redo()
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    /// The number of lines; a trailing newline starts an empty last line.
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Replaces the bytes in `range` with `text`.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        if range.is_empty() && text.is_empty() {
+            return;
+        }
+        let change = Change {
+            offset: range.start,
+            old: self.slice(range.clone()).into_owned(),
+            new: text.to_string(),
+        };
+        self.history.record(change, &self.selections);
+        self.apply(range.clone(), text);
+        self.selections.map(range.start, range.len(), text.len());
+    }
+
+    // replaces without recording the change in the history
+    fn apply(&mut self, range: Range<usize>, text: &str) {
+        let change = TextChange {
+            range: range.clone(),
+            start: self.line_utf16(range.start),
+            end: self.line_utf16(range.end),
+            text: text.to_string(),
+        };
+        let start = self.rope.byte_to_char(range.start);
+        let end = self.rope.byte_to_char(range.end);
+        self.rope.remove(start..end);
+        self.rope.insert(start, text);
+        self.flat.take();
+        self.folds.map(range.start, range.len(), text.len());
+
+        if self.changes.len() == MAX_CHANGES {
+            self.changes.pop_front();
+        }
+        self.changes.push_back((self.revision, change));
+        self.revision = next_revision();
+    }
+
+    fn line_utf16(&self, offset: usize) -> (usize, usize) {
+        let line = self.rope.byte_to_line(offset);
+        let line_start = self.rope.char_to_utf16_cu(self.rope.line_to_char(line));
+        (line, self.byte_to_utf16(offset) - line_start)
+    }
+
+    pub fn insert(&mut self, offset: usize, text: &str) {
+        self.replace(offset..offset, text);
+    }
+
+    pub fn delete(&mut self, range: Range<usize>) {
+        self.replace(range, "");
+    }
+
+    /// Replaces the whole text, touching only the part that differs so an
+    /// unchanged text keeps its revision.
+    pub fn set_text(&mut self, text: &str) {
+        if let Some(edit) = Edit::diff(&self.contents(), text) {
+            let new = &text[edit.range.start..edit.range.start + edit.new_len];
+            self.replace(edit.range, new);
+        }
+    }
+
+    pub fn byte_to_char(&self, offset: usize) -> usize {
+        self.rope.byte_to_char(offset)
+    }
+
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        self.rope.char_to_byte(index)
+    }
+
+    /// The offset in UTF-16 code units, as JavaScript and the Language
+    /// Server Protocol count.
+    pub fn byte_to_utf16(&self, offset: usize) -> usize {
+        self.rope.char_to_utf16_cu(self.rope.byte_to_char(offset))
+    }
+
+    pub fn utf16_to_byte(&self, offset: usize) -> usize {
+        self.rope.char_to_byte(self.rope.utf16_cu_to_char(offset))
+    }
+
+    /// The zero-based line holding byte `offset`.
+    pub fn byte_to_line(&self, offset: usize) -> usize {
+        self.rope.byte_to_line(offset)
+    }
+
+    /// The byte offset where `line` starts; the end of the text past the
+    /// last line.
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        if line >= self.rope.len_lines() {
+            return self.len();
+        }
+        self.rope.line_to_byte(line)
+    }
+
+    /// The zero-based line and column (in chars) of byte `offset`.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let char = self.rope.byte_to_char(offset);
+        let line = self.rope.char_to_line(char);
+        (line, char - self.rope.line_to_char(line))
+    }
+
+    /// The byte offset of `column` (in chars) on `line`, clamped to the end
+    /// of the line.
+    pub fn line_col_to_byte(&self, line: usize, column: usize) -> usize {
+        if line >= self.rope.len_lines() {
+            return self.len();
+        }
+        let start = self.rope.line_to_char(line);
+        let end = if line + 1 < self.rope.len_lines() {
+            // stop before the line break
+            self.rope.line_to_char(line + 1) - 1
+        } else {
+            self.rope.len_chars()
+        };
+        self.rope.char_to_byte((start + column).min(end))
+    }
+}
+
+impl LineIndex for TextBuffer {
+    fn offset_to_position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.len());
+        while self.char_index(offset).is_none() {
+            offset -= 1;
+        }
+        let (line, character) = self.line_utf16(offset);
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    fn position_to_offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        if line >= self.rope.len_lines() {
+            return self.len();
+        }
+        let start = self.rope.line_to_char(line);
+        let end = if line + 1 < self.rope.len_lines() {
+            // stop before the line break
+            self.rope.line_to_char(line + 1) - 1
+        } else {
+            self.rope.len_chars()
+        };
+        let start_units = self.rope.char_to_utf16_cu(start);
+        let end_units = self.rope.char_to_utf16_cu(end);
+        let units = (start_units + position.character as usize).min(end_units);
+        // a position inside a surrogate pair resolves to the char after it
+        let mut char = self.rope.utf16_cu_to_char(units);
+        if self.rope.char_to_utf16_cu(char) < units {
+            char += 1;
+        }
+        self.rope.char_to_byte(char)
+    }
+}
+
+impl From<&str> for TextBuffer {
+    fn from(text: &str) -> Self {
+        TextBuffer {
+            rope: Rope::from_str(text),
+            revision: next_revision(),
+            ..TextBuffer::default()
+        }
+    }
+}
+
+impl From<String> for TextBuffer {
+    fn from(text: String) -> Self {
+        TextBuffer::from(text.as_str())
+    }
+}
+
+impl fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl PartialEq<str> for TextBuffer {
+    fn eq(&self, other: &str) -> bool {
+        self.rope == other
+    }
+}
+
+impl PartialEq<&str> for TextBuffer {
+    fn eq(&self, other: &&str) -> bool {
+        self.rope == *other
+    }
+}
+
+/// A [`TextBuffer`] as `egui::TextEdit` and egui's editing helpers (word
+/// boundaries, indentation) want it: one `&str`. The flat copy is only made
+/// when one of them asks for it, and is patched along with the buffer after,
+/// so typing and plain deletes never copy the text. Make one for the edits
+/// of a frame and drop it after.
+pub struct FlatEdit<'a> {
+    buffer: &'a mut TextBuffer,
+    flat: OnceCell<String>,
+}
+
+impl<'a> FlatEdit<'a> {
+    pub fn new(buffer: &'a mut TextBuffer) -> Self {
+        FlatEdit {
+            buffer,
+            flat: OnceCell::new(),
+        }
+    }
+
+    pub fn buffer(&self) -> &TextBuffer {
+        self.buffer
+    }
+}
+
+impl eframe::egui::TextBuffer for FlatEdit<'_> {
+    fn is_mutable(&self) -> bool {
+        true
+    }
+
+    fn as_str(&self) -> &str {
+        self.flat.get_or_init(|| self.buffer.to_string())
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        let offset = self.buffer.char_to_byte(char_index);
+        self.buffer.insert(offset, text);
+        if let Some(flat) = self.flat.get_mut() {
+            flat.insert_str(offset, text);
+        }
+        text.chars().count()
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        let start = self.buffer.char_to_byte(char_range.start);
+        let end = self.buffer.char_to_byte(char_range.end);
+        self.buffer.delete(start..end);
+        if let Some(flat) = self.flat.get_mut() {
+            flat.replace_range(start..end, "");
+        }
+    }
+
+    fn replace_with(&mut self, text: &str) {
+        self.buffer.set_text(text);
+        self.flat = OnceCell::from(text.to_string());
+    }
+
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<FlatEdit<'static>>()
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use eframe::egui;
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum FileType {
     Folder,
@@ -28,4 +30,20 @@ pub enum Hotkeys {
     FindFile,       // cmd + p
     FindSettings,   // cmd + shift + p
     FindText,       // cmd + f
+    FindInFiles,    // cmd + shift + f
+}
+
+impl Hotkeys {
+    pub fn shortcut(&self) -> egui::KeyboardShortcut {
+        let command = egui::Modifiers::COMMAND;
+        let (modifiers, key) = match self {
+            Hotkeys::ToggleExplorer => (command, egui::Key::B),
+            Hotkeys::CloseFile => (command, egui::Key::W),
+            Hotkeys::FindFile => (command, egui::Key::P),
+            Hotkeys::FindSettings => (command | egui::Modifiers::SHIFT, egui::Key::P),
+            Hotkeys::FindText => (command, egui::Key::F),
+            Hotkeys::FindInFiles => (command | egui::Modifiers::SHIFT, egui::Key::F),
+        };
+        egui::KeyboardShortcut::new(modifiers, key)
+    }
 }
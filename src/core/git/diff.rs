@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+/// Above this many removed and added lines the middle of two texts is not
+/// aligned line by line but reported as one modification.
+const MAX_EDIT_DISTANCE: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    /// Lines removed right before `LineChange::lines.start`; the range is empty.
+    Deleted,
+}
+
+/// Lines of the new text that differ from the old one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineChange {
+    /// Zero-based lines of the new text.
+    pub lines: Range<usize>,
+    pub kind: ChangeKind,
+}
+
+/// What changed line by line from `old` to `new`, as a gutter shows it: runs
+/// of new lines are added, runs of removed lines deleted, and runs that
+/// replace each other modified.
+///
+/// Takes time in the size of the texts times the number of changed lines, so
+/// call it off the UI thread for buffers being edited.
+pub fn line_changes(old: &str, new: &str) -> Vec<LineChange> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut changes = vec![];
+    // lines removed and added since the last common line
+    let mut removed = 0;
+    let mut added = prefix..prefix;
+    let mut flush = |removed: &mut usize, added: &mut Range<usize>| {
+        let kind = match (*removed, added.len()) {
+            (0, 0) => return,
+            (0, _) => ChangeKind::Added,
+            (_, 0) => ChangeKind::Deleted,
+            _ => ChangeKind::Modified,
+        };
+        changes.push(LineChange {
+            lines: added.clone(),
+            kind,
+        });
+        *removed = 0;
+        *added = added.end..added.end;
+    };
+
+    for step in align(old_middle, new_middle) {
+        match step {
+            Step::Same => {
+                flush(&mut removed, &mut added);
+                added = added.end + 1..added.end + 1;
+            }
+            Step::Removed => removed += 1,
+            Step::Added => added.end += 1,
+        }
+    }
+    flush(&mut removed, &mut added);
+    changes
+}
+
+enum Step {
+    Same,
+    Removed,
+    Added,
+}
+
+/// A shortest edit script from `old` to `new`, found with Myers' greedy
+/// algorithm.
+fn align(old: &[&str], new: &[&str]) -> Vec<Step> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_EDIT_DISTANCE) as isize;
+    // furthest x reached on each diagonal k = x - y, at index(k)
+    let index = |k: isize| (k + max + 1) as usize;
+    let mut furthest = vec![0isize; index(max + 1) + 1];
+    // the diagonals -d..=d of `furthest` after each number d of edits
+    let mut trace: Vec<Vec<isize>> = vec![];
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let down = k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]);
+            let mut x = if down {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index(k)] = x;
+            if x >= n && y >= m {
+                return backtrack(&trace, n, m);
+            }
+        }
+        trace.push(furthest[index(-d)..=index(d)].to_vec());
+    }
+
+    let removed = old.iter().map(|_| Step::Removed);
+    removed.chain(new.iter().map(|_| Step::Added)).collect()
+}
+
+/// Walks the furthest points of `align` back from the end of both texts.
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Step> {
+    let mut steps = vec![];
+    let (mut x, mut y) = (n, m);
+    for d in (1..=trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let at = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let down = k == -d || (k != d && at(k - 1) < at(k + 1));
+        let previous_k = if down { k + 1 } else { k - 1 };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            steps.push(Step::Same);
+            x -= 1;
+            y -= 1;
+        }
+        steps.push(if down { Step::Added } else { Step::Removed });
+        (x, y) = (previous_x, previous_y);
+    }
+    steps.extend((0..x).map(|_| Step::Same));
+    steps.reverse();
+    steps
+}
@@ -0,0 +1,29 @@
+pub mod diff;
+#[cfg(test)]
+mod tests;
+
+pub use diff::{ChangeKind, LineChange, line_changes};
+
+use std::path::Path;
+use std::process::Command;
+
+/// The text of `path` in the last commit of its repository; `None` when the
+/// file is outside a repository, untracked, or git is not installed.
+///
+/// Runs `git`, so it blocks: call it off the UI thread.
+pub fn head_text(path: &Path) -> Option<String> {
+    let dir = path.parent()?;
+    let name = path.file_name()?.to_str()?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        // `./` makes the path relative to `dir` rather than the repository root
+        .arg(format!("HEAD:./{name}"))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
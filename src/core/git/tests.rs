@@ -0,0 +1,49 @@
+use crate::core::git::{ChangeKind, LineChange, line_changes};
+
+fn change(lines: std::ops::Range<usize>, kind: ChangeKind) -> LineChange {
+    LineChange { lines, kind }
+}
+
+#[test]
+fn test_added_modified_and_deleted_lines() {
+    let old = "fn main() {\n    let a = 1;\n    let b = 2;\n    println!();\n}\n";
+    let new = "// entry\nfn main() {\n    let a = 10;\n    println!();\n    done();\n}\n";
+
+    assert_eq!(
+        line_changes(old, new),
+        vec![
+            change(0..1, ChangeKind::Added),
+            // `let a` changed and `let b` went away: one modified run
+            change(2..3, ChangeKind::Modified),
+            change(4..5, ChangeKind::Added),
+        ]
+    );
+}
+
+#[test]
+fn test_deletions_point_at_the_next_line() {
+    let old = "a\nb\nc\nd\n";
+    assert_eq!(
+        line_changes(old, "a\nd\n"),
+        vec![change(1..1, ChangeKind::Deleted)]
+    );
+    assert_eq!(
+        line_changes(old, "a\nb\nc\n"),
+        vec![change(3..3, ChangeKind::Deleted)]
+    );
+    assert_eq!(line_changes(old, old), vec![]);
+    // a missing final newline is not a change of its own
+    assert_eq!(line_changes(old, "a\nb\nc\nd"), vec![]);
+}
+
+#[test]
+fn test_common_lines_inside_a_change_split_it() {
+    assert_eq!(
+        line_changes("x\na\ny\nb\n", "z\na\nb\nw\n"),
+        vec![
+            change(0..1, ChangeKind::Modified),
+            change(2..2, ChangeKind::Deleted),
+            change(3..4, ChangeKind::Added),
+        ]
+    );
+}
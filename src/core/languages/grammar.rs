@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use regex::Regex;
+use regex_syntax::hir::literal::{ExtractKind, Extractor};
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+use serde::Deserialize;
+
+use crate::core::lexer::{Edit, Lexer, Token, TokenKind, relex};
+
+/// How the text of a language is split into tokens.
+#[derive(Debug)]
+pub enum Grammar {
+    /// The hand-written Rust lexer in `core::lexer`.
+    Rust,
+    /// A tokenizer described by a JSON spec (see `assets/grammars`).
+    Declarative(DeclarativeGrammar),
+}
+
+/// The tokens of a text, and for a declarative grammar what
+/// [`Grammar::relex`] needs to pick lexing up in the middle of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lexed {
+    pub tokens: Vec<Token>,
+    // one per token of a declarative grammar
+    marks: Vec<Mark>,
+}
+
+/// Where a declarative lexer stood at the start of a token.
+#[derive(Debug, Clone, PartialEq)]
+struct Mark {
+    /// The state stack the token was lexed in.
+    stack: Arc<[usize]>,
+    /// End of the text looked at to lex the token; an edit starting before
+    /// it can change the token.
+    reach: usize,
+}
+
+impl Grammar {
+    pub fn lex(&self, src: &str) -> Lexed {
+        match self {
+            Grammar::Rust => Lexed {
+                tokens: Lexer::new(src).lex(),
+                marks: vec![],
+            },
+            Grammar::Declarative(grammar) => grammar.lex(src),
+        }
+    }
+
+    /// Re-lexes `src` after `edit` was applied to the text `old` came from,
+    /// starting around the edit and only as far as the tokens differ.
+    pub fn relex(&self, src: &str, old: &Lexed, edit: &Edit) -> Lexed {
+        match self {
+            Grammar::Rust => Lexed {
+                tokens: relex(src, &old.tokens, edit),
+                marks: vec![],
+            },
+            Grammar::Declarative(grammar) => grammar.relex(src, old, edit),
+        }
+    }
+}
+
+/// JSON shape of a grammar definition.
+///
+/// Example:
+/// ```json
+/// {
+///   "id": "toml",
+///   "name": "TOML",
+///   "extensions": ["toml"],
+///   "states": {
+///     "root": [
+///       { "match": "#.*", "token": "comment" },
+///       { "match": "\"\"\"", "token": "string", "push": "multiline" }
+///     ],
+///     "multiline": [
+///       { "match": "\"\"\"", "token": "string", "pop": true },
+///       { "match": "[^\"]+|\"", "token": "string" }
+///     ]
+///   }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct GrammarSpec {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Exact file names, e.g. `Makefile` or `.bashrc`.
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    /// Interpreter names matched against a `#!` line, e.g. `bash`.
+    #[serde(default)]
+    pub shebangs: Vec<String>,
+    /// Rule lists by state name; lexing starts in `root`.
+    pub states: HashMap<String, Vec<RuleSpec>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RuleSpec {
+    /// Regex matched at the current position.
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub token: TokenKind,
+    /// State entered after the match.
+    #[serde(default)]
+    pub push: Option<String>,
+    /// Leave the current state after the match.
+    #[serde(default)]
+    pub pop: bool,
+    /// Only try this rule at the beginning of a line.
+    #[serde(default)]
+    pub line_start: bool,
+}
+
+#[derive(Debug)]
+pub enum GrammarError {
+    Json(serde_json::Error),
+    Regex(regex::Error),
+    MissingRootState,
+    UnknownState(String),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarError::Json(e) => write!(f, "invalid grammar json: {}", e),
+            GrammarError::Regex(e) => write!(f, "invalid grammar regex: {}", e),
+            GrammarError::MissingRootState => write!(f, "grammar has no `root` state"),
+            GrammarError::UnknownState(name) => write!(f, "grammar pushes unknown state `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    token: TokenKind,
+    push: Option<usize>,
+    pop: bool,
+    line_start: bool,
+    // whether a match can run past the end of a line, or depends on the end
+    // of the text; other rules never look past the line they start on.
+    // Matches that can are expected to end as early as they can, like
+    // `(?s:.*?)`, so the text after them doesn't change them
+    multiline: bool,
+    // literals every match starts with, when known
+    prefixes: Option<Vec<Box<[u8]>>>,
+}
+
+impl Rule {
+    /// End of the text looked at to find that the rule doesn't match at
+    /// `pos`, when that is further than the line.
+    fn reach_on_failure(&self, src: &str, pos: usize) -> usize {
+        if !self.multiline {
+            return pos;
+        }
+        let rest = &src.as_bytes()[pos..];
+        match &self.prefixes {
+            // the text doesn't start the way a match would
+            Some(prefixes)
+                if !prefixes
+                    .iter()
+                    .any(|prefix| rest.starts_with(prefix) || prefix.starts_with(rest)) =>
+            {
+                let longest = prefixes.iter().map(|prefix| prefix.len()).max();
+                (pos + longest.unwrap_or(0)).min(src.len())
+            }
+            // e.g. an opening `--[[` without its `]]`, which can come with
+            // any edit later on
+            _ => src.len(),
+        }
+    }
+}
+
+/// Whether `hir` can match a line break or looks at the end of the text.
+fn spans_lines(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Empty => false,
+        HirKind::Literal(literal) => literal.0.contains(&b'\n'),
+        HirKind::Class(Class::Unicode(class)) => class
+            .ranges()
+            .iter()
+            .any(|range| range.start() <= '\n' && '\n' <= range.end()),
+        HirKind::Class(Class::Bytes(class)) => class
+            .ranges()
+            .iter()
+            .any(|range| range.start() <= b'\n' && b'\n' <= range.end()),
+        HirKind::Look(look) => *look == Look::End,
+        HirKind::Repetition(repetition) => spans_lines(&repetition.sub),
+        HirKind::Capture(capture) => spans_lines(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(spans_lines),
+    }
+}
+
+/// Offsets past the end of lines, looking for each line break only once
+/// while the offsets asked for go forward.
+struct LineEnds<'a> {
+    src: &'a str,
+    // the first line break at or after the last offset asked for
+    next: Option<usize>,
+}
+
+impl<'a> LineEnds<'a> {
+    fn new(src: &'a str) -> Self {
+        LineEnds { src, next: None }
+    }
+
+    /// Offset past the line break ending the line of `offset`, or the end of
+    /// the text.
+    fn after(&mut self, offset: usize) -> usize {
+        let next = match self.next {
+            Some(next) if next >= offset => next,
+            _ => {
+                let next = self.src[offset..]
+                    .find('\n')
+                    .map_or(self.src.len(), |i| offset + i);
+                *self.next.insert(next)
+            }
+        };
+        (next + 1).min(self.src.len())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeclarativeGrammar {
+    states: Vec<Vec<Rule>>,
+    root: usize,
+}
+
+impl DeclarativeGrammar {
+    pub fn compile(spec: &GrammarSpec) -> Result<Self, GrammarError> {
+        let mut names: Vec<&String> = spec.states.keys().collect();
+        names.sort();
+        let index_of = |name: &str| names.iter().position(|n| n.as_str() == name);
+
+        let root = index_of("root").ok_or(GrammarError::MissingRootState)?;
+
+        let mut states = Vec::with_capacity(names.len());
+        for name in &names {
+            let mut rules = Vec::new();
+            for rule in &spec.states[*name] {
+                let push = match &rule.push {
+                    Some(state) => Some(
+                        index_of(state).ok_or_else(|| GrammarError::UnknownState(state.clone()))?,
+                    ),
+                    None => None,
+                };
+                // anchor every rule at the current position
+                let regex = Regex::new(&format!(r"\A(?:{})", rule.pattern))
+                    .map_err(GrammarError::Regex)?;
+                let hir = regex_syntax::parse(&rule.pattern).ok();
+                let prefixes = hir.as_ref().and_then(|hir| {
+                    let prefixes = Extractor::new().kind(ExtractKind::Prefix).extract(hir);
+                    let literals = prefixes.literals()?;
+                    Some(literals.iter().map(|literal| literal.as_bytes().into()).collect())
+                });
+
+                rules.push(Rule {
+                    regex,
+                    token: rule.token,
+                    push,
+                    pop: rule.pop,
+                    line_start: rule.line_start,
+                    multiline: hir.as_ref().is_none_or(spans_lines),
+                    prefixes,
+                });
+            }
+            states.push(rules);
+        }
+
+        Ok(Self { states, root })
+    }
+
+    pub fn lex(&self, src: &str) -> Lexed {
+        let mut lexed = Lexed::default();
+        let mut stack: Arc<[usize]> = Arc::from([self.root]);
+        let mut line_ends = LineEnds::new(src);
+        let mut pos = 0;
+        while pos < src.len() {
+            pos = self.push_token(src, pos, &mut stack, &mut line_ends, &mut lexed);
+        }
+        lexed
+    }
+
+    /// Re-lexes `src` after `edit` was applied to the text `old` came from.
+    ///
+    /// Lexing restarts at the first token that looked at the edited text,
+    /// in the state stack it had, and stops once a token starts where a
+    /// shifted old one started, in the same state: from there on the tokens
+    /// are the old ones.
+    pub fn relex(&self, src: &str, old: &Lexed, edit: &Edit) -> Lexed {
+        let restart = old
+            .marks
+            .iter()
+            .position(|mark| mark.reach >= edit.range.start)
+            .unwrap_or(0);
+        let (mut pos, mut stack) = match (old.tokens.get(restart), old.marks.get(restart)) {
+            (Some(token), Some(mark)) => (token.range.start, mark.stack.clone()),
+            _ => (0, Arc::from([self.root])),
+        };
+        let mut lexed = Lexed {
+            tokens: old.tokens[..restart].to_vec(),
+            marks: old.marks[..restart].to_vec(),
+        };
+
+        let mut line_ends = LineEnds::new(src);
+        let edit_end = edit.range.start + edit.new_len;
+        while pos < src.len() {
+            // `line_start` rules look at the char before a token too, so
+            // the text is the old one only past the edit's end
+            if pos > edit_end {
+                let old_pos = pos - edit.new_len + edit.range.len();
+                let synced = old
+                    .tokens
+                    .binary_search_by_key(&old_pos, |token| token.range.start)
+                    .ok()
+                    .filter(|&idx| old.marks.get(idx).is_some_and(|mark| mark.stack == stack));
+                if let Some(idx) = synced {
+                    lexed.tokens.extend(old.tokens[idx..].iter().map(|token| Token {
+                        kind: token.kind,
+                        range: edit.shift(token.range.start)..edit.shift(token.range.end),
+                    }));
+                    lexed.marks.extend(old.marks[idx..].iter().map(|mark| Mark {
+                        stack: mark.stack.clone(),
+                        reach: edit.shift(mark.reach),
+                    }));
+                    return lexed;
+                }
+            }
+            pos = self.push_token(src, pos, &mut stack, &mut line_ends, &mut lexed);
+        }
+        lexed
+    }
+
+    /// Lexes the token at `pos` in the state on top of `stack` into `lexed`
+    /// and returns where the next one starts.
+    fn push_token(
+        &self,
+        src: &str,
+        pos: usize,
+        stack: &mut Arc<[usize]>,
+        line_ends: &mut LineEnds,
+        lexed: &mut Lexed,
+    ) -> usize {
+        let rest = &src[pos..];
+        let at_line_start = pos == 0 || src.as_bytes()[pos - 1] == b'\n';
+        let state = *stack.last().expect("the root state is never popped");
+        let mark_stack = stack.clone();
+
+        let mut reach = pos;
+        let mut matched = None;
+        for rule in self.states[state]
+            .iter()
+            .filter(|rule| at_line_start || !rule.line_start)
+        {
+            if let Some(m) = rule.regex.find(rest).filter(|m| !m.is_empty()) {
+                matched = Some((rule, m.end()));
+                break;
+            }
+            reach = reach.max(rule.reach_on_failure(src, pos));
+        }
+
+        let (kind, len) = match matched {
+            Some((rule, len)) => {
+                if (rule.pop && stack.len() > 1) || rule.push.is_some() {
+                    let mut next = stack.to_vec();
+                    if rule.pop && next.len() > 1 {
+                        next.pop();
+                    }
+                    next.extend(rule.push);
+                    *stack = next.into();
+                }
+                (rule.token, len)
+            }
+            None => {
+                let ch = rest.chars().next().expect("pos is before the end");
+                if ch.is_whitespace() {
+                    let len = rest
+                        .find(|c: char| !c.is_whitespace())
+                        .unwrap_or(rest.len());
+                    (TokenKind::Whitespace, len)
+                } else {
+                    (TokenKind::Unknown, ch.len_utf8())
+                }
+            }
+        };
+
+        let end = pos + len;
+        lexed.tokens.push(Token {
+            kind,
+            range: pos..end,
+        });
+        // the rest of the line the token ends on, for rules like `$` or `\b`
+        // looking past the match
+        lexed.marks.push(Mark {
+            stack: mark_stack,
+            reach: reach.max(line_ends.after(end)),
+        });
+        end
+    }
+}
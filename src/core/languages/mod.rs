@@ -0,0 +1,7 @@
+pub mod grammar;
+pub mod registry;
+#[cfg(test)]
+mod tests;
+
+pub use grammar::{DeclarativeGrammar, Grammar, GrammarError, GrammarSpec, Lexed};
+pub use registry::{Language, LanguageRegistry, language_registry};
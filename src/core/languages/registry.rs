@@ -0,0 +1,146 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::core::languages::grammar::{DeclarativeGrammar, Grammar, GrammarError, GrammarSpec};
+
+/// A language the editor knows how to highlight.
+#[derive(Debug)]
+pub struct Language {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+    pub shebangs: Vec<String>,
+    pub grammar: Grammar,
+}
+
+impl Language {
+    pub fn from_spec(spec: GrammarSpec) -> Result<Self, GrammarError> {
+        let grammar = Grammar::Declarative(DeclarativeGrammar::compile(&spec)?);
+        Ok(Self {
+            id: spec.id,
+            name: spec.name,
+            extensions: spec.extensions,
+            filenames: spec.filenames,
+            shebangs: spec.shebangs,
+            grammar,
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, GrammarError> {
+        let spec: GrammarSpec = serde_json::from_str(json).map_err(GrammarError::Json)?;
+        Self::from_spec(spec)
+    }
+
+    fn rust() -> Self {
+        Self {
+            id: "rust".to_string(),
+            name: "Rust".to_string(),
+            extensions: vec!["rs".to_string()],
+            filenames: vec![],
+            shebangs: vec![],
+            grammar: Grammar::Rust,
+        }
+    }
+}
+
+/// Grammar definitions shipped with the editor.
+const BUILTIN_GRAMMARS: &[&str] = &[
+    include_str!("../../../assets/grammars/toml.json"),
+    include_str!("../../../assets/grammars/json.json"),
+    include_str!("../../../assets/grammars/markdown.json"),
+    include_str!("../../../assets/grammars/lua.json"),
+    include_str!("../../../assets/grammars/shell.json"),
+];
+
+/// Maps file names and `#!` lines to languages.
+pub struct LanguageRegistry {
+    languages: Vec<Rc<Language>>,
+}
+
+impl LanguageRegistry {
+    pub fn new() -> Self {
+        Self {
+            languages: Vec::new(),
+        }
+    }
+
+    /// Registry with Rust and every grammar from `assets/grammars`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Language::rust());
+        for json in BUILTIN_GRAMMARS {
+            match Language::from_json(json) {
+                Ok(language) => registry.register(language),
+                Err(e) => eprintln!("Failed to load builtin grammar: {}", e),
+            }
+        }
+        registry
+    }
+
+    pub fn register(&mut self, language: Language) {
+        self.languages.retain(|l| l.id != language.id);
+        self.languages.push(Rc::new(language));
+    }
+
+    pub fn by_id(&self, id: &str) -> Option<Rc<Language>> {
+        self.languages.iter().find(|l| l.id == id).cloned()
+    }
+
+    /// Looks the language up by exact file name first, then by extension.
+    pub fn for_path(&self, path: &Path) -> Option<Rc<Language>> {
+        let file_name = path.file_name()?.to_str()?;
+        if let Some(language) = self
+            .languages
+            .iter()
+            .find(|l| l.filenames.iter().any(|f| f == file_name))
+        {
+            return Some(language.clone());
+        }
+
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.languages
+            .iter()
+            .find(|l| l.extensions.contains(&extension))
+            .cloned()
+    }
+
+    /// Looks the language up from a `#!/usr/bin/env bash` style first line.
+    pub fn for_shebang(&self, first_line: &str) -> Option<Rc<Language>> {
+        let interpreter = shebang_interpreter(first_line)?;
+        self.languages
+            .iter()
+            .find(|l| l.shebangs.iter().any(|s| s == interpreter))
+            .cloned()
+    }
+
+    /// Picks the language of a file from its path, falling back to its `#!` line.
+    pub fn detect(&self, path: &Path, content: &str) -> Option<Rc<Language>> {
+        self.for_path(path)
+            .or_else(|| self.for_shebang(content.lines().next().unwrap_or("")))
+    }
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `#!/usr/bin/env -S lua5.4 -i` -> `lua`, `#!/bin/bash` -> `bash`
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut parts = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|arg| !arg.starts_with('-'))?;
+    }
+    Some(program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+}
+
+thread_local! {
+    static REGISTRY: Rc<LanguageRegistry> = Rc::new(LanguageRegistry::with_builtins());
+}
+
+pub fn language_registry() -> Rc<LanguageRegistry> {
+    REGISTRY.with(|r| r.clone())
+}
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use crate::core::languages::{Language, LanguageRegistry};
+use crate::core::lexer::{Edit, TokenKind};
+
+fn lex<'a>(language: &Language, src: &'a str) -> Vec<(TokenKind, &'a str)> {
+    let tokens = language.grammar.lex(src).tokens;
+
+    // declarative grammars must cover the input without gaps, like the Rust lexer
+    let mut expected_start = 0;
+    for token in &tokens {
+        assert_eq!(token.range.start, expected_start);
+        expected_start = token.range.end;
+    }
+    assert_eq!(expected_start, src.len());
+
+    tokens
+        .into_iter()
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .map(|t| (t.kind, &src[t.range]))
+        .collect()
+}
+
+#[test]
+fn test_builtin_grammars_compile() {
+    let registry = LanguageRegistry::with_builtins();
+    for id in ["rust", "toml", "json", "markdown", "lua", "shell"] {
+        assert!(registry.by_id(id).is_some(), "missing builtin language {id}");
+    }
+}
+
+#[test]
+fn test_detect_by_path_and_shebang() {
+    let registry = LanguageRegistry::with_builtins();
+    let id = |path: &str, content: &str| registry.detect(Path::new(path), content).map(|l| l.id.clone());
+
+    assert_eq!(id("src/main.rs", ""), Some("rust".to_string()));
+    assert_eq!(id("Cargo.toml", ""), Some("toml".to_string()));
+    assert_eq!(id("Cargo.lock", ""), Some("toml".to_string()));
+    assert_eq!(id("README.MD", ""), Some("markdown".to_string()));
+    assert_eq!(id("/home/me/.bashrc", ""), Some("shell".to_string()));
+    assert_eq!(id("scripts/deploy", "#!/usr/bin/env bash\necho hi"), Some("shell".to_string()));
+    assert_eq!(id("tool", "#!/usr/bin/lua5.4\nprint(1)"), Some("lua".to_string()));
+    assert_eq!(id("notes.txt", "plain"), None);
+}
+
+#[test]
+fn test_declarative_states() {
+    let registry = LanguageRegistry::with_builtins();
+    let toml = registry.by_id("toml").unwrap();
+
+    assert_eq!(
+        lex(&toml, "[package]\nname = \"riff\" # c\ndoc = \"\"\"a\n\\\"b\"\"\"\n"),
+        vec![
+            (TokenKind::Keyword, "[package]"),
+            (TokenKind::Ident, "name"),
+            (TokenKind::Operator, "="),
+            (TokenKind::String, "\"riff\""),
+            (TokenKind::Comment, "# c"),
+            (TokenKind::Ident, "doc"),
+            (TokenKind::Operator, "="),
+            (TokenKind::String, "\"\"\""),
+            (TokenKind::String, "a\n"),
+            (TokenKind::Char, "\\\""),
+            (TokenKind::String, "b"),
+            (TokenKind::String, "\"\"\""),
+        ]
+    );
+}
+
+#[test]
+fn test_line_start_rules() {
+    let registry = LanguageRegistry::with_builtins();
+    let markdown = registry.by_id("markdown").unwrap();
+
+    let tokens = lex(&markdown, "# Title\ntext # not a heading\n```rust\nfn x() {}\n```\n");
+    assert_eq!(tokens[0], (TokenKind::Keyword, "# Title"));
+    assert!(!tokens.contains(&(TokenKind::Keyword, "# not a heading")));
+    assert!(tokens.contains(&(TokenKind::String, "fn x() {}")));
+}
+
+#[test]
+fn test_invalid_spec_is_rejected() {
+    let missing_root = r#"{ "id": "x", "name": "X", "states": { "other": [] } }"#;
+    assert!(Language::from_json(missing_root).is_err());
+
+    let unknown_state = r#"{ "id": "x", "name": "X", "states": { "root": [
+        { "match": "a", "token": "ident", "push": "nowhere" }
+    ] } }"#;
+    assert!(Language::from_json(unknown_state).is_err());
+}
+
+/// Applies `range -> replacement` to `old` and checks that re-lexing only
+/// around the edit gives exactly what lexing the new text does.
+fn assert_relex(language: &Language, old: &str, range: std::ops::Range<usize>, replacement: &str) {
+    let mut new = old.to_string();
+    new.replace_range(range, replacement);
+
+    let grammar = &language.grammar;
+    let edit = Edit::diff(old, &new).expect("texts differ");
+    assert_eq!(
+        grammar.relex(&new, &grammar.lex(old), &edit),
+        grammar.lex(&new),
+        "{old:?} -> {new:?}"
+    );
+}
+
+/// Tries `inserts` at every position of `src`, and deleting every char.
+fn assert_relex_everywhere(language: &Language, src: &str, inserts: &[&str]) {
+    for pos in (0..=src.len()).filter(|&p| src.is_char_boundary(p)) {
+        for insert in inserts {
+            assert_relex(language, src, pos..pos, insert);
+        }
+        if let Some(ch) = src[pos..].chars().next() {
+            assert_relex(language, src, pos..pos + ch.len_utf8(), "");
+        }
+    }
+}
+
+#[test]
+fn test_relex_markdown() {
+    let registry = LanguageRegistry::with_builtins();
+    let markdown = registry.by_id("markdown").unwrap();
+
+    let src = "# Title\n\ntext *em* `code`\n\n```rust\nfn x() {}\n```\n\n- item\nend\n";
+    // opening a fence turns the rest into code, up to the old opening one
+    assert_relex(&markdown, src, 9..9, "```\n");
+    // closing it early
+    assert_relex(&markdown, src, 35..35, "```\n");
+    // a line break before `#` makes it a heading
+    assert_relex(&markdown, "a # b\nc\n", 2..2, "\n");
+    // an unclosed comment is closed lines later
+    assert_relex(&markdown, "<!-- a\nb\nc -\nd\n", 12..12, "->");
+
+    assert_relex_everywhere(&markdown, src, &["`", "```", "\n", "*", "#", " ", "<!--", "-->"]);
+}
+
+#[test]
+fn test_relex_lua() {
+    let registry = LanguageRegistry::with_builtins();
+    let lua = registry.by_id("lua").unwrap();
+
+    let src = "local x = 1 -- one\n--[[ block\ncomment ]]\nprint(\"a\\\"b\", [[long\nstring]])\nreturn x\n";
+    // an unclosed block comment, closed lines later
+    assert_relex(&lua, "--[[ a\nb\nc\nlocal d\n", 11..11, "]]");
+    // opening a long string swallows everything up to the next `]]`
+    assert_relex(&lua, src, 0..0, "[[");
+    // a keyword grows into an identifier
+    assert_relex(&lua, src, 5..5, "ly");
+
+    assert_relex_everywhere(&lua, src, &["--", "[[", "]]", "\"", "\n", "x", " ", "-"]);
+}
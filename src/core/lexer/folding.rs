@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use crate::core::lexer::token::{Token, TokenKind};
+
+/// Regions of `src` that can be folded, as zero-based lines from the one
+/// that stays visible to the first one shown again after the folded ones.
+///
+/// A region runs from the line of an opening brace, bracket or parenthesis
+/// to the line of its closing one, or from a line to the last of the more
+/// indented lines after it, so languages without braces fold too. Of the
+/// regions starting on one line (e.g. `foo(|x| {`) the outermost one is
+/// kept. Sorted by their first line.
+pub fn fold_ranges(src: &str, tokens: &[Token]) -> Vec<Range<usize>> {
+    let mut ranges = delimiter_ranges(src, tokens);
+    ranges.extend(indent_ranges(src));
+    ranges.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+    ranges.dedup_by_key(|range| range.start);
+    ranges
+}
+
+/// Unbalanced delimiters are skipped.
+fn delimiter_ranges(src: &str, tokens: &[Token]) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    // open delimiters with the line they are on
+    let mut open: Vec<(char, usize)> = vec![];
+    let mut line = 0;
+    for token in tokens {
+        let text = &src[token.range.clone()];
+        if token.kind == TokenKind::Punct {
+            match text {
+                "{" | "[" | "(" => open.push((text.chars().next().unwrap(), line)),
+                "}" | "]" | ")" => {
+                    let opening = match text {
+                        "}" => '{',
+                        "]" => '[',
+                        _ => '(',
+                    };
+                    // drop what was left open inside, e.g. a stray `(`
+                    if let Some(index) = open.iter().rposition(|(c, _)| *c == opening) {
+                        let (_, start) = open[index];
+                        open.truncate(index);
+                        // the closing line stays visible, so something
+                        // has to be between the two
+                        if line > start + 1 {
+                            ranges.push(start..line);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        line += text.matches('\n').count();
+    }
+    ranges
+}
+
+/// Blank lines belong to the region they are in, not to the one after.
+fn indent_ranges(src: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    // lines that may start a region, with their indentation
+    let mut open: Vec<(usize, usize)> = vec![];
+    let mut last_text_line = 0;
+    for (line, text) in src.lines().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+        let indent = indentation(text);
+        while let Some(&(open_indent, start)) = open.last() {
+            if open_indent < indent {
+                break;
+            }
+            open.pop();
+            if last_text_line > start {
+                ranges.push(start..last_text_line + 1);
+            }
+        }
+        open.push((indent, line));
+        last_text_line = line;
+    }
+    for (_, start) in open {
+        if last_text_line > start {
+            ranges.push(start..last_text_line + 1);
+        }
+    }
+    ranges
+}
+
+/// Width of the leading whitespace, a tab counting as four spaces.
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
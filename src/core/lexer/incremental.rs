@@ -0,0 +1,108 @@
+use std::ops::Range;
+
+use crate::core::lexer::lexer::Lexer;
+use crate::core::lexer::token::Token;
+
+/// A single text change: the bytes in `range` of the old text were replaced
+/// by `new_len` bytes of new text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub new_len: usize,
+}
+
+impl Edit {
+    /// Finds the smallest edit turning `old` into `new` by trimming their
+    /// common prefix and suffix. Returns `None` when the texts are equal.
+    pub fn diff(old: &str, new: &str) -> Option<Edit> {
+        if old == new {
+            return None;
+        }
+
+        let mut prefix = old
+            .bytes()
+            .zip(new.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !old.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let mut suffix = old
+            .bytes()
+            .rev()
+            .zip(new.bytes().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !old.is_char_boundary(old.len() - suffix) {
+            suffix -= 1;
+        }
+
+        Some(Edit {
+            range: prefix..old.len() - suffix,
+            new_len: new.len() - suffix - prefix,
+        })
+    }
+
+    /// A single edit doing `self` and then `next`, which is located in the
+    /// text `self` left.
+    pub fn then(&self, next: &Edit) -> Edit {
+        let start = self.range.start.min(next.range.start);
+        // the end of both in the text between them; past `self` that is
+        // shifted by what `self` added
+        let end = (self.range.start + self.new_len).max(next.range.end);
+        let old_end = end + self.range.len() - self.new_len;
+        let new_end = end + next.new_len - next.range.len();
+        Edit {
+            range: start..old_end,
+            new_len: new_end - start,
+        }
+    }
+
+    /// Maps an offset in the old text that lies after the edit into the new text.
+    pub fn shift(&self, old_offset: usize) -> usize {
+        old_offset - self.range.end + self.range.start + self.new_len
+    }
+}
+
+/// Re-lexes `src` after `edit` was applied to the text `old_tokens` came from.
+///
+/// Lexing restarts at the first token touching the edited line (the lexer has
+/// no state besides its position, so any earlier token boundary is safe) and
+/// stops as soon as a new token starts where a shifted old token started
+/// after the edit: from there on the text, and therefore the tokens, are the same.
+/// Multi-line tokens (block comments, raw strings) simply keep the lexer going
+/// until such a boundary is found, or to the end of the input.
+pub fn relex(src: &str, old_tokens: &[Token], edit: &Edit) -> Vec<Token> {
+    let line_start = src[..edit.range.start]
+        .rfind('\n')
+        .map_or(0, |idx| idx + 1);
+
+    // tokens ending at `line_start` may have peeked at the first char of the line
+    let restart = old_tokens.partition_point(|t| t.range.end < line_start);
+    let restart_pos = old_tokens.get(restart).map_or(line_start, |t| t.range.start);
+
+    let mut tokens = old_tokens[..restart].to_vec();
+    let mut lexer = Lexer::starting_at(src, restart_pos);
+    let edit_end = edit.range.start + edit.new_len;
+
+    while let Some(pos) = lexer.position() {
+        if pos >= edit_end {
+            let old_pos = pos - edit.new_len + edit.range.end - edit.range.start;
+            let synced = old_tokens.binary_search_by_key(&old_pos, |t| t.range.start);
+            if let Ok(idx) = synced {
+                tokens.extend(old_tokens[idx..].iter().map(|t| Token {
+                    kind: t.kind,
+                    range: edit.shift(t.range.start)..edit.shift(t.range.end),
+                }));
+                return tokens;
+            }
+        }
+
+        tokens.push(lexer.next_token());
+    }
+
+    tokens
+}
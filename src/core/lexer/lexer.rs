@@ -1,21 +1,44 @@
 use crate::core::lexer::token::{Token, TokenKind};
 
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", // reserved
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Operators ordered so that the longest match wins.
+const OPERATORS: &[&str] = &[
+    "<<=", ">>=", "...", "..=", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..",
+];
+
 pub struct Lexer<'a> {
     src: &'a str,
-    chars: Vec<(usize, char)>,
     pos: usize,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(src: &'a str) -> Self {
-        let chars: Vec<_> = src.char_indices().collect();
-        Self { src, chars, pos: 0 }
+        Self { src, pos: 0 }
+    }
+
+    /// Lexer that starts at byte `pos`, which must be a token boundary.
+    pub fn starting_at(src: &'a str, pos: usize) -> Self {
+        Self { src, pos }
+    }
+
+    /// Byte offset of the next token, or `None` at the end of the input.
+    pub fn position(&self) -> Option<usize> {
+        (self.pos < self.src.len()).then_some(self.pos)
     }
 
     pub fn lex(mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
-        while self.pos < self.chars.len() {
+        while self.pos < self.src.len() {
             let token = self.next_token();
             tokens.push(token);
         }
@@ -23,102 +46,304 @@ impl<'a> Lexer<'a> {
         tokens
     }
 
-    fn next_token(&mut self) -> Token {
-        let start_byte = self.chars[self.pos].0;
-        let c = self.chars[self.pos].1;
+    pub(crate) fn next_token(&mut self) -> Token {
+        let start = self.pos;
+        let kind = self.scan();
+        debug_assert!(self.pos > start, "lexer must always make progress");
+
+        Token {
+            kind,
+            range: start..self.pos,
+        }
+    }
+
+    fn scan(&mut self) -> TokenKind {
+        let c = self.peek().expect("scan is only called before the end of input");
 
         // 1. whitespace
         if c.is_whitespace() {
-            self.consume_while(|_, ch| ch.is_whitespace());
-            let end_byte = if self.pos == self.chars.len() {
-                self.src.len()
-            } else {
-                self.chars[self.pos].0
-            };
-            return Token {
-                kind: TokenKind::Whitespace,
-                range: start_byte..end_byte,
-            };
+            self.eat_while(char::is_whitespace);
+            return TokenKind::Whitespace;
+        }
+
+        // 2. comments
+        if self.starts_with("//") {
+            return self.line_comment();
+        }
+        if self.starts_with("/*") {
+            return self.block_comment();
+        }
+
+        // 3. literals with a prefix: b'x', b"..", br"..", r"..", r#ident, c".."
+        if let Some(kind) = self.prefixed_literal() {
+            return kind;
         }
 
-        // 2. line comment //
-        if c == '/' && self.peek_char() == Some('/') {
-            self.pos += 2; // пропускаем //
-            self.consume_while(|_, ch| ch != '\n');
-            let end_byte = if self.pos == self.chars.len() {
-                self.src.len()
+        // 4. identifiers and keywords
+        if is_ident_start(c) {
+            let start = self.pos;
+            self.eat_while(is_ident_continue);
+            return if KEYWORDS.contains(&&self.src[start..self.pos]) {
+                TokenKind::Keyword
             } else {
-                self.chars[self.pos].0
-            };
-            return Token {
-                kind: TokenKind::Comment,
-                range: start_byte..end_byte,
+                TokenKind::Ident
             };
         }
 
-        // 3. string literal "..."
+        // 5. string literal "..."
         if c == '"' {
-            self.pos += 1; // пропускаем открывающую "
-            self.consume_while(|_, ch| ch != '"'); // простая строка, не поддерживает экранирование здесь
-            if self.pos < self.chars.len() && self.chars[self.pos].1 == '"' {
-                self.pos += 1; // закрывающая "
-            }
-            let end_byte = if self.pos == self.chars.len() {
-                self.src.len()
-            } else {
-                self.chars[self.pos].0
-            };
-            return Token {
-                kind: TokenKind::String,
-                range: start_byte..end_byte,
-            };
+            self.bump();
+            self.quoted('"');
+            return TokenKind::String;
         }
 
-        // 4. number
+        // 6. char literal or lifetime
+        if c == '\'' {
+            return self.char_or_lifetime();
+        }
+
+        // 7. number
         if c.is_ascii_digit() {
-            self.consume_while(|_, ch| ch.is_ascii_digit());
-            let end_byte = if self.pos == self.chars.len() {
-                self.src.len()
-            } else {
-                self.chars[self.pos].0
-            };
-            return Token {
-                kind: TokenKind::Number,
-                range: start_byte..end_byte,
-            };
+            self.number();
+            return TokenKind::Number;
+        }
+
+        // 8. operators and punctuation
+        if self.starts_with("::") {
+            self.pos += 2;
+            return TokenKind::Punct;
         }
+        if let Some(op) = OPERATORS.iter().find(|op| self.starts_with(op)) {
+            self.pos += op.len();
+            return TokenKind::Operator;
+        }
+
+        self.bump();
+        match c {
+            '(' | ')' | '[' | ']' | '{' | '}' | ',' | ';' | ':' | '.' | '#' | '?' | '@' | '$' => {
+                TokenKind::Punct
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '!' | '&' | '|' | '=' | '<' | '>' | '~' => {
+                TokenKind::Operator
+            }
+            // 9. unknown
+            _ => TokenKind::Unknown,
+        }
+    }
 
-        // 5. unknown
-        self.pos += 1;
-        let end_byte = if self.pos == self.chars.len() {
-            self.src.len()
+    fn line_comment(&mut self) -> TokenKind {
+        // `///` is a doc comment but `////` is a plain one; `//!` is an inner doc comment.
+        let is_doc = (self.starts_with("///") && !self.starts_with("////"))
+            || self.starts_with("//!");
+        self.eat_while(|ch| ch != '\n');
+        if is_doc {
+            TokenKind::DocComment
         } else {
-            self.chars[self.pos].0
-        };
-        Token {
-            kind: TokenKind::Unknown,
-            range: start_byte..end_byte,
+            TokenKind::Comment
         }
     }
 
-    fn peek_char(&self) -> Option<char> {
-        if self.pos + 1 < self.chars.len() {
-            Some(self.chars[self.pos + 1].1)
+    fn block_comment(&mut self) -> TokenKind {
+        // `/**/` and `/***` are plain comments, `/** */` and `/*! */` are docs.
+        let is_doc = (self.starts_with("/**") && !self.starts_with("/**/") && !self.starts_with("/***"))
+            || self.starts_with("/*!");
+        self.pos += 2;
+
+        let mut depth = 1usize;
+        while depth > 0 && self.pos < self.src.len() {
+            if self.starts_with("/*") {
+                self.pos += 2;
+                depth += 1;
+            } else if self.starts_with("*/") {
+                self.pos += 2;
+                depth -= 1;
+            } else {
+                self.bump();
+            }
+        }
+
+        if is_doc {
+            TokenKind::DocComment
         } else {
-            None
+            TokenKind::Comment
+        }
+    }
+
+    fn prefixed_literal(&mut self) -> Option<TokenKind> {
+        let rest = &self.src[self.pos..];
+
+        if rest.starts_with("b'") {
+            self.pos += 1;
+            return Some(self.char_or_lifetime());
+        }
+        if rest.starts_with("b\"") {
+            self.pos += 2;
+            self.quoted('"');
+            return Some(TokenKind::ByteString);
+        }
+        if rest.starts_with("c\"") {
+            self.pos += 2;
+            self.quoted('"');
+            return Some(TokenKind::String);
+        }
+        if rest.starts_with("br") && self.raw_string_ahead(2) {
+            self.pos += 2;
+            self.raw_string();
+            return Some(TokenKind::ByteString);
+        }
+        if rest.starts_with("cr") && self.raw_string_ahead(2) {
+            self.pos += 2;
+            self.raw_string();
+            return Some(TokenKind::String);
+        }
+        if rest.starts_with('r') && self.raw_string_ahead(1) {
+            self.pos += 1;
+            self.raw_string();
+            return Some(TokenKind::RawString);
+        }
+        if rest.starts_with("r#") && rest[2..].chars().next().is_some_and(is_ident_start) {
+            // raw identifier: r#match
+            self.pos += 2;
+            self.eat_while(is_ident_continue);
+            return Some(TokenKind::Ident);
+        }
+
+        None
+    }
+
+    /// Whether a raw string (`#*"`) starts `offset` bytes after the current position.
+    fn raw_string_ahead(&self, offset: usize) -> bool {
+        self.src[self.pos + offset..]
+            .trim_start_matches('#')
+            .starts_with('"')
+    }
+
+    /// Consumes `#*"..."#*` with the same number of hashes on both sides.
+    fn raw_string(&mut self) {
+        let hashes = self.src[self.pos..]
+            .bytes()
+            .take_while(|&b| b == b'#')
+            .count();
+        self.pos += hashes + 1; // hashes and the opening quote
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+        match self.src[self.pos..].find(&closing) {
+            Some(idx) => self.pos += idx + closing.len(),
+            None => self.pos = self.src.len(),
         }
     }
 
-    fn consume_while<F>(&mut self, mut f: F)
+    /// Consumes the body of a quoted literal up to and including `quote`,
+    /// skipping over escape sequences. Unterminated literals run to the end.
+    fn quoted(&mut self, quote: char) {
+        while let Some(ch) = self.bump() {
+            if ch == '\\' {
+                self.bump();
+            } else if ch == quote {
+                break;
+            }
+        }
+    }
+
+    fn char_or_lifetime(&mut self) -> TokenKind {
+        self.bump(); // opening '
+
+        let mut chars = self.src[self.pos..].chars();
+        let (first, second) = (chars.next(), chars.next());
+
+        match (first, second) {
+            (Some('\\'), _) => {
+                self.quoted('\'');
+                TokenKind::Char
+            }
+            (Some(ch), Some('\'')) if ch != '\n' => {
+                self.bump();
+                self.bump();
+                TokenKind::Char
+            }
+            (Some(ch), _) if is_ident_start(ch) => {
+                self.eat_while(is_ident_continue);
+                TokenKind::Lifetime
+            }
+            _ => TokenKind::Unknown,
+        }
+    }
+
+    fn number(&mut self) {
+        if self.starts_with("0x") || self.starts_with("0o") || self.starts_with("0b") {
+            self.pos += 2;
+            // hex digits and the type suffix are both alphanumeric
+            self.eat_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            return;
+        }
+
+        self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+
+        // fractional part, but not a range (`1..2`) or a method call (`1.max(2)`)
+        let mut chars = self.src[self.pos..].chars();
+        if chars.next() == Some('.') {
+            match chars.next() {
+                Some('.') => return,
+                Some(ch) if is_ident_start(ch) => return,
+                _ => {
+                    self.bump();
+                    self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+                }
+            }
+        }
+
+        // exponent: 1e10, 2.5E-3
+        let mut chars = self.src[self.pos..].chars();
+        if matches!(chars.next(), Some('e' | 'E')) {
+            let digits_at = match chars.next() {
+                Some('+' | '-') => 2,
+                _ => 1,
+            };
+            if self.src[self.pos + digits_at..]
+                .chars()
+                .next()
+                .is_some_and(|ch| ch.is_ascii_digit())
+            {
+                self.pos += digits_at;
+                self.eat_while(|ch| ch.is_ascii_digit() || ch == '_');
+            }
+        }
+
+        // suffix: u8, i64, f32, usize
+        self.eat_while(is_ident_continue);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn starts_with(&self, pat: &str) -> bool {
+        self.src[self.pos..].starts_with(pat)
+    }
+
+    fn eat_while<F>(&mut self, mut f: F)
     where
-        F: FnMut(usize, char) -> bool,
+        F: FnMut(char) -> bool,
     {
-        while self.pos < self.chars.len() {
-            let (i, c) = self.chars[self.pos];
-            if !f(i, c) {
+        while let Some(ch) = self.peek() {
+            if !f(ch) {
                 break;
             }
-            self.pos += 1;
+            self.pos += ch.len_utf8();
         }
     }
 }
+
+fn is_ident_start(ch: char) -> bool {
+    ch == '_' || ch.is_alphabetic()
+}
+
+fn is_ident_continue(ch: char) -> bool {
+    ch == '_' || ch.is_alphanumeric()
+}
@@ -1,5 +1,13 @@
+pub mod folding;
+pub mod incremental;
 pub mod lexer;
+pub mod outline;
 pub mod token;
+#[cfg(test)]
+mod tests;
 
+pub use folding::fold_ranges;
+pub use incremental::{Edit, relex};
 pub use lexer::Lexer;
+pub use outline::{OutlineItem, OutlineKind, outline};
 pub use token::{Token, TokenKind};
@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use crate::core::lexer::token::{Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlineKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Module,
+}
+
+impl OutlineKind {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "fn" => OutlineKind::Function,
+            "struct" => OutlineKind::Struct,
+            "enum" => OutlineKind::Enum,
+            "trait" => OutlineKind::Trait,
+            "impl" => OutlineKind::Impl,
+            "mod" => OutlineKind::Module,
+            _ => return None,
+        })
+    }
+}
+
+/// An item found by [`outline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineItem {
+    pub kind: OutlineKind,
+    /// The identifier, or the header of an `impl` (`Display for Foo`).
+    pub name: String,
+    /// From the keyword to the closing `}` or `;`.
+    pub range: Range<usize>,
+    pub name_range: Range<usize>,
+    pub children: Vec<OutlineItem>,
+}
+
+/// Keywords that may stand right before the keyword of an item.
+const MODIFIERS: &[&str] = &["pub", "unsafe", "async", "const", "extern", "default"];
+
+/// `fn`, `struct`, `enum`, `trait`, `impl` and `mod` items of `src`, nested
+/// by their braces. A stand-in for the document symbols of a language server,
+/// so it only looks at the token stream and never fails: anything it doesn't
+/// understand is skipped.
+pub fn outline(src: &str, tokens: &[Token]) -> Vec<OutlineItem> {
+    let tokens: Vec<&Token> = tokens.iter().filter(|t| !t.kind.is_trivia()).collect();
+    let text = |i: usize| &src[tokens[i].range.clone()];
+
+    let mut roots = vec![];
+    // open items with the brace depth inside their body
+    let mut open: Vec<(OutlineItem, usize)> = vec![];
+    let mut depth = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        match (tokens[i].kind, text(i)) {
+            (TokenKind::Punct, "{") => depth += 1,
+            (TokenKind::Punct, "}") => {
+                if open.last().is_some_and(|(_, body)| *body == depth) {
+                    let (mut item, _) = open.pop().unwrap();
+                    item.range.end = tokens[i].range.end;
+                    attach(&mut open, &mut roots, item);
+                }
+                depth = depth.saturating_sub(1);
+            }
+            (TokenKind::Keyword, keyword) => {
+                if let Some(kind) = OutlineKind::from_keyword(keyword)
+                    && at_item_start(src, &tokens, i)
+                    && let Some((item, end)) = item_header(src, &tokens, i, kind)
+                {
+                    if text(end) == "{" {
+                        depth += 1;
+                        open.push((item, depth));
+                    } else {
+                        attach(&mut open, &mut roots, item);
+                    }
+                    i = end;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    while let Some((mut item, _)) = open.pop() {
+        item.range.end = src.len();
+        attach(&mut open, &mut roots, item);
+    }
+    roots
+}
+
+fn attach(open: &mut [(OutlineItem, usize)], roots: &mut Vec<OutlineItem>, item: OutlineItem) {
+    match open.last_mut() {
+        Some((parent, _)) => parent.children.push(item),
+        None => roots.push(item),
+    }
+}
+
+/// Whether the keyword at `i` starts an item rather than, say, a
+/// `fn(u8) -> u8` pointer or an `impl Trait` argument.
+fn at_item_start(src: &str, tokens: &[&Token], i: usize) -> bool {
+    let Some(previous) = i.checked_sub(1).map(|p| tokens[p]) else {
+        return true;
+    };
+    let text = &src[previous.range.clone()];
+    match previous.kind {
+        TokenKind::Punct => matches!(text, ";" | "{" | "}" | "]" | ")"),
+        TokenKind::Keyword => MODIFIERS.contains(&text),
+        // extern "C" fn
+        TokenKind::String => true,
+        _ => false,
+    }
+}
+
+/// The item whose keyword is at `i` and the index of the `{` or `;` that
+/// ends its header. Items with a body get their end once it is closed.
+fn item_header(
+    src: &str,
+    tokens: &[&Token],
+    i: usize,
+    kind: OutlineKind,
+) -> Option<(OutlineItem, usize)> {
+    let text = |i: usize| &src[tokens[i].range.clone()];
+
+    let mut start = i + 1;
+    if kind == OutlineKind::Impl && tokens.get(start).is_some_and(|_| text(start) == "<") {
+        start = skip_generics(src, tokens, start)?;
+    }
+    let mut nesting = 0usize;
+    let mut end = start;
+    let mut name_end = None;
+    loop {
+        let token = tokens.get(end)?;
+        let word = text(end);
+        match word {
+            "(" | "[" => nesting += 1,
+            ")" | "]" => nesting = nesting.saturating_sub(1),
+            "{" | ";" if nesting == 0 => break,
+            "where" if nesting == 0 => {
+                name_end.get_or_insert(end);
+            }
+            _ => {}
+        }
+        if token.kind == TokenKind::Keyword && matches!(word, "struct" | "enum" | "trait" | "mod") {
+            // a header never holds another type; we lost track of the item
+            return None;
+        }
+        end += 1;
+    }
+
+    let name_range = if kind == OutlineKind::Impl {
+        let last = name_end.unwrap_or(end).checked_sub(1)?;
+        (last >= start).then(|| tokens[start].range.start..tokens[last].range.end)?
+    } else {
+        let name = tokens.get(start).filter(|t| t.kind == TokenKind::Ident)?;
+        name.range.clone()
+    };
+    let name = src[name_range.clone()]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let item = OutlineItem {
+        kind,
+        name,
+        range: tokens[i].range.start..tokens[end].range.end,
+        name_range,
+        children: vec![],
+    };
+    Some((item, end))
+}
+
+/// Index of the token after the `<...>` that starts at `i`.
+fn skip_generics(src: &str, tokens: &[&Token], mut i: usize) -> Option<usize> {
+    let mut angles = 0i32;
+    loop {
+        match &src[tokens.get(i)?.range.clone()] {
+            "<" => angles += 1,
+            ">" => angles -= 1,
+            ">>" => angles -= 2,
+            _ => {}
+        }
+        i += 1;
+        if angles <= 0 {
+            return Some(i);
+        }
+    }
+}
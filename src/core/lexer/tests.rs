@@ -0,0 +1,284 @@
+use crate::core::lexer::{
+    Edit, Lexer, OutlineItem, OutlineKind, TokenKind, fold_ranges, outline, relex,
+};
+
+/// Lexes `src` and returns `(kind, text)` pairs without whitespace.
+fn lex(src: &str) -> Vec<(TokenKind, &str)> {
+    Lexer::new(src)
+        .lex()
+        .into_iter()
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .map(|t| (t.kind, &src[t.range]))
+        .collect()
+}
+
+#[test]
+fn test_tokens_cover_whole_source() {
+    let src = "fn main() {\n    let s = \"héllo\"; // коммент\n}\n";
+    let tokens = Lexer::new(src).lex();
+
+    let mut expected_start = 0;
+    for token in &tokens {
+        assert_eq!(token.range.start, expected_start);
+        expected_start = token.range.end;
+    }
+    assert_eq!(expected_start, src.len());
+}
+
+#[test]
+fn test_keywords_and_identifiers() {
+    assert_eq!(
+        lex("pub fn r#match self_ Self"),
+        vec![
+            (TokenKind::Keyword, "pub"),
+            (TokenKind::Keyword, "fn"),
+            (TokenKind::Ident, "r#match"),
+            (TokenKind::Ident, "self_"),
+            (TokenKind::Keyword, "Self"),
+        ]
+    );
+}
+
+#[test]
+fn test_lifetimes_and_chars() {
+    assert_eq!(
+        lex(r"'a 'static 'x' '\n' '\'' b'z'"),
+        vec![
+            (TokenKind::Lifetime, "'a"),
+            (TokenKind::Lifetime, "'static"),
+            (TokenKind::Char, "'x'"),
+            (TokenKind::Char, r"'\n'"),
+            (TokenKind::Char, r"'\''"),
+            (TokenKind::Char, "b'z'"),
+        ]
+    );
+}
+
+#[test]
+fn test_strings_with_escapes() {
+    assert_eq!(
+        lex(r#""a \" b" "c\\" x"#),
+        vec![
+            (TokenKind::String, r#""a \" b""#),
+            (TokenKind::String, r#""c\\""#),
+            (TokenKind::Ident, "x"),
+        ]
+    );
+}
+
+#[test]
+fn test_raw_and_byte_strings() {
+    assert_eq!(
+        lex(r###"r"raw" r#"has "quotes""# b"bytes" br##"a"#b"## c"cstr""###),
+        vec![
+            (TokenKind::RawString, r#"r"raw""#),
+            (TokenKind::RawString, r###"r#"has "quotes""#"###),
+            (TokenKind::ByteString, r#"b"bytes""#),
+            (TokenKind::ByteString, r###"br##"a"#b"##"###),
+            (TokenKind::String, r#"c"cstr""#),
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_string_runs_to_end() {
+    assert_eq!(lex("\"open\nline"), vec![(TokenKind::String, "\"open\nline")]);
+}
+
+#[test]
+fn test_comments() {
+    assert_eq!(
+        lex("// plain\n/// doc\n//! inner\n//// plain\n/* a /* nested */ b */ /** doc */ /**/"),
+        vec![
+            (TokenKind::Comment, "// plain"),
+            (TokenKind::DocComment, "/// doc"),
+            (TokenKind::DocComment, "//! inner"),
+            (TokenKind::Comment, "//// plain"),
+            (TokenKind::Comment, "/* a /* nested */ b */"),
+            (TokenKind::DocComment, "/** doc */"),
+            (TokenKind::Comment, "/**/"),
+        ]
+    );
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(
+        lex("42 1_000u32 3.14 1e-9f64 0xFF_u8 0b1010 2. 1..2 1.max"),
+        vec![
+            (TokenKind::Number, "42"),
+            (TokenKind::Number, "1_000u32"),
+            (TokenKind::Number, "3.14"),
+            (TokenKind::Number, "1e-9f64"),
+            (TokenKind::Number, "0xFF_u8"),
+            (TokenKind::Number, "0b1010"),
+            (TokenKind::Number, "2."),
+            (TokenKind::Number, "1"),
+            (TokenKind::Operator, ".."),
+            (TokenKind::Number, "2"),
+            (TokenKind::Number, "1"),
+            (TokenKind::Punct, "."),
+            (TokenKind::Ident, "max"),
+        ]
+    );
+}
+
+#[test]
+fn test_operators_and_punctuation() {
+    assert_eq!(
+        lex("a::b -> c => d..=e != f; #[x]"),
+        vec![
+            (TokenKind::Ident, "a"),
+            (TokenKind::Punct, "::"),
+            (TokenKind::Ident, "b"),
+            (TokenKind::Operator, "->"),
+            (TokenKind::Ident, "c"),
+            (TokenKind::Operator, "=>"),
+            (TokenKind::Ident, "d"),
+            (TokenKind::Operator, "..="),
+            (TokenKind::Ident, "e"),
+            (TokenKind::Operator, "!="),
+            (TokenKind::Ident, "f"),
+            (TokenKind::Punct, ";"),
+            (TokenKind::Punct, "#"),
+            (TokenKind::Punct, "["),
+            (TokenKind::Ident, "x"),
+            (TokenKind::Punct, "]"),
+        ]
+    );
+}
+
+/// Applies `range -> replacement` to `old` and checks that incremental
+/// re-lexing gives exactly the tokens of a full lex.
+fn assert_relex(old: &str, range: std::ops::Range<usize>, replacement: &str) {
+    let mut new = old.to_string();
+    new.replace_range(range, replacement);
+
+    let old_tokens = Lexer::new(old).lex();
+    let edit = Edit::diff(old, &new).expect("texts differ");
+    assert_eq!(relex(&new, &old_tokens, &edit), Lexer::new(&new).lex(), "{old:?} -> {new:?}");
+}
+
+#[test]
+fn test_edit_diff() {
+    assert_eq!(Edit::diff("abc", "abc"), None);
+    assert_eq!(
+        Edit::diff("let x = 1;", "let xy = 1;"),
+        Some(Edit {
+            range: 5..5,
+            new_len: 1
+        })
+    );
+    assert_eq!(
+        Edit::diff("aaaa", "aa"),
+        Some(Edit {
+            range: 2..4,
+            new_len: 0
+        })
+    );
+    // edits inside multi-byte chars snap to char boundaries
+    assert_eq!(
+        Edit::diff("é", "è"),
+        Some(Edit {
+            range: 0..2,
+            new_len: 2
+        })
+    );
+}
+
+#[test]
+fn test_relex_simple_edits() {
+    let src = "fn main() {\n    let x = 1;\n    let y = x + 2;\n}\n";
+    assert_relex(src, 19..19, "yz");
+    assert_relex(src, 24..25, "1.5e3");
+    assert_relex(src, 0..2, "");
+    assert_relex(src, src.len()..src.len(), "// end");
+    assert_relex(src, 12..12, "    ");
+}
+
+#[test]
+fn test_relex_block_comments_invalidate() {
+    let src = "a\nb /* c */ d\ne\nf\n";
+    // opening a comment swallows the following lines up to the old `*/`
+    assert_relex(src, 2..2, "/*");
+    // closing the comment early un-comments the rest
+    let src = "a /* b\nc\nd */ e\n";
+    assert_relex(src, 6..6, "*/");
+    // nested comment depth changes
+    assert_relex(src, 8..8, "/*");
+}
+
+#[test]
+fn test_relex_raw_strings_and_quotes() {
+    let src = "let s = r#\"a\nb\"#;\nlet t = 1;\n";
+    assert_relex(src, 10..11, "");
+    assert_relex(src, 17..17, "#");
+    let src = "x\ny = 1;\nz = \"q\";\n";
+    assert_relex(src, 2..2, "\"");
+    assert_relex(src, 3..3, "'");
+}
+
+#[test]
+fn test_relex_matches_full_lex_at_every_position() {
+    let src = "/// doc\nfn f<'a>(s: &'a str) -> u8 {\n    /* c */ let r = br#\"x\"#; 'c' as u8 + 1.5e3\n}\n";
+    for pos in (0..=src.len()).filter(|&p| src.is_char_boundary(p)) {
+        for insert in ["\"", "/*", "*/", "r#\"", "'", "\n", "x", "#", "."] {
+            assert_relex(src, pos..pos, insert);
+        }
+        if pos < src.len() {
+            assert_relex(src, pos..pos + 1, "");
+        }
+    }
+}
+
+#[test]
+fn test_outline_nests_items() {
+    let src = "pub struct Point(u8);\n\
+               impl<T: Into<u8>> From<T> for Point where T: Copy {\n\
+               \x20   fn from(t: T) -> Self { let f = |x: fn(u8) -> u8| x; Point(t.into()) }\n\
+               }\n\
+               trait Shape { fn area(&self, f: impl Fn()) -> f32; }\n\
+               mod inner { pub(crate) fn helper() {} }\n\
+               fn main() {";
+    let items = outline(src, &Lexer::new(src).lex());
+
+    fn summary(items: &[OutlineItem]) -> Vec<(OutlineKind, String, usize)> {
+        items
+            .iter()
+            .map(|item| (item.kind, item.name.clone(), item.children.len()))
+            .collect()
+    }
+    assert_eq!(
+        summary(&items),
+        vec![
+            (OutlineKind::Struct, "Point".to_string(), 0),
+            (OutlineKind::Impl, "From<T> for Point".to_string(), 1),
+            (OutlineKind::Trait, "Shape".to_string(), 1),
+            (OutlineKind::Module, "inner".to_string(), 1),
+            (OutlineKind::Function, "main".to_string(), 0),
+        ]
+    );
+    assert_eq!(&src[items[0].range.clone()], "struct Point(u8);");
+    assert_eq!(&src[items[1].name_range.clone()], "From<T> for Point");
+    assert!(src[items[1].range.clone()].ends_with("x; Point(t.into()) }\n}"));
+    assert_eq!(items[1].children[0].name, "from");
+    assert_eq!(items[2].children[0].name, "area");
+    assert_eq!(items[3].children[0].name, "helper");
+    // an unclosed body runs to the end of the text
+    assert_eq!(items[4].range.end, src.len());
+}
+
+#[test]
+fn test_fold_ranges_follow_delimiters() {
+    let src = "fn main() {\n    let v = vec![\n        1,\n    ];\n    run(|x| {\n        x\n    });\n    // }\n    if a { b }\n}\n";
+    assert_eq!(fold_ranges(src, &Lexer::new(src).lex()), vec![0..9, 1..3, 4..6]);
+}
+
+#[test]
+fn test_fold_ranges_follow_indentation() {
+    let src = "def f(x):\n    if x:\n        return 1\n\n    return 2\n\nprint(f(1))\n";
+    assert_eq!(fold_ranges(src, &Lexer::new(src).lex()), vec![0..5, 1..3]);
+    // a brace pair on adjacent lines leaves nothing to fold
+    let src = "fn a() {\n}\n";
+    assert!(fold_ranges(src, &Lexer::new(src).lex()).is_empty());
+}
@@ -1,15 +1,48 @@
 use std::ops::Range;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TokenKind {
+    /// `// ...` and `/* ... */` (block comments may nest)
     Comment,
+    /// `/// ...`, `//! ...`, `/** ... */` and `/*! ... */`
+    DocComment,
+    /// `"..."` and `c"..."`
     String,
+    /// `r"..."`, `r#"..."#`
+    RawString,
+    /// `b"..."`, `br"..."`, `br#"..."#`
+    ByteString,
+    /// `'a'`, `'\n'`, `b'a'`
+    Char,
+    /// `'a`, `'static`
+    Lifetime,
+    /// `42`, `0xff`, `1_000u32`, `3.14`, `1e-9f64`
     Number,
+    Keyword,
+    Ident,
+    /// Delimiters and separators: `( ) [ ] { } , ; : :: . # ? @ $`
+    Punct,
+    /// `+ - * / % = == != < <= -> => .. ..= && ||` and friends
+    Operator,
     Whitespace,
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+impl TokenKind {
+    /// Whitespace and comments carry no meaning for the parser-like features
+    /// built on top of the token stream (outline, folding, ...).
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::Comment | TokenKind::DocComment
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token {
     pub kind: TokenKind,
     pub range: Range<usize>,
@@ -1,8 +1,14 @@
 pub mod app;
+pub mod buffer;
 pub mod context;
 pub mod enums;
+pub mod git;
+pub mod languages;
+pub mod lexer;
 pub mod lib;
 pub mod models;
+pub mod plugins;
+pub mod search;
 pub mod stores;
 pub mod types;
 pub mod ui;
@@ -0,0 +1,102 @@
+//! `textDocument/codeAction`, `codeAction/resolve` and
+//! `workspace/executeCommand`.
+
+use serde_json::{Value, json};
+
+use super::protocol::Range;
+
+/// A code action, or a bare `Command` the server offered instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeAction {
+    pub title: String,
+    /// e.g. `quickfix` or `refactor.extract`
+    pub kind: Option<String>,
+    pub is_preferred: bool,
+    /// Why the action can't be applied right now; shown, but not applied.
+    pub disabled: Option<String>,
+    /// A `WorkspaceEdit` to apply before running `command`.
+    pub edit: Option<Value>,
+    /// A `Command` to run with `workspace/executeCommand`.
+    pub command: Option<Value>,
+    /// The action as sent, for `codeAction/resolve`.
+    pub raw: Value,
+}
+
+impl CodeAction {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let title = value["title"].as_str()?.to_string();
+
+        // a bare `Command` has a string `command`
+        if value["command"].is_string() {
+            return Some(Self {
+                title,
+                kind: None,
+                is_preferred: false,
+                disabled: None,
+                edit: None,
+                command: Some(value.clone()),
+                raw: value.clone(),
+            });
+        }
+
+        Some(Self {
+            title,
+            kind: value["kind"].as_str().map(str::to_string),
+            is_preferred: value["isPreferred"].as_bool().unwrap_or(false),
+            disabled: value["disabled"]["reason"].as_str().map(str::to_string),
+            edit: value.get("edit").filter(|edit| !edit.is_null()).cloned(),
+            command: value
+                .get("command")
+                .filter(|command| !command.is_null())
+                .cloned(),
+            raw: value.clone(),
+        })
+    }
+
+    /// Whether there is nothing to do yet, so the server has to fill in
+    /// `edit` with `codeAction/resolve`.
+    pub fn needs_resolve(&self) -> bool {
+        self.edit.is_none() && self.command.is_none()
+    }
+}
+
+/// Whether the server offers code actions at all (`None`), and if so
+/// whether it supports `codeAction/resolve`.
+pub fn code_action_support(capabilities: &Value) -> Option<bool> {
+    match &capabilities["codeActionProvider"] {
+        Value::Bool(true) => Some(false),
+        Value::Object(options) => Some(options.get("resolveProvider") == Some(&Value::Bool(true))),
+        _ => None,
+    }
+}
+
+/// Actions of a `textDocument/codeAction` result, preferred ones first.
+pub fn parse_code_actions(value: &Value) -> Vec<CodeAction> {
+    let mut actions: Vec<CodeAction> = value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(CodeAction::from_value)
+        .collect();
+    actions.sort_by_key(|action| (!action.is_preferred, action.disabled.is_some()));
+    actions
+}
+
+/// Params for the actions at `range`, given the diagnostics (as the server
+/// sent them) that overlap it.
+pub fn code_action_params(uri: &str, range: Range, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "range": range,
+        "context": { "diagnostics": diagnostics, "triggerKind": 2 },
+    })
+}
+
+/// `ExecuteCommandParams` of a `Command`.
+pub fn execute_command_params(command: &Value) -> Value {
+    let mut params = json!({ "command": command["command"] });
+    if let Some(arguments) = command.get("arguments") {
+        params["arguments"] = arguments.clone();
+    }
+    params
+}
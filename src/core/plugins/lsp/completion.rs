@@ -0,0 +1,491 @@
+//! `textDocument/completion`: parsing items, fuzzy filtering them while the
+//! user keeps typing and applying the accepted one (snippets included).
+
+use std::ops::Range as ByteRange;
+
+use serde_json::{Value, json};
+
+use super::lsp::{LspClient, PendingRequest};
+use super::protocol::{Position, TextEdit, apply_text_edits, offsets_to_range, range_to_offsets};
+
+/// `CompletionItemKind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompletionKind {
+    Text,
+    Method,
+    Function,
+    Constructor,
+    Field,
+    Variable,
+    Class,
+    Interface,
+    Module,
+    Property,
+    Unit,
+    Value,
+    Enum,
+    Keyword,
+    Snippet,
+    Color,
+    File,
+    Reference,
+    Folder,
+    EnumMember,
+    Constant,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl CompletionKind {
+    const ALL: [CompletionKind; 25] = [
+        CompletionKind::Text,
+        CompletionKind::Method,
+        CompletionKind::Function,
+        CompletionKind::Constructor,
+        CompletionKind::Field,
+        CompletionKind::Variable,
+        CompletionKind::Class,
+        CompletionKind::Interface,
+        CompletionKind::Module,
+        CompletionKind::Property,
+        CompletionKind::Unit,
+        CompletionKind::Value,
+        CompletionKind::Enum,
+        CompletionKind::Keyword,
+        CompletionKind::Snippet,
+        CompletionKind::Color,
+        CompletionKind::File,
+        CompletionKind::Reference,
+        CompletionKind::Folder,
+        CompletionKind::EnumMember,
+        CompletionKind::Constant,
+        CompletionKind::Struct,
+        CompletionKind::Event,
+        CompletionKind::Operator,
+        CompletionKind::TypeParameter,
+    ];
+
+    /// Kinds are numbered from 1 in the protocol.
+    pub fn from_lsp(kind: u64) -> Option<Self> {
+        Self::ALL.get((kind as usize).checked_sub(1)?).copied()
+    }
+
+    /// Short tag shown in front of the label.
+    pub fn icon(self) -> &'static str {
+        match self {
+            CompletionKind::Method | CompletionKind::Function | CompletionKind::Constructor => "ƒ",
+            CompletionKind::Field | CompletionKind::Property => "◆",
+            CompletionKind::Variable | CompletionKind::Value | CompletionKind::Reference => "v",
+            CompletionKind::Class | CompletionKind::Struct => "S",
+            CompletionKind::Interface => "T",
+            CompletionKind::Enum => "E",
+            CompletionKind::EnumMember => "e",
+            CompletionKind::Module | CompletionKind::Folder | CompletionKind::File => "M",
+            CompletionKind::Keyword => "K",
+            CompletionKind::Snippet => "✂",
+            CompletionKind::Constant | CompletionKind::Unit => "C",
+            CompletionKind::TypeParameter => "τ",
+            CompletionKind::Operator => "±",
+            CompletionKind::Event => "⚡",
+            CompletionKind::Color => "●",
+            CompletionKind::Text => "≡",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: Option<CompletionKind>,
+    /// e.g. the signature of a function
+    pub detail: Option<String>,
+    /// Plain text or markdown.
+    pub documentation: Option<String>,
+    pub filter_text: Option<String>,
+    pub sort_text: Option<String>,
+    pub insert_text: Option<String>,
+    /// `insertTextFormat == Snippet`: the inserted text contains `$1`, `${2:x}`, ...
+    pub is_snippet: bool,
+    pub text_edit: Option<TextEdit>,
+    /// e.g. a `use` line added at the top of the file
+    pub additional_text_edits: Vec<TextEdit>,
+    pub preselect: bool,
+}
+
+impl CompletionItem {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let documentation = match &value["documentation"] {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(markup) => markup
+                .get("value")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+
+        // InsertReplaceEdit carries two ranges; inserting never eats the
+        // rest of the word after the cursor
+        let text_edit = value.get("textEdit").and_then(|edit| {
+            let range = edit.get("range").or_else(|| edit.get("insert"))?;
+            Some(TextEdit {
+                range: serde_json::from_value(range.clone()).ok()?,
+                new_text: edit["newText"].as_str()?.to_string(),
+            })
+        });
+
+        let additional_text_edits = value
+            .get("additionalTextEdits")
+            .and_then(|edits| serde_json::from_value(edits.clone()).ok())
+            .unwrap_or_default();
+
+        let string = |key: &str| value[key].as_str().map(str::to_string);
+
+        Some(Self {
+            label: value["label"].as_str()?.to_string(),
+            kind: value["kind"].as_u64().and_then(CompletionKind::from_lsp),
+            detail: string("detail"),
+            documentation,
+            filter_text: string("filterText"),
+            sort_text: string("sortText"),
+            insert_text: string("insertText"),
+            is_snippet: value["insertTextFormat"].as_u64() == Some(2),
+            text_edit,
+            additional_text_edits,
+            preselect: value["preselect"].as_bool().unwrap_or(false),
+        })
+    }
+
+    fn filter_text(&self) -> &str {
+        self.filter_text.as_deref().unwrap_or(&self.label)
+    }
+
+    fn sort_text(&self) -> &str {
+        self.sort_text.as_deref().unwrap_or(&self.label)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompletionList {
+    pub items: Vec<CompletionItem>,
+    /// The server wants to be asked again as the user keeps typing.
+    pub is_incomplete: bool,
+}
+
+/// Accepts both result shapes: `CompletionItem[]` and `CompletionList`.
+pub fn parse_completion_response(value: &Value) -> CompletionList {
+    let (items, is_incomplete) = match value {
+        Value::Array(items) => (items.as_slice(), false),
+        Value::Object(list) => (
+            list.get("items")
+                .and_then(Value::as_array)
+                .map_or(&[][..], |items| items.as_slice()),
+            list.get("isIncomplete")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        ),
+        _ => (&[][..], false),
+    };
+
+    CompletionList {
+        items: items
+            .iter()
+            .filter_map(CompletionItem::from_value)
+            .collect(),
+        is_incomplete,
+    }
+}
+
+pub fn completion_params(uri: &str, position: Position, trigger_character: Option<&str>) -> Value {
+    let context = match trigger_character {
+        Some(character) => json!({ "triggerKind": 2, "triggerCharacter": character }),
+        None => json!({ "triggerKind": 1 }),
+    };
+    json!({
+        "textDocument": { "uri": uri },
+        "position": position,
+        "context": context,
+    })
+}
+
+pub fn request_completion(
+    client: &LspClient,
+    uri: &str,
+    position: Position,
+    trigger_character: Option<&str>,
+) -> PendingRequest {
+    client.request(
+        "textDocument/completion",
+        completion_params(uri, position, trigger_character),
+    )
+}
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Start of the identifier that ends at `offset`; the text in between is
+/// what completion items are filtered by.
+pub fn word_start(text: &str, offset: usize) -> usize {
+    text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map_or(offset, |(i, _)| i)
+}
+
+/// The identifier around `offset` (empty if there is none).
+pub fn word_at(text: &str, offset: usize) -> ByteRange<usize> {
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_word_char(*c))
+        .map_or(text.len(), |(i, _)| offset + i);
+    word_start(text, offset)..end
+}
+
+/// Indices of the items matching `query`, best first. `score` rates the
+/// filter text of an item against `query`, `None` when it doesn't match;
+/// the editor passes the matcher quick open ranks files with.
+pub fn filter_items(
+    items: &[CompletionItem],
+    query: &str,
+    score: impl Fn(&str, &str) -> Option<i64>,
+) -> Vec<usize> {
+    let mut matches: Vec<(usize, i64)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| Some((i, score(query, item.filter_text())?)))
+        .collect();
+
+    matches.sort_by(|(a, a_score), (b, b_score)| {
+        let (a, b) = (&items[*a], &items[*b]);
+        b_score
+            .cmp(a_score)
+            .then(b.preselect.cmp(&a.preselect))
+            .then_with(|| a.sort_text().cmp(b.sort_text()))
+            .then(a.label.len().cmp(&b.label.len()))
+    });
+
+    matches.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Text of an expanded snippet and its tab stops: `$1`, `$2`, ... in order,
+/// `$0` (or the end of the text) last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    pub tabstops: Vec<ByteRange<usize>>,
+}
+
+pub fn expand_snippet(snippet: &str) -> Snippet {
+    let mut parser = SnippetParser {
+        chars: snippet.chars().collect(),
+        pos: 0,
+        text: String::new(),
+        stops: Vec::new(),
+    };
+    parser.parse(false);
+
+    let mut stops = parser.stops;
+    // without `$0` the cursor ends up after the snippet
+    if !stops.iter().any(|(index, _)| *index == 0) {
+        stops.push((0, parser.text.len()..parser.text.len()));
+    }
+    // `$0` goes last; mirrors of a tab stop keep only the first occurrence
+    stops.sort_by_key(|(index, _)| if *index == 0 { u32::MAX } else { *index });
+    stops.dedup_by_key(|(index, _)| *index);
+
+    let tabstops = stops.into_iter().map(|(_, range)| range).collect();
+
+    Snippet {
+        text: parser.text,
+        tabstops,
+    }
+}
+
+struct SnippetParser {
+    chars: Vec<char>,
+    pos: usize,
+    text: String,
+    stops: Vec<(u32, ByteRange<usize>)>,
+}
+
+impl SnippetParser {
+    /// Parses until the end, or until the `}` closing a placeholder when `nested`.
+    fn parse(&mut self, nested: bool) {
+        while let Some(&c) = self.chars.get(self.pos) {
+            match c {
+                '\\' if matches!(
+                    self.chars.get(self.pos + 1),
+                    Some('$' | '}' | '\\' | ',' | '|')
+                ) =>
+                {
+                    self.text.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                '}' if nested => {
+                    self.pos += 1;
+                    return;
+                }
+                '$' => self.parse_dollar(),
+                _ => {
+                    self.text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_dollar(&mut self) {
+        self.pos += 1;
+        match self.chars.get(self.pos) {
+            Some(c) if c.is_ascii_digit() => {
+                let index = self.number();
+                let at = self.text.len();
+                self.stops.push((index, at..at));
+            }
+            Some('{') => {
+                self.pos += 1;
+                if self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+                    let index = self.number();
+                    self.placeholder(Some(index));
+                } else {
+                    // variables (`${TM_FILENAME:default}`) expand to their default
+                    self.name();
+                    self.placeholder(None);
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' => self.name(),
+            _ => self.text.push('$'),
+        }
+    }
+
+    /// Rest of `${n...}` / `${VAR...}` after the number or name.
+    fn placeholder(&mut self, index: Option<u32>) {
+        let start = self.text.len();
+        match self.chars.get(self.pos) {
+            Some(':') => {
+                self.pos += 1;
+                self.parse(true);
+            }
+            Some('|') => {
+                // choice: insert the first option
+                self.pos += 1;
+                let mut first = true;
+                while let Some(&c) = self.chars.get(self.pos) {
+                    self.pos += 1;
+                    match c {
+                        '|' => {
+                            if self.chars.get(self.pos) == Some(&'}') {
+                                self.pos += 1;
+                            }
+                            break;
+                        }
+                        ',' => first = false,
+                        '\\' => {
+                            if let Some(&escaped) = self.chars.get(self.pos) {
+                                self.pos += 1;
+                                if first {
+                                    self.text.push(escaped);
+                                }
+                            }
+                        }
+                        _ if first => self.text.push(c),
+                        _ => {}
+                    }
+                }
+            }
+            Some('}') => self.pos += 1,
+            _ => {}
+        }
+        if let Some(index) = index {
+            self.stops.push((index, start..self.text.len()));
+        }
+    }
+
+    fn number(&mut self) -> u32 {
+        let mut n = 0u32;
+        while let Some(digit) = self.chars.get(self.pos).and_then(|c| c.to_digit(10)) {
+            n = n.saturating_mul(10).saturating_add(digit);
+            self.pos += 1;
+        }
+        n
+    }
+
+    fn name(&mut self) {
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+        {
+            self.pos += 1;
+        }
+    }
+}
+
+/// Result of accepting a completion item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedCompletion {
+    pub text: String,
+    /// Byte ranges in `text` to visit with Tab; the first one is selected
+    /// right away.
+    pub tabstops: Vec<ByteRange<usize>>,
+}
+
+/// Applies `item` to `text`. `word` is the part of the identifier typed so
+/// far (it is replaced when the item has no `textEdit`); the edit's range is
+/// extended to `cursor`, since the user may have typed on after the request.
+pub fn apply_completion(
+    text: &str,
+    item: &CompletionItem,
+    word: ByteRange<usize>,
+    cursor: usize,
+) -> AppliedCompletion {
+    let (range, new_text) = match &item.text_edit {
+        Some(edit) => {
+            let range = range_to_offsets(text, edit.range);
+            (range.start..range.end.max(cursor), edit.new_text.as_str())
+        }
+        None => (
+            word.start..word.end.max(cursor),
+            item.insert_text.as_deref().unwrap_or(&item.label),
+        ),
+    };
+
+    let snippet = if item.is_snippet {
+        expand_snippet(new_text)
+    } else {
+        let end = new_text.len();
+        Snippet {
+            text: new_text.to_string(),
+            tabstops: std::iter::once(end..end).collect(),
+        }
+    };
+
+    // additional edits before the completion shift where it lands
+    let shift: isize = item
+        .additional_text_edits
+        .iter()
+        .map(|edit| (range_to_offsets(text, edit.range), edit.new_text.len()))
+        .filter(|(edit_range, _)| edit_range.end <= range.start)
+        .map(|(edit_range, new_len)| new_len as isize - edit_range.len() as isize)
+        .sum();
+    let start = range.start.saturating_add_signed(shift);
+
+    let mut edits = item.additional_text_edits.clone();
+    edits.push(TextEdit {
+        range: offsets_to_range(text, range),
+        new_text: snippet.text,
+    });
+
+    AppliedCompletion {
+        text: apply_text_edits(text, &edits),
+        tabstops: snippet
+            .tabstops
+            .into_iter()
+            .map(|stop| start + stop.start..start + stop.end)
+            .collect(),
+    }
+}
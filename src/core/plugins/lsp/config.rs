@@ -0,0 +1,105 @@
+//! Which language server runs for which language, and in which directory.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// How to run the language server of one language.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfig {
+    /// Id of the language in the language registry, e.g. `rust`.
+    pub language: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the server process.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Sent as `initializationOptions` in `initialize`.
+    #[serde(default)]
+    pub initialization_options: Option<Value>,
+    /// Files that mark the root of a project, e.g. `Cargo.toml`.
+    #[serde(default)]
+    pub root_markers: Vec<String>,
+}
+
+impl ServerConfig {
+    /// Root directory of the server for a document at `path`: the outermost
+    /// directory between the document and `workspace` containing one of the
+    /// root markers, else `workspace` itself. Documents outside `workspace`
+    /// get the outermost marked directory above them, or their own directory.
+    pub fn find_root(&self, path: &Path, workspace: Option<&Path>) -> PathBuf {
+        let inside = workspace.filter(|workspace| path.starts_with(workspace));
+        let mut root = None;
+        for dir in path.ancestors().skip(1) {
+            if self
+                .root_markers
+                .iter()
+                .any(|marker| dir.join(marker).exists())
+            {
+                root = Some(dir);
+            }
+            if Some(dir) == inside {
+                break;
+            }
+        }
+
+        root.or(inside)
+            .or_else(|| path.parent())
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+/// Server configurations shipped with the editor.
+const BUILTIN_SERVERS: &str = include_str!("../../../../assets/lsp/servers.json");
+
+/// Maps language ids to server configurations.
+pub struct ServerRegistry {
+    servers: Vec<ServerConfig>,
+}
+
+impl ServerRegistry {
+    pub fn new() -> Self {
+        Self {
+            servers: Vec::new(),
+        }
+    }
+
+    /// Registry with every server from `assets/lsp/servers.json`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        if let Err(e) = registry.load_json(BUILTIN_SERVERS) {
+            eprintln!("Failed to load builtin language servers: {}", e);
+        }
+        registry
+    }
+
+    /// Adds the servers of a JSON array of configurations, replacing those
+    /// already registered for the same languages.
+    pub fn load_json(&mut self, json: &str) -> serde_json::Result<()> {
+        let servers: Vec<ServerConfig> = serde_json::from_str(json)?;
+        for server in servers {
+            self.register(server);
+        }
+        Ok(())
+    }
+
+    pub fn register(&mut self, server: ServerConfig) {
+        self.servers.retain(|s| s.language != server.language);
+        self.servers.push(server);
+    }
+
+    pub fn for_language(&self, language: &str) -> Option<&ServerConfig> {
+        self.servers.iter().find(|s| s.language == language)
+    }
+}
+
+impl Default for ServerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,37 @@
+//! `textDocument/foldingRange` results.
+
+use std::ops::Range;
+
+use serde_json::{Value, json};
+
+/// Whether `capabilities` announce folding ranges.
+pub fn folding_range_support(capabilities: &Value) -> bool {
+    matches!(
+        capabilities["foldingRangeProvider"],
+        Value::Bool(true) | Value::Object(_)
+    )
+}
+
+pub fn folding_range_params(uri: &str) -> Value {
+    json!({ "textDocument": { "uri": uri } })
+}
+
+/// Regions of a `foldingRange` result as zero-based lines, from the line
+/// that stays visible to the first one after the folded ones, sorted by
+/// their first line. Ranges with nothing to fold are dropped.
+pub fn parse_folding_ranges(value: &Value) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|range| {
+            let start = range["startLine"].as_u64()? as usize;
+            // the last folded line
+            let end = range["endLine"].as_u64()? as usize;
+            (end > start).then_some(start..end + 1)
+        })
+        .collect();
+    ranges.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+    ranges.dedup_by_key(|range| range.start);
+    ranges
+}
@@ -0,0 +1,121 @@
+//! `textDocument/formatting` and `textDocument/rangeFormatting`, and
+//! `rustfmt` for Rust files no server formats.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::{Value, json};
+
+use super::protocol::{Range, TextEdit, offsets_to_range};
+
+/// Whether `capabilities` announce whole-document formatting, or range
+/// formatting if `range` is set.
+pub fn formatting_support(capabilities: &Value, range: bool) -> bool {
+    let provider = if range {
+        &capabilities["documentRangeFormattingProvider"]
+    } else {
+        &capabilities["documentFormattingProvider"]
+    };
+    matches!(provider, Value::Bool(true) | Value::Object(_))
+}
+
+/// `FormattingOptions` for indentation with `tab_size` wide tabs or spaces.
+pub fn formatting_options(tab_size: u32, insert_spaces: bool) -> Value {
+    json!({
+        "tabSize": tab_size,
+        "insertSpaces": insert_spaces,
+        "trimTrailingWhitespace": true,
+        "insertFinalNewline": true,
+        "trimFinalNewlines": true,
+    })
+}
+
+pub fn formatting_params(uri: &str, options: Value) -> Value {
+    json!({ "textDocument": { "uri": uri }, "options": options })
+}
+
+pub fn range_formatting_params(uri: &str, range: Range, options: Value) -> Value {
+    json!({ "textDocument": { "uri": uri }, "range": range, "options": options })
+}
+
+/// Edits of a formatting result; `null` means there is nothing to change.
+pub fn parse_text_edits(value: &Value) -> Vec<TextEdit> {
+    serde_json::from_value(value.clone()).unwrap_or_default()
+}
+
+/// A single edit turning `old` into `new`, covering only what lies between
+/// their common prefix and suffix, or `None` if they are equal.
+pub fn diff_edit(old: &str, new: &str) -> Option<TextEdit> {
+    if old == new {
+        return None;
+    }
+
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+
+    Some(TextEdit {
+        range: offsets_to_range(old, prefix..old.len() - suffix),
+        new_text: new[prefix..new.len() - suffix].to_string(),
+    })
+}
+
+/// `edition` of the closest `Cargo.toml` above `path`, as `rustfmt` wants it.
+pub fn rust_edition(path: &Path) -> Option<String> {
+    path.ancestors().skip(1).find_map(|dir| {
+        let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+        manifest.lines().find_map(|line| {
+            let value = line
+                .trim()
+                .strip_prefix("edition")?
+                .trim()
+                .strip_prefix('=')?;
+            Some(value.trim().trim_matches('"').to_string())
+        })
+    })
+}
+
+/// Runs `rustfmt` over `text` through stdin, from the directory of `path`
+/// so it picks up the project's `rustfmt.toml`.
+pub fn rustfmt(path: &Path, text: &str) -> anyhow::Result<String> {
+    let mut command = Command::new("rustfmt");
+    command
+        .args(["--emit", "stdout", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(edition) = rust_edition(path) {
+        command.args(["--edition", &edition]);
+    }
+    if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+        command.current_dir(dir);
+    }
+
+    let mut child = command.spawn()?;
+    // written from another thread: rustfmt may fill stdout before it has
+    // read all of stdin
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("no stdin"))?;
+    let input = text.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
@@ -0,0 +1,115 @@
+//! `textDocument/hover` and `textDocument/signatureHelp` results.
+
+use std::ops::Range as ByteRange;
+
+use serde_json::Value;
+
+/// Markdown of a hover result, or `None` when there is nothing to show.
+///
+/// `contents` may be `MarkupContent`, a `MarkedString` (a plain string or
+/// `{ language, value }`, shown as a code block) or an array of those.
+pub fn parse_hover(value: &Value) -> Option<String> {
+    fn marked(value: &Value) -> Option<String> {
+        match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(object) => {
+                let text = object.get("value")?.as_str()?;
+                match object.get("language").and_then(Value::as_str) {
+                    Some(language) => Some(format!("```{}\n{}\n```", language, text)),
+                    None => Some(text.to_string()),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    let text = match &value["contents"] {
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(marked)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        contents => marked(contents)?,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The active signature of a `textDocument/signatureHelp` result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelp {
+    /// e.g. `fn push(&mut self, value: T)`
+    pub label: String,
+    pub documentation: Option<String>,
+    /// Byte range of the active parameter in `label`.
+    pub active_parameter: Option<ByteRange<usize>>,
+    /// Shown as "1/3" when the function is overloaded.
+    pub active_signature: usize,
+    pub signature_count: usize,
+}
+
+pub fn parse_signature_help(value: &Value) -> Option<SignatureHelp> {
+    let signatures = value["signatures"].as_array()?;
+    let active_signature = value["activeSignature"]
+        .as_u64()
+        .map_or(0, |i| i as usize)
+        .min(signatures.len().checked_sub(1)?);
+    let signature = &signatures[active_signature];
+    let label = signature["label"].as_str()?.to_string();
+
+    // the signature's own activeParameter wins over the top-level one
+    let active = signature["activeParameter"]
+        .as_u64()
+        .or_else(|| value["activeParameter"].as_u64())
+        .unwrap_or(0) as usize;
+    let active_parameter = signature["parameters"]
+        .as_array()
+        .and_then(|parameters| parameters.get(active))
+        .and_then(|parameter| parameter_range(&label, &parameter["label"]));
+
+    let documentation = match &signature["documentation"] {
+        Value::String(text) => Some(text.clone()),
+        Value::Object(markup) => markup
+            .get("value")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    };
+
+    Some(SignatureHelp {
+        label,
+        documentation,
+        active_parameter,
+        active_signature,
+        signature_count: signatures.len(),
+    })
+}
+
+/// A parameter label is either a substring of the signature label or a
+/// `[start, end]` pair of UTF-16 offsets into it.
+fn parameter_range(label: &str, parameter: &Value) -> Option<ByteRange<usize>> {
+    match parameter {
+        Value::String(name) => {
+            // skip the function name, which may contain the parameter's name
+            let from = label.find('(').unwrap_or(0);
+            let start = from + label[from..].find(name.as_str())?;
+            Some(start..start + name.len())
+        }
+        Value::Array(offsets) => {
+            let utf16_to_byte = |units: u64| {
+                let mut count = 0;
+                for (i, c) in label.char_indices() {
+                    if count >= units {
+                        return i;
+                    }
+                    count += c.len_utf16() as u64;
+                }
+                label.len()
+            };
+            let start = utf16_to_byte(offsets.first()?.as_u64()?);
+            let end = utf16_to_byte(offsets.get(1)?.as_u64()?);
+            (start <= end).then_some(start..end)
+        }
+        _ => None,
+    }
+}
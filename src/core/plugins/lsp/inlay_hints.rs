@@ -0,0 +1,79 @@
+//! `textDocument/inlayHint` results.
+
+use serde_json::{Value, json};
+
+use super::protocol::{Position, Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayHintKind {
+    Type,
+    Parameter,
+}
+
+/// A piece of text the server wants shown between two characters, e.g. the
+/// inferred type after `let x`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    pub kind: Option<InlayHintKind>,
+    pub padding_left: bool,
+    pub padding_right: bool,
+}
+
+impl InlayHint {
+    /// The label with the padding the server asked for.
+    pub fn text(&self) -> String {
+        format!(
+            "{}{}{}",
+            if self.padding_left { " " } else { "" },
+            self.label,
+            if self.padding_right { " " } else { "" }
+        )
+    }
+}
+
+/// Whether `capabilities` announce inlay hints.
+pub fn inlay_hint_support(capabilities: &Value) -> bool {
+    matches!(
+        capabilities["inlayHintProvider"],
+        Value::Bool(true) | Value::Object(_)
+    )
+}
+
+pub fn inlay_hint_params(uri: &str, range: Range) -> Value {
+    json!({ "textDocument": { "uri": uri }, "range": range })
+}
+
+/// Hints of an `inlayHint` result, in document order. A label made of
+/// parts (with links to definitions) is joined into plain text.
+pub fn parse_inlay_hints(value: &Value) -> Vec<InlayHint> {
+    let mut hints: Vec<InlayHint> = value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| {
+            let label = match &hint["label"] {
+                Value::String(label) => label.clone(),
+                Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|part| part["value"].as_str())
+                    .collect(),
+                _ => return None,
+            };
+            Some(InlayHint {
+                position: serde_json::from_value(hint["position"].clone()).ok()?,
+                label,
+                kind: match hint["kind"].as_u64() {
+                    Some(1) => Some(InlayHintKind::Type),
+                    Some(2) => Some(InlayHintKind::Parameter),
+                    _ => None,
+                },
+                padding_left: hint["paddingLeft"].as_bool().unwrap_or(false),
+                padding_right: hint["paddingRight"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect();
+    hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+    hints
+}
@@ -1,47 +1,439 @@
+//! Language server client speaking JSON-RPC over the server's stdio.
+//!
+//! Only depends on `super::transport`, so `tests/lsp_client.rs` can build it
+//! against the mock server in `src/bin/mock_lsp.rs`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
 use serde_json::{Value, json};
-use std::process::{Child, Stdio};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::process::Command;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot};
+
+use super::transport::{FrameDecoder, encode};
+
+pub type RequestId = i64;
+
+type Pending = Arc<Mutex<HashMap<RequestId, oneshot::Sender<Result<Value, ResponseError>>>>>;
+
+type Partials = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>;
+
+/// `error` member of a JSON-RPC response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl ResponseError {
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const REQUEST_CANCELLED: i64 = -32800;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn from_value(value: &Value) -> Self {
+        Self {
+            code: value["code"].as_i64().unwrap_or(0),
+            message: value["message"].as_str().unwrap_or_default().to_string(),
+            data: value.get("data").cloned(),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let mut value = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            value["data"] = data.clone();
+        }
+        value
+    }
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "language server error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+/// Messages the server sends without being asked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    /// e.g. `textDocument/publishDiagnostics`
+    Notification { method: String, params: Value },
+    /// e.g. `workspace/applyEdit`; answer with [`LspClient::respond`].
+    Request {
+        id: Value,
+        method: String,
+        params: Value,
+    },
+}
+
+/// Resolves to the result of a request once the server answers.
+///
+/// Fails if the server answers with an error or exits before answering.
+pub struct PendingRequest {
+    id: RequestId,
+    rx: oneshot::Receiver<Result<Value, ResponseError>>,
+    partial_results: Option<PartialResults>,
+}
+
+impl PendingRequest {
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    /// Keeps what is streamed for the `partialResultToken` the request was
+    /// sent with along with it.
+    pub fn with_partial_results(mut self, partial_results: PartialResults) -> Self {
+        self.partial_results = Some(partial_results);
+        self
+    }
+
+    /// The partial results given to [`PendingRequest::with_partial_results`],
+    /// to read from while the response is awaited elsewhere.
+    pub fn take_partial_results(&mut self) -> Option<PartialResults> {
+        self.partial_results.take()
+    }
+}
+
+impl Future for PendingRequest {
+    type Output = anyhow::Result<Value>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let id = self.id;
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|response| match response {
+                Ok(Ok(result)) => Ok(result),
+                Ok(Err(e)) => Err(e.into()),
+                Err(_) => Err(anyhow::anyhow!(
+                    "language server closed before answering request {}",
+                    id
+                )),
+            })
+    }
+}
+
+/// The values a server streams with `$/progress` for a `partialResultToken`
+/// of [`LspClient::partial_results`]. They come on their own channel rather
+/// than as [`ServerMessage`]s, and ahead of the response of their request:
+/// once that arrived, [`PartialResults::take`] has all of them.
+pub struct PartialResults {
+    token: String,
+    rx: mpsc::UnboundedReceiver<Value>,
+    partials: Partials,
+}
+
+impl PartialResults {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// The next value, for a task that awaits them as they arrive.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Value>> {
+        self.rx.poll_recv(cx)
+    }
+
+    /// The values that arrived since the last call.
+    pub fn take(&mut self) -> Vec<Value> {
+        let mut values = Vec::new();
+        while let Ok(value) = self.rx.try_recv() {
+            values.push(value);
+        }
+        values
+    }
+}
+
+impl Drop for PartialResults {
+    fn drop(&mut self) {
+        self.partials.lock().unwrap().remove(&self.token);
+    }
+}
 
+/// Handle to a running language server.
+///
+/// Sending is synchronous (messages are queued for a writer task), so the
+/// client can be used from the UI thread; only awaiting responses needs a
+/// runtime. Everything the server sends on its own arrives on the
+/// [`ServerMessage`] receiver returned by [`LspClient::start`], except
+/// [`PartialResults`].
 pub struct LspClient {
-    child: Child,
-    stdin: tokio::process::ChildStdin,
-    stdout: tokio::process::ChildStdout,
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: Pending,
+    partials: Partials,
+    next_id: AtomicI64,
+    next_token: AtomicI64,
+    connected: Arc<AtomicBool>,
+    // killed when the client is dropped
+    child: Option<Child>,
 }
 
 impl LspClient {
-    pub async fn start() -> anyhow::Result<Self> {
-        let mut child = Command::new("rust-analyzer")
+    /// Spawns `command` and connects to its stdio. Must be called from
+    /// within a tokio runtime.
+    pub async fn start(
+        command: &str,
+        args: &[String],
+    ) -> anyhow::Result<(Self, mpsc::UnboundedReceiver<ServerMessage>)> {
+        let mut command = Command::new(command);
+        command.args(args);
+        Self::spawn(command).await
+    }
+
+    /// Like [`LspClient::start`], for a command the caller set up further
+    /// (environment, working directory). Its stdio is replaced by pipes.
+    pub async fn spawn(
+        mut command: Command,
+    ) -> anyhow::Result<(Self, mpsc::UnboundedReceiver<ServerMessage>)> {
+        let mut child = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()?;
 
-        Ok(Self {
-            stdin: child.stdin.take().unwrap(),
-            stdout: child.stdout.take().unwrap(),
-            child,
-        })
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (mut client, messages) = Self::connect(stdout, stdin);
+        client.child = Some(child);
+        Ok((client, messages))
+    }
+
+    /// Speaks the protocol over an arbitrary byte stream pair.
+    pub fn connect<R, W>(reader: R, writer: W) -> (Self, mpsc::UnboundedReceiver<ServerMessage>)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        let (messages_tx, messages) = mpsc::unbounded_channel();
+        let pending = Pending::default();
+        let partials = Partials::default();
+        let connected = Arc::new(AtomicBool::new(true));
+
+        tokio::spawn(write_loop(writer, outgoing_rx));
+        tokio::spawn(read_loop(
+            reader,
+            pending.clone(),
+            partials.clone(),
+            messages_tx,
+            connected.clone(),
+        ));
+
+        let client = Self {
+            outgoing,
+            pending,
+            partials,
+            next_id: AtomicI64::new(1),
+            next_token: AtomicI64::new(1),
+            connected,
+            child: None,
+        };
+        (client, messages)
+    }
+
+    /// Sends a request; await the returned value for the result.
+    pub fn request(&self, method: &str, params: Value) -> PendingRequest {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if self.outgoing.send(message).is_err() {
+            // writer is gone: dropping the sender fails the request
+            self.pending.lock().unwrap().remove(&id);
+        }
+
+        PendingRequest {
+            id,
+            rx,
+            partial_results: None,
+        }
     }
 
-    pub async fn send(&mut self, value: Value) -> anyhow::Result<()> {
-        let body = value.to_string();
-        let msg = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
-        self.stdin.write_all(msg.as_bytes()).await?;
-        Ok(())
+    /// A fresh `partialResultToken` to send a request with; the values
+    /// streamed for it are collected until the [`PartialResults`] is dropped.
+    pub fn partial_results(&self) -> PartialResults {
+        let n = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let token = format!("riff-partial-{}", n);
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.partials.lock().unwrap().insert(token.clone(), tx);
+        PartialResults {
+            token,
+            rx,
+            partials: self.partials.clone(),
+        }
     }
 
-    pub async fn read(&mut self) -> anyhow::Result<Option<Value>> {
-        let mut buf = Vec::new();
-        self.stdout.read_buf(&mut buf).await?;
-        if buf.is_empty() {
-            return Ok(None);
+    /// Sends `$/cancelRequest` for request `id`. Its [`PendingRequest`] fails
+    /// right away with [`ResponseError::REQUEST_CANCELLED`]; whatever the
+    /// server still answers is dropped.
+    pub fn cancel(&self, id: RequestId) -> anyhow::Result<()> {
+        if let Some(tx) = self.pending.lock().unwrap().remove(&id) {
+            let _ = tx.send(Err(ResponseError::new(
+                ResponseError::REQUEST_CANCELLED,
+                "request cancelled",
+            )));
         }
+        self.notify("$/cancelRequest", json!({ "id": id }))
+    }
+
+    pub fn notify(&self, method: &str, params: Value) -> anyhow::Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    /// Answers a [`ServerMessage::Request`].
+    pub fn respond(&self, id: Value, result: Result<Value, ResponseError>) -> anyhow::Result<()> {
+        let message = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": e.to_value() }),
+        };
+        self.send(message)
+    }
+
+    /// Asks the server to exit with `shutdown` and `exit`, then waits up to
+    /// `timeout` for the process to end. It is killed if it doesn't.
+    pub async fn shutdown(mut self, timeout: Duration) -> anyhow::Result<()> {
+        let shutdown = tokio::time::timeout(timeout, self.request("shutdown", Value::Null)).await;
+        let _ = self.notify("exit", Value::Null);
 
-        let text = String::from_utf8_lossy(&buf);
-        if let Some(idx) = text.find("\r\n\r\n") {
-            let json = &text[idx + 4..];
-            return Ok(Some(serde_json::from_str(json)?));
+        if let Some(mut child) = self.child.take()
+            && tokio::time::timeout(timeout, child.wait()).await.is_err()
+        {
+            child.kill().await?;
+            anyhow::bail!("language server did not exit, killed it");
+        }
+        match shutdown {
+            Ok(result) => result.map(drop),
+            Err(_) => anyhow::bail!("language server did not answer `shutdown`"),
+        }
+    }
+
+    /// Whether the connection to the server is still open.
+    pub fn is_running(&self) -> bool {
+        self.connected.load(Ordering::Relaxed) && !self.outgoing.is_closed()
+    }
+
+    fn send(&self, message: Value) -> anyhow::Result<()> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| anyhow::anyhow!("language server connection is closed"))
+    }
+}
+
+async fn write_loop<W>(mut writer: W, mut outgoing: mpsc::UnboundedReceiver<Value>)
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = outgoing.recv().await {
+        let frame = encode(&message);
+        if writer.write_all(&frame).await.is_err() || writer.flush().await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn read_loop<R>(
+    mut reader: R,
+    pending: Pending,
+    partials: Partials,
+    messages: mpsc::UnboundedSender<ServerMessage>,
+    connected: Arc<AtomicBool>,
+) where
+    R: AsyncRead + Unpin,
+{
+    let mut decoder = FrameDecoder::new();
+    let mut chunk = [0u8; 8192];
+
+    'read: loop {
+        let n = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        decoder.extend(&chunk[..n]);
+
+        loop {
+            match decoder.next_message() {
+                Ok(Some(message)) => dispatch(message, &pending, &partials, &messages),
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("Dropping language server connection: {}", e);
+                    break 'read;
+                }
+            }
+        }
+    }
+
+    // fail every request still waiting for an answer
+    connected.store(false, Ordering::Relaxed);
+    pending.lock().unwrap().clear();
+    partials.lock().unwrap().clear();
+}
+
+fn dispatch(
+    message: Value,
+    pending: &Pending,
+    partials: &Partials,
+    messages: &mpsc::UnboundedSender<ServerMessage>,
+) {
+    let method = message["method"].as_str().map(str::to_string);
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    match (method, message.get("id")) {
+        (Some(method), Some(id)) => {
+            let _ = messages.send(ServerMessage::Request {
+                id: id.clone(),
+                method,
+                params,
+            });
+        }
+        (Some(method), None) => {
+            let partial_results = match method.as_str() {
+                "$/progress" => params["token"]
+                    .as_str()
+                    .and_then(|token| partials.lock().unwrap().get(token).cloned()),
+                _ => None,
+            };
+            // streamed values go straight to their request, so they are
+            // there by the time its response is
+            match partial_results {
+                Some(tx) => {
+                    let _ = tx.send(params["value"].clone());
+                }
+                None => {
+                    let _ = messages.send(ServerMessage::Notification { method, params });
+                }
+            }
+        }
+        (None, Some(id)) => {
+            let Some(id) = id.as_i64() else {
+                return;
+            };
+            let Some(tx) = pending.lock().unwrap().remove(&id) else {
+                return;
+            };
+            let response = match message.get("error") {
+                Some(error) => Err(ResponseError::from_value(error)),
+                None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+            };
+            let _ = tx.send(response);
         }
-        Ok(None)
+        (None, None) => eprintln!("Ignoring malformed language server message: {}", message),
     }
 }
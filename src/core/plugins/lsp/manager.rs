@@ -0,0 +1,970 @@
+//! Keeps language servers in sync with the documents open in the editor.
+//!
+//! Lives on the UI thread: servers run on a tokio runtime owned by the
+//! manager and everything they send is picked up by [`LspManager::poll`]
+//! once per frame.
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use serde_json::{Value, json};
+use tokio::process::Command;
+use tokio::runtime::Runtime;
+use tokio::sync::oneshot;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::languages::Language;
+use crate::core::plugins::lsp::config::{ServerConfig, ServerRegistry};
+use crate::core::plugins::lsp::protocol::{
+    Position, Range, parse_publish_diagnostics, parse_workspace_edit, path_to_uri,
+};
+use crate::core::plugins::lsp::semantic_tokens::{TOKEN_MODIFIERS, TOKEN_TYPES};
+use crate::core::plugins::lsp::{
+    LspClient, PendingRequest, RequestId, ResponseError, ServerMessage,
+};
+use crate::modules::editor::stores::{diagnostics_store, editor_interactions_store};
+
+/// `TextDocumentSyncKind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncKind {
+    None,
+    Full,
+    Incremental,
+}
+
+/// What the server asked for in `capabilities.textDocumentSync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncOptions {
+    pub open_close: bool,
+    pub change: SyncKind,
+    pub save: bool,
+    pub save_include_text: bool,
+}
+
+impl SyncOptions {
+    pub fn from_capabilities(capabilities: &Value) -> Self {
+        let sync = &capabilities["textDocumentSync"];
+        let kind = |value: &Value| match value.as_u64() {
+            Some(1) => SyncKind::Full,
+            Some(2) => SyncKind::Incremental,
+            _ => SyncKind::None,
+        };
+
+        // legacy servers send just the kind
+        if sync.is_number() {
+            return Self {
+                open_close: true,
+                change: kind(sync),
+                save: true,
+                save_include_text: false,
+            };
+        }
+
+        let save = &sync["save"];
+        Self {
+            open_close: sync["openClose"].as_bool().unwrap_or(false),
+            change: kind(&sync["change"]),
+            save: save.as_bool().unwrap_or(save.is_object()),
+            save_include_text: save["includeText"].as_bool().unwrap_or(false),
+        }
+    }
+}
+
+/// `contentChanges` for the edits of `text` since `revision`: one ranged
+/// change per edit, or the whole text for full sync and when the edits are
+/// no longer kept.
+pub fn content_changes(text: &TextBuffer, revision: u64, kind: SyncKind) -> Vec<Value> {
+    let changes = text
+        .changes_since(revision)
+        .filter(|_| kind == SyncKind::Incremental);
+    let Some(changes) = changes else {
+        return vec![json!({ "text": text.to_string() })];
+    };
+    let position = |(line, character): (usize, usize)| Position {
+        line: line as u32,
+        character: character as u32,
+    };
+    changes
+        .map(|change| {
+            let range = Range {
+                start: position(change.start),
+                end: position(change.end),
+            };
+            json!({ "range": range, "text": change.text })
+        })
+        .collect()
+}
+
+/// A request sent through [`LspManager::request`]; the response is picked up
+/// on the UI thread with [`RequestHandle::try_take`].
+pub struct RequestHandle {
+    id: RequestId,
+    rx: oneshot::Receiver<anyhow::Result<Value>>,
+    partial_results: mpsc::Receiver<Value>,
+}
+
+impl RequestHandle {
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    /// The response, once it arrived. `None` while the request is pending.
+    pub fn try_take(&mut self) -> Option<anyhow::Result<Value>> {
+        match self.rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                Some(Err(anyhow::anyhow!("request was dropped")))
+            }
+        }
+    }
+
+    /// What the server streamed for a request sent
+    /// [`PendingRequest::with_partial_results`] since the last call. Every
+    /// value is in by the time [`RequestHandle::try_take`] has the response,
+    /// so take them after that.
+    pub fn take_partial_results(&mut self) -> Vec<Value> {
+        self.partial_results.try_iter().collect()
+    }
+}
+
+/// Awaits `pending` on the runtime and hands its response to the UI thread,
+/// after what the server streamed for it; repaints whenever either arrives.
+fn spawn_request(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    mut pending: PendingRequest,
+) -> RequestHandle {
+    let id = pending.id();
+    let mut streamed = pending.take_partial_results();
+    let (tx, rx) = oneshot::channel();
+    let (partial_tx, partial_results) = mpsc::channel();
+    let repaint_ctx = ctx.clone();
+    runtime.spawn(async move {
+        let response = std::future::poll_fn(|cx| {
+            if let Some(streamed) = &mut streamed {
+                while let Poll::Ready(Some(value)) = streamed.poll_recv(cx) {
+                    let _ = partial_tx.send(value);
+                    repaint_ctx.request_repaint();
+                }
+            }
+            Pin::new(&mut pending).poll(cx)
+        })
+        .await;
+        // the last values may have come right before the response
+        if let Some(streamed) = &mut streamed {
+            for value in streamed.take() {
+                let _ = partial_tx.send(value);
+            }
+        }
+        let _ = tx.send(response);
+        repaint_ctx.request_repaint();
+    });
+    RequestHandle {
+        id,
+        rx,
+        partial_results,
+    }
+}
+
+/// Whether a request failed because it was cancelled, which is no error
+/// worth reporting.
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<ResponseError>()
+        .is_some_and(|e| e.code == ResponseError::REQUEST_CANCELLED)
+}
+
+/// The state of a document as last sent to its server.
+struct Document {
+    language_id: String,
+    server: ServerKey,
+    version: i64,
+    // the buffer of the tab showing the document, for `didOpen`, and the
+    // revision the server has
+    text: Rc<RefCell<TextBuffer>>,
+    revision: u64,
+}
+
+/// Servers are started per language and project root.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerKey {
+    pub language_id: String,
+    pub root: PathBuf,
+}
+
+/// What the status bar shows for a server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerStatus {
+    Starting,
+    Running,
+    /// Crashed; started again after a delay.
+    Restarting {
+        attempt: u32,
+    },
+    /// Could not be started or crashed too often; restarted by hand only.
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerInfo {
+    pub key: ServerKey,
+    pub command: String,
+    pub status: ServerStatus,
+    /// The last message the server asked to show, if any.
+    pub message: Option<String>,
+}
+
+/// A server crashing this many times in a row is given up on.
+const MAX_RESTARTS: u32 = 5;
+/// Running this long counts as stable again; crashes start over from there.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Time `shutdown`/`exit` get before the process is killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Delay before the `crashes`-th restart in a row: 1s, 2s, 4s, ... up to 30s.
+pub fn restart_delay(crashes: u32) -> Duration {
+    Duration::from_secs(1 << crashes.saturating_sub(1).min(5)).min(Duration::from_secs(30))
+}
+
+/// The process of a server and what it sends.
+struct Connection {
+    client: LspClient,
+    messages: mpsc::Receiver<ServerMessage>,
+}
+
+enum ServerState {
+    /// Waiting for the `initialize` response.
+    Starting {
+        connection: Connection,
+        initialized: oneshot::Receiver<anyhow::Result<Value>>,
+    },
+    Running {
+        connection: Connection,
+        sync: SyncOptions,
+        capabilities: Value,
+    },
+    Restarting {
+        at: Instant,
+    },
+    Failed(String),
+}
+
+struct Server {
+    config: ServerConfig,
+    state: ServerState,
+    started_at: Instant,
+    /// Crashes in a row, see [`STABLE_AFTER`].
+    crashes: u32,
+    /// The last `window/showMessage`, shown in the status bar.
+    message: Option<String>,
+}
+
+impl Server {
+    fn client(&self) -> Option<&LspClient> {
+        match &self.state {
+            ServerState::Starting { connection, .. } | ServerState::Running { connection, .. } => {
+                Some(&connection.client)
+            }
+            ServerState::Restarting { .. } | ServerState::Failed(_) => None,
+        }
+    }
+
+    /// The client of a server that finished initializing.
+    fn running(&self) -> Option<(&LspClient, SyncOptions)> {
+        match &self.state {
+            ServerState::Running {
+                connection, sync, ..
+            } => Some((&connection.client, *sync)),
+            _ => None,
+        }
+    }
+
+    fn status(&self) -> ServerStatus {
+        match &self.state {
+            ServerState::Starting { .. } => ServerStatus::Starting,
+            ServerState::Running { .. } => ServerStatus::Running,
+            ServerState::Restarting { .. } => ServerStatus::Restarting {
+                attempt: self.crashes,
+            },
+            ServerState::Failed(reason) => ServerStatus::Failed(reason.clone()),
+        }
+    }
+}
+
+pub struct LspManager {
+    runtime: OnceCell<Runtime>,
+    root: RefCell<Option<PathBuf>>,
+    registry: RefCell<ServerRegistry>,
+    servers: RefCell<HashMap<ServerKey, Server>>,
+    documents: RefCell<HashMap<PathBuf, Document>>,
+}
+
+impl LspManager {
+    pub fn new() -> Self {
+        Self {
+            runtime: OnceCell::new(),
+            root: RefCell::new(None),
+            registry: RefCell::new(ServerRegistry::with_builtins()),
+            servers: RefCell::new(HashMap::new()),
+            documents: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Workspace root; servers of documents without a root marker run there.
+    /// Servers configured in `.riff/lsp.json` below it replace the builtin
+    /// ones.
+    pub fn set_root(&self, root: PathBuf) {
+        let config = root.join(".riff").join("lsp.json");
+        if let Ok(json) = std::fs::read_to_string(&config)
+            && let Err(e) = self.registry.borrow_mut().load_json(&json)
+        {
+            eprintln!("Failed to load {}: {}", config.display(), e);
+        }
+        *self.root.borrow_mut() = Some(root);
+    }
+
+    pub fn did_open(
+        &self,
+        ctx: &egui::Context,
+        path: &Path,
+        language: Option<&Rc<Language>>,
+        text: &Rc<RefCell<TextBuffer>>,
+    ) {
+        let Some(language) = language else {
+            return;
+        };
+        if self.documents.borrow().contains_key(path) {
+            return;
+        }
+        let Some(key) = self.ensure_server(ctx, &language.id, path) else {
+            return;
+        };
+
+        self.documents.borrow_mut().insert(
+            path.to_path_buf(),
+            Document {
+                language_id: language.id.clone(),
+                server: key.clone(),
+                version: 1,
+                revision: text.borrow().revision(),
+                text: text.clone(),
+            },
+        );
+
+        // a starting server receives the document once it is initialized
+        let servers = self.servers.borrow();
+        if let Some((client, sync)) = servers.get(&key).and_then(Server::running) {
+            let mut documents = self.documents.borrow_mut();
+            if let Some(document) = documents.get_mut(path) {
+                send_did_open(client, sync, path, document);
+            }
+        }
+    }
+
+    /// Sends the edits of `text`, the buffer of `path`, since it was last
+    /// synced.
+    pub fn did_change(&self, path: &Path, text: &TextBuffer) {
+        let mut documents = self.documents.borrow_mut();
+        let Some(document) = documents.get_mut(path) else {
+            return;
+        };
+        if document.revision == text.revision() {
+            return;
+        }
+
+        let servers = self.servers.borrow();
+        // not running yet: didOpen will carry the latest text
+        let Some((client, sync)) = servers.get(&document.server).and_then(Server::running) else {
+            document.revision = text.revision();
+            return;
+        };
+
+        document.version += 1;
+        let changes = match sync.change {
+            SyncKind::None => vec![],
+            kind => content_changes(text, document.revision, kind),
+        };
+        document.revision = text.revision();
+
+        if sync.change == SyncKind::None || changes.is_empty() {
+            return;
+        }
+        let _ = client.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": path_to_uri(path), "version": document.version },
+                "contentChanges": changes,
+            }),
+        );
+    }
+
+    pub fn did_save(&self, path: &Path, text: &TextBuffer) {
+        self.did_change(path, text);
+
+        let documents = self.documents.borrow();
+        let Some(document) = documents.get(path) else {
+            return;
+        };
+        let servers = self.servers.borrow();
+        let Some((client, sync)) = servers.get(&document.server).and_then(Server::running) else {
+            return;
+        };
+        if !sync.save {
+            return;
+        }
+
+        let mut params = json!({ "textDocument": { "uri": path_to_uri(path) } });
+        if sync.save_include_text {
+            params["text"] = json!(text.to_string());
+        }
+        let _ = client.notify("textDocument/didSave", params);
+    }
+
+    pub fn did_close(&self, path: &Path) {
+        let Some(document) = self.documents.borrow_mut().remove(path) else {
+            return;
+        };
+        let servers = self.servers.borrow();
+        if let Some((client, sync)) = servers.get(&document.server).and_then(Server::running)
+            && sync.open_close
+        {
+            let _ = client.notify(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": path_to_uri(path) } }),
+            );
+        }
+    }
+
+    /// Capabilities of the running server for `path`.
+    pub fn capabilities(&self, path: &Path) -> Option<Value> {
+        let documents = self.documents.borrow();
+        let servers = self.servers.borrow();
+        match &servers.get(&documents.get(path)?.server)?.state {
+            ServerState::Running { capabilities, .. } => Some(capabilities.clone()),
+            _ => None,
+        }
+    }
+
+    /// Sends a request to the running server for `path` with `send` and
+    /// repaints once the response arrived. `None` if there is no such server.
+    pub fn request(
+        &self,
+        ctx: &egui::Context,
+        path: &Path,
+        send: impl FnOnce(&LspClient) -> PendingRequest,
+    ) -> Option<RequestHandle> {
+        let documents = self.documents.borrow();
+        let servers = self.servers.borrow();
+        let (client, _) = servers.get(&documents.get(path)?.server)?.running()?;
+
+        Some(spawn_request(self.runtime.get()?, ctx, send(client)))
+    }
+
+    /// Sends a request to every running server whose capabilities pass
+    /// `supports`, e.g. for a query about the whole workspace.
+    pub fn request_all(
+        &self,
+        ctx: &egui::Context,
+        supports: impl Fn(&Value) -> bool,
+        send: impl Fn(&LspClient) -> PendingRequest,
+    ) -> Vec<(ServerKey, RequestHandle)> {
+        let Some(runtime) = self.runtime.get() else {
+            return vec![];
+        };
+        let servers = self.servers.borrow();
+        let mut handles = vec![];
+        for (key, server) in servers.iter() {
+            let ServerState::Running {
+                connection,
+                capabilities,
+                ..
+            } = &server.state
+            else {
+                continue;
+            };
+            if !supports(capabilities) {
+                continue;
+            }
+            let handle = spawn_request(runtime, ctx, send(&connection.client));
+            handles.push((key.clone(), handle));
+        }
+        handles
+    }
+
+    /// Cancels a request sent with [`LspManager::request_all`].
+    pub fn cancel_on(&self, key: &ServerKey, request: RequestHandle) {
+        if let Some(client) = self.servers.borrow().get(key).and_then(Server::client) {
+            let _ = client.cancel(request.id);
+        }
+    }
+
+    /// Cancels a request sent with [`LspManager::request`] for `path`.
+    pub fn cancel(&self, path: &Path, request: RequestHandle) {
+        let documents = self.documents.borrow();
+        let servers = self.servers.borrow();
+        if let Some(document) = documents.get(path)
+            && let Some(client) = servers.get(&document.server).and_then(Server::client)
+        {
+            let _ = client.cancel(request.id);
+        }
+    }
+
+    /// Every server started so far, for the status bar.
+    pub fn servers(&self) -> Vec<ServerInfo> {
+        let mut servers: Vec<ServerInfo> = self
+            .servers
+            .borrow()
+            .iter()
+            .map(|(key, server)| ServerInfo {
+                key: key.clone(),
+                command: server.config.command.clone(),
+                status: server.status(),
+                message: server.message.clone(),
+            })
+            .collect();
+        servers.sort_by(|a, b| {
+            (&a.key.language_id, &a.key.root).cmp(&(&b.key.language_id, &b.key.root))
+        });
+        servers
+    }
+
+    /// Shuts the server down gracefully and starts it again, e.g. after it
+    /// was given up on.
+    pub fn restart(&self, ctx: &egui::Context, key: &ServerKey) {
+        let Some(server) = self.servers.borrow_mut().remove(key) else {
+            return;
+        };
+        self.clear_diagnostics(ctx, key);
+        self.shutdown_in_background(server);
+        self.start_server(ctx, key);
+    }
+
+    /// Shuts every server down with `shutdown`/`exit`; blocks until they
+    /// exited (or were killed). Called when the editor closes.
+    pub fn shutdown(&self) {
+        let servers: Vec<Server> = self.servers.borrow_mut().drain().map(|(_, s)| s).collect();
+        let Some(runtime) = self.runtime.get() else {
+            return;
+        };
+        let shutdowns: Vec<_> = servers
+            .into_iter()
+            .filter_map(|server| match server.state {
+                ServerState::Starting { connection, .. }
+                | ServerState::Running { connection, .. } => {
+                    Some(runtime.spawn(connection.client.shutdown(SHUTDOWN_TIMEOUT)))
+                }
+                _ => None,
+            })
+            .collect();
+        runtime.block_on(async {
+            for shutdown in shutdowns {
+                if let Ok(Err(e)) = shutdown.await {
+                    eprintln!("Language server shutdown: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Finishes handshakes, handles messages from the servers and restarts
+    /// crashed ones. Called once per frame.
+    pub fn poll(&self, ctx: &egui::Context) {
+        let mut crashed = Vec::new();
+        let mut due = Vec::new();
+        // handled once `servers` is no longer borrowed, as handling them may
+        // edit documents
+        let mut messages = Vec::new();
+        {
+            let mut servers = self.servers.borrow_mut();
+            for (key, server) in servers.iter_mut() {
+                if let ServerState::Starting { initialized, .. } = &mut server.state {
+                    match initialized.try_recv() {
+                        Ok(Ok(result)) => self.initialized(key, server, &result["capabilities"]),
+                        Ok(Err(e)) => {
+                            eprintln!(
+                                "Failed to initialize {} language server: {}",
+                                key.language_id, e
+                            );
+                            crashed.push(key.clone());
+                        }
+                        Err(oneshot::error::TryRecvError::Empty) => {}
+                        Err(oneshot::error::TryRecvError::Closed) => crashed.push(key.clone()),
+                    }
+                }
+
+                match &server.state {
+                    ServerState::Starting { connection, .. }
+                    | ServerState::Running { connection, .. } => {
+                        while let Ok(message) = connection.messages.try_recv() {
+                            messages.push((key.clone(), message));
+                        }
+                        if !connection.client.is_running() && !crashed.contains(key) {
+                            crashed.push(key.clone());
+                        }
+                    }
+                    ServerState::Restarting { at } if Instant::now() >= *at => {
+                        due.push(key.clone())
+                    }
+                    _ => {}
+                }
+            }
+
+            for key in &crashed {
+                let Some(server) = servers.get_mut(key) else {
+                    continue;
+                };
+                if server.started_at.elapsed() >= STABLE_AFTER {
+                    server.crashes = 0;
+                }
+                server.crashes += 1;
+                server.state = if server.crashes > MAX_RESTARTS {
+                    eprintln!("{} keeps crashing, giving up", server.config.command);
+                    ServerState::Failed(format!("crashed {} times", MAX_RESTARTS + 1))
+                } else {
+                    let delay = restart_delay(server.crashes);
+                    eprintln!(
+                        "{} crashed, restarting in {:?}",
+                        server.config.command, delay
+                    );
+                    ctx.request_repaint_after(delay);
+                    ServerState::Restarting {
+                        at: Instant::now() + delay,
+                    }
+                };
+            }
+        }
+
+        for (key, message) in messages {
+            self.handle_server_message(ctx, &key, message);
+        }
+        for key in crashed {
+            self.clear_diagnostics(ctx, &key);
+        }
+        for key in due {
+            let crashes = self
+                .servers
+                .borrow_mut()
+                .remove(&key)
+                .map_or(0, |s| s.crashes);
+            self.start_server(ctx, &key);
+            if let Some(server) = self.servers.borrow_mut().get_mut(&key) {
+                server.crashes = crashes;
+            }
+        }
+    }
+
+    /// Starts the server for documents of `language_id` at `path` unless it
+    /// is already there. `None` if no server is configured for the language.
+    fn ensure_server(
+        &self,
+        ctx: &egui::Context,
+        language_id: &str,
+        path: &Path,
+    ) -> Option<ServerKey> {
+        let root = {
+            let registry = self.registry.borrow();
+            let config = registry.for_language(language_id)?;
+            config.find_root(path, self.root.borrow().as_deref())
+        };
+        let key = ServerKey {
+            language_id: language_id.to_string(),
+            root,
+        };
+
+        if !self.servers.borrow().contains_key(&key) {
+            self.start_server(ctx, &key);
+        }
+        Some(key)
+    }
+
+    /// Spawns the server for `key` and sends `initialize`; a server that
+    /// can't be spawned is registered as failed.
+    fn start_server(&self, ctx: &egui::Context, key: &ServerKey) {
+        let Some(config) = self
+            .registry
+            .borrow()
+            .for_language(&key.language_id)
+            .cloned()
+        else {
+            return;
+        };
+
+        let state = match self.connect(ctx, &config, &key.root) {
+            Ok((connection, initialized)) => ServerState::Starting {
+                connection,
+                initialized,
+            },
+            Err(e) => {
+                eprintln!(
+                    "Failed to start language server `{}`: {}",
+                    config.command, e
+                );
+                ServerState::Failed(e.to_string())
+            }
+        };
+        self.servers.borrow_mut().insert(
+            key.clone(),
+            Server {
+                config,
+                state,
+                started_at: Instant::now(),
+                crashes: 0,
+                message: None,
+            },
+        );
+    }
+
+    fn connect(
+        &self,
+        ctx: &egui::Context,
+        config: &ServerConfig,
+        root: &Path,
+    ) -> anyhow::Result<(Connection, oneshot::Receiver<anyhow::Result<Value>>)> {
+        let runtime = self.runtime()?;
+        let mut command = Command::new(&config.command);
+        command
+            .args(&config.args)
+            .envs(&config.env)
+            .current_dir(root);
+        let (client, mut server_messages) = runtime.block_on(LspClient::spawn(command))?;
+
+        // forward messages to the UI thread and wake it up
+        let (messages_tx, messages) = mpsc::channel();
+        let repaint_ctx = ctx.clone();
+        runtime.spawn(async move {
+            while let Some(message) = server_messages.recv().await {
+                if messages_tx.send(message).is_err() {
+                    break;
+                }
+                repaint_ctx.request_repaint();
+            }
+            // closed: the server exited
+            repaint_ctx.request_repaint();
+        });
+
+        let initialize = client.request("initialize", self.initialize_params(config, root));
+        let (initialized_tx, initialized) = oneshot::channel();
+        let repaint_ctx = ctx.clone();
+        runtime.spawn(async move {
+            let _ = initialized_tx.send(initialize.await);
+            repaint_ctx.request_repaint();
+        });
+
+        Ok((Connection { client, messages }, initialized))
+    }
+
+    /// `initialize` succeeded: sends `initialized` and opens the documents
+    /// of the server.
+    fn initialized(&self, key: &ServerKey, server: &mut Server, capabilities: &Value) {
+        let ServerState::Starting { connection, .. } =
+            std::mem::replace(&mut server.state, ServerState::Failed(String::new()))
+        else {
+            return;
+        };
+
+        let sync = SyncOptions::from_capabilities(capabilities);
+        let _ = connection.client.notify("initialized", json!({}));
+        for (path, document) in self.documents.borrow_mut().iter_mut() {
+            if &document.server == key {
+                send_did_open(&connection.client, sync, path, document);
+            }
+        }
+
+        server.state = ServerState::Running {
+            connection,
+            sync,
+            capabilities: capabilities.clone(),
+        };
+    }
+
+    fn shutdown_in_background(&self, server: Server) {
+        if let ServerState::Starting { connection, .. } | ServerState::Running { connection, .. } =
+            server.state
+            && let Some(runtime) = self.runtime.get()
+        {
+            runtime.spawn(connection.client.shutdown(SHUTDOWN_TIMEOUT));
+        }
+    }
+
+    fn handle_server_message(&self, ctx: &egui::Context, key: &ServerKey, message: ServerMessage) {
+        match message {
+            ServerMessage::Request { id, method, params } => {
+                let result = match method.as_str() {
+                    "window/workDoneProgress/create" | "client/registerCapability" => {
+                        Ok(Value::Null)
+                    }
+                    // one (empty) settings object per requested item
+                    "workspace/configuration" => {
+                        let items = params["items"].as_array().map_or(0, |items| items.len());
+                        Ok(Value::Array(vec![Value::Null; items]))
+                    }
+                    "workspace/applyEdit" => {
+                        let changes = parse_workspace_edit(&params["edit"]);
+                        let expected = changes
+                            .iter()
+                            .filter(|(_, edits)| !edits.is_empty())
+                            .count();
+                        let applied =
+                            editor_interactions_store().apply_workspace_edit(ctx, changes);
+                        Ok(json!({ "applied": applied.files.len() == expected }))
+                    }
+                    _ => Err(ResponseError::new(
+                        ResponseError::METHOD_NOT_FOUND,
+                        format!("unsupported request {}", method),
+                    )),
+                };
+                if let Some(client) = self.servers.borrow().get(key).and_then(Server::client) {
+                    let _ = client.respond(id, result);
+                }
+            }
+            ServerMessage::Notification { method, params } => match method.as_str() {
+                "textDocument/publishDiagnostics" => {
+                    if let Some((path, diagnostics)) = parse_publish_diagnostics(&params) {
+                        diagnostics_store().set_diagnostics(ctx, path, diagnostics);
+                    }
+                }
+                "window/showMessage" => {
+                    if let Some(server) = self.servers.borrow_mut().get_mut(key) {
+                        let message = params["message"].as_str().and_then(|m| m.lines().next());
+                        server.message = message.map(str::to_string);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    /// Diagnostics of a server that went away are stale.
+    fn clear_diagnostics(&self, ctx: &egui::Context, key: &ServerKey) {
+        let paths: Vec<PathBuf> = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| &document.server == key)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in paths {
+            diagnostics_store().set_diagnostics(ctx, path, Vec::new());
+        }
+    }
+
+    fn initialize_params(&self, config: &ServerConfig, root: &Path) -> Value {
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        json!({
+            "processId": std::process::id(),
+            "clientInfo": { "name": "riff" },
+            "rootPath": root,
+            "rootUri": path_to_uri(root),
+            "workspaceFolders": [{ "uri": path_to_uri(root), "name": name }],
+            "initializationOptions": config.initialization_options,
+            "capabilities": {
+                "general": { "positionEncodings": ["utf-16"] },
+                "textDocument": {
+                    "synchronization": { "didSave": true, "dynamicRegistration": false },
+                    "definition": { "linkSupport": true },
+                    "hover": { "contentFormat": ["markdown", "plaintext"] },
+                    "signatureHelp": {
+                        "signatureInformation": {
+                            "documentationFormat": ["markdown", "plaintext"],
+                            "parameterInformation": { "labelOffsetSupport": true }
+                        }
+                    },
+                    "references": {},
+                    "rename": { "prepareSupport": true },
+                    "codeAction": {
+                        "codeActionLiteralSupport": {
+                            "codeActionKind": {
+                                "valueSet": ["", "quickfix", "refactor", "source"]
+                            }
+                        },
+                        "isPreferredSupport": true,
+                        "disabledSupport": true,
+                        "dataSupport": true,
+                        "resolveSupport": { "properties": ["edit"] }
+                    },
+                    "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+                    "inlayHint": {},
+                    "foldingRange": { "lineFoldingOnly": true },
+                    "semanticTokens": {
+                        "requests": { "full": { "delta": true } },
+                        "tokenTypes": TOKEN_TYPES,
+                        "tokenModifiers": TOKEN_MODIFIERS,
+                        "formats": ["relative"],
+                        "multilineTokenSupport": false,
+                        "overlappingTokenSupport": false
+                    },
+                    "completion": {
+                        "completionItem": {
+                            "snippetSupport": true,
+                            "documentationFormat": ["markdown", "plaintext"]
+                        },
+                        "contextSupport": true
+                    }
+                },
+                "workspace": {
+                    "workspaceFolders": true,
+                    "symbol": {},
+                    "applyEdit": true,
+                    "executeCommand": {},
+                    "workspaceEdit": { "documentChanges": true }
+                }
+            }
+        })
+    }
+
+    fn runtime(&self) -> anyhow::Result<&Runtime> {
+        if self.runtime.get().is_none() {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .thread_name("lsp")
+                .enable_all()
+                .build()?;
+            let _ = self.runtime.set(runtime);
+        }
+        Ok(self.runtime.get().expect("runtime was just set"))
+    }
+}
+
+impl Default for LspManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn send_did_open(client: &LspClient, sync: SyncOptions, path: &Path, document: &mut Document) {
+    if !sync.open_close {
+        return;
+    }
+    let text = document.text.borrow();
+    document.revision = text.revision();
+    let _ = client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": path_to_uri(path),
+                "languageId": document.language_id,
+                "version": document.version,
+                "text": text.to_string(),
+            }
+        }),
+    );
+}
+
+thread_local! {
+    static MANAGER: Rc<LspManager> = Rc::new(LspManager::new());
+}
+
+pub fn lsp_manager() -> Rc<LspManager> {
+    MANAGER.with(|m| m.clone())
+}
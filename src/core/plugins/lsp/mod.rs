@@ -1 +1,29 @@
+//! Language server client, its registry of servers and the requests the
+//! editor makes.
+//!
+//! `tests/lsp_client.rs` builds the client and most request modules on their
+//! own with `#[path]` (see the list there), so those modules only reach each
+//! other through `super::` and never through `crate::` paths.
+
+pub mod code_action;
+pub mod completion;
+pub mod config;
+pub mod folding_range;
+pub mod formatting;
+pub mod hover;
+pub mod inlay_hints;
 pub mod lsp;
+pub mod manager;
+pub mod protocol;
+pub mod rename;
+pub mod semantic_tokens;
+pub mod symbols;
+pub mod transport;
+
+#[cfg(test)]
+mod tests;
+
+pub use lsp::{
+    LspClient, PartialResults, PendingRequest, RequestId, ResponseError, ServerMessage,
+};
+pub use manager::{LspManager, RequestHandle, ServerInfo, ServerKey, ServerStatus, lsp_manager};
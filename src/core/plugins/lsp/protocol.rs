@@ -0,0 +1,337 @@
+//! Conversions between editor state and LSP wire types.
+//!
+//! LSP positions count lines and UTF-16 code units, while the editor works
+//! with byte offsets into a `String`.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A text LSP positions can be resolved in.
+pub trait LineIndex {
+    /// Position of the byte `offset` (clamped to the end of the text).
+    fn offset_to_position(&self, offset: usize) -> Position;
+
+    /// Byte offset of `position`. Positions past the end of a line resolve
+    /// to the end of that line, positions past the last line to the end of
+    /// the text.
+    fn position_to_offset(&self, position: Position) -> usize;
+}
+
+impl LineIndex for str {
+    fn offset_to_position(&self, offset: usize) -> Position {
+        let text = self;
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.matches('\n').count() as u32,
+            character: before[line_start..].encode_utf16().count() as u32,
+        }
+    }
+
+    fn position_to_offset(&self, position: Position) -> usize {
+        let text = self;
+        let mut line_start = 0;
+        for _ in 0..position.line {
+            match text[line_start..].find('\n') {
+                Some(i) => line_start += i + 1,
+                None => return text.len(),
+            }
+        }
+
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |i| line_start + i);
+
+        let mut units = 0;
+        for (i, ch) in text[line_start..line_end].char_indices() {
+            if units >= position.character {
+                return line_start + i;
+            }
+            units += ch.len_utf16() as u32;
+        }
+        line_end
+    }
+}
+
+impl LineIndex for String {
+    fn offset_to_position(&self, offset: usize) -> Position {
+        self.as_str().offset_to_position(offset)
+    }
+
+    fn position_to_offset(&self, position: Position) -> usize {
+        self.as_str().position_to_offset(position)
+    }
+}
+
+/// Position of the byte `offset` in `text` (clamped to the end of the text).
+pub fn offset_to_position(text: &(impl LineIndex + ?Sized), offset: usize) -> Position {
+    text.offset_to_position(offset)
+}
+
+/// Byte offset of `position` in `text`. Positions past the end of a line
+/// resolve to the end of that line, positions past the last line to the end
+/// of the text.
+pub fn position_to_offset(text: &(impl LineIndex + ?Sized), position: Position) -> usize {
+    text.position_to_offset(position)
+}
+
+pub fn range_to_offsets(text: &(impl LineIndex + ?Sized), range: Range) -> std::ops::Range<usize> {
+    position_to_offset(text, range.start)..position_to_offset(text, range.end)
+}
+
+pub fn offsets_to_range(
+    text: &(impl LineIndex + ?Sized),
+    offsets: std::ops::Range<usize>,
+) -> Range {
+    Range {
+        start: offset_to_position(text, offsets.start),
+        end: offset_to_position(text, offsets.end),
+    }
+}
+
+/// `TextDocumentPositionParams`: the params of requests about a spot in a
+/// document, e.g. `textDocument/definition`.
+pub fn text_document_position(uri: &str, position: Position) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": position,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+/// Result of `textDocument/definition`, `textDocument/references` and the
+/// like: `null`, a `Location`, or an array of `Location`s or `LocationLink`s.
+pub fn parse_locations(value: &Value) -> Vec<Location> {
+    fn location(value: &Value) -> Option<Location> {
+        // links point at the whole definition; the selection range is the name
+        let (uri, range) = match value.get("targetUri") {
+            Some(uri) => (
+                uri,
+                value
+                    .get("targetSelectionRange")
+                    .or_else(|| value.get("targetRange"))?,
+            ),
+            None => (value.get("uri")?, value.get("range")?),
+        };
+        Some(Location {
+            path: uri_to_path(uri.as_str()?)?,
+            range: serde_json::from_value(range.clone()).ok()?,
+        })
+    }
+
+    match value {
+        Value::Array(locations) => locations.iter().filter_map(location).collect(),
+        value => location(value).into_iter().collect(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// Applies edits whose ranges refer to the original `text` (as LSP requires)
+/// and returns the result. Edits must not overlap.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> String {
+    let mut result = text.to_string();
+    for (range, new_text) in resolve_text_edits(text, edits) {
+        result.replace_range(range, new_text);
+    }
+    result
+}
+
+/// The byte ranges of `edits` in `text` with their new text, back to front,
+/// so applying them in order keeps the earlier offsets valid; inserts at the
+/// same offset end up in array order, and edits overlapping a later one are
+/// dropped.
+pub fn resolve_text_edits<'e>(
+    text: &(impl LineIndex + ?Sized),
+    edits: &'e [TextEdit],
+) -> Vec<(std::ops::Range<usize>, &'e str)> {
+    let mut resolved: Vec<(usize, std::ops::Range<usize>, &str)> = edits
+        .iter()
+        .enumerate()
+        .map(|(i, edit)| {
+            (
+                i,
+                range_to_offsets(text, edit.range),
+                edit.new_text.as_str(),
+            )
+        })
+        .collect();
+    resolved.sort_by_key(|(i, range, _)| std::cmp::Reverse((range.start, *i)));
+
+    let mut last_start = usize::MAX;
+    resolved.retain(|(_, range, _)| {
+        let keep = range.end <= last_start;
+        if keep {
+            last_start = range.start;
+        }
+        keep
+    });
+    resolved
+        .into_iter()
+        .map(|(_, range, new_text)| (range, new_text))
+        .collect()
+}
+
+/// Edits of a `WorkspaceEdit`, one entry per file in the order they have to
+/// be applied. `documentChanges` wins over `changes` when both are present;
+/// resource operations (create, rename, delete) are skipped.
+pub fn parse_workspace_edit(value: &Value) -> Vec<(PathBuf, Vec<TextEdit>)> {
+    fn edits(value: &Value) -> Vec<TextEdit> {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+
+    if let Some(document_changes) = value["documentChanges"].as_array() {
+        return document_changes
+            .iter()
+            .filter_map(|change| {
+                let path = uri_to_path(change["textDocument"]["uri"].as_str()?)?;
+                Some((path, edits(&change["edits"])))
+            })
+            .collect();
+    }
+
+    let Some(changes) = value["changes"].as_object() else {
+        return vec![];
+    };
+    changes
+        .iter()
+        .filter_map(|(uri, change)| Some((uri_to_path(uri)?, edits(change))))
+        .collect()
+}
+
+/// `file://` URI of an absolute path, percent-encoding everything outside
+/// the unreserved set.
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive letters: file:///C:/...
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = path
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    let path = String::from_utf8(decoded).ok()?;
+    // file:///C:/... -> C:/...
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// `DiagnosticSeverity`; servers may omit it, in which case it is an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    pub message: String,
+    /// e.g. `rustc` or `clippy`
+    pub source: Option<String>,
+    pub code: Option<String>,
+    /// The diagnostic as the server sent it; code action requests pass it
+    /// back.
+    pub raw: Value,
+}
+
+impl Diagnostic {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let range = serde_json::from_value(value["range"].clone()).ok()?;
+        let severity = match value["severity"].as_u64() {
+            Some(2) => Severity::Warning,
+            Some(3) => Severity::Information,
+            Some(4) => Severity::Hint,
+            _ => Severity::Error,
+        };
+        // codes are either numbers or strings
+        let code = match &value["code"] {
+            Value::String(code) => Some(code.clone()),
+            Value::Number(code) => Some(code.to_string()),
+            _ => None,
+        };
+
+        Some(Self {
+            range,
+            severity,
+            message: value["message"].as_str()?.to_string(),
+            source: value["source"].as_str().map(str::to_string),
+            code,
+            raw: value.clone(),
+        })
+    }
+}
+
+/// Path and diagnostics of a `textDocument/publishDiagnostics` notification.
+pub fn parse_publish_diagnostics(params: &Value) -> Option<(PathBuf, Vec<Diagnostic>)> {
+    let path = uri_to_path(params["uri"].as_str()?)?;
+    let diagnostics = params["diagnostics"]
+        .as_array()?
+        .iter()
+        .filter_map(Diagnostic::from_value)
+        .collect();
+    Some((path, diagnostics))
+}
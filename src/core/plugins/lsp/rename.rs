@@ -0,0 +1,48 @@
+//! `textDocument/prepareRename` and `textDocument/rename`.
+
+use serde_json::{Value, json};
+
+use super::protocol::{Position, Range};
+
+/// What a `textDocument/prepareRename` result allows renaming.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrepareRename {
+    /// The range of the symbol, with the text to start the input with.
+    Range {
+        range: Range,
+        placeholder: Option<String>,
+    },
+    /// `{ defaultBehavior: true }`: the word under the cursor.
+    Word,
+}
+
+/// `None` when the server says there is nothing to rename at the position.
+pub fn parse_prepare_rename(value: &Value) -> Option<PrepareRename> {
+    if value["defaultBehavior"].as_bool() == Some(true) {
+        return Some(PrepareRename::Word);
+    }
+    // either a bare `Range` or `{ range, placeholder }`
+    let range = value.get("range").unwrap_or(value);
+    Some(PrepareRename::Range {
+        range: serde_json::from_value(range.clone()).ok()?,
+        placeholder: value["placeholder"].as_str().map(str::to_string),
+    })
+}
+
+/// Whether `capabilities` announce rename support, and whether it includes
+/// `textDocument/prepareRename`.
+pub fn rename_support(capabilities: &Value) -> Option<bool> {
+    match &capabilities["renameProvider"] {
+        Value::Bool(true) => Some(false),
+        Value::Object(options) => Some(options.get("prepareProvider") == Some(&Value::Bool(true))),
+        _ => None,
+    }
+}
+
+pub fn rename_params(uri: &str, position: Position, new_name: &str) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": position,
+        "newName": new_name,
+    })
+}
@@ -0,0 +1,210 @@
+//! `textDocument/semanticTokens/full` and `.../full/delta` results.
+
+use std::ops::Range as ByteRange;
+
+use serde_json::{Value, json};
+
+/// Token types the client understands, in the order of the LSP spec.
+pub const TOKEN_TYPES: &[&str] = &[
+    "namespace",
+    "type",
+    "class",
+    "enum",
+    "interface",
+    "struct",
+    "typeParameter",
+    "parameter",
+    "variable",
+    "property",
+    "enumMember",
+    "event",
+    "function",
+    "method",
+    "macro",
+    "keyword",
+    "modifier",
+    "comment",
+    "string",
+    "number",
+    "regexp",
+    "operator",
+    "decorator",
+];
+
+pub const TOKEN_MODIFIERS: &[&str] = &[
+    "declaration",
+    "definition",
+    "readonly",
+    "static",
+    "deprecated",
+    "abstract",
+    "async",
+    "modification",
+    "documentation",
+    "defaultLibrary",
+];
+
+/// What the numbers in the token data stand for, from the server's
+/// capabilities.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SemanticLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+impl SemanticLegend {
+    pub fn token_type(&self, index: u32) -> Option<&str> {
+        self.token_types.get(index as usize).map(String::as_str)
+    }
+
+    /// Names of the modifiers set in `bits`.
+    pub fn modifiers(&self, bits: u32) -> Vec<&str> {
+        self.token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i < 32 && bits & (1 << i) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+            .collect()
+    }
+}
+
+/// The legend of the server, and whether it supports `full/delta`, if it
+/// provides semantic tokens for whole documents.
+pub fn semantic_tokens_support(capabilities: &Value) -> Option<(SemanticLegend, bool)> {
+    let provider = &capabilities["semanticTokensProvider"];
+    let delta = match &provider["full"] {
+        Value::Bool(true) => false,
+        Value::Object(full) => full.get("delta") == Some(&Value::Bool(true)),
+        _ => return None,
+    };
+    let names = |value: &Value| -> Vec<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect()
+    };
+    let legend = SemanticLegend {
+        token_types: names(&provider["legend"]["tokenTypes"]),
+        token_modifiers: names(&provider["legend"]["tokenModifiers"]),
+    };
+    Some((legend, delta))
+}
+
+/// Params of `full`, or of `full/delta` when there is a previous result.
+pub fn semantic_tokens_params(uri: &str, previous_result_id: Option<&str>) -> Value {
+    match previous_result_id {
+        Some(id) => json!({ "textDocument": { "uri": uri }, "previousResultId": id }),
+        None => json!({ "textDocument": { "uri": uri } }),
+    }
+}
+
+/// Token data as the server sent it: five numbers per token, relative to
+/// the previous one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SemanticTokens {
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+/// The tokens after a `full` or `full/delta` response. A delta applies to
+/// `previous`; `None` if it can't (the caller should ask for all tokens).
+pub fn apply_semantic_tokens(
+    previous: Option<&SemanticTokens>,
+    value: &Value,
+) -> Option<SemanticTokens> {
+    let numbers = |value: &Value| -> Vec<u32> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|n| n.as_u64().map(|n| n as u32))
+            .collect()
+    };
+    let result_id = value["resultId"].as_str().map(str::to_string);
+
+    if value["data"].is_array() {
+        return Some(SemanticTokens {
+            result_id,
+            data: numbers(&value["data"]),
+        });
+    }
+
+    let mut data = previous?.data.clone();
+    let mut edits: Vec<&Value> = value["edits"].as_array()?.iter().collect();
+    // each edit refers to the original array, so apply them back to front
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit["start"].as_u64().unwrap_or(0)));
+    for edit in edits {
+        let start = edit["start"].as_u64()? as usize;
+        let end = start + edit["deleteCount"].as_u64().unwrap_or(0) as usize;
+        if end > data.len() {
+            return None;
+        }
+        data.splice(start..end, numbers(&edit["data"]));
+    }
+    Some(SemanticTokens { result_id, data })
+}
+
+/// A token with its place in the text resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: ByteRange<usize>,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// Resolves token `data` against `text` in a single pass. Tokens past the
+/// end of a line are cut at it, tokens past the end of the text dropped.
+pub fn decode_semantic_tokens(text: &str, data: &[u32]) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let mut line_start = 0;
+    let mut line_end = text.find('\n').unwrap_or(text.len());
+    // byte offset and UTF-16 column of the previous token's start
+    let mut start = (0, 0);
+
+    for token in data.chunks_exact(5) {
+        let [delta_line, delta_start, length, token_type, modifiers] =
+            [token[0], token[1], token[2], token[3], token[4]];
+
+        if delta_line > 0 {
+            for _ in 0..delta_line {
+                if line_end == text.len() {
+                    return tokens;
+                }
+                line_start = line_end + 1;
+                line_end = text[line_start..]
+                    .find('\n')
+                    .map_or(text.len(), |i| line_start + i);
+            }
+            start = (line_start, 0);
+        }
+
+        start = advance(text, line_end, start, start.1 + delta_start);
+        let end = advance(text, line_end, start, start.1 + length);
+        tokens.push(SemanticToken {
+            range: start.0..end.0,
+            token_type,
+            modifiers,
+        });
+    }
+    tokens
+}
+
+/// Walks from `(byte, column)` to UTF-16 column `target`, stopping at
+/// `line_end`.
+fn advance(
+    text: &str,
+    line_end: usize,
+    (mut byte, mut column): (usize, u32),
+    target: u32,
+) -> (usize, u32) {
+    for ch in text[byte..line_end].chars() {
+        if column >= target {
+            break;
+        }
+        byte += ch.len_utf8();
+        column += ch.len_utf16() as u32;
+    }
+    (byte, column)
+}
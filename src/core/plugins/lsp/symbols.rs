@@ -0,0 +1,261 @@
+//! `textDocument/documentSymbol` and `workspace/symbol` results.
+
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+
+use super::protocol::{Range, uri_to_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    File,
+    Module,
+    Namespace,
+    Package,
+    Class,
+    Method,
+    Property,
+    Field,
+    Constructor,
+    Enum,
+    Interface,
+    Function,
+    Variable,
+    Constant,
+    String,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    Key,
+    Null,
+    EnumMember,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl SymbolKind {
+    const ALL: [SymbolKind; 26] = [
+        SymbolKind::File,
+        SymbolKind::Module,
+        SymbolKind::Namespace,
+        SymbolKind::Package,
+        SymbolKind::Class,
+        SymbolKind::Method,
+        SymbolKind::Property,
+        SymbolKind::Field,
+        SymbolKind::Constructor,
+        SymbolKind::Enum,
+        SymbolKind::Interface,
+        SymbolKind::Function,
+        SymbolKind::Variable,
+        SymbolKind::Constant,
+        SymbolKind::String,
+        SymbolKind::Number,
+        SymbolKind::Boolean,
+        SymbolKind::Array,
+        SymbolKind::Object,
+        SymbolKind::Key,
+        SymbolKind::Null,
+        SymbolKind::EnumMember,
+        SymbolKind::Struct,
+        SymbolKind::Event,
+        SymbolKind::Operator,
+        SymbolKind::TypeParameter,
+    ];
+
+    /// Kinds are numbered from 1 in the protocol.
+    pub fn from_lsp(kind: u64) -> Option<Self> {
+        Self::ALL.get((kind as usize).checked_sub(1)?).copied()
+    }
+
+    /// Short tag shown in front of the name, matching the completion icons.
+    pub fn icon(self) -> &'static str {
+        match self {
+            SymbolKind::Method | SymbolKind::Function | SymbolKind::Constructor => "ƒ",
+            SymbolKind::Field | SymbolKind::Property | SymbolKind::Key => "◆",
+            SymbolKind::Variable => "v",
+            SymbolKind::Class | SymbolKind::Struct => "S",
+            SymbolKind::Interface => "T",
+            SymbolKind::Enum => "E",
+            SymbolKind::EnumMember => "e",
+            SymbolKind::Module | SymbolKind::Namespace | SymbolKind::Package => "M",
+            SymbolKind::File => "≡",
+            // rust-analyzer reports `impl` blocks as objects
+            SymbolKind::Object => "I",
+            SymbolKind::Constant
+            | SymbolKind::String
+            | SymbolKind::Number
+            | SymbolKind::Boolean
+            | SymbolKind::Array
+            | SymbolKind::Null => "C",
+            SymbolKind::TypeParameter => "τ",
+            SymbolKind::Operator => "±",
+            SymbolKind::Event => "⚡",
+        }
+    }
+}
+
+/// A node of the outline of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    /// e.g. the signature of a function
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    /// The whole item, doc comments and body included.
+    pub range: Range,
+    /// The part to reveal when the symbol is picked, usually its name.
+    pub selection_range: Range,
+    pub children: Vec<DocumentSymbol>,
+}
+
+impl DocumentSymbol {
+    fn from_value(value: &Value) -> Option<Self> {
+        let range: Range = serde_json::from_value(value["range"].clone()).ok()?;
+        Some(DocumentSymbol {
+            name: value["name"].as_str()?.to_string(),
+            detail: value["detail"]
+                .as_str()
+                .filter(|detail| !detail.is_empty())
+                .map(str::to_string),
+            kind: SymbolKind::from_lsp(value["kind"].as_u64()?)?,
+            selection_range: serde_json::from_value(value["selectionRange"].clone())
+                .unwrap_or(range),
+            range,
+            children: parse_document_symbols(&value["children"]),
+        })
+    }
+
+    /// A flat `SymbolInformation`, which only knows its location.
+    fn from_information(value: &Value) -> Option<Self> {
+        let range: Range = serde_json::from_value(value["location"]["range"].clone()).ok()?;
+        Some(DocumentSymbol {
+            name: value["name"].as_str()?.to_string(),
+            detail: None,
+            kind: SymbolKind::from_lsp(value["kind"].as_u64()?)?,
+            range,
+            selection_range: range,
+            children: vec![],
+        })
+    }
+}
+
+/// Whether `capabilities` announce document symbols.
+pub fn document_symbol_support(capabilities: &Value) -> bool {
+    matches!(
+        capabilities["documentSymbolProvider"],
+        Value::Bool(true) | Value::Object(_)
+    )
+}
+
+pub fn document_symbol_params(uri: &str) -> Value {
+    json!({ "textDocument": { "uri": uri } })
+}
+
+/// The symbol tree of a `documentSymbol` result. Servers without
+/// hierarchical support answer with a flat `SymbolInformation[]`; those are
+/// nested by range containment.
+pub fn parse_document_symbols(value: &Value) -> Vec<DocumentSymbol> {
+    let Some(symbols) = value.as_array() else {
+        return vec![];
+    };
+    if !symbols
+        .iter()
+        .any(|symbol| symbol.get("location").is_some())
+    {
+        let mut symbols: Vec<DocumentSymbol> = symbols
+            .iter()
+            .filter_map(DocumentSymbol::from_value)
+            .collect();
+        symbols.sort_by_key(|symbol| symbol.range.start);
+        return symbols;
+    }
+
+    let mut flat: Vec<DocumentSymbol> = symbols
+        .iter()
+        .filter_map(DocumentSymbol::from_information)
+        .collect();
+    // outer symbols first, so every symbol comes after its container
+    flat.sort_by(|a, b| {
+        a.range
+            .start
+            .cmp(&b.range.start)
+            .then(b.range.end.cmp(&a.range.end))
+    });
+    let mut roots = vec![];
+    let mut stack: Vec<DocumentSymbol> = vec![];
+    for symbol in flat {
+        while let Some(top) = stack.last() {
+            if symbol.range.end <= top.range.end {
+                break;
+            }
+            let done = stack.pop().unwrap();
+            attach(&mut stack, &mut roots, done);
+        }
+        stack.push(symbol);
+    }
+    while let Some(done) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+    roots
+}
+
+fn attach(stack: &mut [DocumentSymbol], roots: &mut Vec<DocumentSymbol>, symbol: DocumentSymbol) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(symbol),
+        None => roots.push(symbol),
+    }
+}
+
+/// A match of a `workspace/symbol` query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// e.g. the module or type the symbol is declared in
+    pub container: Option<String>,
+    pub path: PathBuf,
+    /// Servers may leave the range to a `workspaceSymbol/resolve`; the file
+    /// is opened at the top then.
+    pub range: Option<Range>,
+}
+
+/// Whether `capabilities` announce workspace symbols.
+pub fn workspace_symbol_support(capabilities: &Value) -> bool {
+    matches!(
+        capabilities["workspaceSymbolProvider"],
+        Value::Bool(true) | Value::Object(_)
+    )
+}
+
+/// Params asking for the matches of `query` to be streamed as `$/progress`
+/// notifications for `partial_result_token` before the response.
+pub fn workspace_symbol_params(query: &str, partial_result_token: &str) -> Value {
+    json!({ "query": query, "partialResultToken": partial_result_token })
+}
+
+/// Symbols of a `workspace/symbol` result or of one of its partial results,
+/// both `SymbolInformation[]` and `WorkspaceSymbol[]`.
+pub fn parse_workspace_symbols(value: &Value) -> Vec<WorkspaceSymbol> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|symbol| {
+            let location = &symbol["location"];
+            Some(WorkspaceSymbol {
+                name: symbol["name"].as_str()?.to_string(),
+                kind: SymbolKind::from_lsp(symbol["kind"].as_u64()?)?,
+                container: symbol["containerName"]
+                    .as_str()
+                    .filter(|container| !container.is_empty())
+                    .map(str::to_string),
+                path: uri_to_path(location["uri"].as_str()?)?,
+                range: serde_json::from_value(location["range"].clone()).ok(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,892 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::core::buffer::TextBuffer;
+
+use crate::core::plugins::lsp::code_action::{
+    code_action_support, execute_command_params, parse_code_actions,
+};
+use crate::core::plugins::lsp::completion::{
+    CompletionItem, expand_snippet, filter_items, parse_completion_response, word_start,
+};
+use crate::core::plugins::lsp::config::{ServerConfig, ServerRegistry};
+use crate::core::plugins::lsp::folding_range::{folding_range_support, parse_folding_ranges};
+use crate::core::plugins::lsp::formatting::{
+    diff_edit, formatting_support, parse_text_edits, rust_edition,
+};
+use crate::core::plugins::lsp::hover::{parse_hover, parse_signature_help};
+use crate::core::plugins::lsp::inlay_hints::{InlayHintKind, parse_inlay_hints};
+use crate::core::plugins::lsp::manager::{SyncKind, SyncOptions, content_changes, restart_delay};
+use crate::core::plugins::lsp::protocol::{
+    Position, Range, Severity, TextEdit, apply_text_edits, offset_to_position, offsets_to_range,
+    parse_locations, parse_publish_diagnostics, parse_workspace_edit, path_to_uri,
+    position_to_offset, range_to_offsets, uri_to_path,
+};
+use crate::core::plugins::lsp::rename::{PrepareRename, parse_prepare_rename, rename_support};
+use crate::core::plugins::lsp::semantic_tokens::{
+    SemanticTokens, apply_semantic_tokens, decode_semantic_tokens, semantic_tokens_support,
+};
+use crate::core::plugins::lsp::symbols::{
+    SymbolKind, document_symbol_support, parse_document_symbols, parse_workspace_symbols,
+};
+use crate::core::plugins::lsp::transport::{FrameDecoder, FrameError, encode};
+use crate::core::search::fuzzy_match;
+
+#[test]
+fn test_decode_message_split_across_reads() {
+    let frame = encode(&json!({ "jsonrpc": "2.0", "id": 1, "result": "héllo" }));
+    let mut decoder = FrameDecoder::new();
+
+    // split inside the header, inside the separator and inside a multi-byte char
+    for piece in frame.chunks(5) {
+        assert!(decoder.next_message().unwrap().is_none());
+        decoder.extend(piece);
+    }
+
+    let message = decoder.next_message().unwrap().unwrap();
+    assert_eq!(message["result"], "héllo");
+    assert!(decoder.next_message().unwrap().is_none());
+}
+
+#[test]
+fn test_decode_several_messages_in_one_read() {
+    let mut bytes = encode(&json!({ "id": 1 }));
+    bytes.extend(encode(&json!({ "id": 2 })));
+    bytes.extend(&encode(&json!({ "id": 3 }))[..10]);
+
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(&bytes);
+
+    assert_eq!(decoder.next_message().unwrap().unwrap()["id"], 1);
+    assert_eq!(decoder.next_message().unwrap().unwrap()["id"], 2);
+    assert!(decoder.next_message().unwrap().is_none());
+}
+
+#[test]
+fn test_decode_extra_headers() {
+    let body = r#"{"id":7}"#;
+    let frame = format!(
+        "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(frame.as_bytes());
+    assert_eq!(decoder.next_message().unwrap().unwrap()["id"], 7);
+}
+
+#[test]
+fn test_decode_rejects_missing_length() {
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(b"Content-Type: text/plain\r\n\r\n{}");
+    assert!(matches!(
+        decoder.next_message(),
+        Err(FrameError::MissingContentLength)
+    ));
+}
+
+#[test]
+fn test_positions_count_utf16_units() {
+    let text = "fn a() {}\nlet é = \"😀x\";\n";
+    let x = text.find('x').unwrap();
+
+    assert_eq!(
+        offset_to_position(text, 0),
+        Position {
+            line: 0,
+            character: 0
+        }
+    );
+    // `😀` is two UTF-16 units, `é` one
+    assert_eq!(
+        offset_to_position(text, x),
+        Position {
+            line: 1,
+            character: 11
+        }
+    );
+    assert_eq!(
+        position_to_offset(
+            text,
+            Position {
+                line: 1,
+                character: 11
+            }
+        ),
+        x
+    );
+    assert_eq!(
+        offset_to_position(text, text.len()),
+        Position {
+            line: 2,
+            character: 0
+        }
+    );
+
+    // past the end of a line / of the text
+    assert_eq!(
+        position_to_offset(
+            text,
+            Position {
+                line: 0,
+                character: 99
+            }
+        ),
+        9
+    );
+    assert_eq!(
+        position_to_offset(
+            text,
+            Position {
+                line: 9,
+                character: 0
+            }
+        ),
+        text.len()
+    );
+}
+
+#[test]
+fn test_uri_round_trip() {
+    let path = Path::new("/home/me/my project/100%.rs");
+    let uri = path_to_uri(path);
+    assert_eq!(uri, "file:///home/me/my%20project/100%25.rs");
+    assert_eq!(uri_to_path(&uri).unwrap(), path);
+    assert_eq!(uri_to_path("untitled:1"), None);
+}
+
+#[test]
+fn test_incremental_changes_apply_to_old_text() {
+    let cases = [
+        ("fn main() {}\n", vec![(11..11, "\n    println!();\n")]),
+        ("let a = 1;\nlet b = 2;\n", vec![(11..22, ""), (4..5, "x")]),
+        (
+            "héllo wörld",
+            vec![(7..7, "😀 "), (0..1, "H"), (12..17, "")],
+        ),
+        ("same", vec![]),
+    ];
+
+    for (old, edits) in cases {
+        let mut buffer = TextBuffer::from(old);
+        let revision = buffer.revision();
+        for (range, text) in &edits {
+            buffer.replace(range.clone(), text);
+        }
+
+        let changes = content_changes(&buffer, revision, SyncKind::Incremental);
+        assert_eq!(changes.len(), edits.len());
+        let mut text = old.to_string();
+        for change in changes {
+            let range: Range = serde_json::from_value(change["range"].clone()).unwrap();
+            let offsets = range_to_offsets(text.as_str(), range);
+            text.replace_range(offsets, change["text"].as_str().unwrap());
+        }
+        assert_eq!(buffer, text.as_str());
+    }
+
+    let mut buffer = TextBuffer::from("a");
+    let revision = buffer.revision();
+    buffer.replace(0..1, "b");
+    assert_eq!(
+        content_changes(&buffer, revision, SyncKind::Full),
+        vec![json!({ "text": "b" })]
+    );
+    // edits of another buffer can't be replayed
+    assert_eq!(
+        content_changes(
+            &buffer,
+            TextBuffer::from("a").revision(),
+            SyncKind::Incremental
+        ),
+        vec![json!({ "text": "b" })]
+    );
+}
+
+#[test]
+fn test_buffer_positions_match_text_positions() {
+    let text = "héllo\n😀 wörld\n\nend";
+    let buffer = TextBuffer::from(text);
+    for offset in 0..=text.len() + 1 {
+        assert_eq!(
+            offset_to_position(&buffer, offset),
+            offset_to_position(text, offset),
+            "offset {offset}"
+        );
+    }
+    for line in 0..5 {
+        for character in 0..12 {
+            let position = Position { line, character };
+            assert_eq!(
+                position_to_offset(&buffer, position),
+                position_to_offset(text, position),
+                "{position:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_sync_options_from_capabilities() {
+    let legacy = SyncOptions::from_capabilities(&json!({ "textDocumentSync": 1 }));
+    assert_eq!(legacy.change, SyncKind::Full);
+    assert!(legacy.open_close);
+
+    let options = SyncOptions::from_capabilities(&json!({
+        "textDocumentSync": { "openClose": true, "change": 2, "save": { "includeText": true } }
+    }));
+    assert_eq!(
+        options,
+        SyncOptions {
+            open_close: true,
+            change: SyncKind::Incremental,
+            save: true,
+            save_include_text: true,
+        }
+    );
+
+    let none = SyncOptions::from_capabilities(&Value::Null);
+    assert_eq!(none.change, SyncKind::None);
+    assert!(!none.open_close && !none.save);
+}
+
+#[test]
+fn test_parse_publish_diagnostics() {
+    let range = json!({
+        "start": { "line": 2, "character": 4 },
+        "end": { "line": 2, "character": 9 }
+    });
+    let params = json!({
+        "uri": "file:///src/main.rs",
+        "diagnostics": [
+            { "range": range, "severity": 2, "message": "unused variable", "source": "rustc", "code": "unused_variables" },
+            { "range": range, "message": "mismatched types", "code": 308 },
+            { "range": range }
+        ]
+    });
+
+    let (path, diagnostics) = parse_publish_diagnostics(&params).unwrap();
+    assert_eq!(path, Path::new("/src/main.rs"));
+    // the entry without a message is dropped
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].source.as_deref(), Some("rustc"));
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].code.as_deref(), Some("308"));
+    assert_eq!(
+        diagnostics[1].range.start,
+        Position {
+            line: 2,
+            character: 4
+        }
+    );
+    // kept as sent, for `textDocument/codeAction`
+    assert_eq!(diagnostics[0].raw, params["diagnostics"][0]);
+}
+
+#[test]
+fn test_apply_text_edits_back_to_front() {
+    let text = "let a = 1;\nlet b = 2;\n";
+    let edit = |range: std::ops::Range<usize>, new_text: &str| TextEdit {
+        range: offsets_to_range(text, range),
+        new_text: new_text.to_string(),
+    };
+
+    let edits = [
+        edit(15..16, "bee"),
+        edit(0..0, "use x;\n"),
+        edit(4..5, "alpha"),
+        // inserts at the same offset keep their order
+        edit(0..0, "use y;\n"),
+    ];
+    assert_eq!(
+        apply_text_edits(text, &edits),
+        "use x;\nuse y;\nlet alpha = 1;\nlet bee = 2;\n"
+    );
+}
+
+#[test]
+fn test_filter_items_orders_by_score_then_sort_text() {
+    let items = parse_completion_response(&json!([
+        { "label": "expression" },
+        { "label": "println!", "sortText": "b" },
+        { "label": "print!", "sortText": "a" },
+        { "label": "chmod" },
+        { "label": "HashMap" }
+    ]))
+    .items;
+    let score = |query: &str, text: &str| Some(fuzzy_match(query, text)?.score);
+    let labels = |indices: Vec<usize>| -> Vec<String> {
+        indices
+            .into_iter()
+            .map(|i| items[i].label.clone())
+            .collect()
+    };
+
+    assert_eq!(
+        labels(filter_items(&items, "pri", score)),
+        vec!["print!", "println!", "expression"]
+    );
+    // word starts, camel case humps included, beat matches within a word
+    assert_eq!(
+        labels(filter_items(&items, "hm", score)),
+        vec!["HashMap", "chmod"]
+    );
+    // an empty query keeps everything
+    assert_eq!(filter_items(&items, "", score).len(), 5);
+}
+
+#[test]
+fn test_parse_completion_items() {
+    let list = parse_completion_response(&json!({
+        "isIncomplete": true,
+        "items": [
+            {
+                "label": "len",
+                "kind": 2,
+                "documentation": { "kind": "markdown", "value": "Returns the length." },
+                "textEdit": {
+                    "insert": { "start": { "line": 0, "character": 2 }, "end": { "line": 0, "character": 3 } },
+                    "replace": { "start": { "line": 0, "character": 2 }, "end": { "line": 0, "character": 5 } },
+                    "newText": "len()"
+                }
+            },
+            { "kind": 3 }
+        ]
+    }));
+
+    assert!(list.is_incomplete);
+    assert_eq!(list.items.len(), 1);
+    let item: &CompletionItem = &list.items[0];
+    assert_eq!(item.documentation.as_deref(), Some("Returns the length."));
+    // InsertReplaceEdit: the insert range is used
+    assert_eq!(item.text_edit.as_ref().unwrap().range.end.character, 3);
+}
+
+#[test]
+fn test_expand_snippet() {
+    let snippet = expand_snippet("println!(\"${1:text}\")$0");
+    assert_eq!(snippet.text, "println!(\"text\")");
+    assert_eq!(snippet.tabstops, vec![10..14, 16..16]);
+
+    // nested placeholders, choices, variables and escapes
+    let snippet = expand_snippet(
+        "fn ${1:name}(${2:arg: ${3:u32}}) -> ${4|i32,u64|} { \\$ ${TM_FILENAME:main} }",
+    );
+    assert_eq!(snippet.text, "fn name(arg: u32) -> i32 { $ main }");
+    assert_eq!(
+        snippet
+            .tabstops
+            .iter()
+            .map(|r| &snippet.text[r.clone()])
+            .collect::<Vec<_>>(),
+        vec!["name", "arg: u32", "u32", "i32", ""]
+    );
+
+    // without tab stops the cursor goes to the end
+    assert_eq!(expand_snippet("foo()").tabstops, vec![5..5]);
+}
+
+#[test]
+fn test_word_start() {
+    let text = "self.café_count";
+    assert_eq!(word_start(text, text.len()), 5);
+    assert_eq!(word_start(text, 5), 5);
+    assert_eq!(word_start(text, 4), 0);
+}
+
+#[test]
+fn test_parse_locations() {
+    let range = json!({
+        "start": { "line": 1, "character": 3 },
+        "end": { "line": 1, "character": 9 }
+    });
+    let whole = json!({
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 4, "character": 1 }
+    });
+
+    assert!(parse_locations(&Value::Null).is_empty());
+
+    let single = parse_locations(&json!({ "uri": "file:///a.rs", "range": range }));
+    assert_eq!(single.len(), 1);
+    assert_eq!(single[0].path, Path::new("/a.rs"));
+
+    let links = parse_locations(&json!([
+        { "targetUri": "file:///b.rs", "targetRange": whole, "targetSelectionRange": range },
+        { "targetUri": "file:///c.rs", "targetRange": whole },
+        { "uri": "not a file uri", "range": range }
+    ]));
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].range.start.character, 3);
+    assert_eq!(links[1].range.end.line, 4);
+}
+
+#[test]
+fn test_parse_hover_contents() {
+    let markup = json!({ "contents": { "kind": "markdown", "value": "**bold**" } });
+    assert_eq!(parse_hover(&markup).as_deref(), Some("**bold**"));
+
+    let marked = json!({
+        "contents": [{ "language": "rust", "value": "fn len(&self) -> usize" }, "Returns the length."]
+    });
+    assert_eq!(
+        parse_hover(&marked).as_deref(),
+        Some("```rust\nfn len(&self) -> usize\n```\n\nReturns the length.")
+    );
+
+    assert_eq!(parse_hover(&Value::Null), None);
+    assert_eq!(parse_hover(&json!({ "contents": "  " })), None);
+}
+
+#[test]
+fn test_parse_signature_help() {
+    let help = parse_signature_help(&json!({
+        "signatures": [
+            { "label": "fn a()" },
+            {
+                "label": "fn max(max: u8, b: u8)",
+                "documentation": { "kind": "markdown", "value": "Larger one." },
+                "parameters": [{ "label": "max: u8" }, { "label": [16, 21] }],
+                "activeParameter": 0
+            }
+        ],
+        "activeSignature": 1,
+        "activeParameter": 1
+    }))
+    .unwrap();
+
+    // the parameter name is looked up after the function name
+    assert_eq!(help.active_parameter, Some(7..14));
+    assert_eq!(help.documentation.as_deref(), Some("Larger one."));
+    assert_eq!((help.active_signature, help.signature_count), (1, 2));
+
+    let help = parse_signature_help(&json!({
+        "signatures": [{ "label": "fn é(a: u8, b: u8)", "parameters": [{ "label": "a: u8" }, { "label": [12, 17] }] }],
+        "activeParameter": 1
+    }))
+    .unwrap();
+    // UTF-16 offsets are converted to bytes
+    assert_eq!(&help.label[help.active_parameter.unwrap()], "b: u8");
+
+    assert_eq!(parse_signature_help(&json!({ "signatures": [] })), None);
+}
+
+#[test]
+fn test_parse_workspace_edit() {
+    let edit = |line: u32, text: &str| {
+        json!({
+            "range": { "start": { "line": line, "character": 0 }, "end": { "line": line, "character": 3 } },
+            "newText": text
+        })
+    };
+
+    let changes = parse_workspace_edit(&json!({
+        "changes": { "file:///tmp/a.rs": [edit(0, "one"), edit(2, "two")] }
+    }));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, PathBuf::from("/tmp/a.rs"));
+    assert_eq!(changes[0].1.len(), 2);
+    assert_eq!(changes[0].1[1].new_text, "two");
+
+    // `documentChanges` wins; resource operations are skipped
+    let changes = parse_workspace_edit(&json!({
+        "changes": { "file:///tmp/a.rs": [edit(0, "one")] },
+        "documentChanges": [
+            { "textDocument": { "uri": "file:///tmp/b.rs", "version": 3 }, "edits": [edit(1, "b")] },
+            { "kind": "create", "uri": "file:///tmp/c.rs" },
+            { "textDocument": { "uri": "file:///tmp/b.rs", "version": null }, "edits": [edit(4, "c")] }
+        ]
+    }));
+    let paths: Vec<&PathBuf> = changes.iter().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        [&PathBuf::from("/tmp/b.rs"), &PathBuf::from("/tmp/b.rs")]
+    );
+
+    assert!(parse_workspace_edit(&Value::Null).is_empty());
+}
+
+#[test]
+fn test_parse_prepare_rename() {
+    let range =
+        json!({ "start": { "line": 1, "character": 4 }, "end": { "line": 1, "character": 9 } });
+    let expected = Range {
+        start: Position {
+            line: 1,
+            character: 4,
+        },
+        end: Position {
+            line: 1,
+            character: 9,
+        },
+    };
+
+    assert_eq!(
+        parse_prepare_rename(&range),
+        Some(PrepareRename::Range {
+            range: expected,
+            placeholder: None
+        })
+    );
+    assert_eq!(
+        parse_prepare_rename(&json!({ "range": range, "placeholder": "count" })),
+        Some(PrepareRename::Range {
+            range: expected,
+            placeholder: Some("count".to_string())
+        })
+    );
+    assert_eq!(
+        parse_prepare_rename(&json!({ "defaultBehavior": true })),
+        Some(PrepareRename::Word)
+    );
+    assert_eq!(parse_prepare_rename(&Value::Null), None);
+
+    assert_eq!(
+        rename_support(&json!({ "renameProvider": true })),
+        Some(false)
+    );
+    assert_eq!(
+        rename_support(&json!({ "renameProvider": { "prepareProvider": true } })),
+        Some(true)
+    );
+    assert_eq!(rename_support(&json!({})), None);
+}
+
+#[test]
+fn test_parse_code_actions() {
+    let actions = parse_code_actions(&json!([
+        { "title": "Organize imports", "command": "organize", "arguments": [1] },
+        {
+            "title": "Extract function",
+            "kind": "refactor.extract",
+            "disabled": { "reason": "nothing selected" }
+        },
+        { "title": "Add `;`", "kind": "quickfix", "isPreferred": true, "edit": { "changes": {} } },
+        { "kind": "quickfix" }
+    ]));
+
+    let titles: Vec<&str> = actions.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(titles, ["Add `;`", "Organize imports", "Extract function"]);
+    assert_eq!(actions[0].kind.as_deref(), Some("quickfix"));
+    assert!(!actions[0].needs_resolve());
+    // a bare command runs as is
+    assert_eq!(actions[1].kind, None);
+    assert_eq!(
+        execute_command_params(actions[1].command.as_ref().unwrap()),
+        json!({ "command": "organize", "arguments": [1] })
+    );
+    assert_eq!(actions[2].disabled.as_deref(), Some("nothing selected"));
+    assert!(actions[2].needs_resolve());
+
+    assert_eq!(
+        code_action_support(&json!({ "codeActionProvider": true })),
+        Some(false)
+    );
+    assert_eq!(
+        code_action_support(&json!({ "codeActionProvider": { "resolveProvider": true } })),
+        Some(true)
+    );
+    assert_eq!(code_action_support(&json!({})), None);
+}
+
+#[test]
+fn test_diff_edit() {
+    let old = "fn main(){\n  let é=1;\n}\n";
+    let new = "fn main() {\n    let é = 1;\n}\n";
+    let edit = diff_edit(old, new).unwrap();
+    // only what lies between the common prefix and suffix
+    assert_eq!(range_to_offsets(old, edit.range), 9..20);
+    assert_eq!(edit.new_text, " {\n    let é = ");
+    assert_eq!(apply_text_edits(old, &[edit]), new);
+
+    assert_eq!(diff_edit(old, old), None);
+    let edit = diff_edit("aé", "a").unwrap();
+    assert_eq!(apply_text_edits("aé", &[edit]), "a");
+}
+
+#[test]
+fn test_formatting_support_and_edits() {
+    let capabilities = json!({
+        "documentFormattingProvider": true,
+        "documentRangeFormattingProvider": { "rangesSupport": false }
+    });
+    assert!(formatting_support(&capabilities, false));
+    assert!(formatting_support(&capabilities, true));
+    assert!(!formatting_support(
+        &json!({ "documentFormattingProvider": false }),
+        false
+    ));
+
+    assert_eq!(parse_text_edits(&Value::Null), vec![]);
+    let range = offsets_to_range("a  \n", 1..3);
+    assert_eq!(
+        parse_text_edits(&json!([{ "range": range, "newText": "" }])),
+        vec![TextEdit {
+            range,
+            new_text: String::new()
+        }]
+    );
+}
+
+#[test]
+fn test_rust_edition() {
+    let dir = std::env::temp_dir().join(format!("riff-edition-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        rust_edition(&dir.join("src/main.rs")).as_deref(),
+        Some("2021")
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_parse_inlay_hints() {
+    let hints = parse_inlay_hints(&json!([
+        {
+            "position": { "line": 3, "character": 8 },
+            "label": [{ "value": "count" }, { "value": ":" }],
+            "kind": 2,
+            "paddingRight": true
+        },
+        { "position": { "line": 1, "character": 9 }, "label": ": i32", "kind": 1 },
+        { "position": { "line": 2, "character": 0 } }
+    ]));
+
+    // sorted, the one without a label dropped
+    assert_eq!(hints.len(), 2);
+    assert_eq!(hints[0].text(), ": i32");
+    assert_eq!(hints[0].kind, Some(InlayHintKind::Type));
+    assert_eq!(hints[1].text(), "count: ");
+    assert_eq!(hints[1].kind, Some(InlayHintKind::Parameter));
+    assert_eq!(parse_inlay_hints(&Value::Null), vec![]);
+}
+
+#[test]
+fn test_parse_folding_ranges() {
+    let ranges = parse_folding_ranges(&json!([
+        { "startLine": 4, "endLine": 6, "kind": "region" },
+        { "startLine": 0, "startCharacter": 10, "endLine": 8 },
+        { "startLine": 0, "endLine": 2 },
+        { "startLine": 7, "endLine": 7 }
+    ]));
+
+    // the outer one of two on a line; nothing to fold on one line
+    assert_eq!(ranges, vec![0..9, 4..7]);
+    assert!(folding_range_support(
+        &json!({ "foldingRangeProvider": true })
+    ));
+    assert!(!folding_range_support(&json!({})));
+}
+
+#[test]
+fn test_semantic_tokens_support_and_delta() {
+    let capabilities = json!({
+        "semanticTokensProvider": {
+            "legend": { "tokenTypes": ["function", "variable"], "tokenModifiers": ["declaration", "static"] },
+            "full": { "delta": true }
+        }
+    });
+    let (legend, delta) = semantic_tokens_support(&capabilities).unwrap();
+    assert!(delta);
+    assert_eq!(legend.token_type(1), Some("variable"));
+    assert_eq!(legend.token_type(2), None);
+    assert_eq!(legend.modifiers(0b11), ["declaration", "static"]);
+    assert_eq!(
+        semantic_tokens_support(&json!({ "semanticTokensProvider": { "range": true } })),
+        None
+    );
+
+    let previous = SemanticTokens {
+        result_id: Some("1".to_string()),
+        data: vec![0, 0, 2, 0, 0, 1, 4, 3, 1, 0],
+    };
+    // both edits refer to the original data
+    let delta = json!({
+        "resultId": "2",
+        "edits": [
+            { "start": 5, "deleteCount": 5, "data": [2, 0, 1, 1, 0] },
+            { "start": 2, "deleteCount": 1, "data": [3] }
+        ]
+    });
+    assert_eq!(
+        apply_semantic_tokens(Some(&previous), &delta),
+        Some(SemanticTokens {
+            result_id: Some("2".to_string()),
+            data: vec![0, 0, 3, 0, 0, 2, 0, 1, 1, 0],
+        })
+    );
+    // nothing to apply a delta to
+    assert_eq!(apply_semantic_tokens(None, &delta), None);
+}
+
+#[test]
+fn test_decode_semantic_tokens() {
+    let text = "let é = 1;\nfn 𝒇() {}\n";
+    // `é`, `1` and, on the next line, `𝒇` (two UTF-16 units) and a token
+    // running past the end of its line
+    let data = [0, 4, 1, 1, 0, 0, 4, 1, 2, 0, 1, 3, 2, 0, 1, 0, 2, 40, 3, 0];
+    let tokens = decode_semantic_tokens(text, &data);
+
+    let words: Vec<&str> = tokens.iter().map(|t| &text[t.range.clone()]).collect();
+    assert_eq!(words, ["é", "1", "𝒇", "() {}"]);
+    assert_eq!(tokens[2].token_type, 0);
+    assert_eq!(tokens[2].modifiers, 1);
+
+    // tokens past the last line are dropped
+    assert_eq!(decode_semantic_tokens("x", &[5, 0, 1, 0, 0]), vec![]);
+}
+
+#[test]
+fn test_server_registry_overrides() {
+    let mut registry = ServerRegistry::with_builtins();
+    assert_eq!(
+        registry.for_language("rust").unwrap().command,
+        "rust-analyzer"
+    );
+
+    registry
+        .load_json(
+            r#"[{
+                "language": "rust",
+                "command": "ra-multiplex",
+                "args": ["client"],
+                "env": { "RA_LOG": "error" },
+                "initializationOptions": { "cargo": { "features": "all" } }
+            }]"#,
+        )
+        .unwrap();
+    let rust = registry.for_language("rust").unwrap();
+    assert_eq!(rust.command, "ra-multiplex");
+    assert_eq!(rust.args, ["client"]);
+    assert_eq!(rust.env["RA_LOG"], "error");
+    assert_eq!(
+        rust.initialization_options,
+        Some(json!({ "cargo": { "features": "all" } }))
+    );
+    assert!(rust.root_markers.is_empty());
+
+    assert!(registry.for_language("lua").is_some());
+    assert!(registry.for_language("markdown").is_none());
+    assert!(registry.load_json(r#"[{ "language": "go" }]"#).is_err());
+}
+
+#[test]
+fn test_find_root() {
+    let workspace = std::env::temp_dir().join(format!("riff-root-{}", std::process::id()));
+    let member = workspace.join("crates").join("member");
+    std::fs::create_dir_all(member.join("src")).unwrap();
+    std::fs::write(workspace.join("Cargo.toml"), "").unwrap();
+    std::fs::write(member.join("Cargo.toml"), "").unwrap();
+
+    let config = ServerConfig {
+        language: "rust".to_string(),
+        command: "rust-analyzer".to_string(),
+        args: vec![],
+        env: Default::default(),
+        initialization_options: None,
+        root_markers: vec!["Cargo.toml".to_string()],
+    };
+    let file = member.join("src").join("lib.rs");
+
+    // the outermost marked directory, so a cargo workspace gets one server
+    assert_eq!(config.find_root(&file, Some(&workspace)), workspace);
+    // but not above the workspace
+    assert_eq!(config.find_root(&file, Some(&member)), member);
+
+    let unmarked = ServerConfig {
+        root_markers: vec![],
+        ..config
+    };
+    assert_eq!(unmarked.find_root(&file, Some(&workspace)), workspace);
+    assert_eq!(unmarked.find_root(&file, None), member.join("src"));
+
+    std::fs::remove_dir_all(&workspace).unwrap();
+}
+
+#[test]
+fn test_restart_delay_backs_off() {
+    let delays: Vec<u64> = (1..=8)
+        .map(|crashes| restart_delay(crashes).as_secs())
+        .collect();
+    assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+}
+
+fn lines(start: u32, end: u32) -> Value {
+    json!({
+        "start": { "line": start, "character": 0 },
+        "end": { "line": end, "character": 1 }
+    })
+}
+
+#[test]
+fn test_parse_document_symbols() {
+    assert!(document_symbol_support(
+        &json!({ "documentSymbolProvider": {} })
+    ));
+    assert!(!document_symbol_support(&json!({})));
+
+    let tree = parse_document_symbols(&json!([
+        { "name": "main", "kind": 12, "range": lines(6, 8), "selectionRange": lines(6, 6) },
+        {
+            "name": "Point",
+            "detail": "struct Point",
+            "kind": 23,
+            "range": lines(0, 3),
+            "selectionRange": lines(0, 0),
+            "children": [{ "name": "x", "kind": 8, "range": lines(1, 1), "selectionRange": lines(1, 1) }]
+        },
+        { "name": "broken", "kind": 99, "range": lines(4, 4), "selectionRange": lines(4, 4) }
+    ]));
+    let names: Vec<&str> = tree.iter().map(|symbol| symbol.name.as_str()).collect();
+    assert_eq!(names, ["Point", "main"]);
+    assert_eq!(tree[0].kind, SymbolKind::Struct);
+    assert_eq!(tree[0].detail.as_deref(), Some("struct Point"));
+    assert_eq!(tree[0].children[0].kind.icon(), "◆");
+
+    // flat `SymbolInformation` is nested by range
+    let location = |start, end| json!({ "uri": "file:///a.rs", "range": lines(start, end) });
+    let tree = parse_document_symbols(&json!([
+        { "name": "new", "kind": 6, "location": location(2, 4) },
+        { "name": "Point", "kind": 19, "location": location(1, 9) },
+        { "name": "len", "kind": 6, "location": location(5, 8) },
+        { "name": "main", "kind": 12, "location": location(10, 12) }
+    ]));
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree[0].name, "Point");
+    let children: Vec<&str> = tree[0].children.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(children, ["new", "len"]);
+    assert_eq!(tree[1].name, "main");
+    assert_eq!(parse_document_symbols(&Value::Null), vec![]);
+}
+
+#[test]
+fn test_parse_workspace_symbols() {
+    let symbols = parse_workspace_symbols(&json!([
+        {
+            "name": "Point",
+            "kind": 23,
+            "containerName": "geometry",
+            "location": { "uri": "file:///src/geometry.rs", "range": lines(3, 5) }
+        },
+        // a `WorkspaceSymbol` whose range is left to `workspaceSymbol/resolve`
+        { "name": "main", "kind": 12, "containerName": "", "location": { "uri": "file:///src/main.rs" } },
+        { "name": "nowhere", "kind": 12 }
+    ]));
+
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0].container.as_deref(), Some("geometry"));
+    assert_eq!(symbols[0].path, PathBuf::from("/src/geometry.rs"));
+    assert_eq!(symbols[0].range.unwrap().start.line, 3);
+    assert_eq!(symbols[1].kind, SymbolKind::Function);
+    assert_eq!(symbols[1].container, None);
+    assert_eq!(symbols[1].range, None);
+}
@@ -0,0 +1,103 @@
+//! JSON-RPC framing used by the Language Server Protocol:
+//! `Content-Length: N\r\n\r\n` followed by `N` bytes of JSON.
+//!
+//! Kept free of `crate::` paths so `src/bin/mock_lsp.rs` can reuse it.
+
+use std::fmt;
+
+use serde_json::Value;
+
+const HEADER_END: &[u8] = b"\r\n\r\n";
+const CONTENT_LENGTH: &str = "content-length";
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The header block has no usable `Content-Length`.
+    MissingContentLength,
+    InvalidHeader(String),
+    InvalidJson(serde_json::Error),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::MissingContentLength => write!(f, "message has no Content-Length header"),
+            FrameError::InvalidHeader(header) => write!(f, "invalid header: {}", header),
+            FrameError::InvalidJson(e) => write!(f, "invalid message body: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Serialises a message with its `Content-Length` header.
+pub fn encode(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    let mut frame = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    frame.extend_from_slice(body.as_bytes());
+    frame
+}
+
+/// Accumulates bytes from the server and yields complete messages.
+///
+/// Reads may split a message anywhere (even inside the header) or carry
+/// several messages at once, so bytes are buffered until a whole frame is
+/// available.
+#[derive(Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete message, or `None` if more bytes are needed.
+    pub fn next_message(&mut self) -> Result<Option<Value>, FrameError> {
+        let Some(header_len) = self
+            .buf
+            .windows(HEADER_END.len())
+            .position(|w| w == HEADER_END)
+        else {
+            return Ok(None);
+        };
+
+        let header = String::from_utf8_lossy(&self.buf[..header_len]).into_owned();
+        let content_length = parse_content_length(&header)?;
+
+        let body_start = header_len + HEADER_END.len();
+        let body_end = body_start + content_length;
+        if self.buf.len() < body_end {
+            return Ok(None);
+        }
+
+        let message = serde_json::from_slice(&self.buf[body_start..body_end]);
+        self.buf.drain(..body_end);
+        message.map(Some).map_err(FrameError::InvalidJson)
+    }
+}
+
+fn parse_content_length(header: &str) -> Result<usize, FrameError> {
+    let mut content_length = None;
+
+    for line in header.split("\r\n") {
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(FrameError::InvalidHeader(line.to_string()));
+        };
+        // other headers (Content-Type) are allowed and ignored
+        if name.trim().eq_ignore_ascii_case(CONTENT_LENGTH) {
+            let length = value
+                .trim()
+                .parse()
+                .map_err(|_| FrameError::InvalidHeader(line.to_string()))?;
+            content_length = Some(length);
+        }
+    }
+
+    content_length.ok_or(FrameError::MissingContentLength)
+}
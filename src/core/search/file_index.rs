@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The paths of the files of a folder, relative to it and `/` separated,
+/// kept between scans: a scan adds what it finds as it goes and drops what
+/// it didn't find once it is over, so the index stays usable meanwhile.
+/// After the first scan it is kept up to date by the files the editor
+/// saves, creates, deletes and renames.
+#[derive(Debug, Clone, Default)]
+pub struct FileIndex {
+    root: PathBuf,
+    paths: Vec<String>,
+    known: HashSet<String>,
+    // paths seen by the scan running, if one is
+    scanned: Option<HashSet<String>>,
+    // a scan of the root went through
+    complete: bool,
+    revision: u64,
+}
+
+impl FileIndex {
+    pub fn new(root: PathBuf) -> Self {
+        FileIndex {
+            root,
+            ..FileIndex::default()
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Changes whenever paths are added or removed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scanned.is_some()
+    }
+
+    /// Whether the root was scanned to the end once.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// `path` relative to the root, as the index holds it; `None` outside of
+    /// the root.
+    pub fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<_> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    /// The full path of an indexed one.
+    pub fn absolute(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Adds a file, e.g. one just saved, without waiting for a scan.
+    pub fn insert(&mut self, path: &Path) {
+        let Some(relative) = self.relative(path) else {
+            return;
+        };
+        if let Some(scanned) = &mut self.scanned {
+            scanned.insert(relative.clone());
+        }
+        if self.known.insert(relative.clone()) {
+            self.paths.push(relative);
+            self.revision += 1;
+        }
+    }
+
+    /// Drops a deleted file, or every file under a deleted folder.
+    pub fn remove(&mut self, path: &Path) {
+        let Some(relative) = self.relative(path) else {
+            return;
+        };
+        let folder = format!("{relative}/");
+        let removed = |other: &String| *other == relative || other.starts_with(&folder);
+        if let Some(scanned) = &mut self.scanned {
+            scanned.retain(|other| !removed(other));
+        }
+        let before = self.paths.len();
+        self.paths.retain(|other| !removed(other));
+        if self.paths.len() != before {
+            self.known.retain(|other| !removed(other));
+            self.revision += 1;
+        }
+    }
+
+    /// Moves a renamed file, or every file under a renamed folder.
+    pub fn rename(&mut self, from: &Path, to: &Path) {
+        let (Some(old), Some(new)) = (self.relative(from), self.relative(to)) else {
+            // moved into or out of the root
+            self.remove(from);
+            self.insert(to);
+            return;
+        };
+        let folder = format!("{old}/");
+        let moved: Vec<String> = self
+            .paths
+            .iter()
+            .filter_map(|path| match path.strip_prefix(&folder) {
+                Some(rest) => Some(format!("{new}/{rest}")),
+                None => (*path == old).then(|| new.clone()),
+            })
+            .collect();
+        self.remove(from);
+        for path in moved {
+            self.insert(&self.absolute(&path));
+        }
+    }
+
+    pub fn begin_scan(&mut self) {
+        self.scanned = Some(HashSet::new());
+    }
+
+    /// Adds the files a scan found.
+    pub fn add_scanned(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            self.insert(&path);
+        }
+    }
+
+    /// Drops the files the scan didn't find.
+    pub fn end_scan(&mut self) {
+        let Some(scanned) = self.scanned.take() else {
+            return;
+        };
+        self.complete = true;
+        let before = self.paths.len();
+        self.paths.retain(|path| scanned.contains(path));
+        if self.paths.len() != before {
+            self.known = scanned;
+            self.revision += 1;
+        }
+    }
+}
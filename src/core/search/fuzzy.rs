@@ -0,0 +1,224 @@
+/// Score of each query char matched.
+const SCORE_MATCH: i64 = 16;
+/// Score of the first candidate char skipped after a match.
+const GAP_START: i64 = -3;
+/// Score of each further char skipped.
+const GAP_EXTENSION: i64 = -1;
+/// A match right after a space or at the start.
+const BONUS_BOUNDARY_WHITE: i64 = SCORE_MATCH / 2 + 2;
+/// A match right after a path separator.
+const BONUS_BOUNDARY_DELIMITER: i64 = SCORE_MATCH / 2 + 1;
+/// A match right after another non-word char, e.g. `_` or `.`.
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+/// A match on a non-word char, which is as telling as a boundary.
+const BONUS_NON_WORD: i64 = SCORE_MATCH / 2;
+/// A match on a capital after a lower case letter, or a digit after a
+/// non-digit.
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY + GAP_EXTENSION;
+/// A match right after another: worth as much as the gap it avoids.
+const BONUS_CONSECUTIVE: i64 = -(GAP_START + GAP_EXTENSION);
+/// The bonus of the first query char counts this many times.
+const FIRST_CHAR_MULTIPLIER: i64 = 2;
+/// Added when the whole query matches within the file name, as that is
+/// usually what is typed.
+const BONUS_BASENAME: i64 = 2 * SCORE_MATCH;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    White,
+    NonWord,
+    Delimiter,
+    Lower,
+    Upper,
+    Letter,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        c if c.is_ascii_lowercase() => CharClass::Lower,
+        c if c.is_ascii_uppercase() => CharClass::Upper,
+        c if c.is_ascii_digit() => CharClass::Number,
+        '/' | '\\' | ',' | ':' | ';' | '|' => CharClass::Delimiter,
+        c if c.is_whitespace() => CharClass::White,
+        c if c.is_lowercase() => CharClass::Lower,
+        c if c.is_uppercase() => CharClass::Upper,
+        c if c.is_alphabetic() => CharClass::Letter,
+        c if c.is_numeric() => CharClass::Number,
+        _ => CharClass::NonWord,
+    }
+}
+
+fn is_word(class: CharClass) -> bool {
+    !matches!(
+        class,
+        CharClass::White | CharClass::NonWord | CharClass::Delimiter
+    )
+}
+
+/// How telling a match on a char of `class` after one of `previous` is.
+fn bonus(previous: CharClass, class: CharClass) -> i64 {
+    if is_word(class) {
+        match previous {
+            CharClass::White => return BONUS_BOUNDARY_WHITE,
+            CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+            CharClass::NonWord => return BONUS_BOUNDARY,
+            _ => {}
+        }
+    }
+    if (previous == CharClass::Lower && class == CharClass::Upper)
+        || (previous != CharClass::Number && class == CharClass::Number)
+    {
+        return BONUS_CAMEL;
+    }
+    match class {
+        CharClass::NonWord | CharClass::Delimiter => BONUS_NON_WORD,
+        CharClass::White => BONUS_BOUNDARY_WHITE,
+        _ => 0,
+    }
+}
+
+/// A candidate matching a fuzzy query: higher scores are better matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices of the candidate matched by the query chars.
+    pub positions: Vec<usize>,
+}
+
+/// Matches the chars of `query` in order anywhere in `candidate`, the way
+/// fzf does: the shortest window holding them is scored, with bonuses for
+/// matches at word starts and in a row and penalties for the gaps between.
+/// Whitespace in the query is ignored; it is case sensitive only when it
+/// has a capital.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let text: Vec<char> = candidate.chars().collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+    let case_sensitive = query.iter().any(|c| c.is_uppercase());
+    let same = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a == b || a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    // the first place the whole query matches by
+    let mut next = 0;
+    let mut end = None;
+    for (index, &c) in text.iter().enumerate() {
+        if same(c, query[next]) {
+            next += 1;
+            if next == query.len() {
+                end = Some(index);
+                break;
+            }
+        }
+    }
+    let end = end?;
+    // back from there, for the latest start of a match ending there
+    let mut next = query.len();
+    let mut start = end;
+    for index in (0..=end).rev() {
+        if same(text[index], query[next - 1]) {
+            next -= 1;
+            if next == 0 {
+                start = index;
+                break;
+            }
+        }
+    }
+
+    let mut score = 0;
+    let mut positions = Vec::with_capacity(query.len());
+    let mut in_gap = false;
+    let mut consecutive = 0;
+    let mut first_bonus = 0;
+    let mut previous = match start {
+        0 => CharClass::White,
+        _ => char_class(text[start - 1]),
+    };
+    let mut next = 0;
+    for (index, &c) in text.iter().enumerate().take(end + 1).skip(start) {
+        let class = char_class(c);
+        if next < query.len() && same(c, query[next]) {
+            positions.push(index);
+            score += SCORE_MATCH;
+            let mut char_bonus = bonus(previous, class);
+            if consecutive == 0 {
+                first_bonus = char_bonus;
+            } else {
+                // a run keeps the bonus of where it started
+                if char_bonus >= BONUS_BOUNDARY && char_bonus > first_bonus {
+                    first_bonus = char_bonus;
+                }
+                char_bonus = char_bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            score += if next == 0 {
+                char_bonus * FIRST_CHAR_MULTIPLIER
+            } else {
+                char_bonus
+            };
+            in_gap = false;
+            consecutive += 1;
+            next += 1;
+        } else {
+            score += if in_gap { GAP_EXTENSION } else { GAP_START };
+            in_gap = true;
+            consecutive = 0;
+            first_bonus = 0;
+        }
+        previous = class;
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Like [`fuzzy_match`] for a `/` separated path, but a query matching
+/// within the file name alone scores that match, plus a bonus.
+pub fn fuzzy_match_path(query: &str, path: &str) -> Option<FuzzyMatch> {
+    let whole = fuzzy_match(query, path)?;
+    let Some(slash) = path.rfind('/') else {
+        return Some(FuzzyMatch {
+            score: whole.score + BONUS_BASENAME,
+            ..whole
+        });
+    };
+    let offset = path[..=slash].chars().count();
+    match fuzzy_match(query, &path[slash + 1..]) {
+        Some(name) if name.score + BONUS_BASENAME > whole.score => Some(FuzzyMatch {
+            score: name.score + BONUS_BASENAME,
+            positions: name.positions.iter().map(|i| i + offset).collect(),
+        }),
+        _ => Some(whole),
+    }
+}
+
+/// Splits a `path:line` or `path:line:column` query into the path and the
+/// zero-based line and column typed after it.
+pub fn split_location(query: &str) -> (&str, Option<(usize, usize)>) {
+    let number = |text: &str| {
+        text.parse::<usize>()
+            .ok()
+            .filter(|_| text.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let Some((rest, last)) = query.rsplit_once(':') else {
+        return (query, None);
+    };
+    let Some(last) = number(last) else {
+        return (query, None);
+    };
+    let location =
+        |line: usize, column: usize| Some((line.saturating_sub(1), column.saturating_sub(1)));
+    match rest.rsplit_once(':') {
+        Some((path, line)) if number(line).is_some() => {
+            (path, location(number(line).unwrap(), last))
+        }
+        _ => (rest, location(last, 1)),
+    }
+}
@@ -0,0 +1,14 @@
+pub mod file_index;
+pub mod fuzzy;
+pub mod project;
+pub mod query;
+#[cfg(test)]
+mod tests;
+
+pub use file_index::FileIndex;
+pub use fuzzy::{FuzzyMatch, fuzzy_match, fuzzy_match_path, split_location};
+pub use project::{
+    FileMatches, LineMatch, ProjectQuery, SearchError, line_matches, project_files, search_project,
+    text_hash,
+};
+pub use query::{Matcher, SearchQuery, preserve_case};
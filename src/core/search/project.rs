@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::WalkBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
+
+use crate::core::search::{Matcher, SearchQuery};
+use crate::core::utils::utils::SKIPPED_NAMES;
+
+/// Chars of the line kept before a match in its preview.
+const PREVIEW_BEFORE: usize = 40;
+/// Chars of the line kept after a match in its preview.
+const PREVIEW_AFTER: usize = 80;
+
+/// A search across the files of a folder.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ProjectQuery {
+    pub query: SearchQuery,
+    /// Globs of the files to search, e.g. `src/**/*.rs`; every file when
+    /// there are none.
+    pub include: Vec<String>,
+    /// Globs of the files and folders to leave out, e.g. `*.lock`.
+    pub exclude: Vec<String>,
+}
+
+impl ProjectQuery {
+    /// The globs of a comma separated list, as typed into an input.
+    pub fn globs(text: &str) -> Vec<String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|glob| !glob.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// A match in a file with the line it is on, for a preview.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMatch {
+    /// Byte range in the file.
+    pub range: Range<usize>,
+    /// Zero-based line the match starts on.
+    pub line: usize,
+    /// The line, without its indentation and cut down around the match
+    /// when it is long.
+    pub preview: String,
+    /// The match within `preview`, up to the end of the line.
+    pub preview_range: Range<usize>,
+}
+
+/// The matches in one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    /// Hash of the text searched, to tell whether it changed since.
+    pub hash: u64,
+    pub matches: Vec<LineMatch>,
+}
+
+#[derive(Debug)]
+pub enum SearchError {
+    Regex(regex::Error),
+    Glob(ignore::Error),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Regex(e) => write!(f, "Invalid regex: {}", e),
+            SearchError::Glob(e) => write!(f, "Invalid glob: {}", e),
+        }
+    }
+}
+
+pub fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Searches the files under `root` in path order and calls `found` with the
+/// matches of each file that has some, until it returns `false` or
+/// `cancelled` does `true`, which is asked before every file.
+///
+/// `.gitignore` files are respected, in a repository or not, and the names
+/// the file explorer skips are skipped. Files that aren't UTF-8 text are
+/// left out. `texts` are searched instead of the files at their paths, e.g.
+/// open tabs with unsaved changes.
+///
+/// Reads the whole tree, so it blocks: call it off the UI thread.
+pub fn search_project(
+    root: &Path,
+    query: &ProjectQuery,
+    texts: &HashMap<PathBuf, Arc<str>>,
+    mut cancelled: impl FnMut() -> bool,
+    mut found: impl FnMut(FileMatches) -> bool,
+) -> Result<(), SearchError> {
+    let matcher = Matcher::new(&query.query).map_err(SearchError::Regex)?;
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &query.include {
+        overrides.add(glob).map_err(SearchError::Glob)?;
+    }
+    for glob in &query.exclude {
+        overrides
+            .add(&format!("!{}", glob))
+            .map_err(SearchError::Glob)?;
+    }
+    let overrides = overrides.build().map_err(SearchError::Glob)?;
+
+    for path in walk(root, overrides) {
+        if cancelled() {
+            break;
+        }
+        let text = match texts.get(&path) {
+            Some(text) => Cow::Borrowed(&**text),
+            None => match std::fs::read_to_string(&path) {
+                Ok(text) if !text.contains('\0') => Cow::Owned(text),
+                _ => continue,
+            },
+        };
+        let matches = line_matches(&matcher, &text);
+        if matches.is_empty() {
+            continue;
+        }
+        let file = FileMatches {
+            hash: text_hash(&text),
+            path,
+            matches,
+        };
+        if !found(file) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Calls `found` with the files under `root` in path order, skipping what
+/// [`search_project`] skips, until it returns `false`.
+///
+/// Reads the whole tree, so it blocks: call it off the UI thread.
+pub fn project_files(root: &Path, mut found: impl FnMut(PathBuf) -> bool) {
+    for path in walk(root, Override::empty()) {
+        if !found(path) {
+            break;
+        }
+    }
+}
+
+/// The files under `root`, respecting `.gitignore` files in a repository
+/// or not and the `overrides`, and leaving out the names the file explorer
+/// skips.
+fn walk(root: &Path, overrides: Override) -> impl Iterator<Item = PathBuf> {
+    WalkBuilder::new(root)
+        // dotfiles show in the explorer, so they are searched too
+        .hidden(false)
+        .require_git(false)
+        .overrides(overrides)
+        .filter_entry(|entry| {
+            !entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| SKIPPED_NAMES.contains(&name))
+        })
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .map(|entry| entry.into_path())
+}
+
+/// The matches of `matcher` in `text`, with their lines.
+pub fn line_matches(matcher: &Matcher, text: &str) -> Vec<LineMatch> {
+    let mut line = 0;
+    let mut counted = 0;
+    matcher
+        .find_all(text)
+        .into_iter()
+        .map(|range| {
+            line += text[counted..range.start].matches('\n').count();
+            counted = range.start;
+            let (preview, preview_range) = preview(text, range.clone());
+            LineMatch {
+                range,
+                line,
+                preview,
+                preview_range,
+            }
+        })
+        .collect()
+}
+
+fn preview(text: &str, range: Range<usize>) -> (String, Range<usize>) {
+    let line_start = text[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[range.start..]
+        .find('\n')
+        .map_or(text.len(), |i| range.start + i);
+    let line = text[line_start..line_end].trim_end_matches('\r');
+    let line_end = (line_start + line.len()).max(range.start);
+    let match_end = range.end.min(line_end);
+
+    let indent = line.len() - line.trim_start().len();
+    let mut start = (line_start + indent).min(range.start);
+    let mut prefix = "";
+    let before = &text[start..range.start];
+    if before.chars().rev().nth(PREVIEW_BEFORE).is_some() {
+        let (i, _) = before.char_indices().rev().nth(PREVIEW_BEFORE - 1).unwrap();
+        start += i;
+        prefix = "…";
+    }
+    let mut end = line_end;
+    let mut suffix = "";
+    if let Some((i, _)) = text[match_end..line_end].char_indices().nth(PREVIEW_AFTER) {
+        end = match_end + i;
+        suffix = "…";
+    }
+
+    let preview = format!("{}{}{}", prefix, &text[start..end], suffix);
+    let preview_start = prefix.len() + range.start - start;
+    (
+        preview,
+        preview_start..preview_start + match_end - range.start,
+    )
+}
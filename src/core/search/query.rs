@@ -0,0 +1,134 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+/// What to look for in a text and how, as set in a find bar.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SearchQuery {
+    pub text: String,
+    pub case_sensitive: bool,
+    /// Only matches with no word chars right before and after them.
+    pub whole_word: bool,
+    /// `text` is a regular expression rather than plain text.
+    pub regex: bool,
+}
+
+/// A [`SearchQuery`] ready to run; plain text queries become escaped
+/// regexes, so both kinds go the same way.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    query: SearchQuery,
+    regex: Regex,
+}
+
+impl Matcher {
+    /// Fails when the query is an invalid regex.
+    pub fn new(query: &SearchQuery) -> Result<Self, regex::Error> {
+        let pattern = if query.regex {
+            query.text.clone()
+        } else {
+            regex::escape(&query.text)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            // `^` and `$` match at every line, as in an editor
+            .multi_line(true)
+            .build()?;
+        Ok(Matcher {
+            query: query.clone(),
+            regex,
+        })
+    }
+
+    /// The byte ranges of the matches in `text`, in order and not
+    /// overlapping. Empty matches (e.g. of `^`) are left out: there is
+    /// nothing to show or replace.
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        let mut matches = vec![];
+        if self.query.text.is_empty() {
+            return matches;
+        }
+        let mut from = 0;
+        while from <= text.len() {
+            let Some(found) = self.regex.find_at(text, from) else {
+                break;
+            };
+            let range = found.range();
+            if range.is_empty() || (self.query.whole_word && !is_whole_word(text, &range)) {
+                // try again from the next char, which may start a match
+                // this one hid
+                from = next_char(text, range.start);
+                continue;
+            }
+            from = range.end;
+            matches.push(range);
+        }
+        matches
+    }
+
+    /// What the match at `range` of `text` becomes when replaced with
+    /// `replacement`: `$1` and `${name}` are expanded for regex queries, and
+    /// with `preserve` the case of the match carries over.
+    pub fn replacement(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        replacement: &str,
+        preserve: bool,
+    ) -> String {
+        let mut expanded = String::new();
+        match self.regex.captures_at(text, range.start) {
+            Some(captures) if self.query.regex => {
+                captures.expand(replacement, &mut expanded);
+            }
+            _ => expanded.push_str(replacement),
+        }
+        if preserve {
+            preserve_case(&text[range], &expanded)
+        } else {
+            expanded
+        }
+    }
+}
+
+/// `replacement` in the case of `matched`: all upper case, all lower case,
+/// or with a capital first letter. Anything else leaves it as it is.
+pub fn preserve_case(matched: &str, replacement: &str) -> String {
+    let has_letters = matched.chars().any(char::is_alphabetic);
+    if !has_letters {
+        return replacement.to_string();
+    }
+    if !matched.chars().any(char::is_lowercase) {
+        return replacement.to_uppercase();
+    }
+    if !matched.chars().any(char::is_uppercase) {
+        return replacement.to_lowercase();
+    }
+    let mut chars = matched.chars();
+    if chars.next().is_some_and(char::is_uppercase) && !chars.any(char::is_uppercase) {
+        let mut replaced = replacement.chars();
+        return match replaced.next() {
+            Some(first) => first.to_uppercase().chain(replaced).collect(),
+            None => String::new(),
+        };
+    }
+    replacement.to_string()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_whole_word(text: &str, range: &Range<usize>) -> bool {
+    let before = text[..range.start].chars().next_back();
+    let after = text[range.end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+/// The offset after the char at `offset`, past the end when there is none.
+fn next_char(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .chars()
+        .next()
+        .map_or(text.len() + 1, |c| offset + c.len_utf8())
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::core::search::{
+    FileIndex, Matcher, ProjectQuery, SearchQuery, fuzzy_match, fuzzy_match_path, line_matches,
+    preserve_case, search_project, split_location,
+};
+
+fn query(text: &str) -> SearchQuery {
+    SearchQuery {
+        text: text.to_string(),
+        ..SearchQuery::default()
+    }
+}
+
+#[test]
+fn test_case_and_whole_word() {
+    let text = "Foo foo food _foo foo.";
+
+    let matcher = Matcher::new(&query("foo")).unwrap();
+    assert_eq!(
+        matcher.find_all(text),
+        vec![0..3, 4..7, 8..11, 14..17, 18..21]
+    );
+
+    let matcher = Matcher::new(&SearchQuery {
+        case_sensitive: true,
+        whole_word: true,
+        ..query("foo")
+    })
+    .unwrap();
+    assert_eq!(matcher.find_all(text), vec![4..7, 18..21]);
+
+    // plain text is not a pattern; whole words need not start with a word char
+    let matcher = Matcher::new(&SearchQuery {
+        whole_word: true,
+        ..query(".")
+    })
+    .unwrap();
+    assert_eq!(matcher.find_all("a.b ."), vec![4..5]);
+    assert!(Matcher::new(&query("")).unwrap().find_all(text).is_empty());
+}
+
+#[test]
+fn test_regex_matches_and_replacements() {
+    let regex = |text: &str| SearchQuery {
+        regex: true,
+        ..query(text)
+    };
+    assert!(Matcher::new(&regex("(")).is_err());
+
+    let text = "let a = 1;\nlet bc = 22;\n";
+    let matcher = Matcher::new(&regex(r"^let (\w+)")).unwrap();
+    let matches = matcher.find_all(text);
+    assert_eq!(matches, vec![0..5, 11..17]);
+    assert_eq!(
+        matcher.replacement(text, matches[1].clone(), "const ${1}_x", false),
+        "const bc_x"
+    );
+    // empty matches are skipped
+    assert!(Matcher::new(&regex("$")).unwrap().find_all(text).is_empty());
+
+    // `$1` is only special in regex queries
+    let matcher = Matcher::new(&query("a")).unwrap();
+    assert_eq!(matcher.replacement(text, 4..5, "$1", false), "$1");
+}
+
+#[test]
+fn test_preserve_case() {
+    assert_eq!(preserve_case("FOO", "bar"), "BAR");
+    assert_eq!(preserve_case("foo", "Bar"), "bar");
+    assert_eq!(preserve_case("Foo", "bar"), "Bar");
+    assert_eq!(preserve_case("fooBar", "bazQux"), "bazQux");
+    assert_eq!(preserve_case("42", "Answer"), "Answer");
+
+    let matcher = Matcher::new(&query("foo")).unwrap();
+    assert_eq!(matcher.replacement("a Foo", 2..5, "bar", true), "Bar");
+}
+
+#[test]
+fn test_line_matches_preview_the_line() {
+    let text = "fn main() {\n        let answer = 42;\n}\n";
+    let matcher = Matcher::new(&query("answer")).unwrap();
+    let matches = line_matches(&matcher, text);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].line, 1);
+    // without the indentation
+    assert_eq!(matches[0].preview, "let answer = 42;");
+    assert_eq!(matches[0].preview_range, 4..10);
+
+    let long = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+    let matcher = Matcher::new(&query("needle")).unwrap();
+    let preview = &line_matches(&matcher, &long)[0];
+    assert!(preview.preview.starts_with('…') && preview.preview.ends_with('…'));
+    assert_eq!(&preview.preview[preview.preview_range.clone()], "needle");
+}
+
+#[test]
+fn test_search_project_respects_gitignore_and_globs() {
+    let root = std::env::temp_dir().join(format!("riff-search-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target")).unwrap();
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(root.join("src/main.rs"), "todo!();\n").unwrap();
+    std::fs::write(root.join("src/lib.rs"), "// todo\n").unwrap();
+    std::fs::write(root.join("notes.md"), "todo: more\n").unwrap();
+    std::fs::write(root.join("target/out.rs"), "todo\n").unwrap();
+    std::fs::write(root.join(".git/HEAD"), "todo\n").unwrap();
+
+    let search = |project: &ProjectQuery, texts: &HashMap<PathBuf, Arc<str>>| {
+        let mut found = vec![];
+        search_project(
+            &root,
+            project,
+            texts,
+            || false,
+            |file| {
+                let path = file.path.strip_prefix(&root).unwrap().to_path_buf();
+                found.push((path, file.matches.len()));
+                true
+            },
+        )
+        .unwrap();
+        found
+    };
+    let project = ProjectQuery {
+        query: query("todo"),
+        ..ProjectQuery::default()
+    };
+    assert_eq!(
+        search(&project, &HashMap::new()),
+        vec![
+            (PathBuf::from("notes.md"), 1),
+            (PathBuf::from("src/lib.rs"), 1),
+            (PathBuf::from("src/main.rs"), 1),
+        ]
+    );
+
+    // an open tab is searched as it is in the editor
+    let texts = HashMap::from([(root.join("src/lib.rs"), Arc::from("todo todo"))]);
+    let project = ProjectQuery {
+        include: ProjectQuery::globs("*.rs"),
+        exclude: ProjectQuery::globs("main.rs, "),
+        ..project
+    };
+    assert_eq!(
+        search(&project, &texts),
+        vec![(PathBuf::from("src/lib.rs"), 2)]
+    );
+
+    let invalid = ProjectQuery {
+        include: vec!["a{".to_string()],
+        ..project
+    };
+    assert!(search_project(&root, &invalid, &HashMap::new(), || false, |_| true).is_err());
+
+    // cancelling stops before reading any further file, matching or not:
+    // `.gitignore` comes first and has none
+    let project = ProjectQuery {
+        query: query("todo"),
+        ..ProjectQuery::default()
+    };
+    let mut read = 0;
+    let mut found = 0;
+    search_project(
+        &root,
+        &project,
+        &HashMap::new(),
+        || {
+            read += 1;
+            read > 1
+        },
+        |_| {
+            found += 1;
+            true
+        },
+    )
+    .unwrap();
+    assert_eq!((read, found), (2, 0));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_fuzzy_match_favours_boundaries_and_file_names() {
+    assert!(fuzzy_match("xyz", "src/main.rs").is_none());
+    assert!(fuzzy_match("mr", "src/main.rs").is_some());
+    // capitals make the query case sensitive
+    assert!(fuzzy_match("Main", "src/main.rs").is_none());
+    assert_eq!(fuzzy_match("", "anything").unwrap().score, 0);
+
+    let score = |query: &str, path: &str| fuzzy_match_path(query, path).unwrap().score;
+    // word starts beat chars in the middle of words
+    assert!(score("fb", "foo_bar.rs") > score("fb", "fxxbxx.rs"));
+    assert!(score("fb", "FooBar.rs") > score("fb", "fooxbar.rs"));
+    // chars in a row beat scattered ones
+    assert!(score("main", "main.rs") > score("main", "m_a_i_n.rs"));
+    // the file name counts more than the folders
+    assert!(score("edit", "src/editor.rs") > score("edit", "src/edit/x.rs"));
+
+    let matched = fuzzy_match_path("lib", "src/lib.rs").unwrap();
+    assert_eq!(matched.positions, vec![4, 5, 6]);
+    // the shortest window is scored
+    assert_eq!(fuzzy_match("ab", "a_xab").unwrap().positions, vec![3, 4]);
+}
+
+#[test]
+fn test_split_location() {
+    assert_eq!(split_location("main.rs"), ("main.rs", None));
+    assert_eq!(split_location("main.rs:42"), ("main.rs", Some((41, 0))));
+    assert_eq!(split_location("main.rs:42:7"), ("main.rs", Some((41, 6))));
+    assert_eq!(split_location("main.rs:"), ("main.rs:", None));
+    assert_eq!(split_location("a:b"), ("a:b", None));
+}
+
+#[test]
+fn test_file_index_keeps_paths_across_scans() {
+    let root = PathBuf::from("/project");
+    let mut index = FileIndex::new(root.clone());
+    index.begin_scan();
+    index.add_scanned(vec![root.join("src/main.rs"), root.join("Cargo.toml")]);
+    index.end_scan();
+    assert_eq!(index.paths(), ["src/main.rs", "Cargo.toml"]);
+    let revision = index.revision();
+
+    // a rescan keeps what it finds again usable while it runs
+    index.begin_scan();
+    index.add_scanned(vec![root.join("src/main.rs"), root.join("src/lib.rs")]);
+    assert_eq!(index.paths(), ["src/main.rs", "Cargo.toml", "src/lib.rs"]);
+    index.end_scan();
+    assert_eq!(index.paths(), ["src/main.rs", "src/lib.rs"]);
+    assert!(index.revision() > revision);
+
+    index.insert(Path::new("/elsewhere/x.rs"));
+    index.insert(&root.join("new.rs"));
+    assert_eq!(index.paths(), ["src/main.rs", "src/lib.rs", "new.rs"]);
+    assert_eq!(index.absolute("new.rs"), root.join("new.rs"));
+}
+
+#[test]
+fn test_file_index_follows_deletes_and_renames() {
+    let root = PathBuf::from("/project");
+    let mut index = FileIndex::new(root.clone());
+    assert!(!index.is_complete());
+    index.begin_scan();
+    index.add_scanned(vec![
+        root.join("src/main.rs"),
+        root.join("src/ui/view.rs"),
+        root.join("src/ui.rs"),
+    ]);
+    index.end_scan();
+    assert!(index.is_complete());
+
+    index.rename(&root.join("src/ui"), &root.join("src/widgets"));
+    assert_eq!(
+        index.paths(),
+        ["src/main.rs", "src/ui.rs", "src/widgets/view.rs"]
+    );
+    index.rename(&root.join("src/ui.rs"), &root.join("src/widgets.rs"));
+    assert_eq!(
+        index.paths(),
+        ["src/main.rs", "src/widgets/view.rs", "src/widgets.rs"]
+    );
+
+    let revision = index.revision();
+    index.remove(&root.join("src/widgets"));
+    assert_eq!(index.paths(), ["src/main.rs", "src/widgets.rs"]);
+    assert!(index.revision() > revision);
+    // a file moved out of the folder is dropped from it
+    index.rename(&root.join("src/main.rs"), Path::new("/elsewhere/main.rs"));
+    assert_eq!(index.paths(), ["src/widgets.rs"]);
+}
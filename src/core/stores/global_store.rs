@@ -6,6 +6,10 @@ store! {
     is_open_settings: bool = false,
     font_size: f32 = 13.5,
     is_show_settings: bool = false,
+    // format a tab before it is written to disk
+    format_on_save: bool = false,
+    // number the lines of the editor relative to the cursor line
+    relative_line_numbers: bool = false,
   }
 }
 
@@ -27,6 +31,26 @@ impl GlobalStore {
         println!("FONT SIZE AFTER CHANGE: {:?}", self.font_size)
     }
 
+    pub fn get_format_on_save(&self) -> ReField<bool> {
+        self.format_on_save.clone()
+    }
+
+    pub fn toggle_format_on_save(&self, _ctx: &egui::Context) {
+        let mut reactive = self.reactive(_ctx);
+        let current = *reactive.format_on_save();
+        *reactive.format_on_save() = !current;
+    }
+
+    pub fn get_relative_line_numbers(&self) -> ReField<bool> {
+        self.relative_line_numbers.clone()
+    }
+
+    pub fn toggle_relative_line_numbers(&self, _ctx: &egui::Context) {
+        let mut reactive = self.reactive(_ctx);
+        let current = *reactive.relative_line_numbers();
+        *reactive.relative_line_numbers() = !current;
+    }
+
     pub fn change_show_settings(&self, _ctx: &egui::Context) {
         let mut reactive = self.reactive(_ctx);
         let current = *reactive.is_show_settings();
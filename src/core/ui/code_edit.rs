@@ -0,0 +1,731 @@
+use std::sync::Arc;
+
+use eframe::egui::{
+    self, Galley, TextBuffer as _,
+    text::{CCursor, CCursorRange, LayoutJob},
+};
+use egui::text_selection::TextCursorState;
+use egui::text_selection::text_cursor_state::cursor_rect;
+use egui::text_selection::visuals::{paint_text_cursor, paint_text_selection};
+
+use crate::core::buffer::{FlatEdit, Selection, Selections, TextBuffer};
+
+/// Formats the text for the wrap width given, like `egui::TextEdit::layouter`
+/// but without laying it out: the folded regions are hidden afterwards.
+pub type Layouter<'a> = &'a mut dyn FnMut(&egui::Ui, &TextBuffer, f32) -> LayoutJob;
+
+/// A multiline editor for a [`TextBuffer`] with any number of cursors.
+///
+/// Works like `egui::TextEdit::multiline` with focus locked, except that
+/// the cursors live in the buffer ([`TextBuffer::selections`]) and every
+/// edit applies at all of them:
+/// - Alt+click adds a cursor, or removes the one clicked;
+/// - Alt+drag selects a column (box) of text, one selection per row;
+/// - Cmd+D adds the next occurrence of the selected text;
+/// - Cmd+Shift+L selects all occurrences;
+/// - Escape goes back to the primary cursor.
+///
+/// The regions folded in the buffer ([`TextBuffer::folds`]) are left out,
+/// with a `⋯` after the line before each that unfolds it when clicked;
+/// cursors moving into one skip over it.
+pub struct CodeEdit<'t> {
+    text: &'t mut TextBuffer,
+    id: egui::Id,
+    font_id: egui::FontId,
+    layouter: Option<Layouter<'t>>,
+    margin: egui::Margin,
+    gutter: f32,
+    line_highlight: Option<egui::Color32>,
+    min_size: egui::Vec2,
+}
+
+pub struct CodeEditOutput {
+    pub response: egui::Response,
+    /// The text as laid out and painted.
+    pub galley: Arc<Galley>,
+    /// Where the galley was painted.
+    pub galley_pos: egui::Pos2,
+    /// The text area inside the margin and the gutter.
+    pub text_clip_rect: egui::Rect,
+}
+
+/// What the widget keeps between frames; the cursors themselves are in
+/// the buffer.
+#[derive(Clone, Default)]
+struct CodeEditState {
+    last_interaction: f64,
+    // galley position an Alt+drag started at
+    box_origin: Option<egui::Vec2>,
+    // horizontal position each cursor keeps when moving up and down
+    h_pos: Vec<Option<f32>>,
+}
+
+impl<'t> CodeEdit<'t> {
+    pub fn new(text: &'t mut TextBuffer, id: egui::Id) -> Self {
+        Self {
+            text,
+            id,
+            font_id: egui::FontId::monospace(12.0),
+            layouter: None,
+            margin: egui::Margin::symmetric(4, 2),
+            gutter: 0.0,
+            line_highlight: None,
+            min_size: egui::Vec2::ZERO,
+        }
+    }
+
+    /// Font of the default layouter.
+    pub fn font(mut self, font_id: egui::FontId) -> Self {
+        self.font_id = font_id;
+        self
+    }
+
+    pub fn layouter(mut self, layouter: Layouter<'t>) -> Self {
+        self.layouter = Some(layouter);
+        self
+    }
+
+    pub fn margin(mut self, margin: egui::Margin) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Room left of the text, inside the margin, for the caller to paint
+    /// over; it is part of the widget, so clicks there place the cursor
+    /// unless something on top takes them.
+    pub fn gutter(mut self, width: f32) -> Self {
+        self.gutter = width;
+        self
+    }
+
+    /// Paints the rows of the primary cursor's line with `color`, across
+    /// the whole width.
+    pub fn line_highlight(mut self, color: egui::Color32) -> Self {
+        self.line_highlight = Some(color);
+        self
+    }
+
+    pub fn min_size(mut self, min_size: egui::Vec2) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn show(self, ui: &mut egui::Ui) -> CodeEditOutput {
+        let CodeEdit {
+            text,
+            id,
+            font_id,
+            layouter,
+            margin,
+            gutter,
+            line_highlight,
+            min_size,
+        } = self;
+
+        let text_color = ui
+            .visuals()
+            .override_text_color
+            .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+        let row_height = ui.fonts_mut(|f| f.row_height(&font_id));
+        let default_font = font_id.clone();
+        let mut default_layouter = move |_: &egui::Ui, text: &TextBuffer, wrap_width: f32| {
+            LayoutJob::simple(
+                text.to_string(),
+                default_font.clone(),
+                text_color,
+                wrap_width,
+            )
+        };
+        let layouter = layouter.unwrap_or(&mut default_layouter);
+
+        let padding = margin.sum() + egui::vec2(gutter, 0.0);
+        let wrap_width = (ui.available_width() - padding.x).max(24.0);
+        let mut galley = lay_out(ui, layouter, text, wrap_width);
+        let inner_size = egui::vec2(
+            galley.size().x.max(wrap_width),
+            galley.size().y.max(row_height),
+        );
+        let background = ui.painter().add(egui::Shape::Noop);
+        let (_, outer_rect) = ui.allocate_space((inner_size + padding).max(min_size));
+        let mut rect = outer_rect - margin;
+        rect.min.x += gutter;
+        let galley_pos = rect.min;
+
+        let mut response = ui.interact(outer_rect, id, egui::Sense::click_and_drag());
+        let mut state: CodeEditState = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+        let mut selections = text.selections().clone();
+        selections.clamp(text.len());
+        let selections_before = selections.clone();
+
+        if let Some(pointer) = response.interact_pointer_pos() {
+            let local = pointer - galley_pos;
+            let cursor_at_pointer = galley.cursor_from_pos(local);
+            let (pressed, alt, dragging) = ui.input(|i| {
+                (
+                    i.pointer.any_pressed(),
+                    i.modifiers.alt,
+                    i.pointer.is_decidedly_dragging(),
+                )
+            });
+
+            let placeholder = fold_placeholders(&galley, text, row_height)
+                .into_iter()
+                .find(|(_, rect)| rect.contains(local.to_pos2()));
+            let interacted = if let Some((fold, _)) = placeholder {
+                if response.clicked() {
+                    text.unfold(fold);
+                    galley = lay_out(ui, layouter, text, wrap_width);
+                }
+                false
+            } else if pressed && alt {
+                let offset = text.char_to_byte(cursor_at_pointer.index);
+                if !selections.remove_cursor(offset) {
+                    selections.add(Selection::cursor(offset));
+                }
+                state.box_origin = Some(local);
+                true
+            } else if let Some(origin) = state.box_origin.filter(|_| dragging) {
+                let column = box_selection(&galley, text, origin, local);
+                if !column.is_empty() {
+                    let primary = if local.y < origin.y {
+                        0
+                    } else {
+                        column.len() - 1
+                    };
+                    selections = Selections::new(column, primary);
+                }
+                true
+            } else {
+                let mut cursor = TextCursorState::default();
+                cursor.set_char_range(Some(ccursor_range(text, selections.primary())));
+                let is_being_dragged = ui.ctx().is_being_dragged(id);
+                let interacted = cursor.pointer_interaction(
+                    ui,
+                    &response,
+                    cursor_at_pointer,
+                    &galley,
+                    is_being_dragged,
+                );
+                if interacted && let Some(range) = cursor.char_range() {
+                    selections = Selections::single(selection(text, range));
+                }
+                interacted
+            };
+            if interacted || response.clicked() {
+                ui.memory_mut(|m| m.request_focus(id));
+                state.last_interaction = ui.input(|i| i.time);
+            }
+        }
+        if !response.is_pointer_button_down_on() {
+            state.box_origin = None;
+        }
+        if response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Text);
+        }
+
+        let has_focus = ui.memory(|m| m.has_focus(id));
+        if has_focus {
+            let filter = egui::EventFilter {
+                tab: true,
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                // Escape drops the extra cursors before it gives up focus
+                escape: selections.len() > 1,
+            };
+            ui.memory_mut(|m| m.set_focus_lock_filter(id, filter));
+
+            let command = egui::Modifiers::COMMAND;
+            ui.input_mut(|i| {
+                // the galley holds the text as laid out, in one piece
+                if i.consume_key(command | egui::Modifiers::SHIFT, egui::Key::L) {
+                    selections.select_all_occurrences(galley.text());
+                } else if i.consume_key(command, egui::Key::D) {
+                    selections.add_next_occurrence(galley.text());
+                } else if selections.len() > 1
+                    && i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)
+                {
+                    selections.collapse();
+                }
+            });
+
+            let os = ui.ctx().os();
+            let events = ui.input(|i| i.filtered_events(&filter));
+            if state.h_pos.len() != selections.len() {
+                state.h_pos = vec![None; selections.len()];
+            }
+            for event in &events {
+                if let Some(moved) =
+                    move_cursors(os, event, text, &galley, id, &selections, &mut state.h_pos)
+                {
+                    selections = moved;
+                    continue;
+                }
+                state.h_pos = vec![None; selections.len()];
+                if let Some(edited) = edit(ui, os, event, text, &galley, &selections) {
+                    selections = edited;
+                    response.mark_changed();
+                    // lay out again so the next event sees the new text
+                    galley = lay_out(ui, layouter, text, wrap_width);
+                }
+                if state.h_pos.len() != selections.len() {
+                    state.h_pos = vec![None; selections.len()];
+                }
+            }
+        }
+        // moving up leaves a folded region at its top, anything else at
+        // its bottom
+        let forward = selections.primary().head >= selections_before.primary().head;
+        selections.map_offsets(|offset| text.skip_folded(offset, forward));
+        text.set_selections(selections.clone());
+        let selections_changed = *text.selections() != selections_before;
+
+        if ui.is_rect_visible(outer_rect) {
+            let visuals = ui.style().interact(&response);
+            let stroke = if has_focus {
+                ui.visuals().selection.stroke
+            } else {
+                visuals.bg_stroke
+            };
+            ui.painter().set(
+                background,
+                egui::epaint::RectShape::new(
+                    outer_rect.expand(visuals.expansion),
+                    visuals.corner_radius,
+                    ui.visuals().text_edit_bg_color(),
+                    stroke,
+                    egui::StrokeKind::Inside,
+                ),
+            );
+
+            if has_focus {
+                for selection in text.selections().iter() {
+                    paint_text_selection(
+                        &mut galley,
+                        ui.visuals(),
+                        &ccursor_range(text, *selection),
+                        None,
+                    );
+                }
+            }
+
+            // room for what the edits of this frame added
+            let extra = galley.size() - rect.size();
+            if extra.x > 0.0 || extra.y > 0.0 {
+                ui.allocate_rect(
+                    egui::Rect::from_min_size(outer_rect.max, extra),
+                    egui::Sense::hover(),
+                );
+            }
+
+            let painter = ui.painter_at(rect.expand(1.0));
+            if let Some(color) = line_highlight {
+                let rows = line_rows(&galley, text, text.selections().primary().head);
+                let rows = egui::Rect::from_x_y_ranges(outer_rect.x_range(), rows.y_range());
+                ui.painter_at(outer_rect).rect_filled(
+                    rows.translate(egui::vec2(0.0, galley_pos.y)),
+                    0.0,
+                    color,
+                );
+            }
+            painter.galley(galley_pos, galley.clone(), text_color);
+            for (_, rect) in fold_placeholders(&galley, text, row_height) {
+                let rect = rect.translate(galley_pos.to_vec2());
+                let hovered = response.hover_pos().is_some_and(|pos| rect.contains(pos));
+                let visuals = if hovered {
+                    &ui.visuals().widgets.hovered
+                } else {
+                    &ui.visuals().widgets.inactive
+                };
+                painter.rect_filled(rect, 3.0, visuals.weak_bg_fill);
+                painter.text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "⋯",
+                    font_id.clone(),
+                    visuals.fg_stroke.color,
+                );
+                if hovered {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                }
+            }
+
+            if has_focus {
+                let now = ui.input(|i| i.time);
+                if response.changed() || selections_changed {
+                    state.last_interaction = now;
+                }
+                let primary = text.selections().primary();
+                let primary_rect = cursor_rect(
+                    &galley,
+                    &CCursor::new(text.byte_to_char(primary.head)),
+                    row_height,
+                )
+                .translate(galley_pos.to_vec2());
+                if response.changed() || selections_changed {
+                    ui.scroll_to_rect(primary_rect + margin, None);
+                }
+
+                // the cursors blink together
+                if ui.input(|i| i.focused) {
+                    for selection in text.selections().iter() {
+                        let cursor = CCursor::new(text.byte_to_char(selection.head));
+                        let rect = cursor_rect(&galley, &cursor, row_height)
+                            .translate(galley_pos.to_vec2());
+                        paint_text_cursor(ui, &painter, rect, now - state.last_interaction);
+                    }
+                }
+
+                let to_global = ui
+                    .ctx()
+                    .layer_transform_to_global(ui.layer_id())
+                    .unwrap_or_default();
+                ui.ctx().output_mut(|o| {
+                    o.ime = Some(egui::output::IMEOutput {
+                        rect: to_global * rect,
+                        cursor_rect: to_global * primary_rect,
+                    });
+                });
+            }
+        }
+
+        ui.data_mut(|d| d.insert_temp(id, state));
+
+        CodeEditOutput {
+            response,
+            galley,
+            galley_pos,
+            text_clip_rect: rect,
+        }
+    }
+}
+
+/// Lays the text out with the folded regions shrunk to nothing, so their
+/// rows take no room.
+fn lay_out(
+    ui: &egui::Ui,
+    layouter: &mut dyn FnMut(&egui::Ui, &TextBuffer, f32) -> LayoutJob,
+    text: &TextBuffer,
+    wrap_width: f32,
+) -> Arc<Galley> {
+    let mut job = layouter(ui, text, wrap_width);
+    let hidden = text.folds().hidden();
+    if !hidden.is_empty() {
+        let mut sections = Vec::with_capacity(job.sections.len() + hidden.len() * 2);
+        for section in job.sections.drain(..) {
+            let (mut start, end) = (section.byte_range.start, section.byte_range.end);
+            let mut push = |byte_range: std::ops::Range<usize>, format: egui::TextFormat| {
+                if !byte_range.is_empty() {
+                    let leading_space = if byte_range.start == section.byte_range.start {
+                        section.leading_space
+                    } else {
+                        0.0
+                    };
+                    sections.push(egui::text::LayoutSection {
+                        leading_space,
+                        byte_range,
+                        format,
+                    });
+                }
+            };
+            for range in hidden
+                .iter()
+                .filter(|range| range.start < end && range.end > section.byte_range.start)
+            {
+                push(start..range.start.max(start), section.format.clone());
+                let mut format = section.format.clone();
+                format.font_id.size = 0.01;
+                format.line_height = Some(0.0);
+                format.color = egui::Color32::TRANSPARENT;
+                push(range.start.max(start)..range.end.min(end), format);
+                start = range.end.min(end);
+            }
+            push(start..end, section.format.clone());
+        }
+        job.sections = sections;
+    }
+    ui.fonts_mut(|f| f.layout_job(job))
+}
+
+/// The `⋯` after the line a folded region follows, in galley coordinates,
+/// with the lines given to [`TextBuffer::unfold`] for it. Of folds starting
+/// together the outer one is unfolded first.
+fn fold_placeholders(
+    galley: &Galley,
+    text: &TextBuffer,
+    row_height: f32,
+) -> Vec<(std::ops::Range<usize>, egui::Rect)> {
+    let mut shown = 0;
+    let mut placeholders = vec![];
+    for (lines, range) in text.folded_lines().into_iter().zip(text.folds().iter()) {
+        // inside another fold, or right after it where its own line is
+        // hidden too
+        if range.start <= shown {
+            continue;
+        }
+        shown = range.end;
+        let line_end = galley.pos_from_cursor(CCursor::new(text.byte_to_char(range.start - 1)));
+        let rect = egui::Rect::from_min_size(
+            line_end.min + egui::vec2(row_height * 0.4, 0.0),
+            egui::vec2(row_height * 1.4, row_height),
+        );
+        placeholders.push((lines, rect));
+    }
+    placeholders
+}
+
+/// The galley cursors of a selection.
+fn ccursor_range(text: &TextBuffer, selection: Selection) -> CCursorRange {
+    CCursorRange::two(
+        CCursor::new(text.byte_to_char(selection.anchor)),
+        CCursor::new(text.byte_to_char(selection.head)),
+    )
+}
+
+/// The selection between two galley cursors.
+fn selection(text: &TextBuffer, range: CCursorRange) -> Selection {
+    let offset = |cursor: CCursor| text.char_to_byte(cursor.index.min(text.len_chars()));
+    Selection::new(offset(range.secondary), offset(range.primary))
+}
+
+/// The rows of the line holding byte `offset`, in galley coordinates.
+fn line_rows(galley: &Galley, text: &TextBuffer, offset: usize) -> egui::Rect {
+    let line = text.byte_to_line(offset);
+    let start = text.line_to_byte(line);
+    let end = text.line_to_byte(line + 1);
+    // the last row is the one of the line break, not the one after it
+    let end = if text.char_before(end) == Some('\n') {
+        end - 1
+    } else {
+        end
+    };
+    let top = galley.pos_from_cursor(CCursor::new(text.byte_to_char(start)));
+    let bottom = galley.pos_from_cursor(CCursor::new(text.byte_to_char(end.max(start))));
+    top.union(bottom)
+}
+
+/// One selection per row between `from` and `to`, spanning their columns.
+fn box_selection(
+    galley: &Galley,
+    text: &TextBuffer,
+    from: egui::Vec2,
+    to: egui::Vec2,
+) -> Vec<Selection> {
+    let (top, bottom) = (from.y.min(to.y), from.y.max(to.y));
+    galley
+        .rows
+        .iter()
+        .map(|row| row.rect())
+        .filter(|rect| rect.bottom() > top && rect.top() <= bottom)
+        .map(|rect| {
+            let at = |x: f32| {
+                text.char_to_byte(galley.cursor_from_pos(egui::vec2(x, rect.center().y)).index)
+            };
+            Selection::new(at(from.x), at(to.x))
+        })
+        .collect()
+}
+
+/// Moves every cursor if `event` is a cursor movement.
+fn move_cursors(
+    os: egui::os::OperatingSystem,
+    event: &egui::Event,
+    text: &TextBuffer,
+    galley: &Galley,
+    id: egui::Id,
+    selections: &Selections,
+    h_pos: &mut [Option<f32>],
+) -> Option<Selections> {
+    let mut moved = false;
+    let mut items = Vec::with_capacity(selections.len());
+    for (selection, h_pos) in selections.iter().zip(h_pos.iter_mut()) {
+        let mut range = ccursor_range(text, *selection);
+        range.h_pos = *h_pos;
+        moved |= range.on_event(os, event, galley, id);
+        *h_pos = range.h_pos;
+        items.push(self::selection(text, range));
+    }
+    moved.then(|| Selections::new(items, selections.primary_index()))
+}
+
+/// Applies an editing `event` at every cursor; returns the cursors after it
+/// when it changed the text.
+fn edit(
+    ui: &egui::Ui,
+    os: egui::os::OperatingSystem,
+    event: &egui::Event,
+    text: &mut TextBuffer,
+    galley: &Galley,
+    selections: &Selections,
+) -> Option<Selections> {
+    let selected = || {
+        selections
+            .iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| text.slice(selection.range()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    match event {
+        egui::Event::Copy => {
+            let copied = selected();
+            if !copied.is_empty() {
+                ui.ctx().copy_text(copied);
+            }
+            None
+        }
+        egui::Event::Cut => {
+            let copied = selected();
+            if copied.is_empty() {
+                return None;
+            }
+            ui.ctx().copy_text(copied);
+            edit_each(text, selections, |text, _, range| {
+                (!range.is_empty()).then(|| CCursorRange::one(text.delete_selected(&range)))
+            })
+        }
+        egui::Event::Paste(pasted) if !pasted.is_empty() => {
+            // a line per cursor, as copied from as many selections
+            let lines: Vec<&str> = pasted.lines().collect();
+            if selections.len() > 1 && lines.len() == selections.len() {
+                edit_each(text, selections, insert(|index| lines[index]))
+            } else {
+                edit_each(text, selections, insert(|_| pasted.as_str()))
+            }
+        }
+        // line breaks come as `Key::Enter`
+        egui::Event::Text(typed) if !typed.is_empty() && typed != "\n" && typed != "\r" => {
+            edit_each(text, selections, insert(|_| typed.as_str()))
+        }
+        egui::Event::Ime(egui::ImeEvent::Commit(committed))
+            if !committed.is_empty() && committed != "\n" && committed != "\r" =>
+        {
+            edit_each(text, selections, insert(|_| committed.as_str()))
+        }
+        egui::Event::Key {
+            key: egui::Key::Tab,
+            pressed: true,
+            modifiers,
+            ..
+        } => edit_each(text, selections, |text, _, range| {
+            let mut cursor = text.delete_selected(&range);
+            if modifiers.shift {
+                text.decrease_indentation(&mut cursor);
+            } else {
+                text.insert_text_at(&mut cursor, "\t", usize::MAX);
+            }
+            Some(CCursorRange::one(cursor))
+        }),
+        egui::Event::Key {
+            key: egui::Key::Enter,
+            pressed: true,
+            modifiers,
+            ..
+        } if modifiers.matches_logically(egui::Modifiers::NONE) => {
+            edit_each(text, selections, insert(|_| "\n"))
+        }
+        egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => edit_each(text, selections, |text, _, range| {
+            delete_key(os, &range, text, galley, modifiers, *key)
+        }),
+        _ => None,
+    }
+}
+
+/// Replaces every selection with the text `text_at` gives for its index.
+fn insert<'a>(
+    text_at: impl Fn(usize) -> &'a str,
+) -> impl FnMut(&mut FlatEdit, usize, CCursorRange) -> Option<CCursorRange> {
+    move |text, index, range| {
+        let mut cursor = text.delete_selected(&range);
+        text.insert_text_at(&mut cursor, text_at(index), usize::MAX);
+        Some(CCursorRange::one(cursor))
+    }
+}
+
+/// Runs `edit` at every selection, the last one first so the offsets of
+/// the ones before stay valid. `edit` gets the index of the selection and
+/// returns where it ends up, or `None` to leave it alone.
+fn edit_each(
+    text: &mut TextBuffer,
+    selections: &Selections,
+    mut edit: impl FnMut(&mut FlatEdit, usize, CCursorRange) -> Option<CCursorRange>,
+) -> Option<Selections> {
+    // what undo goes back to
+    text.set_selections(selections.clone());
+    let mut text = FlatEdit::new(text);
+
+    let mut items: Vec<Selection> = selections.iter().copied().collect();
+    let mut changed = false;
+    for index in (0..items.len()).rev() {
+        let len = text.buffer().len();
+        let mut range = ccursor_range(text.buffer(), items[index]);
+        // an edit further on (e.g. Cmd+Backspace) may have taken some of
+        // the text before it
+        let len_chars = text.buffer().len_chars();
+        range.primary.index = range.primary.index.min(len_chars);
+        range.secondary.index = range.secondary.index.min(len_chars);
+        if let Some(range) = edit(&mut text, index, range) {
+            items[index] = selection(text.buffer(), range);
+            changed = true;
+        }
+        let delta = text.buffer().len() as isize - len as isize;
+        for item in &mut items[index + 1..] {
+            item.anchor = item.anchor.saturating_add_signed(delta);
+            item.head = item.head.saturating_add_signed(delta);
+        }
+    }
+    changed.then(|| Selections::new(items, selections.primary_index()))
+}
+
+/// The deleting keys of `egui::TextEdit`, for one cursor.
+fn delete_key(
+    os: egui::os::OperatingSystem,
+    range: &CCursorRange,
+    text: &mut FlatEdit,
+    galley: &Galley,
+    modifiers: &egui::Modifiers,
+    key: egui::Key,
+) -> Option<CCursorRange> {
+    let cursor = match key {
+        egui::Key::Backspace if modifiers.mac_cmd => {
+            text.delete_paragraph_before_cursor(galley, range)
+        }
+        egui::Key::Backspace => match range.single() {
+            // alt on mac, ctrl on windows
+            Some(cursor) if modifiers.alt || modifiers.ctrl => text.delete_previous_word(cursor),
+            Some(cursor) => text.delete_previous_char(cursor),
+            None => text.delete_selected(range),
+        },
+        egui::Key::Delete if !modifiers.shift || os != egui::os::OperatingSystem::Windows => {
+            let cursor = if modifiers.mac_cmd {
+                text.delete_paragraph_after_cursor(galley, range)
+            } else {
+                match range.single() {
+                    Some(cursor) if modifiers.alt || modifiers.ctrl => {
+                        text.delete_next_word(cursor)
+                    }
+                    Some(cursor) => text.delete_next_char(cursor),
+                    None => text.delete_selected(range),
+                }
+            };
+            CCursor {
+                prefer_next_row: true,
+                ..cursor
+            }
+        }
+        egui::Key::H if modifiers.ctrl => text.delete_previous_char(range.primary),
+        egui::Key::K if modifiers.ctrl => text.delete_paragraph_after_cursor(galley, range),
+        egui::Key::U if modifiers.ctrl => text.delete_paragraph_before_cursor(galley, range),
+        egui::Key::W if modifiers.ctrl => match range.single() {
+            Some(cursor) => text.delete_previous_word(cursor),
+            None => text.delete_selected(range),
+        },
+        _ => return None,
+    };
+    Some(CCursorRange::one(cursor))
+}
@@ -4,6 +4,7 @@ use std::rc::Rc;
 
 use eframe::egui;
 
+use crate::core::buffer::TextBuffer;
 use crate::core::core::ActionsStore;
 use crate::core::lib::rsx::component::Component;
 use crate::core::ui::ui_kit::{
@@ -13,7 +14,7 @@ use crate::rsx;
 
 pub fn code_editor<'t>(
     opened_file: Option<&PathBuf>,
-    opened_text: &mut String,
+    opened_text: &mut TextBuffer,
     actions_store: &Rc<RefCell<ActionsStore>>,
     ctx: &egui::Context,
 ) {
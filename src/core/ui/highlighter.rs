@@ -0,0 +1,240 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+use eframe::egui::{self, Color32, FontId, text::LayoutJob};
+
+use crate::core::buffer::TextBuffer;
+use crate::core::languages::{Language, Lexed};
+use crate::core::lexer::{Token, TokenKind};
+
+/// Colours for every token kind, resolved from `ThemeInteractionsStore::syntax_palette`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxPalette {
+    pub plain: Color32,
+    pub keyword: Color32,
+    pub ident: Color32,
+    pub string: Color32,
+    pub char: Color32,
+    pub number: Color32,
+    pub comment: Color32,
+    pub doc_comment: Color32,
+    pub lifetime: Color32,
+    pub punct: Color32,
+    pub operator: Color32,
+    pub type_name: Color32,
+    pub function: Color32,
+    pub macro_name: Color32,
+}
+
+impl SyntaxPalette {
+    pub fn color(&self, kind: TokenKind) -> Color32 {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Ident => self.ident,
+            TokenKind::String | TokenKind::RawString | TokenKind::ByteString => self.string,
+            TokenKind::Char => self.char,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+            TokenKind::DocComment => self.doc_comment,
+            TokenKind::Lifetime => self.lifetime,
+            TokenKind::Punct => self.punct,
+            TokenKind::Operator => self.operator,
+            TokenKind::Whitespace | TokenKind::Unknown => self.plain,
+        }
+    }
+
+    /// Colour of a semantic token from a language server, or `None` to keep
+    /// the lexer's colour.
+    pub fn semantic_color(&self, token_type: &str, modifiers: &[&str]) -> Option<Color32> {
+        Some(match token_type {
+            "namespace" | "type" | "class" | "enum" | "interface" | "struct" | "typeParameter"
+            | "enumMember" | "builtinType" | "typeAlias" | "selfTypeKeyword" => self.type_name,
+            "function" | "method" => self.function,
+            "macro" | "decorator" | "attribute" | "derive" => self.macro_name,
+            "parameter" | "variable" | "property" => self.ident,
+            "keyword" | "modifier" | "selfKeyword" => self.keyword,
+            "comment" if modifiers.contains(&"documentation") => self.doc_comment,
+            "comment" => self.comment,
+            "string" | "regexp" => self.string,
+            "number" => self.number,
+            "lifetime" => self.lifetime,
+            "operator" => self.operator,
+            _ => return None,
+        })
+    }
+}
+
+/// Tokens of one revision of a text, used to re-lex only the edited region.
+#[derive(Debug)]
+struct LexedRevision {
+    revision: u64,
+    language_id: String,
+    lexed: Lexed,
+}
+
+#[derive(Debug)]
+struct CachedJob {
+    /// Revision of the text and hash of the language, palette and font the
+    /// job was built with.
+    revision: u64,
+    style: u64,
+    job: Arc<LayoutJob>,
+}
+
+/// The last tokens and job highlighted for a document, so unchanged text is
+/// not re-lexed every frame. Keep it with the document, e.g. in its tab, so
+/// it goes away with it.
+#[derive(Debug, Default)]
+pub struct Highlighting {
+    lexed: Option<LexedRevision>,
+    job: Option<CachedJob>,
+}
+
+impl Highlighting {
+    /// The tokens of `text`, whose content is `src`. Only the edits since the
+    /// last call are re-lexed, with `Grammar::relex`, as long as the history
+    /// of the text reaches back that far.
+    pub fn tokens(&mut self, text: &TextBuffer, src: &str, language: &Language) -> &[Token] {
+        let grammar = &language.grammar;
+        let lexed = match self.lexed.take() {
+            // the language of a buffer can change (e.g. a tab reused for another file)
+            Some(last) if last.language_id == language.id => {
+                match text.edit_since(last.revision) {
+                    Some(Some(edit)) => grammar.relex(src, &last.lexed, &edit),
+                    Some(None) => last.lexed,
+                    None => grammar.lex(src),
+                }
+            }
+            _ => grammar.lex(src),
+        };
+        let last = self.lexed.insert(LexedRevision {
+            revision: text.revision(),
+            language_id: language.id.clone(),
+            lexed,
+        });
+        &last.lexed.tokens
+    }
+}
+
+/// Builds a coloured `LayoutJob` for `text` using the grammar of `language`.
+///
+/// The job kept in `highlighting` is reused as long as the revision of the
+/// text, the palette and the font stay the same; when only the text changed,
+/// its tokens come from `Highlighting::tokens`. The job is shared with the
+/// cache rather than copied, as it holds the whole text.
+pub fn highlight(
+    highlighting: &mut Highlighting,
+    text: &TextBuffer,
+    language: &Language,
+    palette: &SyntaxPalette,
+    font: &FontId,
+) -> Arc<LayoutJob> {
+    let mut hasher = DefaultHasher::new();
+    language.id.hash(&mut hasher);
+    palette.hash(&mut hasher);
+    font.hash(&mut hasher);
+    let style = hasher.finish();
+
+    if let Some(cached) = &highlighting.job
+        && cached.revision == text.revision()
+        && cached.style == style
+    {
+        return cached.job.clone();
+    }
+
+    let src = text.contents();
+    let job = Arc::new(layout_job(
+        &src,
+        highlighting.tokens(text, &src, language),
+        palette,
+        font,
+    ));
+    highlighting.job = Some(CachedJob {
+        revision: text.revision(),
+        style,
+        job: Arc::clone(&job),
+    });
+    job
+}
+
+/// Turns a token stream into a `LayoutJob` with one section per token.
+pub fn layout_job(src: &str, tokens: &[Token], palette: &SyntaxPalette, font: &FontId) -> LayoutJob {
+    let mut job = LayoutJob::default();
+
+    for token in tokens {
+        job.append(
+            &src[token.range.clone()],
+            0.0,
+            egui::TextFormat::simple(font.clone(), palette.color(token.kind)),
+        );
+    }
+
+    job
+}
+
+/// Recolours the byte ranges of `colors` and leaves `gaps` (byte offset,
+/// width in points) of empty space before characters, e.g. for inlay hints.
+/// Both must be sorted by offset; the text itself is unchanged, so cursor
+/// positions stay valid.
+pub fn decorate(
+    job: &LayoutJob,
+    colors: &[(Range<usize>, Color32)],
+    gaps: &[(usize, f32)],
+) -> LayoutJob {
+    if colors.is_empty() && gaps.is_empty() {
+        return job.clone();
+    }
+
+    let mut cuts: Vec<usize> = colors
+        .iter()
+        .flat_map(|(range, _)| [range.start, range.end])
+        .chain(gaps.iter().map(|(offset, _)| *offset))
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut out = job.clone();
+    out.sections.clear();
+    let mut color = colors.iter().peekable();
+    let mut gap = gaps.iter().peekable();
+    let mut cut = cuts.iter().peekable();
+
+    for section in &job.sections {
+        let mut start = section.byte_range.start;
+        while start < section.byte_range.end {
+            while cut.next_if(|&&c| c <= start).is_some() {}
+            let end = cut
+                .peek()
+                .map_or(section.byte_range.end, |&&c| c.min(section.byte_range.end));
+
+            while color.next_if(|(range, _)| range.end <= start).is_some() {}
+            while gap.next_if(|(offset, _)| *offset < start).is_some() {}
+
+            let mut format = section.format.clone();
+            if let Some((range, c)) = color.peek()
+                && range.start <= start
+            {
+                format.color = *c;
+            }
+            let mut leading_space = if start == section.byte_range.start {
+                section.leading_space
+            } else {
+                0.0
+            };
+            if let Some(&&(offset, width)) = gap.peek()
+                && offset == start
+            {
+                leading_space += width;
+            }
+
+            out.sections.push(egui::text::LayoutSection {
+                leading_space,
+                byte_range: start..end,
+                format,
+            });
+            start = end;
+        }
+    }
+    out
+}
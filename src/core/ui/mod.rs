@@ -1,3 +1,5 @@
+pub mod code_edit;
 pub mod draw_entry;
+pub mod highlighter;
 pub mod ui_kit;
 pub mod widgets;
@@ -0,0 +1,267 @@
+use crate::core::buffer::TextBuffer;
+use crate::core::git::{ChangeKind, LineChange};
+use crate::core::ui::code_edit::CodeEditOutput;
+use eframe::egui;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The strip left of the text of a multiline `TextEdit`, painted with the
+/// text so the two scroll together. From left to right it has a glyph
+/// column (breakpoints; the code action lightbulb sits there too), the line
+/// numbers, the fold arrows and the git change bars. Every column scales
+/// with the font of the text.
+///
+/// Example:
+/// ```rust,no_run
+/// gutter: Some(Gutter { relative: true, breakpoints: vec![4], ..Gutter::default() })
+/// ```
+#[derive(Clone, Default)]
+pub struct Gutter {
+    /// Number the lines by their distance to the cursor line, which shows
+    /// its own number.
+    pub relative: bool,
+    /// Zero-based lines with a breakpoint.
+    pub breakpoints: Vec<usize>,
+    /// Foldable regions.
+    pub folds: Vec<Fold>,
+    /// Lines changed since the last commit.
+    pub changes: Rc<[LineChange]>,
+    pub colors: GutterColors,
+    /// Called with the line whose breakpoint slot was clicked.
+    pub on_breakpoint: Option<Rc<dyn Fn(usize)>>,
+}
+
+/// A region that can be folded, marked by an arrow on its first line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fold {
+    /// Zero-based lines from the first to the last.
+    pub lines: Range<usize>,
+    pub folded: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct GutterColors {
+    pub number: egui::Color32,
+    /// Number of the cursor line.
+    pub current_number: egui::Color32,
+    /// Background of the cursor line, across the text too.
+    pub current_line: egui::Color32,
+    pub breakpoint: egui::Color32,
+    pub added: egui::Color32,
+    pub modified: egui::Color32,
+    pub deleted: egui::Color32,
+}
+
+/// The x ranges of the columns, from the left edge of the gutter.
+struct Columns {
+    glyph: egui::Rangef,
+    numbers: egui::Rangef,
+    fold: egui::Rangef,
+    bar: egui::Rangef,
+}
+
+impl Gutter {
+    /// Width of the gutter for `lines` lines of text in `font_id`.
+    pub fn width(ui: &egui::Ui, font_id: &egui::FontId, lines: usize) -> f32 {
+        Self::columns(ui, font_id, lines).bar.max
+    }
+
+    fn columns(ui: &egui::Ui, font_id: &egui::FontId, lines: usize) -> Columns {
+        let (row_height, digit) =
+            ui.fonts_mut(|f| (f.row_height(font_id), f.glyph_width(font_id, '0')));
+        // room for three digits at least, so the text doesn't shift while
+        // the first hundred lines are written
+        let digits = lines.to_string().len().max(3) as f32;
+        let glyph = egui::Rangef::new(0.0, row_height);
+        let numbers = egui::Rangef::new(glyph.max, glyph.max + digits * digit);
+        let fold = egui::Rangef::new(
+            numbers.max + digit * 0.5,
+            numbers.max + digit * 0.5 + row_height * 0.8,
+        );
+        let bar = egui::Rangef::new(fold.max, fold.max + 3.0);
+        Columns {
+            glyph,
+            numbers,
+            fold,
+            bar,
+        }
+    }
+
+    /// Paints the gutter beside the text `output` shows and handles clicks
+    /// on the breakpoint slots and the fold arrows.
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        output: &CodeEditOutput,
+        text: &mut TextBuffer,
+        font_id: &egui::FontId,
+    ) {
+        let columns = Self::columns(ui, font_id, text.len_lines());
+        let left = output.response.rect.left();
+        let bounds = egui::Rect::from_x_y_ranges(
+            left..=left + columns.bar.max,
+            output.response.rect.y_range(),
+        );
+        let x = |range: egui::Rangef| egui::Rangef::new(range.min + left, range.max + left);
+
+        let response = ui.interact(
+            bounds,
+            output.response.id.with("gutter"),
+            egui::Sense::click(),
+        );
+        let pointer = response
+            .hover_pos()
+            .filter(|pos| x(columns.glyph).contains(pos.x));
+        let fold_pointer = response
+            .hover_pos()
+            .filter(|pos| x(columns.fold).contains(pos.x));
+        if pointer.is_some() || fold_pointer.is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        }
+
+        let painter = ui.painter_at(bounds);
+        let clip = ui.clip_rect();
+        let current = text.byte_to_line(text.selections().primary().head);
+        let row_height = ui.fonts_mut(|f| f.row_height(font_id));
+        let colors = &self.colors;
+
+        let mut toggled = None;
+        let mut line = 0;
+        let mut starts_line = true;
+        for row in &output.galley.rows {
+            let rect = row.rect().translate(output.galley_pos.to_vec2());
+            let first_row = starts_line;
+            let row_line = line;
+            starts_line = row.ends_with_newline;
+            if row.ends_with_newline {
+                line += 1;
+            }
+            if rect.bottom() < clip.top() {
+                continue;
+            }
+            if rect.top() > clip.bottom() {
+                break;
+            }
+            // folded away
+            if rect.height() <= 0.0 {
+                continue;
+            }
+
+            for change in self
+                .changes
+                .iter()
+                .filter(|change| change.lines.contains(&row_line))
+            {
+                let color = match change.kind {
+                    ChangeKind::Added => colors.added,
+                    _ => colors.modified,
+                };
+                painter.rect_filled(
+                    egui::Rect::from_x_y_ranges(x(columns.bar), rect.y_range()),
+                    0.0,
+                    color,
+                );
+            }
+            if !first_row {
+                continue;
+            }
+
+            let deleted = self
+                .changes
+                .iter()
+                .any(|change| change.kind == ChangeKind::Deleted && change.lines.start == row_line);
+            if deleted {
+                // a wedge on the boundary the lines were removed from
+                let bar = x(columns.bar);
+                let size = bar.span() + 2.0;
+                painter.add(egui::Shape::convex_polygon(
+                    vec![
+                        egui::pos2(bar.min, rect.top() - size),
+                        egui::pos2(bar.min + size, rect.top()),
+                        egui::pos2(bar.min, rect.top() + size),
+                    ],
+                    colors.deleted,
+                    egui::Stroke::NONE,
+                ));
+            }
+
+            let number = if self.relative && row_line != current {
+                row_line.abs_diff(current)
+            } else {
+                row_line + 1
+            };
+            let color = if row_line == current {
+                colors.current_number
+            } else {
+                colors.number
+            };
+            let center_y = rect.top() + row_height / 2.0;
+            painter.text(
+                egui::pos2(x(columns.numbers).max, center_y),
+                egui::Align2::RIGHT_CENTER,
+                number.to_string(),
+                font_id.clone(),
+                color,
+            );
+
+            let glyph = egui::pos2(x(columns.glyph).center(), center_y);
+            let radius = row_height * 0.28;
+            let slot_hovered = pointer.is_some_and(|pos| rect.y_range().contains(pos.y));
+            if self.breakpoints.contains(&row_line) {
+                painter.circle_filled(glyph, radius, colors.breakpoint);
+            } else if slot_hovered {
+                painter.circle_filled(glyph, radius, colors.breakpoint.gamma_multiply(0.4));
+            }
+            if slot_hovered
+                && response.clicked()
+                && let Some(on_breakpoint) = &self.on_breakpoint
+            {
+                on_breakpoint(row_line);
+            }
+
+            let starting = |fold: &&Fold| fold.lines.start == row_line;
+            // a folded region wins over an open one on the same line
+            let fold = (self.folds.iter().filter(starting))
+                .find(|fold| fold.folded)
+                .or_else(|| self.folds.iter().find(starting));
+            if let Some(fold) = fold {
+                let center = egui::pos2(x(columns.fold).center(), center_y);
+                let half = columns.fold.span() * 0.3;
+                // ▸ folded, ▾ open
+                let points = if fold.folded {
+                    vec![
+                        center + egui::vec2(-half * 0.5, -half),
+                        center + egui::vec2(half * 0.5, 0.0),
+                        center + egui::vec2(-half * 0.5, half),
+                    ]
+                } else {
+                    vec![
+                        center + egui::vec2(-half, -half * 0.5),
+                        center + egui::vec2(half, -half * 0.5),
+                        center + egui::vec2(0.0, half * 0.5),
+                    ]
+                };
+                painter.add(egui::Shape::convex_polygon(
+                    points,
+                    colors.number,
+                    egui::Stroke::NONE,
+                ));
+                if response.clicked()
+                    && fold_pointer.is_some_and(|pos| rect.y_range().contains(pos.y))
+                {
+                    toggled = Some(fold.clone());
+                }
+            }
+        }
+
+        if let Some(fold) = toggled {
+            if fold.folded {
+                text.unfold(fold.lines);
+            } else {
+                text.fold(fold.lines);
+            }
+            // the text was laid out before
+            ui.ctx().request_repaint();
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::core::lib::rsx::component::{Children, Component, ComponentWithProps};
+use eframe::egui;
+
+pub struct Markdown {
+    props: MarkdownProps,
+}
+
+/// Properties for the `Markdown` component - renders the subset of markdown
+/// language servers use: paragraphs, headings, rules and fenced code blocks.
+///
+/// Example usage in `rsx!`:
+/// ```rust,no_run
+/// rsx! {
+///     Markdown {
+///         content: "```rust\nfn main()\n```\n---\nThe entry point.".to_string(),
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MarkdownProps {
+    /// The markdown source.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// content: "**Note**: `len` is O(1)".to_string()
+    /// ```
+    pub content: String,
+    /// Child components (rarely used for Markdown).
+    pub children: Children,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Code(String),
+    Rule,
+}
+
+fn parse_blocks(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(inline(&paragraph.join("\n"))));
+            paragraph.clear();
+        }
+    };
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if let Some(lines) = code.as_mut() {
+            if trimmed.starts_with("```") {
+                blocks.push(Block::Code(lines.join("\n")));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            code = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if matches!(trimmed, "---" | "***" | "___") {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if trimmed.starts_with('#') {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(inline(
+                trimmed.trim_start_matches('#').trim(),
+            )));
+        } else {
+            paragraph.push(line);
+        }
+    }
+
+    // an unterminated fence runs to the end
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Drops emphasis and code markers and backslash escapes.
+fn inline(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek().is_some_and(|next| next.is_ascii_punctuation()) => {
+                result.extend(chars.next());
+            }
+            '`' => {}
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+impl Markdown {
+    pub fn new() -> Self {
+        Self {
+            props: MarkdownProps::default(),
+        }
+    }
+
+    pub fn new_with_props(props: MarkdownProps) -> Self {
+        Self { props }
+    }
+}
+
+impl ComponentWithProps for Markdown {
+    type Props = MarkdownProps;
+
+    fn new() -> Self {
+        Self::new()
+    }
+
+    fn new_with_props(props: Self::Props) -> Self {
+        Self::new_with_props(props)
+    }
+}
+
+impl Component for Markdown {
+    fn render(&self, ui: &mut egui::Ui) {
+        for block in parse_blocks(&self.props.content) {
+            match block {
+                Block::Heading(text) => {
+                    ui.label(egui::RichText::new(text).strong());
+                }
+                Block::Paragraph(text) => {
+                    ui.label(text);
+                }
+                Block::Code(code) => {
+                    egui::Frame::new()
+                        .fill(ui.visuals().code_bg_color)
+                        .inner_margin(egui::Margin::same(4))
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(code).monospace());
+                        });
+                }
+                Block::Rule => {
+                    ui.separator();
+                }
+            }
+        }
+        self.props.children.render(ui);
+    }
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,8 +2,11 @@ pub mod app;
 pub mod button;
 pub mod central_panel;
 pub mod color;
+pub mod gutter;
 pub mod image;
 pub mod list;
+pub mod markdown;
+pub mod popup;
 pub mod scroll_area;
 pub mod selectable_label;
 pub mod separator;
@@ -18,8 +21,11 @@ pub use app::render_app;
 pub use button::Button;
 pub use central_panel::{CentralPanel, CentralPanelProps, render_central_panel};
 pub use color::Color;
+pub use gutter::{Fold, Gutter, GutterColors};
 pub use image::Image;
 pub use list::{List, ListProps};
+pub use markdown::Markdown;
+pub use popup::Popup;
 pub use scroll_area::ScrollArea;
 pub use selectable_label::SelectableLabel;
 pub use separator::Separator;
@@ -0,0 +1,112 @@
+use crate::core::lib::rsx::component::{Children, Component, ComponentWithProps};
+use eframe::egui;
+
+pub struct Popup {
+    props: PopupProps,
+}
+
+/// Properties for the `Popup` component - a framed box floating above
+/// everything else, e.g. an autocomplete list.
+///
+/// Example usage in `rsx!`:
+/// ```rust,no_run
+/// rsx! {
+///     Popup {
+///         id: "completion".to_string(),
+///         position: cursor_rect.left_bottom(),
+///         children: {
+///             Text { content: "println!".to_string() }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct PopupProps {
+    /// Unique identifier for the popup.
+    pub id: String,
+    /// Screen position of the top left corner (see `pivot`). The popup is
+    /// kept on screen.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// position: egui::pos2(120.0, 80.0)
+    /// ```
+    pub position: egui::Pos2,
+    /// Which corner of the popup is placed at `position`; the top left one
+    /// by default.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// pivot: Some(egui::Align2::LEFT_BOTTOM)  // Open above `position`
+    /// ```
+    pub pivot: Option<egui::Align2>,
+    /// Optional maximum width in pixels; longer text wraps.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// max_width: Some(420.0)
+    /// ```
+    pub max_width: Option<f32>,
+    /// Whether to draw the popup's frame; `true` by default.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// frame: Some(false)  // Just the children, e.g. a marker in a gutter
+    /// ```
+    pub frame: Option<bool>,
+    /// Child components to render inside the popup.
+    pub children: Children,
+}
+
+impl Popup {
+    pub fn new() -> Self {
+        Self {
+            props: PopupProps::default(),
+        }
+    }
+
+    pub fn new_with_props(props: PopupProps) -> Self {
+        Self { props }
+    }
+}
+
+impl ComponentWithProps for Popup {
+    type Props = PopupProps;
+
+    fn new() -> Self {
+        Self::new()
+    }
+
+    fn new_with_props(props: Self::Props) -> Self {
+        Self::new_with_props(props)
+    }
+}
+
+impl Component for Popup {
+    fn render(&self, ui: &mut egui::Ui) {
+        egui::Area::new(egui::Id::new(&self.props.id))
+            .order(egui::Order::Foreground)
+            .fixed_pos(self.props.position)
+            .pivot(self.props.pivot.unwrap_or(egui::Align2::LEFT_TOP))
+            .constrain(true)
+            .show(ui.ctx(), |ui| {
+                let frame = if self.props.frame.unwrap_or(true) {
+                    egui::Frame::popup(ui.style())
+                } else {
+                    egui::Frame::NONE
+                };
+                frame.show(ui, |ui| {
+                    if let Some(max_width) = self.props.max_width {
+                        ui.set_max_width(max_width);
+                    }
+                    self.props.children.render(ui);
+                });
+            });
+    }
+}
+
+impl Default for Popup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -41,6 +41,14 @@ pub struct ScrollAreaProps {
     /// auto_shrink: None                  // Don't auto-shrink
     /// ```
     pub auto_shrink: Option<(bool, bool)>,
+    /// Optional maximum height in pixels; taller content scrolls.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// max_height: Some(200.0)
+    /// max_height: None  // Use all available height
+    /// ```
+    pub max_height: Option<f32>,
 }
 
 impl ScrollArea {
@@ -70,11 +78,14 @@ impl ComponentWithProps for ScrollArea {
 impl Component for ScrollArea {
     fn render(&self, ui: &mut egui::Ui) {
         let auto_shrink = self.props.auto_shrink.unwrap_or((false, false));
-        egui::ScrollArea::vertical()
-            .auto_shrink([auto_shrink.0, auto_shrink.1])
-            .show(ui, |ui| {
-                self.props.children.render(ui);
-            });
+        let mut scroll_area =
+            egui::ScrollArea::vertical().auto_shrink([auto_shrink.0, auto_shrink.1]);
+        if let Some(max_height) = self.props.max_height {
+            scroll_area = scroll_area.max_height(max_height);
+        }
+        scroll_area.show(ui, |ui| {
+            self.props.children.render(ui);
+        });
     }
 }
 
@@ -65,6 +65,13 @@ pub struct SelectableLabelProps {
     /// text_color: Some(egui::Color32::from_rgb(200, 200, 200))
     /// ```
     pub text_color: Option<egui::Color32>,
+    /// Scrolls the enclosing scroll area to the label this frame.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// scroll_to: true  // e.g. for the row that follows the cursor
+    /// ```
+    pub scroll_to: bool,
     /// Child components (rarely used for SelectableLabel).
     pub children: Children,
     /// Optional style object for advanced styling.
@@ -105,6 +112,10 @@ impl Component for SelectableLabel {
 
         let response = ui.selectable_label(self.props.selected, text);
 
+        if self.props.scroll_to {
+            response.scroll_to_me(None);
+        }
+
         if response.hovered() {
             if let Some(hover_color) = self.props.hover_color {
                 ui.painter().rect_filled(response.rect, 0.0, hover_color);
@@ -31,6 +31,12 @@ pub struct TextProps {
     pub content: String,
     /// Child components (rarely used for Text).
     pub children: Children,
+    /// Optional style; `color` sets the text color.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// style: Some(Rc::new(Style::new().color(egui::Color32::YELLOW)))
+    /// ```
     pub style: Option<Rc<Style>>,
 }
 
@@ -61,7 +67,11 @@ impl ComponentWithProps for Text {
 impl Component for Text {
     fn render(&self, ui: &mut egui::Ui) {
         if !self.props.content.is_empty() {
-            ui.label(&self.props.content);
+            let mut text = egui::RichText::new(&self.props.content);
+            if let Some(color) = self.props.style.as_ref().and_then(|s| s.color) {
+                text = text.color(color);
+            }
+            ui.label(text);
         }
         self.props.children.render(ui);
     }
@@ -1,17 +1,82 @@
+use crate::core::buffer::{FlatEdit, Selection, Selections, TextBuffer};
+use crate::core::languages::Language;
 use crate::core::lib::rsx::component::{Children, Component, ComponentWithProps};
+use crate::core::types::types::Handler;
+use crate::core::ui::code_edit::{CodeEdit, CodeEditOutput};
+use crate::core::ui::highlighter::{self, Highlighting};
+use crate::core::ui::ui_kit::gutter::Gutter;
+use crate::modules::editor::stores::theme_store;
 use eframe::egui;
+use egui::text::{CCursor, CCursorRange};
 use std::cell::RefCell;
+use std::ops::Range;
 use std::rc::Rc;
 
 pub struct TextEdit {
     props: TextEditProps,
 }
 
+/// A wavy line under a byte range of the text, e.g. a diagnostic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Underline {
+    pub range: Range<usize>,
+    pub color: egui::Color32,
+}
+
+/// A colour laid over the syntax highlighting of a byte range, e.g. a
+/// semantic token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Highlight {
+    pub range: Range<usize>,
+    pub color: egui::Color32,
+}
+
+/// Dimmed text shown before the character at `offset` without being part of
+/// the text, e.g. an inlay hint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inlay {
+    pub offset: usize,
+    pub text: String,
+    pub color: egui::Color32,
+}
+
+/// A translucent box over a byte range of the text, e.g. a search match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mark {
+    pub range: Range<usize>,
+    pub color: egui::Color32,
+}
+
+/// Where the primary cursor of a focused multiline `TextEdit` is, passed to
+/// `on_cursor`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CursorInfo {
+    /// Byte offset into the text.
+    pub offset: usize,
+    /// Byte offset of the other end of the selection; `offset` without one.
+    pub anchor: usize,
+    /// Screen rectangle of the cursor.
+    pub rect: egui::Rect,
+    /// Screen rectangle of the cursor's row, gutter included.
+    pub line: egui::Rect,
+    /// Whether the user changed the text this frame.
+    pub changed: bool,
+}
+
+/// The character under the mouse pointer, passed to `on_hover`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoverInfo {
+    /// Byte offset of the character.
+    pub offset: usize,
+    /// Screen rectangle of the character.
+    pub rect: egui::Rect,
+}
+
 /// Properties for the `TextEdit` component - a text input field.
 ///
 /// Example usage in `rsx!`:
 /// ```rust,no_run
-/// let text = Rc::new(RefCell::new("Initial text".to_string()));
+/// let text = Rc::new(RefCell::new(TextBuffer::from("Initial text")));
 /// rsx! {
 ///     TextEdit {
 ///         value: text.clone(),
@@ -22,14 +87,14 @@ pub struct TextEdit {
 /// ```
 #[derive(Clone, Default)]
 pub struct TextEditProps {
-    /// The text value wrapped in `Rc<RefCell<String>>` for mutable access.
+    /// The text value wrapped in `Rc<RefCell<TextBuffer>>` for mutable access.
     ///
     /// Example:
     /// ```rust,no_run
-    /// let text = Rc::new(RefCell::new(String::new()));
+    /// let text = Rc::new(RefCell::new(TextBuffer::new()));
     /// value: text.clone()
     /// ```
-    pub value: Rc<RefCell<String>>,
+    pub value: Rc<RefCell<TextBuffer>>,
     /// Whether the text edit is multiline (textarea) or single line.
     ///
     /// Example:
@@ -50,6 +115,123 @@ pub struct TextEditProps {
     pub children: Children,
     /// Change font size
     pub font_size: Option<f32>,
+    /// Syntax-highlight the text with the grammar of this language (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// language: language_registry().by_id("rust")
+    /// language: None  // Plain text
+    /// ```
+    pub language: Option<Rc<Language>>,
+    /// Where the highlighting of `language` is kept between frames, e.g. in
+    /// the tab of the document. Without one the text is lexed every frame.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// highlighting: Some(tab.highlighting.clone())
+    /// ```
+    pub highlighting: Option<Rc<RefCell<Highlighting>>>,
+    /// Called after the user changed the text. The value is no longer
+    /// borrowed at that point, so the handler may read it.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// on_change: Some(Rc::new(move || println!("{}", text.borrow())))
+    /// ```
+    pub on_change: Option<Handler>,
+    /// Wavy underlines drawn over the text (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// underlines: vec![Underline { range: 3..7, color: egui::Color32::RED }]
+    /// ```
+    pub underlines: Vec<Underline>,
+    /// Focuses the text edit, selects this byte range (an empty one just
+    /// moves the cursor) and scrolls it into view. Pass it for a single frame.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// reveal: editor_interactions_store().take_pending_reveal()
+    /// ```
+    pub reveal: Option<Range<usize>>,
+    /// Leaves the focus where it is on `reveal`, e.g. with a find bar
+    /// stepping through the matches (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// keep_focus: true
+    /// ```
+    pub keep_focus: bool,
+    /// Called every frame while the text edit has focus, after `on_change`
+    /// (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// on_cursor: Some(Rc::new(move |cursor: CursorInfo| println!("{}", cursor.offset)))
+    /// ```
+    pub on_cursor: Option<Rc<dyn Fn(CursorInfo)>>,
+    /// Called every frame with the character under the mouse pointer, or
+    /// `None` when the pointer is not over any (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// on_hover: Some(Rc::new(move |hover: Option<HoverInfo>| println!("{:?}", hover)))
+    /// ```
+    pub on_hover: Option<Rc<dyn Fn(Option<HoverInfo>)>>,
+    /// Line numbers, fold arrows, breakpoints and change bars left of the
+    /// text (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// gutter: Some(Gutter { relative: true, ..Gutter::default() })
+    /// ```
+    pub gutter: Option<Gutter>,
+    /// Colours laid over the syntax highlighting, sorted by range and not
+    /// overlapping (multiline with a `language` only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// highlights: vec![Highlight { range: 3..7, color: egui::Color32::GOLD }]
+    /// ```
+    pub highlights: Vec<Highlight>,
+    /// Text shown between characters, sorted by offset (multiline with a
+    /// `language` only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// inlays: vec![Inlay { offset: 5, text: ": i32".to_string(), color: egui::Color32::GRAY }]
+    /// ```
+    pub inlays: Vec<Inlay>,
+    /// Boxes painted over the text, e.g. search matches (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// marks: vec![Mark { range: 3..7, color: egui::Color32::from_white_alpha(40) }]
+    /// ```
+    pub marks: Vec<Mark>,
+    /// Called every frame with the byte range of the rows scrolled into
+    /// view (multiline only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// on_visible: Some(Rc::new(move |range: Range<usize>| println!("{:?}", range)))
+    /// ```
+    pub on_visible: Option<Rc<dyn Fn(Range<usize>)>>,
+    /// Greyed-out text shown while the input is empty (single line only).
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// hint: Some("Filter".to_string())
+    /// ```
+    pub hint: Option<String>,
+    /// A fixed id instead of one from the position in the layout, so a
+    /// store can tell whether the text edit has focus.
+    ///
+    /// Example:
+    /// ```rust,no_run
+    /// id: Some(egui::Id::new("find_query"))
+    /// ```
+    pub id: Option<egui::Id>,
 }
 
 impl TextEdit {
@@ -79,25 +261,405 @@ impl ComponentWithProps for TextEdit {
 impl Component for TextEdit {
     fn render(&self, ui: &mut egui::Ui) {
         let mut text = self.props.value.borrow_mut();
+        let id = self.props.id.unwrap_or_else(|| ui.next_auto_id());
+        let undone = ui
+            .memory(|m| m.has_focus(id))
+            .then(|| undo_redo(ui, &mut text))
+            .flatten();
+        text.begin_input();
 
-        if self.props.multiline {
-            let mut text_edit = egui::TextEdit::multiline(&mut *text);
-
-            if let Some(size) = self.props.font_size {
-                text_edit = text_edit.font(egui::FontId::monospace(size));
+        let mut cursor = None;
+        let mut hover = None;
+        let mut visible = None;
+        let changed = if self.props.multiline {
+            let font_id = if let Some(size) = self.props.font_size {
+                egui::FontId::monospace(size)
             } else if let Some(font_name) = &self.props.font && font_name == "monospace" {
-                    text_edit = text_edit.font(egui::TextStyle::Monospace);
+                egui::TextStyle::Monospace.resolve(ui.style())
+            } else {
+                egui::FontSelection::default().resolve(ui.style())
+            };
+            if let Some(range) = self.props.reveal.clone() {
+                text.set_selection(range);
+                if !self.props.keep_focus {
+                    ui.memory_mut(|m| m.request_focus(id));
+                }
+            }
+            let gutter_width = Gutter::width(ui, &font_id, text.len_lines());
+            let mut code_edit = CodeEdit::new(&mut text, id).font(font_id.clone());
+
+            let mut layouter = self.props.language.clone().map(|language| {
+                let palette = theme_store().syntax_palette(ui.ctx());
+                let highlighting = self.props.highlighting.clone().unwrap_or_default();
+                let colors: Vec<(Range<usize>, egui::Color32)> = self
+                    .props
+                    .highlights
+                    .iter()
+                    .map(|highlight| (highlight.range.clone(), highlight.color))
+                    .collect();
+                let inlays = self.props.inlays.clone();
+                let font_id = font_id.clone();
+
+                move |ui: &egui::Ui, text: &TextBuffer, wrap_width: f32| {
+                    let job = highlighter::highlight(
+                        &mut highlighting.borrow_mut(),
+                        text,
+                        &language,
+                        &palette,
+                        &font_id,
+                    );
+                    let gaps = inlay_gaps(ui, text, &inlays, &font_id);
+                    let mut job = highlighter::decorate(&job, &colors, &gaps);
+                    job.wrap.max_width = wrap_width;
+                    job
+                }
+            });
+
+            if let Some(layouter) = layouter.as_mut() {
+                code_edit = code_edit.layouter(layouter);
+            }
+            if let Some(gutter) = &self.props.gutter {
+                code_edit = code_edit
+                    .margin(egui::Margin::symmetric(4, 2))
+                    .gutter(gutter_width)
+                    .line_highlight(gutter.colors.current_line);
+            }
+
+            let size = egui::vec2(ui.available_width(), ui.available_height());
+            let output = code_edit.min_size(size).show(ui);
+            text.end_input();
+
+            if let Some(gutter) = &self.props.gutter {
+                gutter.show(ui, &output, &mut text, &font_id);
             }
 
-            ui.add_sized([ui.available_width(), ui.available_height()], text_edit);
+            // nothing over the rows of folded regions, which take no room
+            let in_sight = |offset: usize| text.skip_folded(offset.min(text.len()), true) == offset;
+            // only the rows in view: a common word can have thousands
+            let shown = visible_range(ui, &output, &text);
+            let marks = self.props.marks.iter().filter(|mark| {
+                mark.range.start < shown.end && shown.start < mark.range.end && in_sight(mark.range.start)
+            });
+            for mark in marks {
+                paint_mark(ui, &output, &text, mark);
+            }
+            let underlines = self.props.underlines.iter().filter(|underline| {
+                // `<=`: empty ranges are drawn too
+                underline.range.start <= shown.end
+                    && shown.start <= underline.range.end
+                    && in_sight(underline.range.start)
+            });
+            for underline in underlines {
+                paint_underline(ui, &output, &text, underline);
+            }
+            if self.props.language.is_some() {
+                let font_id = inlay_font(&font_id);
+                for inlay in self.props.inlays.iter().filter(|inlay| in_sight(inlay.offset)) {
+                    paint_inlay(ui, &output, &text, inlay, &font_id);
+                }
+            }
+            visible = Some(shown);
+            if self.props.reveal.is_some() || undone.is_some() {
+                let primary = text.selections().primary();
+                scroll_to(ui, &output, &text, primary.head);
+            }
+
+            if let Some(pointer) = output.response.hover_pos() {
+                hover = hovered_char(&output, &text, pointer);
+            }
+
+            let changed = output.response.changed() || undone.is_some();
+            if output.response.has_focus() {
+                let primary = text.selections().primary();
+                let rect = output
+                    .galley
+                    .pos_from_cursor(CCursor::new(text.byte_to_char(primary.head)))
+                    .translate(output.galley_pos.to_vec2());
+                let bounds = output.response.rect;
+                cursor = Some(CursorInfo {
+                    offset: primary.head,
+                    anchor: primary.anchor,
+                    rect,
+                    line: egui::Rect::from_x_y_ranges(bounds.x_range(), rect.y_range()),
+                    changed,
+                });
+            }
+            changed
         } else {
-            ui.text_edit_singleline(&mut *text);
+            let mut flat = FlatEdit::new(&mut text);
+            let mut text_edit = egui::TextEdit::singleline(&mut flat).id(id);
+            if let Some(hint) = &self.props.hint {
+                text_edit = text_edit.hint_text(hint.as_str());
+            }
+            let output = text_edit.show(ui);
+            drop(flat);
+            text.end_input();
+            let undone = undone.map(|selections| selections.primary().range());
+            if let Some(range) = undone.clone().or(self.props.reveal.clone()) {
+                reveal(ui, &output, &text, range);
+            }
+            if output.response.has_focus()
+                && let Some(range) = output.cursor_range
+            {
+                remember_selection(&mut text, range);
+            }
+            output.response.changed() || undone.is_some()
+        };
+        drop(text);
+
+        if changed && let Some(on_change) = &self.props.on_change {
+            on_change();
+        }
+        if let (Some(cursor), Some(on_cursor)) = (cursor, &self.props.on_cursor) {
+            on_cursor(cursor);
+        }
+        if self.props.multiline
+            && let Some(on_hover) = &self.props.on_hover
+        {
+            on_hover(hover);
+        }
+        if let (Some(visible), Some(on_visible)) = (visible, &self.props.on_visible) {
+            on_visible(visible);
         }
 
         self.props.children.render(ui);
     }
 }
 
+/// Cmd+Z undoes the last step of the buffer's history, Cmd+Shift+Z and Cmd+Y
+/// redo it. The keys are taken before the widget sees them: `egui::TextEdit`
+/// keeps an undo history of its own. Returns the selections restored when
+/// the text changed.
+fn undo_redo(ui: &egui::Ui, text: &mut TextBuffer) -> Option<Selections> {
+    let command = egui::Modifiers::COMMAND;
+    ui.input_mut(|i| {
+        if i.consume_key(command | egui::Modifiers::SHIFT, egui::Key::Z)
+            || i.consume_key(command, egui::Key::Y)
+        {
+            text.redo().cloned()
+        } else if i.consume_key(command, egui::Key::Z) {
+            text.undo().cloned()
+        } else {
+            None
+        }
+    })
+}
+
+fn remember_selection(text: &mut TextBuffer, range: CCursorRange) {
+    let anchor = text.char_to_byte(range.secondary.index.min(text.len_chars()));
+    let cursor = text.char_to_byte(range.primary.index.min(text.len_chars()));
+    text.set_selections(Selections::single(Selection::new(anchor, cursor)));
+}
+
+/// Char index used by galley cursors for a byte offset into `text`.
+fn char_index(text: &TextBuffer, offset: usize) -> usize {
+    text.byte_to_char(offset.min(text.len()))
+}
+
+/// Byte offset of the char index `index` of a galley cursor.
+fn byte_offset(text: &TextBuffer, index: usize) -> usize {
+    text.char_to_byte(index.min(text.len_chars()))
+}
+
+fn hovered_char(
+    output: &CodeEditOutput,
+    text: &TextBuffer,
+    pointer: egui::Pos2,
+) -> Option<HoverInfo> {
+    let galley = &output.galley;
+    let local = (pointer - output.galley_pos).to_pos2();
+    let nearest = galley.cursor_from_pos(local.to_vec2()).index;
+
+    // the nearest cursor is on one side or the other of the hovered char
+    [Some(nearest), nearest.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .find_map(|index| {
+            let left = galley.pos_from_cursor(CCursor::new(index));
+            let right = galley.pos_from_cursor(CCursor::new(index + 1));
+            // past the end of a row the next cursor is on the following one
+            if right.min.y != left.min.y {
+                return None;
+            }
+            let rect = egui::Rect::from_min_max(left.min, egui::pos2(right.min.x, left.max.y));
+            rect.contains(local).then(|| HoverInfo {
+                offset: byte_offset(text, index),
+                rect: rect.translate(output.galley_pos.to_vec2()),
+            })
+        })
+}
+
+fn paint_underline(
+    ui: &egui::Ui,
+    output: &CodeEditOutput,
+    text: &TextBuffer,
+    underline: &Underline,
+) {
+    let galley = &output.galley;
+    let start = galley.pos_from_cursor(CCursor::new(char_index(text, underline.range.start)));
+    let end = galley.pos_from_cursor(CCursor::new(char_index(text, underline.range.end)));
+    let painter = ui.painter_at(output.text_clip_rect);
+
+    for row in rows_between(galley, start, end) {
+        let rect = row.rect();
+
+        let left = if rect.y_range().contains(start.center().y) { start.left() } else { rect.left() };
+        let right = if rect.y_range().contains(end.center().y) { end.left() } else { rect.right() };
+        // keep empty ranges (e.g. "expected `;`" at the end of a line) visible
+        let right = right.max(left + 6.0);
+
+        let offset = output.galley_pos.to_vec2();
+        let y = rect.bottom() - 1.0;
+        let points = (0..)
+            .map(|i| left + i as f32 * 2.0)
+            .take_while(|x| *x <= right)
+            .enumerate()
+            .map(|(i, x)| egui::pos2(x, if i % 2 == 0 { y } else { y - 2.0 }) + offset)
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, underline.color)));
+    }
+}
+
+/// The rows from the one of the cursor at `start` to the one of the cursor
+/// at `end`, found by position rather than by going through every row.
+fn rows_between(
+    galley: &egui::Galley,
+    start: egui::Rect,
+    end: egui::Rect,
+) -> impl Iterator<Item = &egui::epaint::text::PlacedRow> {
+    let first = galley.rows.partition_point(|row| row.rect().bottom() <= start.center().y);
+    galley.rows[first..]
+        .iter()
+        .take_while(move |row| row.rect().top() < end.center().y)
+}
+
+fn paint_mark(ui: &egui::Ui, output: &CodeEditOutput, text: &TextBuffer, mark: &Mark) {
+    let galley = &output.galley;
+    let start = galley.pos_from_cursor(CCursor::new(char_index(text, mark.range.start)));
+    let end = galley.pos_from_cursor(CCursor::new(char_index(text, mark.range.end)));
+    let painter = ui.painter_at(output.text_clip_rect);
+
+    for row in rows_between(galley, start, end) {
+        let rect = row.rect();
+        let left = if rect.y_range().contains(start.center().y) { start.left() } else { rect.left() };
+        let right = if rect.y_range().contains(end.center().y) { end.left() } else { rect.right() };
+        let rect = egui::Rect::from_x_y_ranges(left..=right, rect.y_range());
+        painter.rect_filled(rect.translate(output.galley_pos.to_vec2()), 2.0, mark.color);
+    }
+}
+
+/// Inlays are a little smaller than the code around them.
+fn inlay_font(font_id: &egui::FontId) -> egui::FontId {
+    egui::FontId::new(font_id.size * 0.9, font_id.family.clone())
+}
+
+/// Whether the inlay at `offset` sits before a character on the same row;
+/// inlays at the end of a row need no room, there is nothing after them.
+fn inlay_inside_row(text: &TextBuffer, offset: usize) -> bool {
+    text.char_at(offset).is_some_and(|c| c != '\n')
+}
+
+fn inlay_width(ui: &egui::Ui, inlay: &Inlay, font_id: &egui::FontId) -> f32 {
+    ui.fonts_mut(|f| f.layout_no_wrap(inlay.text.clone(), font_id.clone(), inlay.color))
+        .size()
+        .x
+}
+
+/// Room to leave before characters for the inlays inside rows.
+fn inlay_gaps(
+    ui: &egui::Ui,
+    text: &TextBuffer,
+    inlays: &[Inlay],
+    font_id: &egui::FontId,
+) -> Vec<(usize, f32)> {
+    let font_id = inlay_font(font_id);
+    inlays
+        .iter()
+        .filter(|inlay| inlay_inside_row(text, inlay.offset))
+        .map(|inlay| (inlay.offset, inlay_width(ui, inlay, &font_id)))
+        .collect()
+}
+
+fn paint_inlay(
+    ui: &egui::Ui,
+    output: &CodeEditOutput,
+    text: &TextBuffer,
+    inlay: &Inlay,
+    font_id: &egui::FontId,
+) {
+    if inlay.offset > text.len() {
+        return;
+    }
+    let cursor = output
+        .galley
+        .pos_from_cursor(CCursor::new(char_index(text, inlay.offset)))
+        .translate(output.galley_pos.to_vec2());
+    let galley =
+        ui.fonts_mut(|f| f.layout_no_wrap(inlay.text.clone(), font_id.clone(), inlay.color));
+
+    // inside a row the room is left of the cursor position, at the end of
+    // one the inlay simply follows the text
+    let x = if inlay_inside_row(text, inlay.offset) {
+        cursor.left() - galley.size().x
+    } else {
+        cursor.left() + 2.0
+    };
+    let pos = egui::pos2(x, cursor.center().y - galley.size().y / 2.0);
+    ui.painter_at(output.text_clip_rect).galley(pos, galley, inlay.color);
+}
+
+/// Byte range of the rows inside the clip rectangle, i.e. scrolled into view.
+fn visible_range(
+    ui: &egui::Ui,
+    output: &CodeEditOutput,
+    text: &TextBuffer,
+) -> Range<usize> {
+    let clip = ui.clip_rect().translate(-output.galley_pos.to_vec2());
+    let mut index = 0;
+    let mut first = None;
+    let mut last = 0;
+    for row in &output.galley.rows {
+        let rect = row.rect();
+        let end = index + row.char_count_including_newline();
+        if rect.bottom() >= clip.top() && rect.top() <= clip.bottom() {
+            first.get_or_insert(index);
+            last = end;
+        }
+        index = end;
+    }
+    byte_offset(text, first.unwrap_or(0))..byte_offset(text, last)
+}
+
+fn reveal(
+    ui: &egui::Ui,
+    output: &egui::text_edit::TextEditOutput,
+    text: &TextBuffer,
+    range: Range<usize>,
+) {
+    let anchor = CCursor::new(char_index(text, range.start));
+    let cursor = CCursor::new(char_index(text, range.end));
+
+    let mut state = output.state.clone();
+    state.cursor.set_char_range(Some(CCursorRange::two(anchor, cursor)));
+    state.store(ui.ctx(), output.response.id);
+    output.response.request_focus();
+
+    let rect = output
+        .galley
+        .pos_from_cursor(cursor)
+        .translate(output.galley_pos.to_vec2());
+    ui.scroll_to_rect(rect, Some(egui::Align::Center));
+}
+
+/// Scrolls the row of `offset` to the middle of the view.
+fn scroll_to(ui: &egui::Ui, output: &CodeEditOutput, text: &TextBuffer, offset: usize) {
+    let rect = output
+        .galley
+        .pos_from_cursor(CCursor::new(char_index(text, offset)))
+        .translate(output.galley_pos.to_vec2());
+    ui.scroll_to_rect(rect, Some(egui::Align::Center));
+}
+
 impl Default for TextEdit {
     fn default() -> Self {
         Self::new()
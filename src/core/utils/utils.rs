@@ -9,6 +9,9 @@ use eframe::egui;
 
 use crate::core::{enums::enums::FileType, models::Entry, types::types::EntryRc};
 
+/// Names left out of the file explorer and the project search.
+pub const SKIPPED_NAMES: [&str; 2] = [".git", ".DS_Store"];
+
 // СКОРЕЕ ВСЕГО ЧТО ТО С ЭТОЙ ФУНКЦИЕЙ ИЛИ ОТОБРАЖЕНИЕМ В КОМПОНЕНТЕ ПОСМОТРЕТЬ fileList and FileTreeItem
 /// читать текущую директорию
 /// TODO: сделать функцию выбирание папки для чтения
@@ -23,7 +26,7 @@ pub fn read_current_folder(path: &PathBuf) -> Vec<EntryRc> {
 
             if let Ok(name) = file_name.into_string() {
                 // todo: если файл скрыт то не показывать его
-                if name == ".git" || name == ".DS_Store" {
+                if SKIPPED_NAMES.contains(&name.as_str()) {
                     continue;
                 }
                 let entry_rc = Rc::new(RefCell::new(Entry {
@@ -1,6 +1,9 @@
 use crate::core::lib::rsx::component::ComponentWrapper;
 use crate::core::types::types::Element;
-use crate::modules::editor::components::{CodeEditorPanel, FileExplorerPanel, Navbar};
+use crate::modules::editor::components::{
+    CodeEditorPanel, FileExplorerPanel, Navbar, OutlinePanel, ProblemsPanel, ReferencesPanel,
+    RenamePanel, SearchPanel, StatusBar,
+};
 use riff_rsx_macro::component;
 use std::rc::Rc;
 
@@ -8,6 +11,14 @@ use std::rc::Rc;
 pub fn App(ctx: eframe::egui::Context) -> Element {
     let _navbar = Navbar(ctx.clone());
     let _explorer = FileExplorerPanel(ctx.clone());
+    let _search = SearchPanel(ctx.clone());
+    let _outline = OutlinePanel(ctx.clone());
+    // bottom panels must be registered before the central editor, the
+    // outermost first
+    let _status_bar = StatusBar(ctx.clone());
+    let _problems = ProblemsPanel(ctx.clone());
+    let _references = ReferencesPanel(ctx.clone());
+    let _rename = RenamePanel(ctx.clone());
     let _editor = CodeEditorPanel(ctx.clone());
 
     println!("[DEBUG] App: all components created");
@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::plugins::lsp::code_action::CodeAction;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{Popup, SelectableLabel, Style, StyleSheet, View};
+use crate::modules::editor::stores::{code_actions_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// 💡 in the gutter of the cursor row while the server offers code actions
+/// there; clicking it opens the menu.
+#[component]
+pub fn CodeActionLightbulb(ctx: eframe::egui::Context) -> Element {
+    let Some(line) = code_actions_store().get_lightbulb() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || code_actions_store().toggle_menu(&click_ctx));
+
+    rsx! {
+        Popup {
+            id: "code_action_lightbulb".to_string(),
+            position: line.left_center(),
+            pivot: Some(eframe::egui::Align2::LEFT_CENTER),
+            frame: Some(false),
+            children: {
+                SelectableLabel {
+                    selected: false,
+                    text: "💡".to_string(),
+                    on_click: Some(on_click),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CodeActionRow(
+    action: CodeAction,
+    index: usize,
+    selected: bool,
+    ctx: eframe::egui::Context,
+) -> Element {
+    let theme = theme_store();
+
+    let text = match &action.disabled {
+        Some(reason) => format!("{} ({})", action.title, reason),
+        None => action.title.clone(),
+    };
+    let text_color = action
+        .disabled
+        .is_some()
+        .then(|| theme.text_secondary.get(&ctx));
+
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || code_actions_store().apply(&click_ctx, index));
+
+    rsx! {
+        SelectableLabel {
+            selected: selected,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            text_color: text_color,
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Fixes and refactorings for the diagnostics under the cursor, below its row.
+#[component]
+pub fn CodeActionMenu(ctx: eframe::egui::Context) -> Element {
+    let Some((line, actions, selected)) = code_actions_store().get_menu() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+
+    let rows: Vec<Element> = actions
+        .into_iter()
+        .enumerate()
+        .map(|(index, action)| CodeActionRow(action, index, index == selected, ctx.clone()))
+        .collect();
+
+    let s = StyleSheet::new().with(
+        "list",
+        Style::new().justify(Justify::Start).align(Align::Start),
+    );
+
+    rsx! {
+        Popup {
+            id: "code_actions".to_string(),
+            position: line.left_bottom(),
+            max_width: Some(420.0),
+            children: {
+                View {
+                    style: s.get("list"),
+                    children: Children::Multiple(rows),
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,26 @@
 use core::f32;
+use std::ops::Range;
+use std::rc::Rc;
 
+use crate::core::plugins::lsp::protocol::range_to_offsets;
 use crate::core::stores::global_store::global_store;
-use crate::core::types::types::Element;
+use crate::core::types::types::{Element, Handler};
 use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::text_edit::{CursorInfo, HoverInfo, Mark, Underline};
 use crate::core::ui::ui_kit::{
     Button, CentralPanel, ScrollArea, Style, StyleSheet, Text, TextEdit, View,
 };
-use crate::modules::editor::components::TabsBar;
-use crate::modules::editor::stores::editor::editor_interactions::editor_interactions_store;
-use crate::modules::editor::stores::theme_store;
+use crate::modules::editor::components::{
+    CodeActionLightbulb, CodeActionMenu, CompletionPopup, FileFinderPopup, FindBar, HoverTooltip, RenameInput,
+    SignatureHelpPopup, TabsBar, WorkspaceSymbolsPopup,
+};
+use crate::modules::editor::stores::editor::editor_interactions::{
+    CODE_EDITOR, editor_interactions_store,
+};
+use crate::modules::editor::stores::{
+    code_actions_store, completion_store, decorations_store, diagnostics_store, find_store, formatting_store, gutter_store, hover_store, navigation_store, outline_store, rename_store,
+    signature_help_store, theme_store,
+};
 use crate::{on_click, rsx};
 use egui::Color32;
 use riff_rsx_macro::component;
@@ -21,6 +33,8 @@ pub fn CodeEditorPanel(ctx: eframe::egui::Context) -> Element {
 
     let current_path = editor_interactions.get_current_tab_path(&ctx);
     let text_value = editor_interactions.get_current_tab_text_ref(&ctx);
+    let language = editor_interactions.get_current_tab_language(&ctx);
+    let highlighting = editor_interactions.get_current_tab_highlighting(&ctx);
 
     let s = StyleSheet::new().with(
         "start",
@@ -30,7 +44,62 @@ pub fn CodeEditorPanel(ctx: eframe::egui::Context) -> Element {
             .background_color(theme.bg_main_100.get(&ctx)),
     );
 
-    if let (Some(_path), Some(text_ref)) = (current_path, text_value) {
+    if let (Some(path), Some(text_ref)) = (current_path, text_value) {
+        let underlines: Vec<Underline> = {
+            let text = text_ref.borrow();
+            diagnostics_store()
+                .get_for_path(&path)
+                .iter()
+                .map(|diagnostic| Underline {
+                    range: range_to_offsets(&*text, diagnostic.range),
+                    color: theme.severity_color(&ctx, diagnostic.severity),
+                })
+                .collect()
+        };
+        let reveal = editor_interactions.take_pending_reveal();
+        let keep_focus = editor_interactions.reveal_keeps_focus();
+        let highlights = decorations_store().get_highlights(&ctx, &path);
+        let inlays = decorations_store().get_inlays(&ctx, &path);
+        let gutter = gutter_store().gutter(&ctx, &path, &text_ref.borrow());
+        let marks: Vec<Mark> = find_store().get_marks(&ctx, &path, &text_ref.borrow());
+
+        let change_ctx = ctx.clone();
+        let on_change: Handler =
+            Rc::new(move || editor_interactions_store().mark_current_tab_dirty(&change_ctx));
+
+        let cursor_ctx = ctx.clone();
+        let cursor_text = text_ref.clone();
+        let cursor_path = path.clone();
+        let on_cursor: Rc<dyn Fn(CursorInfo)> = Rc::new(move |cursor| {
+            let text = cursor_text.borrow();
+            completion_store().on_cursor(&cursor_ctx, &cursor_path, &text, cursor);
+            signature_help_store().on_cursor(&cursor_ctx, &cursor_path, &text, cursor);
+            navigation_store().on_cursor(&cursor_ctx, &cursor_path, &text, cursor);
+            rename_store().on_cursor(&cursor_ctx, &cursor_path, cursor);
+            code_actions_store().on_cursor(&cursor_ctx, &cursor_path, &text, cursor);
+            formatting_store().on_cursor(&cursor_path, cursor);
+            outline_store().on_cursor(&cursor_ctx, &cursor_path, cursor);
+        });
+
+        let visible_ctx = ctx.clone();
+        let visible_text = text_ref.clone();
+        let visible_path = path.clone();
+        let on_visible: Rc<dyn Fn(Range<usize>)> = Rc::new(move |visible| {
+            outline_store().on_visible(&visible_ctx, &visible_path, visible.clone());
+            decorations_store().on_visible(
+                &visible_ctx,
+                &visible_path,
+                &visible_text.borrow(),
+                visible,
+            )
+        });
+
+        let hover_ctx = ctx.clone();
+        let hover_text = text_ref.clone();
+        let on_hover: Rc<dyn Fn(Option<HoverInfo>)> = Rc::new(move |hover| {
+            hover_store().on_hover(&hover_ctx, &path, &hover_text.borrow(), hover)
+        });
+
         rsx! {
             CentralPanel {
                 children: {
@@ -38,6 +107,7 @@ pub fn CodeEditorPanel(ctx: eframe::egui::Context) -> Element {
                         style: s.get("start"),
                         children: {
                             TabsBar(ctx.clone());
+                            FindBar(ctx.clone());
                             ScrollArea {
                                 auto_shrink: Some((false, false)),
                                 children: {
@@ -46,9 +116,31 @@ pub fn CodeEditorPanel(ctx: eframe::egui::Context) -> Element {
                                         multiline: true,
                                         font: Some("monospace".to_string()),
                                         font_size: Some(current_font_size),
+                                        language: language.clone(),
+                                        highlighting: highlighting.clone(),
+                                        on_change: Some(on_change.clone()),
+                                        underlines: underlines.clone(),
+                                        reveal: reveal.clone(),
+                                        keep_focus: keep_focus,
+                                        on_cursor: Some(on_cursor.clone()),
+                                        on_hover: Some(on_hover.clone()),
+                                        gutter: Some(gutter.clone()),
+                                        highlights: highlights.clone(),
+                                        inlays: inlays.clone(),
+                                        marks: marks.clone(),
+                                        on_visible: Some(on_visible.clone()),
+                                        id: Some(egui::Id::new(CODE_EDITOR)),
                                     }
                                 }
-                            }
+                            };
+                            CompletionPopup(ctx.clone());
+                            SignatureHelpPopup(ctx.clone());
+                            HoverTooltip(ctx.clone());
+                            RenameInput(ctx.clone());
+                            CodeActionLightbulb(ctx.clone());
+                            CodeActionMenu(ctx.clone());
+                            WorkspaceSymbolsPopup(ctx.clone());
+                            FileFinderPopup(ctx.clone());
                         }
                     }
                 }
@@ -81,7 +173,9 @@ pub fn CodeEditorPanel(ctx: eframe::egui::Context) -> Element {
                             Button {
                                 text: "Select a file from explorer".to_string(),
                                 on_click: Some(on_click!(hint_handler)),
-                            }
+                            };
+                            WorkspaceSymbolsPopup(ctx.clone());
+                            FileFinderPopup(ctx.clone())
                         }
                     }
                 }
@@ -0,0 +1,111 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::plugins::lsp::completion::CompletionItem;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{Popup, SelectableLabel, Separator, Style, StyleSheet, Text, View};
+use crate::modules::editor::stores::{completion_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// Rows shown at once; the window follows the selection.
+const MAX_ROWS: usize = 10;
+
+#[component]
+fn CompletionRow(
+    item: CompletionItem,
+    index: usize,
+    selected: bool,
+    ctx: eframe::egui::Context,
+) -> Element {
+    let theme = theme_store();
+
+    let icon = item.kind.map_or(" ", |kind| kind.icon());
+    let detail = item
+        .detail
+        .as_ref()
+        .map(|detail| format!("  {}", detail.lines().next().unwrap_or_default()))
+        .unwrap_or_default();
+    let text = format!("{} {}{}", icon, item.label, detail);
+
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || completion_store().accept(&click_ctx, index));
+
+    rsx! {
+        SelectableLabel {
+            selected: selected,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Autocomplete list anchored below the cursor of the code editor.
+#[component]
+pub fn CompletionPopup(ctx: eframe::egui::Context) -> Element {
+    let completion = completion_store();
+    if !completion.is_visible(&ctx) {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    }
+    let session = completion.session.borrow();
+    let Some(session) = session.as_ref() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+
+    let first = session
+        .selected
+        .saturating_sub(MAX_ROWS - 1)
+        .min(session.filtered.len().saturating_sub(MAX_ROWS));
+    let mut rows: Vec<Element> = session
+        .filtered
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MAX_ROWS)
+        .map(|(index, &item)| {
+            CompletionRow(
+                session.list.items[item].clone(),
+                index,
+                index == session.selected,
+                ctx.clone(),
+            )
+        })
+        .collect();
+
+    if let Some(item) = session.selected_item() {
+        let documentation = [item.detail.as_deref(), item.documentation.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if !documentation.is_empty() {
+            rows.push(rsx! { Separator {} });
+            rows.push(rsx! {
+                Text {
+                    content: documentation,
+                }
+            });
+        }
+    }
+
+    let s = StyleSheet::new().with(
+        "list",
+        Style::new().justify(Justify::Start).align(Align::Start),
+    );
+
+    rsx! {
+        Popup {
+            id: "completion".to_string(),
+            position: session.position,
+            max_width: Some(420.0),
+            children: {
+                View {
+                    style: s.get("list"),
+                    children: Children::Multiple(rows),
+                }
+            }
+        }
+    }
+}
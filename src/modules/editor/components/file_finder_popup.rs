@@ -0,0 +1,156 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{
+    Popup, ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, TextEdit, View,
+};
+use crate::modules::editor::stores::file_finder::file_finder_interactions::{
+    FilePreview, FileResult,
+};
+use crate::modules::editor::stores::{file_finder_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+#[component]
+fn FileRow(
+    result: FileResult,
+    index: usize,
+    selected: bool,
+    scroll_to: bool,
+    ctx: eframe::egui::Context,
+) -> Element {
+    let theme = theme_store();
+
+    // the file name first, then the folder it is in
+    let text = match result.path.rsplit_once('/') {
+        Some((folder, name)) => format!("{}    {}", name, folder),
+        None => result.path.clone(),
+    };
+
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || file_finder_store().open_result(&click_ctx, index));
+
+    rsx! {
+        SelectableLabel {
+            selected: selected,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+            scroll_to: scroll_to,
+        }
+    }
+}
+
+/// The lines of the selected file, the one to jump to marked.
+#[component]
+fn FilePreviewLines(preview: Option<FilePreview>) -> Element {
+    let Some(preview) = preview.filter(|preview| !preview.lines.is_empty()) else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    let content = preview
+        .lines
+        .iter()
+        .map(|(number, line)| {
+            let mark = if Some(*number) == preview.target {
+                "›"
+            } else {
+                " "
+            };
+            format!("{}{:>5}  {}", mark, number + 1, line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let s = StyleSheet::new().with(
+        "preview",
+        Style::new().justify(Justify::Start).align(Align::Start),
+    );
+
+    rsx! {
+        View {
+            style: s.get("preview"),
+            children: {
+                Separator {};
+                Text {
+                    content: content,
+                }
+            }
+        }
+    }
+}
+
+/// Cmd+P: files of the workspace matching a fuzzy query, `path:line` to
+/// jump to a line.
+#[component]
+pub fn FileFinderPopup(ctx: eframe::egui::Context) -> Element {
+    let store = file_finder_store();
+    let (Some((input, focus)), Some((results, selected, scroll))) =
+        (store.get_input(), store.get_results())
+    else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    // focus the input when the overlay opens
+    let reveal = focus.then(|| 0..input.borrow().len());
+
+    let (indexed, scanning) = store.get_index_size();
+    let status = if scanning {
+        format!("Indexing… {} files", indexed)
+    } else if results.is_empty() && !input.borrow().is_empty() {
+        "No matching files".to_string()
+    } else {
+        format!("{} of {} files", results.len(), indexed)
+    };
+
+    let rows: Vec<Element> = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let is_selected = index == selected;
+            FileRow(
+                result,
+                index,
+                is_selected,
+                is_selected && scroll,
+                ctx.clone(),
+            )
+        })
+        .collect();
+
+    let s = StyleSheet::new().with(
+        "list",
+        Style::new().justify(Justify::Start).align(Align::Start),
+    );
+
+    rsx! {
+        Popup {
+            id: "file_finder".to_string(),
+            position: ctx.content_rect().center_top() + eframe::egui::vec2(0.0, 60.0),
+            pivot: Some(eframe::egui::Align2::CENTER_TOP),
+            max_width: Some(560.0),
+            children: {
+                View {
+                    style: s.get("list"),
+                    children: {
+                        TextEdit {
+                            value: input.clone(),
+                            multiline: false,
+                            hint: Some("Go to file, `path:line` for a line".to_string()),
+                            reveal: reveal.clone(),
+                        };
+                        Text {
+                            content: status,
+                        };
+                        ScrollArea {
+                            auto_shrink: Some((false, true)),
+                            max_height: Some(300.0),
+                            children: Children::Multiple(rows),
+                        };
+                        FilePreviewLines(store.get_preview())
+                    }
+                }
+            }
+        }
+    }
+}
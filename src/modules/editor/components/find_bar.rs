@@ -0,0 +1,133 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::ComponentWrapper;
+use crate::core::types::types::{Element, Handler};
+use crate::core::ui::ui_kit::style::{Align, FlexDirection, Justify};
+use crate::core::ui::ui_kit::{Button, SelectableLabel, Style, StyleSheet, Text, TextEdit, View};
+use crate::modules::editor::stores::find::find_interactions::{QUERY_INPUT, REPLACE_INPUT};
+use crate::modules::editor::stores::{FindOption, find_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// A toggle of the bar, highlighted while it is on.
+#[component]
+fn OptionToggle(option: FindOption, text: &str, ctx: eframe::egui::Context) -> Element {
+    let click_ctx = ctx.clone();
+    let on_click: Handler = Rc::new(move || find_store().toggle(&click_ctx, option));
+
+    rsx! {
+        SelectableLabel {
+            selected: find_store().is_on(option),
+            text: text.to_string(),
+            hover_color: Some(theme_store().bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Cmd+F: find and replace in the current tab, above the editor.
+#[component]
+pub fn FindBar(ctx: eframe::egui::Context) -> Element {
+    let store = find_store();
+    let Some((query, focus)) = store.get_input() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    let replacement = store.get_replacement();
+    // select the query when the bar opens, so typing replaces it
+    let reveal = focus.then(|| 0..query.borrow().len());
+
+    let status = match store.get_count(&ctx) {
+        Err(_) => "Invalid regex".to_string(),
+        Ok((0, _)) if query.borrow().is_empty() => String::new(),
+        Ok((0, _)) => "No results".to_string(),
+        Ok((count, Some(current))) => format!("{} of {}", current + 1, count),
+        Ok((count, None)) => format!("? of {}", count),
+    };
+
+    let handler = |action: fn(&eframe::egui::Context)| -> Handler {
+        let ctx = ctx.clone();
+        Rc::new(move || action(&ctx))
+    };
+    let on_previous = handler(|ctx| find_store().step(ctx, false));
+    let on_next = handler(|ctx| find_store().step(ctx, true));
+    let on_replace = handler(|ctx| find_store().replace_one(ctx));
+    let on_replace_all = handler(|ctx| find_store().replace_all(ctx));
+    let on_close = handler(|ctx| find_store().close(ctx));
+
+    let theme = theme_store();
+    let s = StyleSheet::new()
+        .with(
+            "bar",
+            Style::new()
+                .padding_xy(8.0, 4.0)
+                .background_color(theme.bg_main_200.get(&ctx))
+                .border_bottom(theme.border_primary.get(&ctx), 1.0)
+                .justify(Justify::Start)
+                .align(Align::Start),
+        )
+        .with(
+            "row",
+            Style::new()
+                .flex_direction(FlexDirection::Row)
+                .align(Align::Center)
+                .gap(4.0),
+        );
+
+    rsx! {
+        View {
+            style: s.get("bar"),
+            children: {
+                View {
+                    style: s.get("row"),
+                    children: {
+                        TextEdit {
+                            value: query.clone(),
+                            multiline: false,
+                            hint: Some("Find".to_string()),
+                            reveal: reveal.clone(),
+                            id: Some(eframe::egui::Id::new(QUERY_INPUT)),
+                        };
+                        OptionToggle(FindOption::CaseSensitive, "Aa", ctx.clone());
+                        OptionToggle(FindOption::WholeWord, "[ab]", ctx.clone());
+                        OptionToggle(FindOption::Regex, ".*", ctx.clone());
+                        Text {
+                            content: status,
+                        };
+                        Button {
+                            text: "↑".to_string(),
+                            on_click: Some(on_previous.clone()),
+                        };
+                        Button {
+                            text: "↓".to_string(),
+                            on_click: Some(on_next.clone()),
+                        };
+                        Button {
+                            text: "×".to_string(),
+                            on_click: Some(on_close.clone()),
+                        }
+                    }
+                };
+                View {
+                    style: s.get("row"),
+                    children: {
+                        TextEdit {
+                            value: replacement.clone(),
+                            multiline: false,
+                            hint: Some("Replace".to_string()),
+                            id: Some(eframe::egui::Id::new(REPLACE_INPUT)),
+                        };
+                        OptionToggle(FindOption::PreserveCase, "AB", ctx.clone());
+                        Button {
+                            text: "Replace".to_string(),
+                            on_click: Some(on_replace.clone()),
+                        };
+                        Button {
+                            text: "Replace all".to_string(),
+                            on_click: Some(on_replace_all.clone()),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
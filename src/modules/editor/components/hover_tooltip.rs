@@ -0,0 +1,30 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::ComponentWrapper;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::{Markdown, Popup};
+use crate::modules::editor::stores::hover::TOOLTIP_ID;
+use crate::modules::editor::stores::hover_store;
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// `textDocument/hover` contents below the hovered word.
+#[component]
+pub fn HoverTooltip(_ctx: eframe::egui::Context) -> Element {
+    let Some((anchor, contents)) = hover_store().get_tooltip() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+
+    rsx! {
+        Popup {
+            id: TOOLTIP_ID.to_string(),
+            position: anchor.left_bottom(),
+            max_width: Some(480.0),
+            children: {
+                Markdown {
+                    content: contents,
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,51 @@
 pub mod app;
 pub mod bottom_panel;
+pub mod code_action_menu;
 pub mod code_editor_panel;
+pub mod completion_popup;
 pub mod file_explorer_panel;
+pub mod file_finder_popup;
 pub mod file_list;
 pub mod file_tree_item;
+pub mod find_bar;
+pub mod hover_tooltip;
 pub mod left_panel;
 pub mod navbar;
+pub mod outline_panel;
+pub mod problems_panel;
+pub mod references_panel;
+pub mod rename_input;
+pub mod rename_panel;
 pub mod right_panel;
+pub mod search_panel;
+pub mod signature_help_popup;
+pub mod status_bar;
 pub mod tabs_bar;
 pub mod top_panel;
+pub mod workspace_symbols_popup;
 
 pub use app::App;
 pub use bottom_panel::BottomPanel;
+pub use code_action_menu::{CodeActionLightbulb, CodeActionMenu};
 pub use code_editor_panel::CodeEditorPanel;
+pub use completion_popup::CompletionPopup;
 pub use file_explorer_panel::FileExplorerPanel;
+pub use file_finder_popup::FileFinderPopup;
 pub use file_list::FileList;
 pub use file_tree_item::FileTreeItem;
+pub use find_bar::FindBar;
+pub use hover_tooltip::HoverTooltip;
 pub use left_panel::LeftPanel;
 pub use navbar::Navbar;
+pub use outline_panel::OutlinePanel;
+pub use problems_panel::ProblemsPanel;
+pub use references_panel::ReferencesPanel;
+pub use rename_input::RenameInput;
+pub use rename_panel::RenamePanel;
 pub use right_panel::RightPanel;
+pub use search_panel::SearchPanel;
+pub use signature_help_popup::SignatureHelpPopup;
+pub use status_bar::StatusBar;
 pub use tabs_bar::TabsBar;
 pub use top_panel::TopPanel;
+pub use workspace_symbols_popup::WorkspaceSymbolsPopup;
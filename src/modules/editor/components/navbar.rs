@@ -3,7 +3,7 @@ use crate::core::types::types::Element;
 use crate::core::ui::ui_kit::style::{Align, Display, FlexDirection, Justify};
 use crate::core::ui::ui_kit::{Button, Color, Style, StyleSheet, Text, View};
 use crate::modules::editor::components::TopPanel;
-use crate::modules::editor::stores::theme_store;
+use crate::modules::editor::stores::{folding_store, theme_store};
 use crate::rsx;
 use riff_rsx_macro::component;
 
@@ -12,6 +12,17 @@ pub fn Navbar(ctx: eframe::egui::Context) -> Element {
     let global_store = global_store();
     let is_show_settings = global_store.is_show_settings.clone();
 
+    let format_on_save_text = if global_store.get_format_on_save().get(&ctx) {
+        "Format on save: on".to_string()
+    } else {
+        "Format on save: off".to_string()
+    };
+    let line_numbers_text = if global_store.get_relative_line_numbers().get(&ctx) {
+        "Line numbers: relative".to_string()
+    } else {
+        "Line numbers: absolute".to_string()
+    };
+
     let theme = theme_store();
 
     // TODO: FIXME: Ебучий навбар тянется куда то дохуя далеко, + кнопка внутри не встает в конец.
@@ -91,6 +102,48 @@ pub fn Navbar(ctx: eframe::egui::Context) -> Element {
                                                    }
                                                }
                                            };
+                                           Button {
+                                               text: format_on_save_text.clone(),
+                                               on_click: {
+                                                   let ctx = ctx.clone();
+
+                                                   Some(std::rc::Rc::new(move || {
+                                                       crate::core::stores::global_store::global_store()
+                                                           .toggle_format_on_save(&ctx);
+                                                   }))
+                                               },
+                                           };
+                                           Button {
+                                               text: line_numbers_text.clone(),
+                                               on_click: {
+                                                   let ctx = ctx.clone();
+
+                                                   Some(std::rc::Rc::new(move || {
+                                                       crate::core::stores::global_store::global_store()
+                                                           .toggle_relative_line_numbers(&ctx);
+                                                   }))
+                                               },
+                                           };
+                                           Button {
+                                               text: "Fold all".to_string(),
+                                               on_click: {
+                                                   let ctx = ctx.clone();
+
+                                                   Some(std::rc::Rc::new(move || {
+                                                       folding_store().fold_all(&ctx);
+                                                   }))
+                                               },
+                                           };
+                                           Button {
+                                               text: "Unfold all".to_string(),
+                                               on_click: {
+                                                   let ctx = ctx.clone();
+
+                                                   Some(std::rc::Rc::new(move || {
+                                                       folding_store().unfold_all(&ctx);
+                                                   }))
+                                               },
+                                           };
 
                                                }
                                            };
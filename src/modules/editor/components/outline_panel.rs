@@ -0,0 +1,89 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::Children;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{
+    ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, TextEdit, View,
+};
+use crate::modules::editor::components::RightPanel;
+use crate::modules::editor::stores::outline::outline_interactions::OutlineRow;
+use crate::modules::editor::stores::{outline_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+#[component]
+fn OutlineRowLabel(row: OutlineRow, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let text = format!(
+        "{}{} {}",
+        "    ".repeat(row.depth),
+        row.kind.icon(),
+        row.name
+    );
+
+    let click_ctx = ctx.clone();
+    let selection = row.selection.clone();
+    let on_click = Rc::new(move || outline_store().reveal(&click_ctx, selection.clone()));
+
+    rsx! {
+        SelectableLabel {
+            selected: row.active,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+            scroll_to: row.scroll_to,
+        }
+    }
+}
+
+/// Symbols of the current tab, nested, with the one under the cursor
+/// highlighted.
+#[component]
+pub fn OutlinePanel(ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+    let outline = outline_store();
+
+    let s = StyleSheet::new().with(
+        "outline",
+        Style::new()
+            .justify(Justify::Start)
+            .align(Align::Start)
+            .background_color(theme.bg_main_200.get(&ctx)),
+    );
+
+    let rows: Vec<Element> = outline
+        .get_rows(&ctx)
+        .into_iter()
+        .map(|row| OutlineRowLabel(row, ctx.clone()))
+        .collect();
+
+    rsx! {
+        RightPanel {
+            id: "outline".to_string(),
+            resizable: true,
+            default_width: Some(220.0),
+            children: {
+                View {
+                    style: s.get("outline"),
+                    children: {
+                        Text {
+                            content: "Outline".to_string(),
+                        };
+                        TextEdit {
+                            value: outline.get_filter(),
+                            multiline: false,
+                            hint: Some("Filter".to_string()),
+                        };
+                        Separator {};
+                        ScrollArea {
+                            auto_shrink: Some((false, false)),
+                            children: Children::Multiple(rows),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::Children;
+use crate::core::plugins::lsp::protocol::{Diagnostic, Severity};
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{
+    ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, View,
+};
+use crate::modules::editor::components::BottomPanel;
+use crate::modules::editor::stores::{diagnostics_store, editor_interactions_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+fn severity_icon(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "✖",
+        Severity::Warning => "⚠",
+        Severity::Information | Severity::Hint => "ℹ",
+    }
+}
+
+#[component]
+fn ProblemRow(path: PathBuf, diagnostic: Diagnostic, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let display_path = editor_interactions_store().display_path(&path);
+    let source = diagnostic
+        .source
+        .as_ref()
+        .map(|source| format!("  [{}]", source))
+        .unwrap_or_default();
+    let text = format!(
+        "{} {}:{}:{}  {}{}",
+        severity_icon(diagnostic.severity),
+        display_path.display(),
+        diagnostic.range.start.line + 1,
+        diagnostic.range.start.character + 1,
+        // multi-line messages are shown on one row
+        diagnostic.message.lines().next().unwrap_or_default(),
+        source,
+    );
+
+    let position = diagnostic.range.start;
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || {
+        editor_interactions_store().reveal_position(&click_ctx, path.clone(), position);
+    });
+
+    rsx! {
+        SelectableLabel {
+            selected: false,
+            text: text,
+            text_color: Some(theme.severity_color(&ctx, diagnostic.severity)),
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+#[component]
+pub fn ProblemsPanel(ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+    let problems = diagnostics_store().get_all_sorted();
+
+    let errors = problems
+        .iter()
+        .filter(|(_, d)| d.severity == Severity::Error)
+        .count();
+    let warnings = problems
+        .iter()
+        .filter(|(_, d)| d.severity == Severity::Warning)
+        .count();
+
+    let s = StyleSheet::new().with(
+        "problems",
+        Style::new()
+            .justify(Justify::Start)
+            .align(Align::Start)
+            .padding_horizontal(6.0)
+            .background_color(theme.bg_main_200.get(&ctx)),
+    );
+
+    let rows: Vec<Element> = if problems.is_empty() {
+        vec![rsx! {
+            Text {
+                content: "No problems have been detected.".to_string(),
+            }
+        }]
+    } else {
+        problems
+            .into_iter()
+            .map(|(path, diagnostic)| ProblemRow(path, diagnostic, ctx.clone()))
+            .collect()
+    };
+
+    rsx! {
+        BottomPanel {
+            id: "problems".to_string(),
+            children: {
+                View {
+                    style: s.get("problems"),
+                    children: {
+                        Text {
+                            content: format!("Problems  ✖ {}  ⚠ {}", errors, warnings),
+                        };
+                        Separator {};
+                        ScrollArea {
+                            auto_shrink: Some((false, true)),
+                            max_height: Some(160.0),
+                            children: Children::Multiple(rows),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::types::types::{Element, Handler};
+use crate::core::ui::ui_kit::style::{Align, FlexDirection, Justify};
+use crate::core::ui::ui_kit::{
+    Button, ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, View,
+};
+use crate::modules::editor::components::BottomPanel;
+use crate::modules::editor::stores::navigation::{LocationEntry, LocationsKind};
+use crate::modules::editor::stores::{editor_interactions_store, navigation_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+#[component]
+fn LocationRow(entry: LocationEntry, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let start = entry.location.range.start;
+    let text = format!(
+        "    {}:{}  {}",
+        start.line + 1,
+        start.character + 1,
+        entry.preview
+    );
+
+    let click_ctx = ctx.clone();
+    let location = entry.location;
+    let on_click = Rc::new(move || {
+        editor_interactions_store().reveal_range(&click_ctx, location.path.clone(), location.range);
+    });
+
+    rsx! {
+        SelectableLabel {
+            selected: false,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Definitions and references found by the language server, grouped by file.
+#[component]
+pub fn ReferencesPanel(ctx: eframe::egui::Context) -> Element {
+    let Some(panel) = navigation_store().get_panel() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    let theme = theme_store();
+
+    let s = StyleSheet::new()
+        .with(
+            "references",
+            Style::new()
+                .justify(Justify::Start)
+                .align(Align::Start)
+                .padding_horizontal(6.0)
+                .background_color(theme.bg_main_200.get(&ctx)),
+        )
+        .with("header", Style::new().flex_direction(FlexDirection::Row));
+
+    let what = match panel.kind {
+        LocationsKind::Definitions => "Definitions of",
+        LocationsKind::References => "References to",
+    };
+    let title = format!(
+        "{} `{}`  {} results in {} files",
+        what,
+        panel.symbol,
+        panel.result_count(),
+        panel.groups.len()
+    );
+
+    let mut rows: Vec<Element> = Vec::new();
+    for (path, entries) in panel.groups {
+        let header = format!(
+            "{}  ({})",
+            editor_interactions_store().display_path(&path).display(),
+            entries.len()
+        );
+        rows.push(rsx! {
+            Text {
+                content: header,
+            }
+        });
+        rows.extend(
+            entries
+                .into_iter()
+                .map(|entry| LocationRow(entry, ctx.clone())),
+        );
+    }
+
+    let close_ctx = ctx.clone();
+    let on_close: Handler = Rc::new(move || navigation_store().close_panel(&close_ctx));
+
+    rsx! {
+        BottomPanel {
+            id: "references".to_string(),
+            children: {
+                View {
+                    style: s.get("references"),
+                    children: {
+                        View {
+                            style: s.get("header"),
+                            children: {
+                                Text {
+                                    content: title,
+                                };
+                                Button {
+                                    text: "✕".to_string(),
+                                    on_click: Some(on_close.clone()),
+                                }
+                            }
+                        };
+                        Separator {};
+                        ScrollArea {
+                            auto_shrink: Some((false, true)),
+                            max_height: Some(200.0),
+                            children: Children::Multiple(rows),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::ComponentWrapper;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::{Popup, Text, TextEdit};
+use crate::modules::editor::stores::rename_store;
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// Input box for the new name of the symbol being renamed, below the cursor.
+#[component]
+pub fn RenameInput(ctx: eframe::egui::Context) -> Element {
+    let Some((anchor, input, focus)) = rename_store().get_input(&ctx) else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    // select the old name so typing replaces it
+    let reveal = focus.then(|| 0..input.borrow().len());
+
+    rsx! {
+        Popup {
+            id: "rename".to_string(),
+            position: anchor,
+            children: {
+                Text {
+                    content: "Rename to (Enter to apply, Esc to cancel)".to_string(),
+                };
+                TextEdit {
+                    value: input.clone(),
+                    multiline: false,
+                    font: Some("monospace".to_string()),
+                    reveal: reveal.clone(),
+                }
+            }
+        }
+    }
+}
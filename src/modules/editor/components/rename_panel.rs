@@ -0,0 +1,146 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::types::types::{Element, Handler};
+use crate::core::ui::ui_kit::style::{Align, FlexDirection, Justify};
+use crate::core::ui::ui_kit::{
+    Button, ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, View,
+};
+use crate::modules::editor::components::BottomPanel;
+use crate::modules::editor::stores::editor::editor_interactions::EditedFile;
+use crate::modules::editor::stores::{editor_interactions_store, rename_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+#[component]
+fn EditedFileRow(file: EditedFile, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let text = format!(
+        "    {}  {} edits, {}",
+        editor_interactions_store()
+            .display_path(&file.path)
+            .display(),
+        file.edits,
+        if file.in_tab {
+            "unsaved in tab"
+        } else {
+            "saved to disk"
+        }
+    );
+
+    let click_ctx = ctx.clone();
+    let path = file.path;
+    let on_click = Rc::new(move || {
+        editor_interactions_store().open_tab(&click_ctx, path.clone());
+    });
+
+    rsx! {
+        SelectableLabel {
+            selected: false,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Files changed by the last rename, with a button to undo all of it, or why
+/// the last rename didn't happen.
+#[component]
+pub fn RenamePanel(ctx: eframe::egui::Context) -> Element {
+    let summary = rename_store().get_summary();
+    let message = rename_store().get_message();
+    if summary.is_none() && message.is_none() {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    }
+    let theme = theme_store();
+
+    let s = StyleSheet::new()
+        .with(
+            "rename",
+            Style::new()
+                .justify(Justify::Start)
+                .align(Align::Start)
+                .padding_horizontal(6.0)
+                .background_color(theme.bg_main_200.get(&ctx)),
+        )
+        .with("header", Style::new().flex_direction(FlexDirection::Row));
+
+    let title = match &summary {
+        Some(summary) => format!(
+            "Renamed `{}` to `{}`  {} edits in {} files",
+            summary.old_name,
+            summary.new_name,
+            summary.edit_count(),
+            summary.files.len()
+        ),
+        None => message.unwrap_or_default(),
+    };
+
+    let close_ctx = ctx.clone();
+    let on_close: Handler = Rc::new(move || rename_store().close_summary(&close_ctx));
+
+    let mut header = vec![rsx! {
+        Text {
+            content: title,
+        }
+    }];
+    let mut body = vec![];
+    if let Some(summary) = summary {
+        // gone once Cmd+Z took the rename back
+        let undoable = summary
+            .step
+            .is_some_and(|step| editor_interactions_store().can_undo_workspace_step(step));
+        if undoable {
+            let undo_ctx = ctx.clone();
+            let on_undo: Handler = Rc::new(move || rename_store().undo(&undo_ctx));
+            header.push(rsx! {
+                Button {
+                    text: "Undo".to_string(),
+                    on_click: Some(on_undo.clone()),
+                }
+            });
+        }
+
+        let rows: Vec<Element> = summary
+            .files
+            .into_iter()
+            .map(|file| EditedFileRow(file, ctx.clone()))
+            .collect();
+        body.push(rsx! { Separator {} });
+        body.push(rsx! {
+            ScrollArea {
+                auto_shrink: Some((false, true)),
+                max_height: Some(200.0),
+                children: Children::Multiple(rows),
+            }
+        });
+    }
+    header.push(rsx! {
+        Button {
+            text: "✕".to_string(),
+            on_click: Some(on_close.clone()),
+        }
+    });
+
+    let mut children = vec![rsx! {
+        View {
+            style: s.get("header"),
+            children: Children::Multiple(header),
+        }
+    }];
+    children.extend(body);
+
+    rsx! {
+        BottomPanel {
+            id: "rename".to_string(),
+            children: {
+                View {
+                    style: s.get("rename"),
+                    children: Children::Multiple(children),
+                }
+            }
+        }
+    }
+}
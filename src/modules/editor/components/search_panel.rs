@@ -0,0 +1,272 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::types::types::{Element, Handler};
+use crate::core::ui::ui_kit::style::{Align, FlexDirection, Justify};
+use crate::core::ui::ui_kit::{
+    Button, ScrollArea, SelectableLabel, Separator, Style, StyleSheet, Text, TextEdit, View,
+};
+use crate::modules::editor::components::LeftPanel;
+use crate::modules::editor::stores::project_search::project_search_interactions::{
+    FileRow, MatchRow, SEARCH_INPUT,
+};
+use crate::modules::editor::stores::{
+    FindOption, editor_interactions_store, project_search_store, theme_store,
+};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// A toggle of the panel, highlighted while it is on.
+#[component]
+fn SearchToggle(option: FindOption, text: &str, ctx: eframe::egui::Context) -> Element {
+    let click_ctx = ctx.clone();
+    let on_click: Handler = Rc::new(move || project_search_store().toggle(&click_ctx, option));
+
+    rsx! {
+        SelectableLabel {
+            selected: project_search_store().is_on(option),
+            text: text.to_string(),
+            hover_color: Some(theme_store().bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// A file of the results; clicking it folds its matches away.
+#[component]
+fn SearchFileRow(file: &FileRow, ctx: eframe::egui::Context) -> Element {
+    let text = format!(
+        "{} {}  ({})",
+        if file.collapsed { "▸" } else { "▾" },
+        editor_interactions_store()
+            .display_path(&file.path)
+            .display(),
+        file.count
+    );
+    let click_ctx = ctx.clone();
+    let path = file.path.clone();
+    let on_click: Handler =
+        Rc::new(move || project_search_store().toggle_file(&click_ctx, path.clone()));
+
+    rsx! {
+        SelectableLabel {
+            selected: false,
+            text: text,
+            hover_color: Some(theme_store().bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// A match with its line; clicking it opens the file there. While there is
+/// a replacement, the check leaves it out of replacing all.
+#[component]
+fn SearchMatchRow(row: MatchRow, replacing: bool, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+    let s = StyleSheet::new().with(
+        "row",
+        Style::new()
+            .flex_direction(FlexDirection::Row)
+            .align(Align::Center)
+            .gap(4.0),
+    );
+
+    let open_ctx = ctx.clone();
+    let (path, range) = (row.path.clone(), row.range.clone());
+    let on_open: Handler =
+        Rc::new(move || project_search_store().open_match(&open_ctx, path.clone(), range.clone()));
+    let label = rsx! {
+        SelectableLabel {
+            selected: false,
+            text: format!("    {}: {}", row.line + 1, row.preview),
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_open),
+        }
+    };
+    if !replacing {
+        return label;
+    }
+
+    let toggle_ctx = ctx.clone();
+    let (path, start) = (row.path.clone(), row.range.start);
+    let on_toggle: Handler =
+        Rc::new(move || project_search_store().toggle_match(&toggle_ctx, path.clone(), start));
+    let check = rsx! {
+        SelectableLabel {
+            selected: row.included,
+            text: if row.included { "✔" } else { " " }.to_string(),
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_toggle),
+        }
+    };
+    rsx! {
+        View {
+            style: s.get("row"),
+            children: Children::Multiple(vec![check, label]),
+        }
+    }
+}
+
+/// Asks before replacing all, with how much it replaces.
+#[component]
+fn ReplaceConfirmation(ctx: eframe::egui::Context) -> Element {
+    let store = project_search_store();
+    if !store.is_confirming() {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    }
+    let (count, files) = store.get_included_count();
+    let replace_ctx = ctx.clone();
+    let on_replace: Handler = Rc::new(move || project_search_store().replace_all(&replace_ctx));
+    let cancel_ctx = ctx.clone();
+    let on_cancel: Handler = Rc::new(move || project_search_store().cancel_replace(&cancel_ctx));
+    let s = StyleSheet::new().with(
+        "row",
+        Style::new()
+            .flex_direction(FlexDirection::Row)
+            .align(Align::Center)
+            .gap(4.0),
+    );
+
+    rsx! {
+        View {
+            style: s.get("row"),
+            children: {
+                Text {
+                    content: format!("Replace {} matches in {} files?", count, files),
+                };
+                Button {
+                    text: "Replace".to_string(),
+                    on_click: Some(on_replace.clone()),
+                };
+                Button {
+                    text: "Cancel".to_string(),
+                    on_click: Some(on_cancel.clone()),
+                }
+            }
+        }
+    }
+}
+
+/// Cmd+Shift+F: search and replace across the files of the folder, the
+/// results grouped by file.
+#[component]
+pub fn SearchPanel(ctx: eframe::egui::Context) -> Element {
+    let store = project_search_store();
+    let Some((query, focus)) = store.get_input() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    // select the query when the panel opens, so typing replaces it
+    let reveal = focus.then(|| 0..query.borrow().len());
+    let replacement = store.get_replacement();
+    let replacing = !replacement.borrow().is_empty() || store.is_confirming();
+
+    let handler = |action: fn(&eframe::egui::Context)| -> Handler {
+        let ctx = ctx.clone();
+        Rc::new(move || action(&ctx))
+    };
+    let on_close = handler(|ctx| project_search_store().close(ctx));
+    let on_request = handler(|ctx| project_search_store().request_replace(ctx));
+
+    let mut rows: Vec<Element> = Vec::new();
+    for file in store.get_rows() {
+        rows.push(SearchFileRow(&file, ctx.clone()));
+        rows.extend(
+            file.matches
+                .into_iter()
+                .map(|row| SearchMatchRow(row, replacing, ctx.clone())),
+        );
+    }
+
+    let theme = theme_store();
+    let s = StyleSheet::new()
+        .with(
+            "search",
+            Style::new()
+                .justify(Justify::Start)
+                .align(Align::Start)
+                .background_color(theme.bg_main_200.get(&ctx)),
+        )
+        .with(
+            "row",
+            Style::new()
+                .flex_direction(FlexDirection::Row)
+                .align(Align::Center)
+                .gap(4.0),
+        );
+
+    rsx! {
+        LeftPanel {
+            id: "search".to_string(),
+            resizable: true,
+            default_width: Some(300.0),
+            children: {
+                View {
+                    style: s.get("search"),
+                    children: {
+                        View {
+                            style: s.get("row"),
+                            children: {
+                                Text {
+                                    content: "Search".to_string(),
+                                };
+                                Button {
+                                    text: "×".to_string(),
+                                    on_click: Some(on_close.clone()),
+                                }
+                            }
+                        };
+                        View {
+                            style: s.get("row"),
+                            children: {
+                                TextEdit {
+                                    value: query.clone(),
+                                    multiline: false,
+                                    hint: Some("Search".to_string()),
+                                    reveal: reveal.clone(),
+                                    id: Some(eframe::egui::Id::new(SEARCH_INPUT)),
+                                };
+                                SearchToggle(FindOption::CaseSensitive, "Aa", ctx.clone());
+                                SearchToggle(FindOption::WholeWord, "[ab]", ctx.clone());
+                                SearchToggle(FindOption::Regex, ".*", ctx.clone())
+                            }
+                        };
+                        View {
+                            style: s.get("row"),
+                            children: {
+                                TextEdit {
+                                    value: replacement.clone(),
+                                    multiline: false,
+                                    hint: Some("Replace".to_string()),
+                                };
+                                SearchToggle(FindOption::PreserveCase, "AB", ctx.clone());
+                                Button {
+                                    text: "Replace all…".to_string(),
+                                    on_click: Some(on_request.clone()),
+                                }
+                            }
+                        };
+                        TextEdit {
+                            value: store.get_include(),
+                            multiline: false,
+                            hint: Some("Files to include, e.g. src/**/*.rs".to_string()),
+                        };
+                        TextEdit {
+                            value: store.get_exclude(),
+                            multiline: false,
+                            hint: Some("Files to exclude, e.g. *.lock".to_string()),
+                        };
+                        Text {
+                            content: store.get_status(),
+                        };
+                        ReplaceConfirmation(ctx.clone());
+                        Separator {};
+                        ScrollArea {
+                            auto_shrink: Some((false, false)),
+                            children: Children::Multiple(rows),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::FlexDirection;
+use crate::core::ui::ui_kit::{Markdown, Popup, Style, StyleSheet, Text, View};
+use crate::modules::editor::stores::{signature_help_store, theme_store};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// Signature of the call around the cursor, above the cursor line, with the
+/// active parameter highlighted.
+#[component]
+pub fn SignatureHelpPopup(ctx: eframe::egui::Context) -> Element {
+    let Some((anchor, help)) = signature_help_store().get_help() else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    let theme = theme_store();
+
+    let s = StyleSheet::new()
+        .with("signature", Style::new().flex_direction(FlexDirection::Row))
+        .with("active", Style::new().color(theme.accent_primary.get(&ctx)));
+
+    let (before, active, after) = match help.active_parameter.clone() {
+        Some(range) => (
+            help.label[..range.start].to_string(),
+            help.label[range.clone()].to_string(),
+            help.label[range.end..].to_string(),
+        ),
+        None => (help.label.clone(), String::new(), String::new()),
+    };
+
+    let mut label: Vec<Element> = vec![
+        rsx! {
+            Text {
+                content: before,
+            }
+        },
+        rsx! {
+            Text {
+                content: active,
+                style: s.get("active"),
+            }
+        },
+        rsx! {
+            Text {
+                content: after,
+            }
+        },
+    ];
+    if help.signature_count > 1 {
+        label.push(rsx! {
+            Text {
+                content: format!("  {}/{}", help.active_signature + 1, help.signature_count),
+            }
+        });
+    }
+
+    let documentation = help.documentation.unwrap_or_default();
+
+    rsx! {
+        Popup {
+            id: "signature_help".to_string(),
+            position: anchor,
+            pivot: Some(eframe::egui::Align2::LEFT_BOTTOM),
+            max_width: Some(520.0),
+            children: {
+                View {
+                    style: s.get("signature"),
+                    spacing: Some(0.0),
+                    children: Children::Multiple(label),
+                };
+                Markdown {
+                    content: documentation,
+                }
+            }
+        }
+    }
+}
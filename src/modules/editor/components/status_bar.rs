@@ -0,0 +1,93 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::Children;
+use crate::core::plugins::lsp::{ServerInfo, ServerStatus, lsp_manager};
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, FlexDirection, Justify};
+use crate::core::ui::ui_kit::{SelectableLabel, Style, StyleSheet, Text, View};
+use crate::modules::editor::components::BottomPanel;
+use crate::modules::editor::stores::theme_store;
+use crate::rsx;
+use riff_rsx_macro::component;
+
+/// A language server and what it is doing; clicking restarts it.
+#[component]
+fn ServerItem(server: ServerInfo, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let (status, color) = match &server.status {
+        ServerStatus::Starting => ("starting".to_string(), theme.info.get(&ctx)),
+        ServerStatus::Running => ("running".to_string(), theme.success.get(&ctx)),
+        ServerStatus::Restarting { attempt } => (
+            format!("crashed, restarting ({})", attempt),
+            theme.warning.get(&ctx),
+        ),
+        ServerStatus::Failed(reason) => (format!("failed: {}", reason), theme.error.get(&ctx)),
+    };
+    let project = server
+        .key
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut text = format!("● {} ({})  {}", server.command, project, status);
+    if let Some(message) = &server.message {
+        text = format!("{}  {}", text, message);
+    }
+
+    let click_ctx = ctx.clone();
+    let key = server.key;
+    let on_click = Rc::new(move || lsp_manager().restart(&click_ctx, &key));
+
+    rsx! {
+        SelectableLabel {
+            selected: false,
+            text: text,
+            text_color: Some(color),
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+        }
+    }
+}
+
+/// Bottom line of the window with the state of the language servers.
+#[component]
+pub fn StatusBar(ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+
+    let s = StyleSheet::new().with(
+        "status_bar",
+        Style::new()
+            .flex_direction(FlexDirection::Row)
+            .justify(Justify::End)
+            .align(Align::Center)
+            .padding_horizontal(6.0)
+            .background_color(theme.bg_main_300.get(&ctx)),
+    );
+
+    let servers = lsp_manager().servers();
+    let items: Vec<Element> = if servers.is_empty() {
+        vec![rsx! {
+            Text {
+                content: "No language server".to_string(),
+            }
+        }]
+    } else {
+        servers
+            .into_iter()
+            .map(|server| ServerItem(server, ctx.clone()))
+            .collect()
+    };
+
+    rsx! {
+        BottomPanel {
+            id: "status_bar".to_string(),
+            children: {
+                View {
+                    style: s.get("status_bar"),
+                    children: Children::Multiple(items),
+                }
+            }
+        }
+    }
+}
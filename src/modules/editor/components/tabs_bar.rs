@@ -1,11 +1,47 @@
 use crate::core::lib::rsx::Children;
 use crate::core::types::types::Element;
 use crate::core::ui::ui_kit::style::{Align, Display, FlexDirection, Justify, Overflow};
-use crate::core::ui::ui_kit::{Button, Style, StyleSheet, Text, View};
-use crate::modules::editor::stores::{editor_interactions_store, theme_store, Tab};
+use crate::core::ui::ui_kit::{Button, SelectableLabel, Style, StyleSheet, Text, View};
+use crate::modules::editor::stores::{
+    diagnostics_store, editor_interactions_store, theme_store, Tab,
+};
 use crate::{on_click, rsx};
 use riff_rsx_macro::component;
 
+/// Error and warning counts shown next to the file name.
+#[component]
+fn render_diagnostic_counts(path: &std::path::Path, ctx: eframe::egui::Context) -> Element {
+    let theme = theme_store();
+    let counts = diagnostics_store().get_counts(path);
+
+    let mut children: Vec<Element> = Vec::new();
+    if counts.errors > 0 {
+        children.push(rsx! {
+            SelectableLabel {
+                selected: false,
+                text: counts.errors.to_string(),
+                text_color: Some(theme.error.get(&ctx)),
+            }
+        });
+    }
+    if counts.warnings > 0 {
+        children.push(rsx! {
+            SelectableLabel {
+                selected: false,
+                text: counts.warnings.to_string(),
+                text_color: Some(theme.warning.get(&ctx)),
+            }
+        });
+    }
+
+    rsx! {
+        View {
+            style: Some(std::rc::Rc::new(Style::new().flex_direction(FlexDirection::Row))),
+            children: Children::Multiple(children),
+        }
+    }
+}
+
 #[component]
 fn render_tab(tab: &Tab, index: usize, ctx: eframe::egui::Context) -> Element {
     let editor_interactions = editor_interactions_store();
@@ -92,6 +128,7 @@ fn render_tab(tab: &Tab, index: usize, ctx: eframe::egui::Context) -> Element {
                             Text {
                                 content: file_name.clone(),
                             };
+                            render_diagnostic_counts(&tab_path, ctx.clone());
                             View {
                                 style: s.get("dirty_circle"),
                             }
@@ -118,7 +155,8 @@ fn render_tab(tab: &Tab, index: usize, ctx: eframe::egui::Context) -> Element {
                                 text: file_name.clone(),
                                 on_click: Some(on_click!(click_handler, ctx_click_handler, tab_index)),
                                 enabled: true,
-                            }
+                            };
+                            render_diagnostic_counts(&tab_path, ctx.clone())
                         }
                     };
                     Button {
@@ -0,0 +1,130 @@
+use std::rc::Rc;
+
+use crate::core::lib::rsx::component::{Children, ComponentWrapper};
+use crate::core::plugins::lsp::symbols::WorkspaceSymbol;
+use crate::core::types::types::Element;
+use crate::core::ui::ui_kit::style::{Align, Justify};
+use crate::core::ui::ui_kit::{
+    Popup, ScrollArea, SelectableLabel, Style, StyleSheet, Text, TextEdit, View,
+};
+use crate::modules::editor::stores::{
+    editor_interactions_store, theme_store, workspace_symbols_store,
+};
+use crate::rsx;
+use riff_rsx_macro::component;
+
+#[component]
+fn SymbolRow(
+    symbol: WorkspaceSymbol,
+    index: usize,
+    selected: bool,
+    scroll_to: bool,
+    ctx: eframe::egui::Context,
+) -> Element {
+    let theme = theme_store();
+
+    let mut location = editor_interactions_store()
+        .display_path(&symbol.path)
+        .display()
+        .to_string();
+    if let Some(range) = symbol.range {
+        location = format!("{}:{}", location, range.start.line + 1);
+    }
+    let text = match &symbol.container {
+        Some(container) => format!(
+            "{} {}    {}  {}",
+            symbol.kind.icon(),
+            symbol.name,
+            container,
+            location
+        ),
+        None => format!("{} {}    {}", symbol.kind.icon(), symbol.name, location),
+    };
+
+    let click_ctx = ctx.clone();
+    let on_click = Rc::new(move || workspace_symbols_store().open_result(&click_ctx, index));
+
+    rsx! {
+        SelectableLabel {
+            selected: selected,
+            text: text,
+            hover_color: Some(theme.bg_hover.get(&ctx)),
+            on_click: Some(on_click),
+            scroll_to: scroll_to,
+        }
+    }
+}
+
+/// Cmd+T: symbols of the whole workspace matching the query, as the
+/// language servers find them.
+#[component]
+pub fn WorkspaceSymbolsPopup(ctx: eframe::egui::Context) -> Element {
+    let store = workspace_symbols_store();
+    let (Some((input, focus)), Some((results, selected, scroll))) =
+        (store.get_input(), store.get_results())
+    else {
+        return Rc::new(ComponentWrapper::new(|_ui: &mut eframe::egui::Ui| {}));
+    };
+    // focus the input when the overlay opens
+    let reveal = focus.then(|| 0..input.borrow().len());
+
+    let status = if !store.has_server() {
+        "No language server is running".to_string()
+    } else if store.is_searching() {
+        format!("Searching… {} found", results.len())
+    } else if results.is_empty() && !store.get_query().is_empty() {
+        "No matching symbols".to_string()
+    } else {
+        format!("{} symbols", results.len())
+    };
+
+    let rows: Vec<Element> = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, symbol)| {
+            let is_selected = index == selected;
+            SymbolRow(
+                symbol,
+                index,
+                is_selected,
+                is_selected && scroll,
+                ctx.clone(),
+            )
+        })
+        .collect();
+
+    let s = StyleSheet::new().with(
+        "list",
+        Style::new().justify(Justify::Start).align(Align::Start),
+    );
+
+    rsx! {
+        Popup {
+            id: "workspace_symbols".to_string(),
+            position: ctx.content_rect().center_top() + eframe::egui::vec2(0.0, 60.0),
+            pivot: Some(eframe::egui::Align2::CENTER_TOP),
+            max_width: Some(560.0),
+            children: {
+                View {
+                    style: s.get("list"),
+                    children: {
+                        TextEdit {
+                            value: input.clone(),
+                            multiline: false,
+                            hint: Some("Go to symbol in workspace".to_string()),
+                            reveal: reveal.clone(),
+                        };
+                        Text {
+                            content: status,
+                        };
+                        ScrollArea {
+                            auto_shrink: Some((false, true)),
+                            max_height: Some(360.0),
+                            children: Children::Multiple(rows),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
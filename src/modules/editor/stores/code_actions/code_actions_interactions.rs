@@ -0,0 +1,301 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::code_action::{
+    CodeAction, code_action_params, code_action_support, execute_command_params, parse_code_actions,
+};
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::protocol::{
+    self, offset_to_position, parse_workspace_edit, path_to_uri, range_to_offsets,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::{diagnostics_store, editor_interactions_store};
+use crate::store;
+
+/// How long the cursor has to stay on a diagnostic before the server is asked.
+const CODE_ACTION_DELAY: f64 = 0.25;
+
+/// The diagnostics under the cursor and the actions the server offers for them.
+pub struct CodeActionsState {
+    pub path: PathBuf,
+    /// Byte ranges of the diagnostics under the cursor.
+    diagnostics: Vec<Range<usize>>,
+    offset: usize,
+    /// Row of the cursor; the lightbulb sits at its left end.
+    pub line: egui::Rect,
+    since: f64,
+    sent: bool,
+    request: Option<RequestHandle>,
+    pub actions: Vec<CodeAction>,
+    /// Highlighted entry while the menu is open.
+    pub menu: Option<usize>,
+}
+
+/// An action that still waits for the server.
+pub enum PendingAction {
+    /// `codeAction/resolve`, answered with the action's edit.
+    Resolve(PathBuf, RequestHandle),
+    /// `workspace/executeCommand`; the server edits through
+    /// `workspace/applyEdit` before it answers.
+    Execute(RequestHandle),
+}
+
+store! {
+    pub struct CodeActionsInteractionsStore {
+        state: Option<CodeActionsState> = None,
+        pending: Option<PendingAction> = None,
+    }
+
+    // called by the editor every frame while it has focus
+    on_cursor(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, cursor: CursorInfo) {
+        let diagnostics: Vec<Range<usize>> = diagnostics_store()
+            .get_for_path(path)
+            .iter()
+            .map(|diagnostic| range_to_offsets(text, diagnostic.range))
+            .filter(|range| range.start <= cursor.offset && cursor.offset <= range.end)
+            .collect();
+
+        if let Some(state) = self.state.borrow_mut().as_mut()
+            && state.path == path
+            && state.diagnostics == diagnostics
+        {
+            state.offset = cursor.offset;
+            state.line = cursor.line;
+            return;
+        }
+
+        self.clear(ctx);
+        if diagnostics.is_empty() {
+            return;
+        }
+        *self.state.borrow_mut() = Some(CodeActionsState {
+            path: path.to_path_buf(),
+            diagnostics,
+            offset: cursor.offset,
+            line: cursor.line,
+            since: ctx.input(|i| i.time),
+            sent: false,
+            request: None,
+            actions: Vec::new(),
+            menu: None,
+        });
+        ctx.request_repaint_after(Duration::from_secs_f64(CODE_ACTION_DELAY));
+    }
+
+    // Ctrl+. opens the menu; arrows, Enter and Escape drive it while open
+    handle_keys(&self, ctx: &egui::Context) {
+        let consume = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+
+        let menu = self.state.borrow().as_ref().and_then(|state| state.menu);
+        if let Some(selected) = menu {
+            if consume(egui::Key::ArrowDown) {
+                self.move_selection(ctx, 1);
+            } else if consume(egui::Key::ArrowUp) {
+                self.move_selection(ctx, -1);
+            } else if consume(egui::Key::Enter) {
+                self.apply(ctx, selected);
+            } else if consume(egui::Key::Escape) {
+                self.toggle_menu(ctx);
+            }
+        } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Period)) {
+            self.toggle_menu(ctx);
+        }
+    }
+
+    // sends the request once the cursor stayed long enough and picks up the
+    // responses; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        self.poll_pending(ctx);
+
+        let mut state = self.state.borrow_mut();
+        let Some(state) = state.as_mut() else {
+            return;
+        };
+
+        if !state.sent {
+            let waited = ctx.input(|i| i.time) - state.since;
+            if waited < CODE_ACTION_DELAY {
+                ctx.request_repaint_after(Duration::from_secs_f64(CODE_ACTION_DELAY - waited));
+                return;
+            }
+            state.sent = true;
+            state.request = request_actions(ctx, state);
+            return;
+        }
+
+        let Some(response) = state.request.as_mut().and_then(|r| r.try_take()) else {
+            return;
+        };
+        state.request = None;
+        match response {
+            Ok(result) => {
+                state.actions = parse_code_actions(&result);
+                ctx.request_repaint();
+            }
+            Err(e) => {
+                if !is_cancelled(&e) {
+                    eprintln!("Code actions failed: {}", e);
+                }
+            }
+        }
+    }
+
+    // opens or closes the menu of the actions under the cursor
+    toggle_menu(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        let mut state = reactive.state();
+        if let Some(state) = state.as_mut().filter(|state| !state.actions.is_empty()) {
+            state.menu = match state.menu {
+                Some(_) => None,
+                None => Some(0),
+            };
+        }
+    }
+
+    // applies the `index`th action: its edit first, then its command
+    apply(&self, ctx: &egui::Context, index: usize) {
+        let Some(state) = self.reactive(ctx).state().take() else {
+            return;
+        };
+        let Some(action) = state.actions.into_iter().nth(index) else {
+            return;
+        };
+        if let Some(reason) = &action.disabled {
+            println!("Can't apply `{}`: {}", action.title, reason);
+            return;
+        }
+
+        let resolve = lsp_manager()
+            .capabilities(&state.path)
+            .and_then(|capabilities| code_action_support(&capabilities))
+            .unwrap_or(false);
+        if action.needs_resolve() && resolve {
+            let params = action.raw.clone();
+            let request = lsp_manager().request(ctx, &state.path, |client| {
+                client.request("codeAction/resolve", params)
+            });
+            *self.pending.borrow_mut() =
+                request.map(|request| PendingAction::Resolve(state.path, request));
+        } else {
+            self.run(ctx, &state.path, action);
+        }
+    }
+
+    // hides the lightbulb and cancels a request still in flight
+    clear(&self, ctx: &egui::Context) {
+        if self.state.borrow().is_none() {
+            return;
+        }
+        let Some(mut state) = self.reactive(ctx).state().take() else {
+            return;
+        };
+        if let Some(request) = state.request.take() {
+            lsp_manager().cancel(&state.path, request);
+        }
+    }
+}
+
+impl CodeActionsInteractionsStore {
+    /// The cursor row to put the lightbulb on, while there are actions.
+    pub fn get_lightbulb(&self) -> Option<egui::Rect> {
+        let state = self.state.borrow();
+        let state = state.as_ref()?;
+        (!state.actions.is_empty()).then_some(state.line)
+    }
+
+    /// The cursor row, the actions and the highlighted one, while the menu
+    /// is open.
+    pub fn get_menu(&self) -> Option<(egui::Rect, Vec<CodeAction>, usize)> {
+        let state = self.state.borrow();
+        let state = state.as_ref()?;
+        Some((state.line, state.actions.clone(), state.menu?))
+    }
+
+    fn move_selection(&self, ctx: &egui::Context, delta: isize) {
+        let mut reactive = self.reactive(ctx);
+        let mut state = reactive.state();
+        if let Some(state) = state.as_mut()
+            && let Some(selected) = state.menu
+        {
+            let count = state.actions.len() as isize;
+            state.menu = Some((selected as isize + delta).rem_euclid(count) as usize);
+        }
+    }
+
+    fn run(&self, ctx: &egui::Context, path: &Path, action: CodeAction) {
+        if let Some(edit) = &action.edit {
+            editor_interactions_store().apply_workspace_edit(ctx, parse_workspace_edit(edit));
+        }
+        if let Some(command) = &action.command {
+            let params = execute_command_params(command);
+            let request = lsp_manager().request(ctx, path, |client| {
+                client.request("workspace/executeCommand", params)
+            });
+            *self.pending.borrow_mut() = request.map(PendingAction::Execute);
+        }
+    }
+
+    fn poll_pending(&self, ctx: &egui::Context) {
+        let response = match self.pending.borrow_mut().as_mut() {
+            Some(PendingAction::Resolve(_, request) | PendingAction::Execute(request)) => {
+                request.try_take()
+            }
+            None => None,
+        };
+        let Some(response) = response else {
+            return;
+        };
+        let Some(pending) = self.pending.borrow_mut().take() else {
+            return;
+        };
+
+        match (pending, response) {
+            (PendingAction::Resolve(path, _), Ok(result)) => {
+                if let Some(action) = CodeAction::from_value(&result) {
+                    self.run(ctx, &path, action);
+                }
+            }
+            (PendingAction::Execute(_), Ok(_)) => {}
+            (_, Err(e)) => eprintln!("Code action failed: {}", e),
+        }
+    }
+}
+
+/// Sends `textDocument/codeAction` at the cursor with the diagnostics under it.
+fn request_actions(ctx: &egui::Context, state: &CodeActionsState) -> Option<RequestHandle> {
+    code_action_support(&lsp_manager().capabilities(&state.path)?)?;
+
+    let text = editor_interactions_store().get_current_tab_text_ref(ctx)?;
+    let text = text.borrow();
+    let position = offset_to_position(&*text, state.offset);
+    let diagnostics = diagnostics_store()
+        .get_for_path(&state.path)
+        .into_iter()
+        .filter(|diagnostic| {
+            state
+                .diagnostics
+                .contains(&range_to_offsets(&*text, diagnostic.range))
+        })
+        .map(|diagnostic| diagnostic.raw)
+        .collect();
+    let params = code_action_params(
+        &path_to_uri(&state.path),
+        protocol::Range {
+            start: position,
+            end: position,
+        },
+        diagnostics,
+    );
+    lsp_manager().request(ctx, &state.path, |client| {
+        client.request("textDocument/codeAction", params)
+    })
+}
+
+pub fn code_actions_store() -> std::cell::Ref<'static, CodeActionsInteractionsStore> {
+    CodeActionsInteractionsStore::instance()
+}
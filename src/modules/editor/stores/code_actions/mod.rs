@@ -0,0 +1,3 @@
+pub mod code_actions_interactions;
+
+pub use code_actions_interactions::{CodeActionsInteractionsStore, code_actions_store};
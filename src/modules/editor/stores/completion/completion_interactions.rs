@@ -0,0 +1,356 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::completion::{
+    CompletionItem, CompletionList, apply_completion, filter_items, is_word_char,
+    parse_completion_response, request_completion,
+};
+use crate::core::plugins::lsp::protocol::{offset_to_position, path_to_uri};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::search::fuzzy_match;
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// An open (or about to open) completion popup.
+pub struct CompletionSession {
+    pub path: PathBuf,
+    /// Start of the word being completed.
+    pub anchor: usize,
+    pub cursor: usize,
+    /// What was typed between `anchor` and `cursor`.
+    pub query: String,
+    /// Screen position right below the cursor.
+    pub position: egui::Pos2,
+    pub list: CompletionList,
+    /// Indices into `list.items` matching `query`, best first.
+    pub filtered: Vec<usize>,
+    /// Index into `filtered`.
+    pub selected: usize,
+    request: Option<RequestHandle>,
+}
+
+impl CompletionSession {
+    fn refilter(&mut self) {
+        self.filtered = filter_items(&self.list.items, &self.query, |query, text| {
+            Some(fuzzy_match(query, text)?.score)
+        });
+        self.selected = 0;
+    }
+
+    pub fn selected_item(&self) -> Option<&CompletionItem> {
+        self.list.items.get(*self.filtered.get(self.selected)?)
+    }
+}
+
+/// Tab stops of an accepted snippet not visited yet.
+pub struct SnippetSession {
+    path: PathBuf,
+    // revision of the text the stops refer to
+    revision: u64,
+    tabstops: Vec<Range<usize>>,
+}
+
+store! {
+    pub struct CompletionInteractionsStore {
+        session: Option<CompletionSession> = None,
+        snippet: Option<SnippetSession> = None,
+        // Ctrl+Space was pressed; the next cursor update asks the server
+        manual_trigger: bool = false,
+        // frame of the last cursor update; the popup hides with the editor's focus
+        last_cursor_frame: u64 = 0,
+    }
+
+    // called by the editor every frame while it has focus
+    on_cursor(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, cursor: CursorInfo) {
+        *self.last_cursor_frame.borrow_mut() = ctx.cumulative_frame_nr();
+        self.track_snippet(path, text, cursor.changed);
+
+        let manual = std::mem::take(&mut *self.manual_trigger.borrow_mut());
+        let offset = cursor.offset;
+        let anchor = text.word_at(offset).start;
+        let typed = text.char_before(offset).filter(|_| cursor.changed);
+
+        if manual {
+            self.request(ctx, path, text, cursor, None);
+            return;
+        }
+        if let Some(c) = typed
+            && !is_word_char(c)
+            && trigger_characters(path).iter().any(|t| t.starts_with(c))
+        {
+            self.request(ctx, path, text, cursor, Some(c));
+            return;
+        }
+
+        let (same_word, moved, incomplete) = match &*self.session.borrow() {
+            Some(session) => (
+                session.path == path && session.anchor == anchor,
+                session.cursor != offset,
+                session.list.is_incomplete,
+            ),
+            None => (false, false, false),
+        };
+
+        if same_word {
+            if !moved {
+                return;
+            }
+            if incomplete && cursor.changed {
+                self.request(ctx, path, text, cursor, None);
+                return;
+            }
+            let mut reactive = self.reactive(ctx);
+            let mut session = reactive.session();
+            if let Some(session) = session.as_mut() {
+                session.cursor = offset;
+                session.query = text.slice(anchor..offset).into_owned();
+                session.position = cursor.rect.left_bottom();
+                session.refilter();
+            }
+        } else if typed.is_some_and(is_word_char) {
+            self.request(ctx, path, text, cursor, None);
+        } else if self.session.borrow().is_some() {
+            self.close(ctx);
+        }
+    }
+
+    // picks up the server's response; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        let response = match self.session.borrow_mut().as_mut() {
+            Some(session) => session.request.as_mut().and_then(|r| r.try_take()),
+            None => None,
+        };
+        let Some(response) = response else {
+            return;
+        };
+
+        match response {
+            Ok(result) => {
+                let mut reactive = self.reactive(ctx);
+                let mut session = reactive.session();
+                if let Some(session) = session.as_mut() {
+                    session.request = None;
+                    session.list = parse_completion_response(&result);
+                    session.refilter();
+                }
+            }
+            Err(e) => {
+                eprintln!("Completion failed: {}", e);
+                self.close(ctx);
+            }
+        }
+    }
+
+    // keys the popup (or an active snippet) takes before the editor sees them
+    handle_keys(&self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::Space)) {
+            *self.manual_trigger.borrow_mut() = true;
+            ctx.request_repaint();
+        }
+
+        let consume = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+
+        if self.is_visible(ctx) {
+            if consume(egui::Key::ArrowDown) {
+                self.move_selection(ctx, 1);
+            } else if consume(egui::Key::ArrowUp) {
+                self.move_selection(ctx, -1);
+            } else if consume(egui::Key::Enter) || consume(egui::Key::Tab) {
+                let selected = self.session.borrow().as_ref().map_or(0, |s| s.selected);
+                self.accept(ctx, selected);
+            } else if consume(egui::Key::Escape) {
+                self.close(ctx);
+            }
+            return;
+        }
+
+        if self.snippet.borrow().is_some() {
+            if consume(egui::Key::Tab) {
+                self.next_tabstop(ctx);
+            } else if consume(egui::Key::Escape) {
+                *self.reactive(ctx).snippet() = None;
+            }
+        }
+    }
+
+    // applies the `index`th filtered item to the current tab
+    accept(&self, ctx: &egui::Context, index: usize) {
+        let Some(session) = self.reactive(ctx).session().take() else {
+            return;
+        };
+        let item = session.filtered.get(index).and_then(|&i| session.list.items.get(i));
+        let Some(item) = item else {
+            return;
+        };
+
+        let editor = editor_interactions_store();
+        if editor.get_current_tab_path(ctx).as_deref() != Some(session.path.as_path()) {
+            return;
+        }
+        let Some(text_ref) = editor.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+
+        let applied = apply_completion(
+            &text_ref.borrow().contents(),
+            item,
+            session.anchor..session.cursor,
+            session.cursor,
+        );
+        text_ref
+            .borrow_mut()
+            .transaction(|text| text.set_text(&applied.text));
+        editor.mark_current_tab_dirty(ctx);
+
+        let mut tabstops = applied.tabstops.into_iter();
+        if let Some(first) = tabstops.next() {
+            editor.select_range(ctx, first);
+        }
+        let rest: Vec<Range<usize>> = tabstops.collect();
+        *self.reactive(ctx).snippet() = (!rest.is_empty()).then(|| SnippetSession {
+            path: session.path.clone(),
+            revision: text_ref.borrow().revision(),
+            tabstops: rest,
+        });
+    }
+
+    close(&self, ctx: &egui::Context) {
+        *self.reactive(ctx).session() = None;
+    }
+}
+
+impl CompletionInteractionsStore {
+    /// Whether the popup is showing: there are matches and the editor had
+    /// focus up to the previous frame (clicking a row takes it away).
+    pub fn is_visible(&self, ctx: &egui::Context) -> bool {
+        ctx.cumulative_frame_nr() <= *self.last_cursor_frame.borrow() + 1
+            && self
+                .session
+                .borrow()
+                .as_ref()
+                .is_some_and(|s| !s.filtered.is_empty())
+    }
+
+    fn move_selection(&self, ctx: &egui::Context, step: isize) {
+        let mut reactive = self.reactive(ctx);
+        let mut session = reactive.session();
+        if let Some(session) = session.as_mut()
+            && !session.filtered.is_empty()
+        {
+            let len = session.filtered.len() as isize;
+            session.selected = (session.selected as isize + step).rem_euclid(len) as usize;
+        }
+    }
+
+    fn next_tabstop(&self, ctx: &egui::Context) {
+        let next = {
+            let mut reactive = self.reactive(ctx);
+            let mut snippet = reactive.snippet();
+            let next = snippet
+                .as_mut()
+                .map(|s| (s.path.clone(), s.tabstops.remove(0)));
+            if snippet.as_ref().is_some_and(|s| s.tabstops.is_empty()) {
+                *snippet = None;
+            }
+            next
+        };
+
+        let editor = editor_interactions_store();
+        if let Some((path, range)) = next
+            && editor.get_current_tab_path(ctx) == Some(path)
+        {
+            editor.select_range(ctx, range);
+        }
+    }
+
+    fn request(
+        &self,
+        ctx: &egui::Context,
+        path: &Path,
+        text: &TextBuffer,
+        cursor: CursorInfo,
+        trigger: Option<char>,
+    ) {
+        let uri = path_to_uri(path);
+        let position = offset_to_position(text, cursor.offset);
+        let trigger = trigger.map(String::from);
+        let Some(request) = lsp_manager().request(ctx, path, |client| {
+            request_completion(client, &uri, position, trigger.as_deref())
+        }) else {
+            return;
+        };
+
+        let anchor = text.word_at(cursor.offset).start;
+        let mut reactive = self.reactive(ctx);
+        let mut session = reactive.session();
+        // keep showing the old items of the same word until the new ones arrive
+        let list = session
+            .take()
+            .filter(|s| s.path == path && s.anchor == anchor)
+            .map(|s| s.list)
+            .unwrap_or_default();
+        let mut new_session = CompletionSession {
+            path: path.to_path_buf(),
+            anchor,
+            cursor: cursor.offset,
+            query: text.slice(anchor..cursor.offset).into_owned(),
+            position: cursor.rect.left_bottom(),
+            list,
+            filtered: Vec::new(),
+            selected: 0,
+            request: Some(request),
+        };
+        new_session.refilter();
+        *session = Some(new_session);
+    }
+
+    /// Moves the remaining snippet tab stops along with edits made before them.
+    fn track_snippet(&self, path: &Path, text: &TextBuffer, changed: bool) {
+        let mut snippet = self.snippet.borrow_mut();
+        let Some(session) = snippet.as_mut() else {
+            return;
+        };
+        if session.path != path {
+            *snippet = None;
+            return;
+        }
+        if !changed {
+            return;
+        }
+
+        // too many edits since to follow them
+        let Some(edit) = text.edit_since(session.revision) else {
+            *snippet = None;
+            return;
+        };
+        if let Some(edit) = edit {
+            let delta = edit.new_len as isize - edit.range.len() as isize;
+            for stop in session.tabstops.iter_mut() {
+                if stop.start >= edit.range.end {
+                    *stop = stop.start.saturating_add_signed(delta)
+                        ..stop.end.saturating_add_signed(delta);
+                }
+            }
+        }
+        session.revision = text.revision();
+    }
+}
+
+/// `completionProvider.triggerCharacters` of the server for `path`.
+fn trigger_characters(path: &Path) -> Vec<String> {
+    lsp_manager()
+        .capabilities(path)
+        .and_then(|capabilities| {
+            serde_json::from_value(capabilities["completionProvider"]["triggerCharacters"].clone())
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+pub fn completion_store() -> std::cell::Ref<'static, CompletionInteractionsStore> {
+    CompletionInteractionsStore::instance()
+}
@@ -0,0 +1,3 @@
+pub mod completion_interactions;
+
+pub use completion_interactions::{CompletionInteractionsStore, completion_store};
@@ -0,0 +1,279 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::inlay_hints::{
+    InlayHint, inlay_hint_params, inlay_hint_support, parse_inlay_hints,
+};
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::protocol::{offsets_to_range, path_to_uri, position_to_offset};
+use crate::core::plugins::lsp::semantic_tokens::{
+    SemanticToken, SemanticTokens, apply_semantic_tokens, decode_semantic_tokens,
+    semantic_tokens_params, semantic_tokens_support,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::{Highlight, Inlay};
+use crate::modules::editor::stores::{editor_interactions_store, theme_store};
+use crate::store;
+
+/// How long the text and the view have to stay put before the server is asked.
+const DECORATIONS_DELAY: f64 = 0.3;
+
+/// The text and the rows the editor shows.
+pub struct View {
+    pub path: PathBuf,
+    revision: u64,
+    visible: Range<usize>,
+    since: f64,
+}
+
+/// Inlay hints and semantic tokens of the document in view.
+#[derive(Default)]
+pub struct Decorations {
+    hints: Vec<InlayHint>,
+    // text revision and visible range the hints were asked for
+    hints_for: Option<(u64, Range<usize>)>,
+    hints_request: Option<RequestHandle>,
+    tokens: Option<SemanticTokens>,
+    // text revision the tokens were asked for
+    tokens_for: Option<u64>,
+    tokens_request: Option<RequestHandle>,
+    // hints and tokens resolved against the text of this revision
+    resolved_for: Option<u64>,
+    inlays: Vec<(usize, String)>,
+    semantic: Vec<SemanticToken>,
+}
+
+store! {
+    pub struct DecorationsInteractionsStore {
+        view: Option<View> = None,
+        decorations: Decorations = Decorations::default(),
+    }
+
+    // called by the editor every frame with the rows scrolled into view
+    on_visible(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, visible: Range<usize>) {
+        let revision = text.revision();
+
+        let same_path = self.is_in_view(path);
+        if !same_path {
+            self.clear();
+        }
+        let mut view = self.view.borrow_mut();
+        let moved = view
+            .as_ref()
+            .is_none_or(|view| !same_path || view.revision != revision || view.visible != visible);
+        if moved {
+            *view = Some(View {
+                path: path.to_path_buf(),
+                revision,
+                visible,
+                since: ctx.input(|i| i.time),
+            });
+            ctx.request_repaint_after(Duration::from_secs_f64(DECORATIONS_DELAY));
+        }
+        drop(view);
+
+        let mut decorations = self.decorations.borrow_mut();
+        if decorations.resolved_for != Some(revision) {
+            decorations.resolved_for = Some(revision);
+            decorations.inlays = decorations
+                .hints
+                .iter()
+                .map(|hint| (position_to_offset(text, hint.position), hint.text()))
+                .collect();
+            decorations.semantic = decorations
+                .tokens
+                .as_ref()
+                .map(|tokens| decode_semantic_tokens(&text.contents(), &tokens.data))
+                .unwrap_or_default();
+            ctx.request_repaint();
+        }
+    }
+
+    // asks for hints and tokens once the view settled and picks up the
+    // responses; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        self.take_responses();
+
+        let view = self.view.borrow();
+        let Some(view) = view.as_ref() else {
+            return;
+        };
+        let editor = editor_interactions_store();
+        if editor.get_current_tab_path(ctx).as_ref() != Some(&view.path) {
+            return;
+        }
+        let Some(text) = editor.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let text = text.borrow();
+        let waited = ctx.input(|i| i.time) - view.since;
+        if waited < DECORATIONS_DELAY {
+            ctx.request_repaint_after(Duration::from_secs_f64(DECORATIONS_DELAY - waited));
+            return;
+        }
+        let Some(capabilities) = lsp_manager().capabilities(&view.path) else {
+            return;
+        };
+        let uri = path_to_uri(&view.path);
+        let mut decorations = self.decorations.borrow_mut();
+
+        let hints_for = Some((view.revision, view.visible.clone()));
+        if inlay_hint_support(&capabilities)
+            && decorations.hints_request.is_none()
+            && decorations.hints_for != hints_for
+        {
+            decorations.hints_for = hints_for;
+            let params = inlay_hint_params(&uri, offsets_to_range(&*text, view.visible.clone()));
+            decorations.hints_request = lsp_manager().request(ctx, &view.path, |client| {
+                client.request("textDocument/inlayHint", params)
+            });
+        }
+
+        if let Some((_, delta)) = semantic_tokens_support(&capabilities)
+            && decorations.tokens_request.is_none()
+            && decorations.tokens_for != Some(view.revision)
+        {
+            decorations.tokens_for = Some(view.revision);
+            let previous = decorations
+                .tokens
+                .as_ref()
+                .and_then(|tokens| tokens.result_id.clone())
+                .filter(|_| delta);
+            let method = if previous.is_some() {
+                "textDocument/semanticTokens/full/delta"
+            } else {
+                "textDocument/semanticTokens/full"
+            };
+            let params = semantic_tokens_params(&uri, previous.as_deref());
+            decorations.tokens_request = lsp_manager().request(ctx, &view.path, |client| {
+                client.request(method, params)
+            });
+        }
+    }
+}
+
+impl DecorationsInteractionsStore {
+    /// Inlay hints of `path`, dimmed.
+    pub fn get_inlays(&self, ctx: &egui::Context, path: &Path) -> Vec<Inlay> {
+        if !self.is_in_view(path) {
+            return vec![];
+        }
+        let color = theme_store().text_secondary.get(ctx);
+        self.decorations
+            .borrow()
+            .inlays
+            .iter()
+            .map(|(offset, text)| Inlay {
+                offset: *offset,
+                text: text.clone(),
+                color,
+            })
+            .collect()
+    }
+
+    /// Semantic token colours of `path`, for the token types the theme has
+    /// a colour for.
+    pub fn get_highlights(&self, ctx: &egui::Context, path: &Path) -> Vec<Highlight> {
+        if !self.is_in_view(path) {
+            return vec![];
+        }
+        let Some((legend, _)) = lsp_manager()
+            .capabilities(path)
+            .and_then(|capabilities| semantic_tokens_support(&capabilities))
+        else {
+            return vec![];
+        };
+        let palette = theme_store().syntax_palette(ctx);
+        self.decorations
+            .borrow()
+            .semantic
+            .iter()
+            .filter(|token| !token.range.is_empty())
+            .filter_map(|token| {
+                let token_type = legend.token_type(token.token_type)?;
+                let modifiers = legend.modifiers(token.modifiers);
+                Some(Highlight {
+                    range: token.range.clone(),
+                    color: palette.semantic_color(token_type, &modifiers)?,
+                })
+            })
+            .collect()
+    }
+
+    fn is_in_view(&self, path: &Path) -> bool {
+        self.view
+            .borrow()
+            .as_ref()
+            .is_some_and(|view| view.path == path)
+    }
+
+    /// Forgets the decorations of the document in view.
+    fn clear(&self) {
+        let previous = std::mem::take(&mut *self.decorations.borrow_mut());
+        let view = self.view.borrow();
+        let Some(view) = view.as_ref() else {
+            return;
+        };
+        for request in [previous.hints_request, previous.tokens_request]
+            .into_iter()
+            .flatten()
+        {
+            lsp_manager().cancel(&view.path, request);
+        }
+    }
+
+    fn take_responses(&self) {
+        let mut decorations = self.decorations.borrow_mut();
+
+        if let Some(response) = decorations
+            .hints_request
+            .as_mut()
+            .and_then(|r| r.try_take())
+        {
+            decorations.hints_request = None;
+            match response {
+                Ok(result) => {
+                    decorations.hints = parse_inlay_hints(&result);
+                    decorations.resolved_for = None;
+                }
+                Err(e) => {
+                    if !is_cancelled(&e) {
+                        eprintln!("Inlay hints failed: {}", e);
+                    }
+                }
+            }
+        }
+
+        if let Some(response) = decorations
+            .tokens_request
+            .as_mut()
+            .and_then(|r| r.try_take())
+        {
+            decorations.tokens_request = None;
+            match response {
+                Ok(result) => {
+                    let tokens = apply_semantic_tokens(decorations.tokens.as_ref(), &result);
+                    if tokens.is_none() {
+                        // a delta we can't apply; ask for all tokens again
+                        decorations.tokens_for = None;
+                    }
+                    decorations.tokens = tokens;
+                    decorations.resolved_for = None;
+                }
+                Err(e) => {
+                    if !is_cancelled(&e) {
+                        eprintln!("Semantic tokens failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn decorations_store() -> std::cell::Ref<'static, DecorationsInteractionsStore> {
+    DecorationsInteractionsStore::instance()
+}
@@ -0,0 +1,3 @@
+pub mod decorations_interactions;
+
+pub use decorations_interactions::{DecorationsInteractionsStore, decorations_store};
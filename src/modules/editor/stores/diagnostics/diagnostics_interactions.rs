@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::core::plugins::lsp::protocol::{Diagnostic, Severity};
+use crate::store;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+store! {
+    pub struct DiagnosticsInteractionsStore {
+        // latest `publishDiagnostics` per file; files without problems are absent
+        diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new(),
+    }
+
+    set_diagnostics(&self, ctx: &egui::Context, path: PathBuf, diagnostics: Vec<Diagnostic>) {
+        let mut reactive = self.reactive(ctx);
+        let mut all = reactive.diagnostics();
+        if diagnostics.is_empty() {
+            all.remove(&path);
+        } else {
+            all.insert(path, diagnostics);
+        }
+    }
+}
+
+impl DiagnosticsInteractionsStore {
+    pub fn get_for_path(&self, path: &Path) -> Vec<Diagnostic> {
+        self.diagnostics
+            .borrow()
+            .get(path)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_counts(&self, path: &Path) -> DiagnosticCounts {
+        let diagnostics = self.diagnostics.borrow();
+        let mut counts = DiagnosticCounts::default();
+        for diagnostic in diagnostics.get(path).into_iter().flatten() {
+            match diagnostic.severity {
+                Severity::Error => counts.errors += 1,
+                Severity::Warning => counts.warnings += 1,
+                Severity::Information | Severity::Hint => {}
+            }
+        }
+        counts
+    }
+
+    /// Every diagnostic, by file path and then by severity and position.
+    pub fn get_all_sorted(&self) -> Vec<(PathBuf, Diagnostic)> {
+        let diagnostics = self.diagnostics.borrow();
+        let mut all: Vec<(PathBuf, Diagnostic)> = diagnostics
+            .iter()
+            .flat_map(|(path, list)| list.iter().map(|d| (path.clone(), d.clone())))
+            .collect();
+        all.sort_by(|(a_path, a), (b_path, b)| {
+            a_path
+                .cmp(b_path)
+                .then(a.severity.cmp(&b.severity))
+                .then(a.range.start.cmp(&b.range.start))
+        });
+        all
+    }
+}
+
+pub fn diagnostics_store() -> std::cell::Ref<'static, DiagnosticsInteractionsStore> {
+    DiagnosticsInteractionsStore::instance()
+}
@@ -0,0 +1,5 @@
+pub mod diagnostics_interactions;
+
+pub use diagnostics_interactions::{
+    DiagnosticCounts, DiagnosticsInteractionsStore, diagnostics_store,
+};
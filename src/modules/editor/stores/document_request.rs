@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+use serde_json::Value;
+
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::{LspClient, PendingRequest, RequestHandle, lsp_manager};
+
+/// A request about one revision of a document, sent once its text stayed
+/// put for a while, for what is derived from the whole document (the
+/// outline, the folding regions).
+///
+/// Call [`DocumentRequest::is_due`] every frame the result is out of date;
+/// once it says so, ask the server with [`DocumentRequest::send`] or work the
+/// result out locally.
+pub struct DocumentRequest {
+    /// Seconds the text has to stay put.
+    delay: f64,
+    pending: Option<Pending>,
+}
+
+struct Pending {
+    path: PathBuf,
+    revision: u64,
+    since: f64,
+    request: Option<RequestHandle>,
+}
+
+impl DocumentRequest {
+    pub fn new(delay: f64) -> Self {
+        DocumentRequest {
+            delay,
+            pending: None,
+        }
+    }
+
+    /// Whether the text at `revision` waited long enough and nothing is
+    /// asked for it yet. A new path or revision restarts the wait and cancels
+    /// the request of the old one; `shown` says whether something of the
+    /// path is shown meanwhile, as a freshly opened tab shouldn't wait.
+    pub fn is_due(&mut self, ctx: &egui::Context, path: &Path, revision: u64, shown: bool) -> bool {
+        let now = ctx.input(|i| i.time);
+        if self
+            .pending
+            .as_ref()
+            .is_none_or(|pending| pending.path != path || pending.revision != revision)
+        {
+            self.cancel();
+            self.pending = Some(Pending {
+                path: path.to_path_buf(),
+                revision,
+                since: if shown { now } else { now - self.delay },
+                request: None,
+            });
+        }
+        let pending = self.pending.as_mut().unwrap();
+        if pending.request.is_some() {
+            return false;
+        }
+        let waited = now - pending.since;
+        if waited < self.delay {
+            ctx.request_repaint_after(Duration::from_secs_f64(self.delay - waited));
+            return false;
+        }
+        true
+    }
+
+    /// Sends the due request to the server of its path; `false` when the
+    /// server isn't running yet, which restarts the wait before the next try.
+    pub fn send(
+        &mut self,
+        ctx: &egui::Context,
+        send: impl FnOnce(&LspClient) -> PendingRequest,
+    ) -> bool {
+        let Some(pending) = self.pending.as_mut() else {
+            return false;
+        };
+        pending.request = lsp_manager().request(ctx, &pending.path, send);
+        if pending.request.is_none() {
+            pending.since = ctx.input(|i| i.time);
+        }
+        pending.request.is_some()
+    }
+
+    /// The path, revision and result of the request, once its response
+    /// arrived. Failures are logged under `what`, except cancellations.
+    pub fn take_response(&mut self, what: &str) -> Option<(PathBuf, u64, Value)> {
+        let response = self.pending.as_mut()?.request.as_mut()?.try_take()?;
+        let pending = self.pending.take().unwrap();
+        match response {
+            Ok(result) => Some((pending.path, pending.revision, result)),
+            Err(e) => {
+                if !is_cancelled(&e) {
+                    eprintln!("{} failed: {}", what, e);
+                }
+                None
+            }
+        }
+    }
+
+    fn cancel(&mut self) {
+        if let Some(old) = self.pending.take()
+            && let Some(request) = old.request
+        {
+            lsp_manager().cancel(&old.path, request);
+        }
+    }
+}
@@ -1,17 +1,32 @@
 use std::cell::RefCell;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
 
 use eframe::egui;
 
+use crate::core::buffer::TextBuffer;
+use crate::core::languages::{Language, language_registry};
+use crate::core::lexer::Token;
+use crate::core::plugins::lsp::lsp_manager;
+use crate::core::plugins::lsp::protocol::{
+    Position, Range as LspRange, TextEdit, apply_text_edits, range_to_offsets, resolve_text_edits,
+};
+use crate::core::stores::global_store::global_store;
+use crate::core::ui::highlighter::Highlighting;
+use crate::modules::editor::stores::{file_finder_store, formatting_store, gutter_store};
 use crate::store;
 
 #[derive(Debug, Clone)]
 pub struct Tab {
     pub path: PathBuf,
-    pub content: Rc<RefCell<String>>,
-    pub original_content: String,
+    pub content: Rc<RefCell<TextBuffer>>,
     pub is_dirty: bool,
+    /// Language picked from `path` (or the `#!` line) when the tab was opened.
+    pub language: Option<Rc<Language>>,
+    /// Highlighting kept between frames, dropped with the tab.
+    pub highlighting: Rc<RefCell<Highlighting>>,
 }
 
 impl PartialEq for Tab {
@@ -22,10 +37,133 @@ impl PartialEq for Tab {
 
 impl Eq for Tab {}
 
+/// A file changed by a `WorkspaceEdit`, with its text before and after.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditedFile {
+    pub path: PathBuf,
+    pub edits: usize,
+    /// Whether it was changed in an open tab (unsaved) rather than on disk.
+    pub in_tab: bool,
+    pub before: Arc<str>,
+    pub after: Arc<str>,
+}
+
+/// Id of the text edit of the current tab.
+pub const CODE_EDITOR: &str = "code_editor";
+
+/// Workspace edits kept for undo; older ones are dropped.
+const MAX_WORKSPACE_STEPS: usize = 100;
+
+/// What [`EditorInteractionsStore::apply_workspace_edit`] changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedEdit {
+    /// The undo step of the workspace the edit makes, unless nothing changed.
+    pub step: Option<u64>,
+    pub files: Vec<EditedFile>,
+}
+
+/// A `WorkspaceEdit` as one undo step of the workspace: undoing it in any
+/// of its tabs takes it back in every file.
+pub struct WorkspaceStep {
+    id: u64,
+    files: Vec<StepFile>,
+}
+
+enum StepFile {
+    /// The undo step `step` of the text of a tab.
+    Tab {
+        path: PathBuf,
+        buffer: Weak<RefCell<TextBuffer>>,
+        step: u64,
+    },
+    /// A file rewritten on disk.
+    Disk {
+        path: PathBuf,
+        before: Arc<str>,
+        after: Arc<str>,
+    },
+}
+
+impl WorkspaceStep {
+    fn made(&self, buffer: &Rc<RefCell<TextBuffer>>, step: Option<u64>) -> bool {
+        self.files.iter().any(|file| match file {
+            StepFile::Tab {
+                buffer: made,
+                step: made_step,
+                ..
+            } => Some(*made_step) == step && std::ptr::eq(made.as_ptr(), Rc::as_ptr(buffer)),
+            StepFile::Disk { .. } => false,
+        })
+    }
+}
+
 store! {
     pub struct EditorInteractionsStore {
         tabs: Vec<Tab> = vec![],
         active_tab_index: Option<usize> = None,
+        // byte range the editor should select (and scroll to) on the next frame
+        pending_reveal: Option<Range<usize>> = None,
+        // whether the editor leaves the focus where it is for that reveal
+        reveal_keeps_focus: bool = false,
+        // folder the editor was opened in
+        root: PathBuf = PathBuf::from("."),
+        // workspace edits that can be undone, the last one on top
+        undo_steps: Vec<WorkspaceStep> = vec![],
+        // undone workspace edits that can be applied again
+        redo_steps: Vec<WorkspaceStep> = vec![],
+        next_step_id: u64 = 0,
+    }
+
+    // Cmd+Z (Cmd+Shift+Z, Cmd+Y) in a tab whose next undo (redo) step is
+    // part of a workspace edit undoes (redoes) the edit in every file;
+    // anything else is left to the editor's own undo
+    handle_keys(&self, ctx: &egui::Context) {
+        if !ctx.memory(|m| m.has_focus(egui::Id::new(CODE_EDITOR))) {
+            return;
+        }
+        let (undo, redo) = ctx.input(|i| {
+            let command = i.modifiers.command;
+            let shift = i.modifiers.shift;
+            (
+                command && !shift && i.key_pressed(egui::Key::Z),
+                command && (shift && i.key_pressed(egui::Key::Z) || i.key_pressed(egui::Key::Y)),
+            )
+        });
+        if !undo && !redo {
+            return;
+        }
+        let Some(buffer) = self.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+
+        if undo {
+            let step = buffer.borrow().next_undo();
+            let found = self
+                .undo_steps
+                .borrow()
+                .iter()
+                .find(|workspace_step| workspace_step.made(&buffer, step))
+                .map(|workspace_step| workspace_step.id);
+            if let Some(id) = found {
+                ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z));
+                self.undo_workspace_step(ctx, id);
+            }
+        } else {
+            let step = buffer.borrow().next_redo();
+            let found = self
+                .redo_steps
+                .borrow()
+                .iter()
+                .find(|workspace_step| workspace_step.made(&buffer, step))
+                .map(|workspace_step| workspace_step.id);
+            if let Some(id) = found {
+                ctx.input_mut(|i| {
+                    i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)
+                        || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+                });
+                self.redo_workspace_step(ctx, id);
+            }
+        }
     }
 
     open_tab(&self, ctx: &egui::Context, path: PathBuf) {
@@ -51,12 +189,15 @@ store! {
             if should_create_new {
                 println!("[DEBUG] Creating new tab (current tab is dirty)");
                 if let Ok(content) = std::fs::read_to_string(&path) {
-                    let original_content = content.clone();
+                    let language = language_registry().detect(&path, &content);
+                    let content = Rc::new(RefCell::new(TextBuffer::from(content)));
+                    lsp_manager().did_open(ctx, &path, language.as_ref(), &content);
                     let new_tab = Tab {
                         path: path.clone(),
-                        content: Rc::new(RefCell::new(content)),
-                        original_content,
+                        content,
                         is_dirty: false,
+                        language,
+                        highlighting: Rc::default(),
                     };
                     let mut reactive = self.reactive(ctx);
                     let mut tabs = reactive.tabs();
@@ -70,18 +211,23 @@ store! {
             } else {
                 println!("[DEBUG] Replacing or creating tab (current tab not dirty)");
                 if let Ok(content) = std::fs::read_to_string(&path) {
-                    let original_content = content.clone();
                     println!("[DEBUG] File read successfully, content length: {}", content.len());
+                    let language = language_registry().detect(&path, &content);
+                    let content = Rc::new(RefCell::new(TextBuffer::from(content)));
+                    lsp_manager().did_open(ctx, &path, language.as_ref(), &content);
                     let new_tab = Tab {
                         path: path.clone(),
-                        content: Rc::new(RefCell::new(content)),
-                        original_content,
+                        content,
                         is_dirty: false,
+                        language,
+                        highlighting: Rc::default(),
                     };
                     let mut reactive = self.reactive(ctx);
                     let mut tabs = reactive.tabs();
                     if let Some(active_idx) = *reactive.active_tab_index() {
                         if active_idx < tabs.len() {
+                            lsp_manager().did_close(&tabs[active_idx].path);
+                            gutter_store().forget(&tabs[active_idx].path);
                             tabs[active_idx] = new_tab;
                             println!("[DEBUG] Replaced tab at index: {}", active_idx);
                         } else {
@@ -107,7 +253,9 @@ store! {
         let mut tabs = reactive.tabs();
 
         if index < tabs.len() {
-            tabs.remove(index);
+            let tab = tabs.remove(index);
+            lsp_manager().did_close(&tab.path);
+            gutter_store().forget(&tab.path);
 
             if let Some(active_idx) = current_active {
                 if active_idx == index {
@@ -125,21 +273,42 @@ store! {
         }
     }
 
+    // with format on save, the formatting store writes the tab once the
+    // edits are in
     save_tab(&self, ctx: &egui::Context, index: usize) {
-        let tab_path_content = {
+        let Some(path) = self.tabs.borrow().get(index).map(|t| t.path.clone()) else {
+            return;
+        };
+        if global_store().get_format_on_save().get(ctx)
+            && formatting_store().format_and_save(ctx, &path)
+        {
+            return;
+        }
+        self.write_tab(ctx, &path);
+    }
+
+    // writes the tab of `path` to disk as it is
+    write_tab(&self, ctx: &egui::Context, path: &Path) {
+        let tab_index_content = {
             let tabs = self.tabs.borrow();
-            tabs.get(index).map(|t| (t.path.clone(), t.content.borrow().clone()))
+            tabs.iter()
+                .position(|t| t.path == path)
+                .map(|index| (index, tabs[index].content.clone()))
         };
 
-        if let Some((path, content)) = tab_path_content {
-            if let Err(e) = std::fs::write(&path, &content) {
+        if let Some((index, content)) = tab_index_content {
+            let mut content = content.borrow_mut();
+            if let Err(e) = std::fs::write(path, content.contents().as_bytes()) {
                 eprintln!("Failed to save file: {}", e);
             } else {
+                lsp_manager().did_save(path, &content);
+                content.mark_saved();
+                gutter_store().reload_head(ctx, path);
+                file_finder_store().file_written(path);
                 let mut reactive = self.reactive(ctx);
                 let mut tabs = reactive.tabs();
                 if let Some(tab) = tabs.get_mut(index) {
                     tab.is_dirty = false;
-                    tab.original_content = content;
                 }
             }
         }
@@ -152,57 +321,224 @@ store! {
         }
     }
 
+    // to call after the text of a tab changed: tells its language server and
+    // updates the dirty flag, which clears again once undo gets back to the
+    // saved text
     mark_tab_dirty(&self, ctx: &egui::Context, index: usize) {
-        println!("[DEBUG] mark_tab_dirty called for index: {}", index);
-        let mut reactive = self.reactive(ctx);
-        let mut tabs = reactive.tabs();
-        if let Some(tab) = tabs.get_mut(index) {
-            println!("[DEBUG] Setting tab.is_dirty = true for index: {}", index);
-            tab.is_dirty = true;
-        } else {
-            println!("[DEBUG] Tab at index {} not found!", index);
+        let is_dirty = {
+            let tabs = self.tabs.borrow();
+            let Some(tab) = tabs.get(index) else {
+                return;
+            };
+            let content = tab.content.borrow();
+            lsp_manager().did_change(&tab.path, &content);
+            content.is_dirty()
+        };
+        if self.tabs.borrow()[index].is_dirty != is_dirty {
+            self.reactive(ctx).tabs()[index].is_dirty = is_dirty;
         }
     }
 
     mark_current_tab_dirty(&self, ctx: &egui::Context) {
         let active_idx = *self.active_tab_index.borrow();
-        println!("[DEBUG] mark_current_tab_dirty called, active_idx: {:?}", active_idx);
-
         if let Some(idx) = active_idx {
-            let (current_content, original_content) = {
-                let tabs = self.tabs.borrow();
-                if let Some(tab) = tabs.get(idx) {
-                    let current = tab.content.borrow().clone();
-                    let original = tab.original_content.clone();
-                    println!("[DEBUG] Tab {} - current len: {}, original len: {}", idx, current.len(), original.len());
-                    (Some(current), Some(original))
-                } else {
-                    println!("[DEBUG] Tab at index {} not found!", idx);
-                    (None, None)
-                }
+            self.mark_tab_dirty(ctx, idx);
+        }
+    }
+
+    // replaces the text of a tab from outside the editor, e.g. with edits
+    // from a language server
+    set_tab_content(&self, ctx: &egui::Context, index: usize, content: &str) {
+        {
+            let tabs = self.tabs.borrow();
+            let Some(tab) = tabs.get(index) else {
+                return;
             };
+            // one undo step, however many edits it took
+            tab.content
+                .borrow_mut()
+                .transaction(|text| text.set_text(content));
+        }
+        self.mark_tab_dirty(ctx, index);
+    }
 
-            if let (Some(content), Some(original)) = (current_content, original_content) {
-                let is_different = content != original;
-                println!("[DEBUG] Content comparison - is_different: {}", is_different);
+    // like `set_tab_content`, for byte ranges of the text to replace, given
+    // back to front
+    replace_in_tab(
+        &self,
+        ctx: &egui::Context,
+        index: usize,
+        replacements: &[(Range<usize>, &str)],
+    ) {
+        {
+            let tabs = self.tabs.borrow();
+            let Some(tab) = tabs.get(index) else {
+                return;
+            };
+            tab.content.borrow_mut().transaction(|text| {
+                for (range, new_text) in replacements {
+                    text.replace(range.clone(), new_text);
+                }
+            });
+        }
+        self.mark_tab_dirty(ctx, index);
+    }
 
-                if is_different {
-                    println!("[DEBUG] Content changed! Marking tab {} as dirty", idx);
-                    self.mark_tab_dirty(ctx, idx);
-                } else {
-                    println!("[DEBUG] Content matches original, clearing dirty flag for tab {}", idx);
-                    let mut reactive = self.reactive(ctx);
-                    let mut tabs = reactive.tabs();
-                    if let Some(tab) = tabs.get_mut(idx) {
-                        tab.is_dirty = false;
+    // applies the edits of a `WorkspaceEdit`: open tabs are changed in place
+    // (and become dirty), other files are rewritten on disk; all of it is one
+    // undo step of the workspace
+    apply_workspace_edit(
+        &self,
+        ctx: &egui::Context,
+        changes: Vec<(PathBuf, Vec<TextEdit>)>,
+    ) -> AppliedEdit {
+        let mut files: Vec<EditedFile> = Vec::new();
+        let mut step_files: Vec<StepFile> = Vec::new();
+        for (path, edits) in changes {
+            if edits.is_empty() {
+                continue;
+            }
+            let index = self.tabs.borrow().iter().position(|tab| tab.path == path);
+            let (before, after): (Arc<str>, Arc<str>) = match index {
+                Some(index) => {
+                    let content = self.tabs.borrow()[index].content.clone();
+                    let before = content.borrow().contents();
+                    let revision = content.borrow().revision();
+                    let replacements = resolve_text_edits(&*content.borrow(), &edits);
+                    self.replace_in_tab(ctx, index, &replacements);
+                    let content_ref = content.borrow();
+                    if content_ref.revision() != revision
+                        && let Some(step) = content_ref.next_undo()
+                    {
+                        step_files.push(StepFile::Tab {
+                            path: path.clone(),
+                            buffer: Rc::downgrade(&content),
+                            step,
+                        });
                     }
+                    (before, content_ref.contents())
                 }
-            } else {
-                println!("[DEBUG] Failed to get content or original content");
+                None => {
+                    let before = match std::fs::read_to_string(&path) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            eprintln!("Failed to read {}: {}", path.display(), e);
+                            continue;
+                        }
+                    };
+                    let after = apply_text_edits(&before, &edits);
+                    if let Err(e) = std::fs::write(&path, &after) {
+                        eprintln!("Failed to write {}: {}", path.display(), e);
+                        continue;
+                    }
+                    let (before, after): (Arc<str>, Arc<str>) = (before.into(), after.into());
+                    step_files.push(StepFile::Disk {
+                        path: path.clone(),
+                        before: before.clone(),
+                        after: after.clone(),
+                    });
+                    (before, after)
+                }
+            };
+
+            // a file may come up more than once in `documentChanges`
+            match files.iter_mut().find(|file| file.path == path) {
+                Some(file) => {
+                    file.edits += edits.len();
+                    file.after = after;
+                }
+                None => files.push(EditedFile {
+                    path,
+                    edits: edits.len(),
+                    in_tab: index.is_some(),
+                    before,
+                    after,
+                }),
             }
-        } else {
-            println!("[DEBUG] No active tab index");
         }
+
+        if step_files.is_empty() {
+            return AppliedEdit { step: None, files };
+        }
+        let id = {
+            let mut next_step_id = self.next_step_id.borrow_mut();
+            *next_step_id += 1;
+            *next_step_id
+        };
+        let mut undo_steps = self.undo_steps.borrow_mut();
+        undo_steps.push(WorkspaceStep { id, files: step_files });
+        if undo_steps.len() > MAX_WORKSPACE_STEPS {
+            undo_steps.remove(0);
+        }
+        self.redo_steps.borrow_mut().clear();
+        AppliedEdit { step: Some(id), files }
+    }
+
+    // undoes the workspace edit `id` in every file that hasn't changed since
+    undo_workspace_step(&self, ctx: &egui::Context, id: u64) {
+        let step = {
+            let mut undo_steps = self.undo_steps.borrow_mut();
+            let Some(index) = undo_steps.iter().position(|step| step.id == id) else {
+                return;
+            };
+            undo_steps.remove(index)
+        };
+        for file in step.files.iter().rev() {
+            self.step_file(ctx, file, true);
+        }
+        self.redo_steps.borrow_mut().push(step);
+    }
+
+    // applies the undone workspace edit `id` again
+    redo_workspace_step(&self, ctx: &egui::Context, id: u64) {
+        let step = {
+            let mut redo_steps = self.redo_steps.borrow_mut();
+            let Some(index) = redo_steps.iter().position(|step| step.id == id) else {
+                return;
+            };
+            redo_steps.remove(index)
+        };
+        for file in &step.files {
+            self.step_file(ctx, file, false);
+        }
+        self.undo_steps.borrow_mut().push(step);
+    }
+
+    // opens `path` and moves the cursor to `position`
+    reveal_position(&self, ctx: &egui::Context, path: PathBuf, position: Position) {
+        self.reveal_range(ctx, path, LspRange { start: position, end: position });
+    }
+
+    // opens `path` and selects `range`
+    reveal_range(&self, ctx: &egui::Context, path: PathBuf, range: LspRange) {
+        self.open_tab(ctx, path.clone());
+
+        let offsets = {
+            let tabs = self.tabs.borrow();
+            tabs.iter()
+                .find(|t| t.path == path)
+                .map(|t| range_to_offsets(&*t.content.borrow(), range))
+        };
+        if let Some(offsets) = offsets {
+            let mut reactive = self.reactive(ctx);
+            *reactive.pending_reveal() = Some(offsets);
+            *reactive.reveal_keeps_focus() = false;
+        }
+    }
+
+    // selects `range` of the current tab on the next frame
+    select_range(&self, ctx: &egui::Context, range: Range<usize>) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.pending_reveal() = Some(range);
+        *reactive.reveal_keeps_focus() = false;
+    }
+
+    // like `select_range`, but the focus stays where it is, e.g. in the
+    // find bar
+    show_range(&self, ctx: &egui::Context, range: Range<usize>) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.pending_reveal() = Some(range);
+        *reactive.reveal_keeps_focus() = true;
     }
 
     set_active_tab(&self, ctx: &egui::Context, index: usize) {
@@ -215,6 +551,17 @@ store! {
 }
 
 impl EditorInteractionsStore {
+    pub fn set_root(&self, root: PathBuf) {
+        *self.root.borrow_mut() = root;
+    }
+
+    /// `path` relative to the workspace root, as panels list it.
+    pub fn display_path(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&*self.root.borrow())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+
     pub fn get_current_tab_path(&self, _ctx: &egui::Context) -> Option<PathBuf> {
         let active_idx = *self.active_tab_index.borrow();
         if let Some(idx) = active_idx {
@@ -225,7 +572,55 @@ impl EditorInteractionsStore {
         }
     }
 
-    pub fn get_current_tab_text_ref(&self, _ctx: &egui::Context) -> Option<Rc<RefCell<String>>> {
+    pub fn get_current_tab_language(&self, _ctx: &egui::Context) -> Option<Rc<Language>> {
+        let active_idx = *self.active_tab_index.borrow();
+        if let Some(idx) = active_idx {
+            let tabs = self.tabs.borrow();
+            tabs.get(idx).and_then(|t| t.language.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn get_current_tab_highlighting(
+        &self,
+        _ctx: &egui::Context,
+    ) -> Option<Rc<RefCell<Highlighting>>> {
+        let active_idx = *self.active_tab_index.borrow();
+        let tabs = self.tabs.borrow();
+        active_idx.and_then(|idx| tabs.get(idx)).map(|t| t.highlighting.clone())
+    }
+
+    /// `derive` run over the text of the current tab and its tokens, taken
+    /// from the highlighting of the tab so only the edits since it was last
+    /// drawn are lexed again. `None` for plain text.
+    pub fn with_current_tab_tokens<R>(
+        &self,
+        derive: impl FnOnce(&str, &[Token]) -> R,
+    ) -> Option<R> {
+        let active_idx = (*self.active_tab_index.borrow())?;
+        let tabs = self.tabs.borrow();
+        let tab = tabs.get(active_idx)?;
+        let language = tab.language.as_ref()?;
+        let text = tab.content.borrow();
+        let src = text.contents();
+        let mut highlighting = tab.highlighting.borrow_mut();
+        Some(derive(&src, highlighting.tokens(&text, &src, language)))
+    }
+
+    pub fn take_pending_reveal(&self) -> Option<Range<usize>> {
+        self.pending_reveal.borrow_mut().take()
+    }
+
+    /// Whether the reveal just taken should leave the focus where it is.
+    pub fn reveal_keeps_focus(&self) -> bool {
+        *self.reveal_keeps_focus.borrow()
+    }
+
+    pub fn get_current_tab_text_ref(
+        &self,
+        _ctx: &egui::Context,
+    ) -> Option<Rc<RefCell<TextBuffer>>> {
         let active_idx = *self.active_tab_index.borrow();
         if let Some(idx) = active_idx {
             let tabs = self.tabs.borrow();
@@ -234,6 +629,91 @@ impl EditorInteractionsStore {
             None
         }
     }
+
+    /// The text of the tab showing `path`, if one does.
+    /// Whether the workspace edit `id` is still there to undo.
+    pub fn can_undo_workspace_step(&self, id: u64) -> bool {
+        self.undo_steps.borrow().iter().any(|step| step.id == id)
+    }
+
+    pub fn get_tab_text_ref(&self, path: &Path) -> Option<Rc<RefCell<TextBuffer>>> {
+        let tabs = self.tabs.borrow();
+        tabs.iter().find(|t| t.path == path).map(|t| t.content.clone())
+    }
+
+    /// Takes `file` back to before its workspace edit (`undo`) or forward to
+    /// after it again, unless it changed since.
+    fn step_file(&self, ctx: &egui::Context, file: &StepFile, undo: bool) {
+        let action = if undo { "undoing" } else { "redoing" };
+        match file {
+            StepFile::Tab { path, buffer, step } => {
+                let index = buffer.upgrade().and_then(|buffer| {
+                    let tabs = self.tabs.borrow();
+                    tabs.iter()
+                        .position(|tab| Rc::ptr_eq(&tab.content, &buffer))
+                });
+                let Some(index) = index else {
+                    eprintln!("{} was closed, not {} it", path.display(), action);
+                    return;
+                };
+                let buffer = self.tabs.borrow()[index].content.clone();
+                let selection = {
+                    let mut text = buffer.borrow_mut();
+                    let next = if undo {
+                        text.next_undo()
+                    } else {
+                        text.next_redo()
+                    };
+                    if next != Some(*step) {
+                        eprintln!("{} changed since, not {} it", path.display(), action);
+                        return;
+                    }
+                    let selections = if undo { text.undo() } else { text.redo() };
+                    selections.map(|selections| selections.primary().range())
+                };
+                self.mark_tab_dirty(ctx, index);
+                if *self.active_tab_index.borrow() == Some(index)
+                    && let Some(range) = selection
+                {
+                    self.select_range(ctx, range);
+                }
+            }
+            StepFile::Disk {
+                path,
+                before,
+                after,
+            } => {
+                let (from, to) = if undo {
+                    (after, before)
+                } else {
+                    (before, after)
+                };
+                // the file may have been opened meanwhile
+                let tab = {
+                    let tabs = self.tabs.borrow();
+                    tabs.iter()
+                        .position(|tab| tab.path == *path)
+                        .map(|index| (index, tabs[index].content.borrow().contents()))
+                };
+                let current = match &tab {
+                    Some((_, content)) => Some(content.clone()),
+                    None => std::fs::read_to_string(path).ok().map(Arc::from),
+                };
+                if current.as_deref() != Some(&**from) {
+                    eprintln!("{} changed since, not {} it", path.display(), action);
+                    return;
+                }
+                match tab {
+                    Some((index, _)) => self.set_tab_content(ctx, index, to),
+                    None => {
+                        if let Err(e) = std::fs::write(path, to.as_bytes()) {
+                            eprintln!("Failed to write {}: {}", path.display(), e);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 pub fn editor_interactions_store() -> std::cell::Ref<'static, EditorInteractionsStore> {
@@ -1,3 +1,5 @@
 pub mod editor_interactions_store;
 
-pub use editor_interactions_store::{EditorInteractionsStore, Tab, editor_interactions_store};
+pub use editor_interactions_store::{
+    AppliedEdit, CODE_EDITOR, EditedFile, EditorInteractionsStore, Tab, editor_interactions_store,
+};
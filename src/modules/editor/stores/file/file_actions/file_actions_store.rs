@@ -2,12 +2,15 @@ use std::path::PathBuf;
 
 use eframe::egui;
 
+use crate::core::buffer::TextBuffer;
+use crate::modules::editor::stores::file_finder_store;
+
 use crate::store;
 
 store! {
     pub struct FileActionsStore {
         opened_file: Option<PathBuf> = None,
-        opened_text: String = String::new(),
+        opened_text: TextBuffer = TextBuffer::new(),
         is_dirty: bool = false,
     }
 
@@ -15,7 +18,7 @@ store! {
         if let Ok(text) = std::fs::read_to_string(&path) {
             let mut reactive = self.reactive(ctx);
             *reactive.opened_file() = Some(path);
-            *reactive.opened_text() = text;
+            *reactive.opened_text() = TextBuffer::from(text);
             *reactive.is_dirty() = false;
         }
     }
@@ -23,11 +26,13 @@ store! {
     save_file(&self, ctx: &egui::Context) {
         let path = self.opened_file.get(ctx);
         if let Some(path) = path.as_ref() {
-            let text = self.opened_text.get(ctx);
-            if let Err(e) = std::fs::write(path, text.as_str()) {
+            let result = std::fs::write(path, self.opened_text.borrow().to_string());
+            if let Err(e) = result {
                 eprintln!("Failed to save file: {}", e);
             } else {
+                file_finder_store().file_written(path);
                 let mut reactive = self.reactive(ctx);
+                reactive.opened_text().mark_saved();
                 *reactive.is_dirty() = false;
             }
         }
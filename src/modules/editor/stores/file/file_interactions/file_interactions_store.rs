@@ -1,4 +1,7 @@
+use crate::core::buffer::TextBuffer;
 use crate::core::enums::enums::UiAction;
+use crate::core::languages::language_registry;
+use crate::core::plugins::lsp::lsp_manager;
 use crate::modules::editor::stores::file::file_actions::file_actions_store;
 use crate::modules::editor::stores::{Tab, editor_interactions_store};
 use std::cell::RefCell;
@@ -50,13 +53,16 @@ store! {
             }
         }
 
+        let content = file_actions_store.opened_text.borrow().to_string();
+        let language = language_registry().detect(path, &content);
+        let content = Rc::new(RefCell::new(TextBuffer::from(content)));
+        lsp_manager().did_open(ctx, path, language.as_ref(), &content);
         let tab = Tab {
             path: path.clone(),
-            content: Rc::new(RefCell::new(
-                file_actions_store.opened_text.borrow().to_string(),
-            )),
-            original_content: String::new(),
+            language,
+            content,
             is_dirty: false,
+            highlighting: Rc::default(),
         };
 
         editor.tabs.borrow_mut().push(tab)
@@ -1,4 +1,5 @@
 use crate::core::{types::types::EntryRc, utils::utils::read_current_folder};
+use crate::modules::editor::stores::file_finder_store;
 use std::path::PathBuf;
 
 pub struct FileServicesStore;
@@ -25,11 +26,20 @@ impl FileServicesStore {
             std::fs::create_dir_all(parent)?;
         }
         std::fs::File::create(path)?;
+        file_finder_store().file_written(path);
         Ok(())
     }
 
     pub fn delete_file(&self, path: &PathBuf) -> Result<(), std::io::Error> {
-        std::fs::remove_file(path)
+        std::fs::remove_file(path)?;
+        file_finder_store().file_deleted(path);
+        Ok(())
+    }
+
+    pub fn rename_file(&self, from: &PathBuf, to: &PathBuf) -> Result<(), std::io::Error> {
+        std::fs::rename(from, to)?;
+        file_finder_store().file_renamed(from, to);
+        Ok(())
     }
 }
 
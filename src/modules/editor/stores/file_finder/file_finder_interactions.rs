@@ -0,0 +1,410 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::enums::enums::Hotkeys;
+use crate::core::search::{FileIndex, fuzzy_match_path, project_files, split_location};
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// More matches than anyone scrolls through; the rest is dropped.
+const MAX_RESULTS: usize = 200;
+/// Paths a scan sends at once.
+const SCAN_BATCH: usize = 512;
+/// Recently opened tabs remembered for ranking.
+const MAX_RECENT: usize = 50;
+/// Added to the score of the last opened tab, less for older ones.
+const BONUS_RECENT: i64 = 48;
+/// Lines of the selected file shown under the list.
+const PREVIEW_LINES: usize = 12;
+/// Files bigger than this aren't read for a preview.
+const MAX_PREVIEW_BYTES: u64 = 1 << 20;
+
+/// A path matching the query, with the chars that matched it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileResult {
+    pub path: String,
+    pub positions: Vec<usize>,
+    score: i64,
+}
+
+/// The indices of the paths matching a query, so a longer query only has
+/// to look through them.
+pub struct Narrowed {
+    query: String,
+    revision: u64,
+    indices: Vec<usize>,
+}
+
+/// Lines of the selected file around where it would open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePreview {
+    pub path: String,
+    /// Zero-based number and text of each line.
+    pub lines: Vec<(usize, String)>,
+    /// The line typed after the path, if any.
+    pub target: Option<usize>,
+}
+
+store! {
+    pub struct FileFinderInteractionsStore {
+        open: bool = false,
+        input: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        // select the input on the next frame
+        focus: bool = false,
+        index: FileIndex = FileIndex::default(),
+        scan: Option<Receiver<Vec<PathBuf>>> = None,
+        // paths of the tabs opened, the last one first
+        recent: Vec<String> = vec![],
+        // the query and index revision the results are for
+        ranked: Option<(String, u64)> = None,
+        narrowed: Option<Narrowed> = None,
+        results: Vec<FileResult> = vec![],
+        selected: usize = 0,
+        // the selection moved; the list scrolls to it
+        scroll_to_selected: bool = false,
+        preview: Option<FilePreview> = None,
+    }
+
+    // Cmd+P opens the overlay; the arrows move through the files and
+    // preview them, Enter opens one and Escape closes it
+    handle_keys(&self, ctx: &egui::Context) {
+        let toggled = ctx.input_mut(|i| {
+            // Cmd+Shift+P is a shortcut of its own
+            !i.modifiers.shift && i.consume_shortcut(&Hotkeys::FindFile.shortcut())
+        });
+        if toggled {
+            if *self.open.borrow() {
+                self.close(ctx);
+            } else {
+                self.open_finder(ctx);
+            }
+            return;
+        }
+        if !*self.open.borrow() {
+            return;
+        }
+
+        let consume = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        if consume(egui::Key::ArrowDown) {
+            self.move_selection(ctx, 1);
+        } else if consume(egui::Key::ArrowUp) {
+            self.move_selection(ctx, -1);
+        } else if consume(egui::Key::Enter) {
+            let selected = *self.selected.borrow();
+            self.open_result(ctx, selected);
+        } else if consume(egui::Key::Escape) {
+            self.close(ctx);
+        }
+    }
+
+    // takes the paths a scan found, remembers the tab shown and ranks the
+    // paths again when the query or the index changed; called once per
+    // frame
+    poll(&self, ctx: &egui::Context) {
+        self.take_scanned();
+        if let Some(path) = editor_interactions_store().get_current_tab_path(ctx) {
+            let mut index = self.index.borrow_mut();
+            index.insert(&path);
+            if let Some(relative) = index.relative(&path) {
+                let mut recent = self.recent.borrow_mut();
+                if recent.first() != Some(&relative) {
+                    recent.retain(|other| *other != relative);
+                    recent.insert(0, relative);
+                    recent.truncate(MAX_RECENT);
+                }
+            }
+        }
+        if !*self.open.borrow() {
+            return;
+        }
+
+        let input = self.input.borrow().borrow().to_string().trim().to_string();
+        let (query, _) = split_location(&input);
+        let revision = self.index.borrow().revision();
+        let up_to_date = self
+            .ranked
+            .borrow()
+            .as_ref()
+            .is_some_and(|(ranked, for_revision)| ranked == query && *for_revision == revision);
+        if !up_to_date {
+            let results = self.rank(query);
+            *self.ranked.borrow_mut() = Some((query.to_string(), revision));
+            let mut reactive = self.reactive(ctx);
+            *reactive.results() = results;
+            *reactive.selected() = 0;
+            *reactive.scroll_to_selected() = true;
+            drop(reactive);
+        }
+        self.update_preview(&input);
+    }
+
+    // opens the overlay, scanning the folder in the background the first
+    // time; the paths found so far can be searched meanwhile
+    open_finder(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = true;
+        *reactive.focus() = true;
+        drop(reactive);
+        if self.scan.borrow().is_some() || self.index.borrow().is_complete() {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let thread_ctx = ctx.clone();
+        let root = self.index.borrow().root().to_path_buf();
+        std::thread::spawn(move || {
+            let mut batch = Vec::with_capacity(SCAN_BATCH);
+            project_files(&root, |path| {
+                batch.push(path);
+                if batch.len() < SCAN_BATCH {
+                    return true;
+                }
+                let sent = sender.send(std::mem::take(&mut batch)).is_ok();
+                thread_ctx.request_repaint();
+                sent
+            });
+            let _ = sender.send(batch);
+            thread_ctx.request_repaint();
+        });
+        self.index.borrow_mut().begin_scan();
+        *self.scan.borrow_mut() = Some(receiver);
+    }
+
+    // opens the file of result `index`, at the line typed after it if any,
+    // and closes the overlay
+    open_result(&self, ctx: &egui::Context, index: usize) {
+        let Some(result) = self.results.borrow().get(index).cloned() else {
+            return;
+        };
+        let input = self.input.borrow().borrow().to_string().trim().to_string();
+        let (_, location) = split_location(&input);
+        let path = self.index.borrow().absolute(&result.path);
+        self.close(ctx);
+
+        let editor = editor_interactions_store();
+        editor.open_tab(ctx, path);
+        let Some((line, column)) = location else {
+            return;
+        };
+        if let Some(text) = editor.get_current_tab_text_ref(ctx) {
+            let mut text = text.borrow_mut();
+            let offset = text.line_col_to_byte(line, column);
+            text.unfold_around(offset..offset);
+            editor.select_range(ctx, offset..offset);
+        }
+    }
+
+    close(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = false;
+        *reactive.preview() = None;
+        *reactive.ranked() = None;
+        reactive.input().borrow_mut().set_text("");
+    }
+}
+
+impl FileFinderInteractionsStore {
+    pub fn set_root(&self, root: PathBuf) {
+        *self.index.borrow_mut() = FileIndex::new(root);
+    }
+
+    /// Indexes a file the editor saved or created.
+    pub fn file_written(&self, path: &Path) {
+        self.index.borrow_mut().insert(path);
+    }
+
+    /// Drops a file or folder the editor deleted from the index.
+    pub fn file_deleted(&self, path: &Path) {
+        self.index.borrow_mut().remove(path);
+    }
+
+    /// Moves a file or folder the editor renamed in the index.
+    pub fn file_renamed(&self, from: &Path, to: &Path) {
+        self.index.borrow_mut().rename(from, to);
+    }
+
+    /// The input and whether it should take focus, while the overlay is open.
+    pub fn get_input(&self) -> Option<(Rc<RefCell<TextBuffer>>, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let focus = std::mem::take(&mut *self.focus.borrow_mut());
+        Some((self.input.borrow().clone(), focus))
+    }
+
+    /// Matches, the selected one and whether to scroll to it; `None` while
+    /// the overlay is closed.
+    pub fn get_results(&self) -> Option<(Vec<FileResult>, usize, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let scroll = std::mem::take(&mut *self.scroll_to_selected.borrow_mut());
+        Some((
+            self.results.borrow().clone(),
+            *self.selected.borrow(),
+            scroll,
+        ))
+    }
+
+    pub fn get_preview(&self) -> Option<FilePreview> {
+        self.preview.borrow().clone()
+    }
+
+    /// How many files are indexed and whether a scan is still running.
+    pub fn get_index_size(&self) -> (usize, bool) {
+        let index = self.index.borrow();
+        (index.paths().len(), index.is_scanning())
+    }
+
+    fn move_selection(&self, ctx: &egui::Context, delta: isize) {
+        let count = self.results.borrow().len() as isize;
+        if count == 0 {
+            return;
+        }
+        let mut reactive = self.reactive(ctx);
+        let selected = *reactive.selected() as isize;
+        *reactive.selected() = (selected + delta).rem_euclid(count) as usize;
+        *reactive.scroll_to_selected() = true;
+    }
+
+    fn take_scanned(&self) {
+        let mut scan = self.scan.borrow_mut();
+        let Some(receiver) = scan.as_ref() else {
+            return;
+        };
+        let mut index = self.index.borrow_mut();
+        loop {
+            match receiver.try_recv() {
+                Ok(paths) => index.add_scanned(paths),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        index.end_scan();
+        *scan = None;
+    }
+
+    /// The best matches of `query` among the indexed paths. Recent tabs come
+    /// first for an empty query and score higher for others. When `query`
+    /// extends the one ranked before, only the paths that matched it are
+    /// looked through.
+    fn rank(&self, query: &str) -> Vec<FileResult> {
+        let index = self.index.borrow();
+        let paths = index.paths();
+        let recent = self.recent.borrow();
+        let recency = |path: &str| {
+            recent
+                .iter()
+                .position(|other| other == path)
+                .map_or(0, |age| {
+                    BONUS_RECENT * (MAX_RECENT - age) as i64 / MAX_RECENT as i64
+                })
+        };
+
+        let mut narrowed = self.narrowed.borrow_mut();
+        let candidates: Vec<usize> = match narrowed.as_ref() {
+            Some(before)
+                if before.revision == index.revision()
+                    && !before.query.is_empty()
+                    && query.starts_with(&before.query) =>
+            {
+                before.indices.clone()
+            }
+            _ => (0..paths.len()).collect(),
+        };
+        let mut matches: Vec<(usize, FileResult)> = candidates
+            .into_iter()
+            .filter_map(|i| {
+                let path = &paths[i];
+                let found = fuzzy_match_path(query, path)?;
+                Some((
+                    i,
+                    FileResult {
+                        path: path.clone(),
+                        positions: found.positions,
+                        score: found.score + recency(path),
+                    },
+                ))
+            })
+            .collect();
+        *narrowed = Some(Narrowed {
+            query: query.to_string(),
+            revision: index.revision(),
+            indices: matches.iter().map(|(i, _)| *i).collect(),
+        });
+
+        // better scores first, then shorter paths, then in path order
+        matches.sort_by(|(_, a), (_, b)| {
+            b.score
+                .cmp(&a.score)
+                .then(a.path.len().cmp(&b.path.len()))
+                .then(a.path.cmp(&b.path))
+        });
+        matches.truncate(MAX_RESULTS);
+        matches.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Reads the lines of the selected file around the line typed, unless
+    /// they are shown already. Open tabs are previewed as they are in the
+    /// editor.
+    fn update_preview(&self, input: &str) {
+        let (_, location) = split_location(input);
+        let target = location.map(|(line, _)| line);
+        let Some(path) = self
+            .results
+            .borrow()
+            .get(*self.selected.borrow())
+            .map(|result| result.path.clone())
+        else {
+            *self.preview.borrow_mut() = None;
+            return;
+        };
+        let shown = self
+            .preview
+            .borrow()
+            .as_ref()
+            .is_some_and(|preview| preview.path == path && preview.target == target);
+        if shown {
+            return;
+        }
+
+        let absolute = self.index.borrow().absolute(&path);
+        let tab_text = editor_interactions_store()
+            .tabs
+            .borrow()
+            .iter()
+            .find(|tab| tab.path == absolute)
+            .map(|tab| tab.content.borrow().to_string());
+        let text = tab_text.or_else(|| {
+            let size = std::fs::metadata(&absolute).ok()?.len();
+            (size <= MAX_PREVIEW_BYTES)
+                .then(|| std::fs::read_to_string(&absolute).ok())
+                .flatten()
+        });
+        // a few lines of context above the target
+        let first = target.map_or(0, |line| line.saturating_sub(PREVIEW_LINES / 3));
+        let lines = text
+            .map(|text| {
+                text.lines()
+                    .enumerate()
+                    .skip(first)
+                    .take(PREVIEW_LINES)
+                    .map(|(number, line)| (number, line.chars().take(160).collect()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        *self.preview.borrow_mut() = Some(FilePreview {
+            path,
+            lines,
+            target,
+        });
+    }
+}
+
+pub fn file_finder_store() -> std::cell::Ref<'static, FileFinderInteractionsStore> {
+    FileFinderInteractionsStore::instance()
+}
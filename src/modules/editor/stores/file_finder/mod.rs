@@ -0,0 +1,3 @@
+pub mod file_finder_interactions;
+
+pub use file_finder_interactions::{FileFinderInteractionsStore, file_finder_store};
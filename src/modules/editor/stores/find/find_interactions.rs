@@ -0,0 +1,431 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::enums::enums::Hotkeys;
+use crate::core::search::{Matcher, SearchQuery};
+use crate::core::ui::ui_kit::text_edit::Mark;
+use crate::modules::editor::stores::{editor_interactions_store, theme_store};
+use crate::store;
+
+/// Id of the query input of the find bar.
+pub const QUERY_INPUT: &str = "find_query";
+/// Id of the replacement input of the find bar.
+pub const REPLACE_INPUT: &str = "find_replace";
+
+/// The matches of the query in a tab, with the revision of the text they
+/// were found in.
+pub struct FindMatches {
+    path: PathBuf,
+    revision: u64,
+    query: SearchQuery,
+    matcher: Result<Matcher, regex::Error>,
+    ranges: Vec<Range<usize>>,
+}
+
+/// The toggles of the find bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FindOption {
+    CaseSensitive,
+    WholeWord,
+    Regex,
+    /// Replacements take the case of the text they replace.
+    PreserveCase,
+}
+
+#[derive(Clone, Copy)]
+enum FindCommand {
+    Open,
+    Close,
+    Next,
+    Previous,
+    ReplaceOne,
+    ReplaceAll,
+}
+
+store! {
+    pub struct FindInteractionsStore {
+        open: bool = false,
+        // select the query input on the next frame
+        focus: bool = false,
+        query: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        replacement: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        case_sensitive: bool = false,
+        whole_word: bool = false,
+        regex: bool = false,
+        preserve_case: bool = false,
+        matches: Option<FindMatches> = None,
+    }
+
+    // Cmd+F opens the bar; in it Enter and Shift+Enter go to the next and
+    // previous match, Enter in the replacement replaces the selected match
+    // and Cmd+Alt+Enter all of them, Escape closes it. Cmd+G and
+    // Cmd+Shift+G step through the matches from the editor too
+    handle_keys(&self, ctx: &egui::Context) {
+        let open = *self.open.borrow();
+        let (in_query, in_replace) = ctx.memory(|m| {
+            (
+                m.has_focus(egui::Id::new(QUERY_INPUT)),
+                m.has_focus(egui::Id::new(REPLACE_INPUT)),
+            )
+        });
+        let command = egui::Modifiers::COMMAND;
+        let shift = egui::Modifiers::SHIFT;
+        let none = egui::Modifiers::NONE;
+        let key_command = ctx.input_mut(|i| {
+            if i.consume_shortcut(&Hotkeys::FindText.shortcut()) {
+                Some(FindCommand::Open)
+            } else if !open {
+                None
+            } else if i.consume_key(command | shift, egui::Key::G)
+                || (in_query && i.consume_key(shift, egui::Key::Enter))
+            {
+                Some(FindCommand::Previous)
+            } else if i.consume_key(command, egui::Key::G)
+                || (in_query && i.consume_key(none, egui::Key::Enter))
+            {
+                Some(FindCommand::Next)
+            } else if in_replace && i.consume_key(command | egui::Modifiers::ALT, egui::Key::Enter) {
+                Some(FindCommand::ReplaceAll)
+            } else if in_replace && i.consume_key(none, egui::Key::Enter) {
+                Some(FindCommand::ReplaceOne)
+            } else if (in_query || in_replace) && i.consume_key(none, egui::Key::Escape) {
+                Some(FindCommand::Close)
+            } else {
+                None
+            }
+        });
+        match key_command {
+            Some(FindCommand::Open) => self.open_bar(ctx),
+            Some(FindCommand::Close) => self.close(ctx),
+            Some(FindCommand::Next) => self.step(ctx, true),
+            Some(FindCommand::Previous) => self.step(ctx, false),
+            Some(FindCommand::ReplaceOne) => self.replace_one(ctx),
+            Some(FindCommand::ReplaceAll) => self.replace_all(ctx),
+            None => {}
+        }
+    }
+
+    // finds the matches again when the text or the query changed, and
+    // selects the first one from the cursor as the query is typed; called
+    // once per frame
+    poll(&self, ctx: &egui::Context) {
+        if !*self.open.borrow() {
+            return;
+        }
+        let editor = editor_interactions_store();
+        let (Some(path), Some(text)) = (
+            editor.get_current_tab_path(ctx),
+            editor.get_current_tab_text_ref(ctx),
+        ) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        if !self.update_matches(&path, &text) {
+            return;
+        }
+        let cursor = text.selections().primary().range().start;
+        let first = self
+            .matches
+            .borrow()
+            .as_ref()
+            .and_then(|matches| matches.ranges.iter().find(|range| range.start >= cursor).cloned());
+        if let Some(range) = first {
+            self.select(ctx, &mut text, range);
+        }
+    }
+
+    // opens the bar with the query selected, taking the selected text as
+    // the query when it is on one line
+    open_bar(&self, ctx: &egui::Context) {
+        if let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) {
+            let text = text.borrow();
+            let selected = text.slice(text.selections().primary().range());
+            if !selected.is_empty() && !selected.contains('\n') {
+                self.query.borrow().borrow_mut().set_text(&selected);
+            }
+        }
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = true;
+        *reactive.focus() = true;
+    }
+
+    // closes the bar and gives the focus back to the editor
+    close(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = false;
+        *reactive.matches() = None;
+        drop(reactive);
+        let editor = editor_interactions_store();
+        if let Some(text) = editor.get_current_tab_text_ref(ctx) {
+            let selection = text.borrow().selections().primary().range();
+            editor.select_range(ctx, selection);
+        }
+    }
+
+    toggle(&self, ctx: &egui::Context, option: FindOption) {
+        let mut reactive = self.reactive(ctx);
+        let mut value = match option {
+            FindOption::CaseSensitive => reactive.case_sensitive(),
+            FindOption::WholeWord => reactive.whole_word(),
+            FindOption::Regex => reactive.regex(),
+            FindOption::PreserveCase => reactive.preserve_case(),
+        };
+        *value = !*value;
+    }
+
+    // selects the next match after the selection, or the previous one
+    // before it, wrapping around
+    step(&self, ctx: &egui::Context, forward: bool) {
+        let Some((path, text)) = self.current_tab(ctx) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        self.update_matches(&path, &text);
+        let selection = text.selections().primary().range();
+        let target = {
+            let matches = self.matches.borrow();
+            let Some(ranges) = matches.as_ref().map(|matches| &matches.ranges) else {
+                return;
+            };
+            if forward {
+                ranges
+                    .iter()
+                    .find(|range| range.start >= selection.end)
+                    .or(ranges.first())
+                    .cloned()
+            } else {
+                ranges
+                    .iter()
+                    .rev()
+                    .find(|range| range.end <= selection.start)
+                    .or(ranges.last())
+                    .cloned()
+            }
+        };
+        if let Some(range) = target {
+            self.select(ctx, &mut text, range);
+        }
+    }
+
+    // replaces the selected match and selects the next one; only selects
+    // the next one when no match is selected
+    replace_one(&self, ctx: &egui::Context) {
+        let Some((path, text_ref)) = self.current_tab(ctx) else {
+            return;
+        };
+        let replaced = {
+            let mut text = text_ref.borrow_mut();
+            self.update_matches(&path, &text);
+            let selection = text.selections().primary().range();
+            let matches = self.matches.borrow();
+            let replacement = matches.as_ref().and_then(|matches| {
+                let matcher = matches.matcher.as_ref().ok()?;
+                matches.ranges.contains(&selection).then(|| {
+                    matcher.replacement(
+                        &text.contents(),
+                        selection.clone(),
+                        &self.replacement.borrow().borrow().to_string(),
+                        *self.preserve_case.borrow(),
+                    )
+                })
+            });
+            drop(matches);
+            match replacement {
+                Some(replacement) => {
+                    text.transaction(|text| text.replace(selection.clone(), &replacement));
+                    let end = selection.start + replacement.len();
+                    text.set_selection(end..end);
+                    true
+                }
+                None => false,
+            }
+        };
+        if replaced {
+            editor_interactions_store().mark_current_tab_dirty(ctx);
+        }
+        self.step(ctx, true);
+    }
+
+    // replaces every match as one undo step
+    replace_all(&self, ctx: &egui::Context) {
+        let Some((path, text_ref)) = self.current_tab(ctx) else {
+            return;
+        };
+        {
+            let mut text = text_ref.borrow_mut();
+            self.update_matches(&path, &text);
+            let matches = self.matches.borrow();
+            let Some(matches) = matches.as_ref() else {
+                return;
+            };
+            let Ok(matcher) = matches.matcher.as_ref() else {
+                return;
+            };
+            if matches.ranges.is_empty() {
+                return;
+            }
+            let replacement = self.replacement.borrow().borrow().to_string();
+            let preserve = *self.preserve_case.borrow();
+            let all = text.contents();
+            let edits: Vec<(Range<usize>, String)> = matches
+                .ranges
+                .iter()
+                .map(|range| {
+                    let new = matcher.replacement(&all, range.clone(), &replacement, preserve);
+                    (range.clone(), new)
+                })
+                .collect();
+            // from the last one, so the ranges before stay where they are
+            text.transaction(|text| {
+                for (range, new) in edits.iter().rev() {
+                    text.replace(range.clone(), new);
+                }
+            });
+        }
+        editor_interactions_store().mark_current_tab_dirty(ctx);
+        ctx.request_repaint();
+    }
+}
+
+impl FindInteractionsStore {
+    /// The query input and whether it should take focus, while the bar is
+    /// open.
+    pub fn get_input(&self) -> Option<(Rc<RefCell<TextBuffer>>, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let focus = std::mem::take(&mut *self.focus.borrow_mut());
+        Some((self.query.borrow().clone(), focus))
+    }
+
+    pub fn get_replacement(&self) -> Rc<RefCell<TextBuffer>> {
+        self.replacement.borrow().clone()
+    }
+
+    pub fn is_on(&self, option: FindOption) -> bool {
+        let value = match option {
+            FindOption::CaseSensitive => &self.case_sensitive,
+            FindOption::WholeWord => &self.whole_word,
+            FindOption::Regex => &self.regex,
+            FindOption::PreserveCase => &self.preserve_case,
+        };
+        *value.borrow()
+    }
+
+    /// How many matches the current tab has and which of them is selected;
+    /// the message of an invalid regex as the error.
+    pub fn get_count(&self, ctx: &egui::Context) -> Result<(usize, Option<usize>), String> {
+        let Some((path, text)) = self.current_tab(ctx) else {
+            return Ok((0, None));
+        };
+        let text = text.borrow();
+        self.update_matches(&path, &text);
+        let matches = self.matches.borrow();
+        let Some(matches) = matches.as_ref() else {
+            return Ok((0, None));
+        };
+        if let Err(e) = &matches.matcher {
+            return Err(e.to_string());
+        }
+        let selection = text.selections().primary().range();
+        let current = matches.ranges.iter().position(|range| *range == selection);
+        Ok((matches.ranges.len(), current))
+    }
+
+    /// The matches in `text` to paint over it, the selected one stronger;
+    /// none while the bar is closed.
+    pub fn get_marks(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer) -> Vec<Mark> {
+        if !*self.open.borrow() {
+            return vec![];
+        }
+        self.update_matches(path, text);
+        let theme = theme_store();
+        let (color, current) = (
+            theme.search_match.get(ctx),
+            theme.search_match_current.get(ctx),
+        );
+        let selection = text.selections().primary().range();
+        self.matches
+            .borrow()
+            .as_ref()
+            .map(|matches| {
+                matches
+                    .ranges
+                    .iter()
+                    .map(|range| Mark {
+                        range: range.clone(),
+                        color: if *range == selection { current } else { color },
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn current_tab(&self, ctx: &egui::Context) -> Option<(PathBuf, Rc<RefCell<TextBuffer>>)> {
+        let editor = editor_interactions_store();
+        Some((
+            editor.get_current_tab_path(ctx)?,
+            editor.get_current_tab_text_ref(ctx)?,
+        ))
+    }
+
+    fn search_query(&self) -> SearchQuery {
+        SearchQuery {
+            text: self.query.borrow().borrow().to_string(),
+            case_sensitive: *self.case_sensitive.borrow(),
+            whole_word: *self.whole_word.borrow(),
+            regex: *self.regex.borrow(),
+        }
+    }
+
+    /// Finds the matches in `text` unless they are up to date; returns
+    /// whether the query changed since they were last found in it.
+    fn update_matches(&self, path: &Path, text: &TextBuffer) -> bool {
+        let query = self.search_query();
+
+        let mut matches = self.matches.borrow_mut();
+        let (up_to_date, query_changed) = match matches.as_ref() {
+            Some(matches) if matches.path == path => (
+                matches.revision == text.revision() && matches.query == query,
+                matches.query != query,
+            ),
+            // a tab switched to keeps its cursor
+            Some(_) => (false, false),
+            None => (false, true),
+        };
+        if !up_to_date {
+            let matcher = Matcher::new(&query);
+            let ranges = match &matcher {
+                // regexes want the text in one piece, which the buffer
+                // shares with the highlighter
+                Ok(matcher) => matcher.find_all(&text.contents()),
+                Err(_) => vec![],
+            };
+            *matches = Some(FindMatches {
+                path: path.to_path_buf(),
+                revision: text.revision(),
+                query,
+                matcher,
+                ranges,
+            });
+        }
+        query_changed
+    }
+
+    /// Selects `range`, unfolding what hides it, and scrolls to it without
+    /// taking the focus from the bar.
+    fn select(&self, ctx: &egui::Context, text: &mut TextBuffer, range: Range<usize>) {
+        text.unfold_around(range.clone());
+        text.set_selection(range.clone());
+        editor_interactions_store().show_range(ctx, range);
+    }
+}
+
+pub fn find_store() -> std::cell::Ref<'static, FindInteractionsStore> {
+    FindInteractionsStore::instance()
+}
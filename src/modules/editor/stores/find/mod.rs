@@ -0,0 +1,3 @@
+pub mod find_interactions;
+
+pub use find_interactions::{FindInteractionsStore, FindOption, find_store};
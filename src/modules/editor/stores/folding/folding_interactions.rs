@@ -0,0 +1,214 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::core::lexer::fold_ranges;
+use crate::core::plugins::lsp::folding_range::{
+    folding_range_params, folding_range_support, parse_folding_ranges,
+};
+use crate::core::plugins::lsp::lsp_manager;
+use crate::core::plugins::lsp::protocol::path_to_uri;
+use crate::modules::editor::stores::document_request::DocumentRequest;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// How long the text has to stay put before the regions are looked for again.
+const FOLDING_DELAY: f64 = 0.3;
+
+/// The regions of a document that can be folded, as the lines given to
+/// `TextBuffer::fold`.
+pub struct FoldRanges {
+    pub path: PathBuf,
+    revision: u64,
+    from_server: bool,
+    ranges: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Copy)]
+enum FoldCommand {
+    FoldAll,
+    UnfoldAll,
+    Fold,
+    Unfold,
+}
+
+store! {
+    pub struct FoldingInteractionsStore {
+        ranges: Option<FoldRanges> = None,
+        request: DocumentRequest = DocumentRequest::new(FOLDING_DELAY),
+        // Cmd+K was pressed; the next key finishes the chord
+        chord: bool = false,
+    }
+
+    // Cmd+Alt+[ folds the region around the cursor and Cmd+Alt+] unfolds
+    // it; Cmd+K Cmd+0 folds everything and Cmd+K Cmd+J unfolds everything
+    handle_keys(&self, ctx: &egui::Context) {
+        let command = egui::Modifiers::COMMAND;
+        let alt = command | egui::Modifiers::ALT;
+        let chord = *self.chord.borrow();
+        let (key_command, chord) = ctx.input_mut(|i| {
+            let key_command = if chord && i.consume_key(command, egui::Key::Num0) {
+                Some(FoldCommand::FoldAll)
+            } else if chord && i.consume_key(command, egui::Key::J) {
+                Some(FoldCommand::UnfoldAll)
+            } else if i.consume_key(alt, egui::Key::OpenBracket) {
+                Some(FoldCommand::Fold)
+            } else if i.consume_key(alt, egui::Key::CloseBracket) {
+                Some(FoldCommand::Unfold)
+            } else {
+                None
+            };
+            let started = i.consume_key(command, egui::Key::K);
+            // the chord waits for the next key, whatever it is
+            let pressed = i
+                .events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Key { pressed: true, .. }));
+            (key_command, started || (chord && key_command.is_none() && !pressed))
+        });
+        *self.chord.borrow_mut() = chord;
+        match key_command {
+            Some(FoldCommand::FoldAll) => self.fold_all(ctx),
+            Some(FoldCommand::UnfoldAll) => self.unfold_all(ctx),
+            Some(FoldCommand::Fold) => self.fold_at_cursor(ctx),
+            Some(FoldCommand::Unfold) => self.unfold_at_cursor(ctx),
+            None => {}
+        }
+    }
+
+    fold_all(&self, ctx: &egui::Context) {
+        let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        for range in self.get_ranges(ctx) {
+            text.fold(range);
+        }
+        ctx.request_repaint();
+    }
+
+    unfold_all(&self, ctx: &egui::Context) {
+        let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        text.borrow_mut().unfold_all();
+        ctx.request_repaint();
+    }
+
+    // finds the regions once the text settled; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        self.take_response(ctx);
+
+        let editor = editor_interactions_store();
+        let (Some(path), Some(text)) = (
+            editor.get_current_tab_path(ctx),
+            editor.get_current_tab_text_ref(ctx),
+        ) else {
+            return;
+        };
+        let text = text.borrow();
+        let revision = text.revision();
+
+        let server = lsp_manager()
+            .capabilities(&path)
+            .is_some_and(|capabilities| folding_range_support(&capabilities));
+        let up_to_date = self.ranges.borrow().as_ref().is_some_and(|ranges| {
+            ranges.path == path && ranges.revision == revision && (ranges.from_server || !server)
+        });
+        if up_to_date {
+            return;
+        }
+
+        // a freshly opened tab shouldn't wait for the text to settle
+        let shown = self
+            .ranges
+            .borrow()
+            .as_ref()
+            .is_some_and(|ranges| ranges.path == path);
+        let mut request = self.request.borrow_mut();
+        if !request.is_due(ctx, &path, revision, shown) {
+            return;
+        }
+        if server {
+            let params = folding_range_params(&path_to_uri(&path));
+            if request.send(ctx, |client| client.request("textDocument/foldingRange", params)) {
+                return;
+            }
+            // the server is busy starting; fold by the lexer meanwhile
+        }
+        let ranges = editor
+            .with_current_tab_tokens(fold_ranges)
+            .unwrap_or_default();
+        *self.reactive(ctx).ranges() = Some(FoldRanges {
+            path,
+            revision,
+            from_server: false,
+            ranges,
+        });
+    }
+}
+
+impl FoldingInteractionsStore {
+    /// The regions of the current tab that can be folded.
+    pub fn get_ranges(&self, ctx: &egui::Context) -> Vec<Range<usize>> {
+        let path = editor_interactions_store().get_current_tab_path(ctx);
+        path.map(|path| self.ranges_of(&path)).unwrap_or_default()
+    }
+
+    pub fn ranges_of(&self, path: &Path) -> Vec<Range<usize>> {
+        self.ranges
+            .borrow()
+            .as_ref()
+            .filter(|ranges| ranges.path == path)
+            .map(|ranges| ranges.ranges.clone())
+            .unwrap_or_default()
+    }
+
+    /// Folds the innermost region the cursor line starts or is inside of.
+    fn fold_at_cursor(&self, ctx: &egui::Context) {
+        let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        let line = text.byte_to_line(text.selections().primary().head);
+        let innermost = self
+            .get_ranges(ctx)
+            .into_iter()
+            .filter(|range| range.contains(&line) && !text.is_folded(range.clone()))
+            .max_by_key(|range| range.start);
+        if let Some(range) = innermost {
+            text.fold(range);
+        }
+    }
+
+    /// Unfolds the region folded after the cursor line.
+    fn unfold_at_cursor(&self, ctx: &egui::Context) {
+        let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        let line = text.byte_to_line(text.selections().primary().head);
+        for range in text.folded_lines() {
+            if range.start == line {
+                text.unfold(range);
+            }
+        }
+    }
+
+    fn take_response(&self, ctx: &egui::Context) {
+        let response = self.request.borrow_mut().take_response("Folding ranges");
+        if let Some((path, revision, result)) = response {
+            *self.reactive(ctx).ranges() = Some(FoldRanges {
+                path,
+                revision,
+                from_server: true,
+                ranges: parse_folding_ranges(&result),
+            });
+        }
+    }
+}
+
+pub fn folding_store() -> std::cell::Ref<'static, FoldingInteractionsStore> {
+    FoldingInteractionsStore::instance()
+}
@@ -0,0 +1,3 @@
+pub mod folding_interactions;
+
+pub use folding_interactions::{FoldingInteractionsStore, folding_store};
@@ -0,0 +1,266 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, TryRecvError};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::core::plugins::lsp::formatting::{
+    diff_edit, formatting_options, formatting_params, formatting_support, parse_text_edits,
+    range_formatting_params, rustfmt,
+};
+use crate::core::plugins::lsp::protocol::{TextEdit, offsets_to_range, path_to_uri};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// How long saving waits for the file to be formatted before writing it as
+/// it is.
+const FORMAT_ON_SAVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Indentation asked of the server.
+const TAB_SIZE: u32 = 4;
+
+/// A format of a tab under way.
+pub struct PendingFormat {
+    path: PathBuf,
+    /// Revision of the text the format is for; the edits are dropped if the
+    /// tab changed since.
+    revision: u64,
+    formatter: Formatter,
+    /// Input time until which a save waits for the edits; the tab is saved
+    /// once they are applied, or without them after that.
+    save_by: Option<f64>,
+}
+
+/// Where the edits of a format come from.
+enum Formatter {
+    /// The server was asked.
+    Server(RequestHandle),
+    /// `rustfmt` runs on a worker thread.
+    Rustfmt(mpsc::Receiver<anyhow::Result<Vec<TextEdit>>>),
+}
+
+impl Formatter {
+    /// The edits, once they are there.
+    fn try_take(&mut self) -> Option<anyhow::Result<Vec<TextEdit>>> {
+        match self {
+            Formatter::Server(request) => request
+                .try_take()
+                .map(|response| response.map(|result| parse_text_edits(&result))),
+            Formatter::Rustfmt(receiver) => match receiver.try_recv() {
+                Ok(edits) => Some(edits),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Err(anyhow::anyhow!("rustfmt stopped"))),
+            },
+        }
+    }
+}
+
+store! {
+    pub struct FormattingInteractionsStore {
+        // path and selection of the focused editor
+        selection: Option<(PathBuf, Range<usize>)> = None,
+        // formats under way, at most one per path
+        pending: Vec<PendingFormat> = vec![],
+    }
+
+    // called by the editor every frame while it has focus
+    on_cursor(&self, path: &Path, cursor: CursorInfo) {
+        let selection = cursor.offset.min(cursor.anchor)..cursor.offset.max(cursor.anchor);
+        *self.selection.borrow_mut() = Some((path.to_path_buf(), selection));
+    }
+
+    // Shift+Alt+F formats the selection, or the whole tab without one
+    handle_keys(&self, ctx: &egui::Context) {
+        let modifiers = egui::Modifiers::SHIFT | egui::Modifiers::ALT;
+        if ctx.input_mut(|i| i.consume_key(modifiers, egui::Key::F)) {
+            self.format_current_tab(ctx);
+        }
+    }
+
+    // picks up the edits of the formats under way, and saves the tabs they
+    // were for once formatted or waited for too long; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let mut finished = Vec::new();
+        {
+            let mut pending = self.pending.borrow_mut();
+            let mut index = 0;
+            while index < pending.len() {
+                let result = pending[index].formatter.try_take();
+                let save_by = pending[index].save_by;
+                match (result, save_by) {
+                    (Some(result), _) => finished.push((pending.remove(index), Some(result))),
+                    (None, Some(save_by)) if now >= save_by => {
+                        finished.push((pending.remove(index), None))
+                    }
+                    (None, save_by) => {
+                        if let Some(save_by) = save_by {
+                            ctx.request_repaint_after(Duration::from_secs_f64(save_by - now));
+                        }
+                        index += 1;
+                    }
+                }
+            }
+        }
+        for (format, result) in finished {
+            self.finish(ctx, format, result);
+        }
+    }
+
+    format_current_tab(&self, ctx: &egui::Context) {
+        let editor = editor_interactions_store();
+        let Some(path) = editor.get_current_tab_path(ctx) else {
+            return;
+        };
+        let selection = self
+            .selection
+            .borrow()
+            .as_ref()
+            .filter(|(selected, range)| *selected == path && !range.is_empty())
+            .map(|(_, range)| range.clone());
+
+        if !self.begin(ctx, &path, selection, false) {
+            println!("Nothing can format {}", path.display());
+        }
+    }
+}
+
+impl FormattingInteractionsStore {
+    /// Formats all of the tab at `path`, then saves it; without the edits
+    /// if they take longer than `FORMAT_ON_SAVE_TIMEOUT`. `false` if nothing
+    /// can format it, for the caller to save it right away.
+    pub fn format_and_save(&self, ctx: &egui::Context, path: &Path) -> bool {
+        self.begin(ctx, path, None, true)
+    }
+
+    /// Starts formatting the tab at `path` (just `selection` of it, where
+    /// supported); a format of it under way is dropped, though the tab is
+    /// still saved if that one was to save it.
+    fn begin(
+        &self,
+        ctx: &egui::Context,
+        path: &Path,
+        selection: Option<Range<usize>>,
+        save: bool,
+    ) -> bool {
+        let Some(text) = editor_interactions_store().get_tab_text_ref(path) else {
+            return false;
+        };
+        let (revision, text) = {
+            let text = text.borrow();
+            (text.revision(), text.contents())
+        };
+        let Some(formatter) = start(ctx, path, text, selection) else {
+            return false;
+        };
+
+        let now = ctx.input(|i| i.time);
+        let mut save_by = save.then(|| now + FORMAT_ON_SAVE_TIMEOUT.as_secs_f64());
+        let mut pending = self.pending.borrow_mut();
+        if let Some(index) = pending.iter().position(|format| format.path == path) {
+            let old = pending.remove(index);
+            if let Formatter::Server(request) = old.formatter {
+                lsp_manager().cancel(&old.path, request);
+            }
+            save_by = save_by.or(old.save_by);
+        }
+        pending.push(PendingFormat {
+            path: path.to_path_buf(),
+            revision,
+            formatter,
+            save_by,
+        });
+        true
+    }
+
+    /// Applies the edits of a finished format unless the tab changed since,
+    /// and saves the tab if the format was for a save. `result` is `None`
+    /// when the save didn't wait any longer.
+    fn finish(
+        &self,
+        ctx: &egui::Context,
+        format: PendingFormat,
+        result: Option<anyhow::Result<Vec<TextEdit>>>,
+    ) {
+        let editor = editor_interactions_store();
+        match result {
+            Some(Ok(edits)) => {
+                let unchanged = editor
+                    .get_tab_text_ref(&format.path)
+                    .is_some_and(|text| text.borrow().revision() == format.revision);
+                if unchanged {
+                    editor.apply_workspace_edit(ctx, vec![(format.path.clone(), edits)]);
+                } else {
+                    println!("{} changed while formatting", format.path.display());
+                }
+            }
+            Some(Err(e)) => eprintln!("Formatting failed: {:#}", e),
+            None => {
+                eprintln!("Formatting {} timed out", format.path.display());
+                if let Formatter::Server(request) = format.formatter {
+                    lsp_manager().cancel(&format.path, request);
+                }
+            }
+        }
+        if format.save_by.is_some() {
+            editor.write_tab(ctx, &format.path);
+        }
+    }
+}
+
+/// Asks the server to format `text`, the content of `path` (just
+/// `selection` of it, if given and supported), else runs `rustfmt` on a
+/// worker thread for Rust files.
+fn start(
+    ctx: &egui::Context,
+    path: &Path,
+    text: Arc<str>,
+    selection: Option<Range<usize>>,
+) -> Option<Formatter> {
+    if let Some(capabilities) = lsp_manager().capabilities(path) {
+        let uri = path_to_uri(path);
+        let options = formatting_options(TAB_SIZE, true);
+        let request = match selection {
+            Some(range) if formatting_support(&capabilities, true) => {
+                let params =
+                    range_formatting_params(&uri, offsets_to_range(&*text, range), options);
+                lsp_manager().request(ctx, path, |client| {
+                    client.request("textDocument/rangeFormatting", params)
+                })
+            }
+            _ if formatting_support(&capabilities, false) => {
+                let params = formatting_params(&uri, options);
+                lsp_manager().request(ctx, path, |client| {
+                    client.request("textDocument/formatting", params)
+                })
+            }
+            _ => None,
+        };
+        if let Some(request) = request {
+            return Some(Formatter::Server(request));
+        }
+    }
+
+    if path.extension().is_some_and(|extension| extension == "rs") {
+        let (sender, receiver) = mpsc::channel();
+        let thread_path = path.to_path_buf();
+        let thread_ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let edits = rustfmt(&thread_path, &text)
+                .map(|formatted| diff_edit(&text, &formatted).into_iter().collect())
+                .map_err(|e| e.context("rustfmt failed"));
+            let _ = sender.send(edits);
+            thread_ctx.request_repaint();
+        });
+        return Some(Formatter::Rustfmt(receiver));
+    }
+    None
+}
+
+pub fn formatting_store() -> std::cell::Ref<'static, FormattingInteractionsStore> {
+    FormattingInteractionsStore::instance()
+}
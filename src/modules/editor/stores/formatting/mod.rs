@@ -0,0 +1,3 @@
+pub mod formatting_interactions;
+
+pub use formatting_interactions::{FormattingInteractionsStore, formatting_store};
@@ -0,0 +1,199 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::git::{self, LineChange, line_changes};
+use crate::core::stores::global_store::global_store;
+use crate::core::ui::ui_kit::{Fold, Gutter};
+use crate::modules::editor::stores::{folding_store, theme_store};
+use crate::store;
+
+/// The text of a file in the last commit, read by `git` on a thread of its own.
+pub struct HeadLoad {
+    path: PathBuf,
+    receiver: Receiver<Option<String>>,
+}
+
+/// Changes of a file against its head, computed on a thread of its own.
+pub struct DiffJob {
+    path: PathBuf,
+    revision: u64,
+    receiver: Receiver<Vec<LineChange>>,
+}
+
+store! {
+    pub struct GutterInteractionsStore {
+        breakpoints: HashMap<PathBuf, BTreeSet<usize>> = HashMap::new(),
+        // text of each file in the last commit; `None` when it isn't tracked
+        heads: HashMap<PathBuf, Option<Arc<str>>> = HashMap::new(),
+        loading: Vec<HeadLoad> = Vec::new(),
+        // changes against the head, with the revision of the text they were
+        // computed from
+        changes: HashMap<PathBuf, (u64, Rc<[LineChange]>)> = HashMap::new(),
+        // at most one per file; shown changes stay those of an older revision
+        // until it is done
+        diffing: Vec<DiffJob> = Vec::new(),
+    }
+
+    // takes the head texts `git` has read and the changes diffed against them
+    poll(&self, ctx: &egui::Context) {
+        let mut diffed = vec![];
+        self.diffing.borrow_mut().retain(|job| match job.receiver.try_recv() {
+            Ok(changes) => {
+                diffed.push((job.path.clone(), (job.revision, changes.into())));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        if !diffed.is_empty() {
+            self.reactive(ctx).changes().extend(diffed);
+        }
+
+        let mut loaded = vec![];
+        self.loading.borrow_mut().retain(|load| match load.receiver.try_recv() {
+            Ok(text) => {
+                loaded.push((load.path.clone(), text));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        if loaded.is_empty() {
+            return;
+        }
+        let mut reactive = self.reactive(ctx);
+        for (path, text) in loaded {
+            self.changes.borrow_mut().remove(&path);
+            // diffed against the head being replaced
+            self.diffing.borrow_mut().retain(|job| job.path != path);
+            reactive.heads().insert(path, text.map(Arc::from));
+        }
+    }
+
+    toggle_breakpoint(&self, ctx: &egui::Context, path: &Path, line: usize) {
+        let mut reactive = self.reactive(ctx);
+        let mut breakpoints = reactive.breakpoints();
+        let lines = breakpoints.entry(path.to_path_buf()).or_default();
+        if !lines.remove(&line) {
+            lines.insert(line);
+        }
+    }
+
+    // reads the file from the last commit again, e.g. after a save, as it may
+    // have been committed meanwhile
+    reload_head(&self, ctx: &egui::Context, path: &Path) {
+        let (sender, receiver) = mpsc::channel();
+        let thread_path = path.to_path_buf();
+        let thread_ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(git::head_text(&thread_path));
+            thread_ctx.request_repaint();
+        });
+        let mut loading = self.loading.borrow_mut();
+        loading.retain(|load| load.path != path);
+        loading.push(HeadLoad {
+            path: path.to_path_buf(),
+            receiver,
+        });
+    }
+
+    // everything the gutter of `path` shows
+    gutter(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer) -> Gutter {
+        let breakpoints = self
+            .breakpoints
+            .borrow()
+            .get(path)
+            .map(|lines| lines.iter().copied().collect())
+            .unwrap_or_default();
+        let click_ctx = ctx.clone();
+        let click_path = path.to_path_buf();
+        Gutter {
+            relative: global_store().get_relative_line_numbers().get(ctx),
+            breakpoints,
+            folds: folds(path, text),
+            changes: self.changes(ctx, path, text),
+            colors: theme_store().gutter_colors(ctx),
+            on_breakpoint: Some(Rc::new(move |line| {
+                gutter_store().toggle_breakpoint(&click_ctx, &click_path, line)
+            })),
+        }
+    }
+}
+
+impl GutterInteractionsStore {
+    /// Forgets the head and changes of a file whose tab was closed;
+    /// breakpoints stay.
+    pub fn forget(&self, path: &Path) {
+        self.heads.borrow_mut().remove(path);
+        self.changes.borrow_mut().remove(path);
+        self.loading.borrow_mut().retain(|load| load.path != path);
+        self.diffing.borrow_mut().retain(|job| job.path != path);
+    }
+
+    fn changes(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer) -> Rc<[LineChange]> {
+        let shown = match self.changes.borrow().get(path) {
+            Some((revision, changes)) if *revision == text.revision() => return changes.clone(),
+            Some((_, changes)) => changes.clone(),
+            None => Rc::default(),
+        };
+        let mut diffing = self.diffing.borrow_mut();
+        if diffing.iter().any(|job| job.path == path) {
+            return shown;
+        }
+        let head = match self.heads.borrow().get(path) {
+            Some(Some(head)) => head.clone(),
+            Some(None) => return Rc::default(),
+            None => {
+                if !self.loading.borrow().iter().any(|load| load.path == path) {
+                    self.reload_head(ctx, path);
+                }
+                return Rc::default();
+            }
+        };
+        let (sender, receiver) = mpsc::channel();
+        let new = text.contents();
+        let thread_ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(line_changes(&head, &new));
+            thread_ctx.request_repaint();
+        });
+        diffing.push(DiffJob {
+            path: path.to_path_buf(),
+            revision: text.revision(),
+            receiver,
+        });
+        shown
+    }
+}
+
+/// The regions that can be folded and the ones folded, which need not be
+/// among them, e.g. when the server found other regions since.
+fn folds(path: &Path, text: &TextBuffer) -> Vec<Fold> {
+    let mut folds: Vec<Fold> = folding_store()
+        .ranges_of(path)
+        .into_iter()
+        .map(|lines| Fold {
+            folded: text.is_folded(lines.clone()),
+            lines,
+        })
+        .collect();
+    for lines in text.folded_lines() {
+        if !folds.iter().any(|fold| fold.lines == lines) {
+            folds.push(Fold {
+                lines,
+                folded: true,
+            });
+        }
+    }
+    folds
+}
+
+pub fn gutter_store() -> std::cell::Ref<'static, GutterInteractionsStore> {
+    GutterInteractionsStore::instance()
+}
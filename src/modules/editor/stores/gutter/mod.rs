@@ -0,0 +1,3 @@
+pub mod gutter_interactions;
+
+pub use gutter_interactions::{GutterInteractionsStore, gutter_store};
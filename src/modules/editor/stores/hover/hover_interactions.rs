@@ -0,0 +1,143 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::hover::parse_hover;
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::protocol::{
+    Position, offset_to_position, path_to_uri, text_document_position,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::HoverInfo;
+use crate::store;
+
+/// How long the pointer has to rest on a word before the server is asked.
+const HOVER_DELAY: f64 = 0.4;
+
+/// Id of the tooltip's popup; the tooltip stays open while it is hovered.
+pub const TOOLTIP_ID: &str = "hover";
+
+/// The word under the pointer, from the moment the pointer rests on it
+/// until the pointer leaves it.
+pub struct HoverState {
+    pub path: PathBuf,
+    word: Range<usize>,
+    position: Position,
+    /// Screen rectangle of the hovered character; the tooltip opens below it.
+    pub anchor: egui::Rect,
+    since: f64,
+    sent: bool,
+    request: Option<RequestHandle>,
+    /// Markdown to show, once the server answered.
+    pub contents: Option<String>,
+}
+
+store! {
+    pub struct HoverInteractionsStore {
+        hover: Option<HoverState> = None,
+    }
+
+    // called by the editor every frame with the character under the pointer
+    on_hover(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, hover: Option<HoverInfo>) {
+        let word = hover
+            .map(|hover| text.word_at(hover.offset))
+            .filter(|word| !word.is_empty());
+
+        let same_word = match (&*self.hover.borrow(), &word) {
+            (Some(state), Some(word)) => state.path == path && state.word == *word,
+            _ => false,
+        };
+        if same_word || (word.is_none() && is_pointer_over_tooltip(ctx)) {
+            return;
+        }
+
+        self.clear(ctx);
+        if let (Some(hover), Some(word)) = (hover, word) {
+            *self.hover.borrow_mut() = Some(HoverState {
+                path: path.to_path_buf(),
+                position: offset_to_position(text, word.start),
+                word,
+                anchor: hover.rect,
+                since: ctx.input(|i| i.time),
+                sent: false,
+                request: None,
+                contents: None,
+            });
+            ctx.request_repaint_after(Duration::from_secs_f64(HOVER_DELAY));
+        }
+    }
+
+    // sends the request once the pointer rested long enough and picks up
+    // the response; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        let mut hover = self.hover.borrow_mut();
+        let Some(state) = hover.as_mut() else {
+            return;
+        };
+
+        if !state.sent {
+            let waited = ctx.input(|i| i.time) - state.since;
+            if waited < HOVER_DELAY {
+                ctx.request_repaint_after(Duration::from_secs_f64(HOVER_DELAY - waited));
+                return;
+            }
+            state.sent = true;
+            let params = text_document_position(&path_to_uri(&state.path), state.position);
+            state.request = lsp_manager().request(ctx, &state.path, |client| {
+                client.request("textDocument/hover", params)
+            });
+            return;
+        }
+
+        let Some(response) = state.request.as_mut().and_then(|r| r.try_take()) else {
+            return;
+        };
+        state.request = None;
+        match response {
+            Ok(result) => {
+                state.contents = parse_hover(&result);
+                ctx.request_repaint();
+            }
+            Err(e) => {
+                if !is_cancelled(&e) {
+                    eprintln!("Hover failed: {}", e);
+                }
+            }
+        }
+    }
+
+    // hides the tooltip and cancels a request still in flight
+    clear(&self, ctx: &egui::Context) {
+        if self.hover.borrow().is_none() {
+            return;
+        }
+        let Some(mut state) = self.reactive(ctx).hover().take() else {
+            return;
+        };
+        if let Some(request) = state.request.take() {
+            lsp_manager().cancel(&state.path, request);
+        }
+    }
+}
+
+impl HoverInteractionsStore {
+    /// Where to show the tooltip and what it says.
+    pub fn get_tooltip(&self) -> Option<(egui::Rect, String)> {
+        let hover = self.hover.borrow();
+        let state = hover.as_ref()?;
+        Some((state.anchor, state.contents.clone()?))
+    }
+}
+
+fn is_pointer_over_tooltip(ctx: &egui::Context) -> bool {
+    ctx.pointer_hover_pos()
+        .and_then(|pos| ctx.layer_id_at(pos))
+        .is_some_and(|layer| layer.id == egui::Id::new(TOOLTIP_ID))
+}
+
+pub fn hover_store() -> std::cell::Ref<'static, HoverInteractionsStore> {
+    HoverInteractionsStore::instance()
+}
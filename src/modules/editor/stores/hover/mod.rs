@@ -0,0 +1,3 @@
+pub mod hover_interactions;
+
+pub use hover_interactions::{HoverInteractionsStore, HoverState, TOOLTIP_ID, hover_store};
@@ -1,14 +1,47 @@
+pub mod code_actions;
+pub mod completion;
 pub mod context;
+pub mod decorations;
+pub mod diagnostics;
+pub mod document_request;
 pub mod editor;
 pub mod file;
+pub mod file_finder;
+pub mod find;
+pub mod folding;
+pub mod formatting;
+pub mod gutter;
 pub mod hotkeys;
+pub mod hover;
+pub mod navigation;
+pub mod outline;
+pub mod project_search;
+pub mod rename;
+pub mod signature_help;
 pub mod theme;
+pub mod workspace_symbols;
 
+pub use code_actions::{CodeActionsInteractionsStore, code_actions_store};
+pub use completion::{CompletionInteractionsStore, completion_store};
+pub use decorations::{DecorationsInteractionsStore, decorations_store};
+pub use diagnostics::{DiagnosticsInteractionsStore, diagnostics_store};
 pub use editor::editor_interactions::{EditorInteractionsStore, Tab, editor_interactions_store};
 pub use file::file_actions::FileActionsStore;
 pub use file::file_interactions::FileInteractionsStore;
 pub use file::file_services::FileServicesStore;
+pub use file_finder::{FileFinderInteractionsStore, file_finder_store};
+pub use find::{FindInteractionsStore, FindOption, find_store};
+pub use folding::{FoldingInteractionsStore, folding_store};
+pub use formatting::{FormattingInteractionsStore, formatting_store};
+pub use gutter::{GutterInteractionsStore, gutter_store};
+pub use hover::{HoverInteractionsStore, hover_store};
+pub use navigation::{NavigationInteractionsStore, navigation_store};
+pub use outline::{OutlineInteractionsStore, outline_store};
+pub use project_search::{ProjectSearchInteractionsStore, project_search_store};
+pub use rename::{RenameInteractionsStore, rename_store};
+pub use signature_help::{SignatureHelpInteractionsStore, signature_help_store};
 pub use theme::{ThemeInteractionsStore, theme_store};
+pub use workspace_symbols::{WorkspaceSymbolsInteractionsStore, workspace_symbols_store};
 
 use crate::core::stores::icons::IconsInteractionsStore;
 use std::cell::RefCell;
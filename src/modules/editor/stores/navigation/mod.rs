@@ -0,0 +1,5 @@
+pub mod navigation_interactions;
+
+pub use navigation_interactions::{
+    LocationEntry, LocationsKind, LocationsPanel, NavigationInteractionsStore, navigation_store,
+};
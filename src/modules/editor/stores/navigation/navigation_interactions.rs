@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::protocol::{
+    Location, offset_to_position, parse_locations, path_to_uri, position_to_offset,
+    text_document_position,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocationsKind {
+    Definitions,
+    References,
+}
+
+/// A location with the line it points at, for the locations panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationEntry {
+    pub location: Location,
+    pub preview: String,
+}
+
+/// Results listed in the bottom panel, grouped by file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationsPanel {
+    pub kind: LocationsKind,
+    /// The identifier the request was made for.
+    pub symbol: String,
+    pub groups: Vec<(PathBuf, Vec<LocationEntry>)>,
+}
+
+impl LocationsPanel {
+    pub fn result_count(&self) -> usize {
+        self.groups.iter().map(|(_, entries)| entries.len()).sum()
+    }
+}
+
+/// A pending `textDocument/definition` or `textDocument/references`.
+pub struct NavigationRequest {
+    kind: LocationsKind,
+    symbol: String,
+    handle: RequestHandle,
+}
+
+store! {
+    pub struct NavigationInteractionsStore {
+        // where the cursor of the focused editor was last seen
+        cursor: Option<(PathBuf, usize)> = None,
+        request: Option<NavigationRequest> = None,
+        panel: Option<LocationsPanel> = None,
+    }
+
+    // called by the editor every frame while it has focus; Cmd+click jumps
+    on_cursor(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, cursor: CursorInfo) {
+        *self.cursor.borrow_mut() = Some((path.to_path_buf(), cursor.offset));
+
+        if ctx.input(|i| i.modifiers.command && i.pointer.primary_clicked()) {
+            self.send(ctx, LocationsKind::Definitions, path, text, cursor.offset);
+        }
+    }
+
+    // F12: go to definition, Shift+F12: find references
+    handle_keys(&self, ctx: &egui::Context) {
+        let kind = ctx.input_mut(|i| {
+            if i.consume_key(egui::Modifiers::SHIFT, egui::Key::F12) {
+                Some(LocationsKind::References)
+            } else if i.consume_key(egui::Modifiers::NONE, egui::Key::F12) {
+                Some(LocationsKind::Definitions)
+            } else {
+                None
+            }
+        });
+        let Some(kind) = kind else {
+            return;
+        };
+
+        let editor = editor_interactions_store();
+        let cursor = self.cursor.borrow().clone();
+        if let Some((path, offset)) = cursor
+            && editor.get_current_tab_path(ctx).as_ref() == Some(&path)
+            && let Some(text) = editor.get_current_tab_text_ref(ctx)
+        {
+            let text = text.borrow().clone();
+            self.send(ctx, kind, &path, &text, offset);
+        }
+    }
+
+    // picks up the server's response; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        let response = match self.request.borrow_mut().as_mut() {
+            Some(request) => request.handle.try_take(),
+            None => None,
+        };
+        let Some(response) = response else {
+            return;
+        };
+        let Some(request) = self.request.borrow_mut().take() else {
+            return;
+        };
+
+        let locations = match response {
+            Ok(result) => parse_locations(&result),
+            Err(e) => {
+                eprintln!("Navigation request failed: {}", e);
+                return;
+            }
+        };
+
+        match (request.kind, locations.as_slice()) {
+            (LocationsKind::Definitions, []) => {
+                println!("No definition found for `{}`", request.symbol);
+            }
+            (LocationsKind::Definitions, [location]) => {
+                editor_interactions_store().reveal_range(ctx, location.path.clone(), location.range);
+            }
+            _ => {
+                let mut reactive = self.reactive(ctx);
+                *reactive.panel() = Some(LocationsPanel {
+                    kind: request.kind,
+                    symbol: request.symbol,
+                    groups: group_by_file(locations),
+                });
+            }
+        }
+    }
+
+    close_panel(&self, ctx: &egui::Context) {
+        *self.reactive(ctx).panel() = None;
+    }
+}
+
+impl NavigationInteractionsStore {
+    pub fn get_panel(&self) -> Option<LocationsPanel> {
+        self.panel.borrow().clone()
+    }
+
+    fn send(
+        &self,
+        ctx: &egui::Context,
+        kind: LocationsKind,
+        path: &Path,
+        text: &TextBuffer,
+        offset: usize,
+    ) {
+        let word = text.word_at(offset);
+        if word.is_empty() {
+            return;
+        }
+
+        let mut params =
+            text_document_position(&path_to_uri(path), offset_to_position(text, offset));
+        let method = match kind {
+            LocationsKind::Definitions => "textDocument/definition",
+            LocationsKind::References => {
+                params["context"] = serde_json::json!({ "includeDeclaration": true });
+                "textDocument/references"
+            }
+        };
+
+        // a newer request replaces a pending one
+        if let Some(handle) =
+            lsp_manager().request(ctx, path, |client| client.request(method, params))
+        {
+            *self.request.borrow_mut() = Some(NavigationRequest {
+                kind,
+                symbol: text.slice(word).into_owned(),
+                handle,
+            });
+        }
+    }
+}
+
+/// Sorts `locations` by file and position and adds the line each one points at.
+fn group_by_file(mut locations: Vec<Location>) -> Vec<(PathBuf, Vec<LocationEntry>)> {
+    locations.sort_by(|a, b| a.path.cmp(&b.path).then(a.range.start.cmp(&b.range.start)));
+    locations.dedup();
+
+    let mut texts: HashMap<PathBuf, String> = HashMap::new();
+    let mut groups: Vec<(PathBuf, Vec<LocationEntry>)> = Vec::new();
+    for location in locations {
+        let text = texts
+            .entry(location.path.clone())
+            .or_insert_with(|| read_text(&location.path));
+        let start = position_to_offset(text, location.range.start);
+        let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        let entry = LocationEntry {
+            preview: text[line_start..line_end].trim().to_string(),
+            location,
+        };
+
+        match groups.last_mut() {
+            Some((path, entries)) if *path == entry.location.path => entries.push(entry),
+            _ => groups.push((entry.location.path.clone(), vec![entry])),
+        }
+    }
+    groups
+}
+
+/// Text of an open tab (which may have unsaved edits), or else of the file.
+fn read_text(path: &Path) -> String {
+    let editor = editor_interactions_store();
+    let tabs = editor.tabs.borrow();
+    match tabs.iter().find(|tab| tab.path == path) {
+        Some(tab) => tab.content.borrow().to_string(),
+        None => std::fs::read_to_string(path).unwrap_or_default(),
+    }
+}
+
+pub fn navigation_store() -> std::cell::Ref<'static, NavigationInteractionsStore> {
+    NavigationInteractionsStore::instance()
+}
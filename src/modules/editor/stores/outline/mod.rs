@@ -0,0 +1,3 @@
+pub mod outline_interactions;
+
+pub use outline_interactions::{OutlineInteractionsStore, outline_store};
@@ -0,0 +1,301 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+
+use crate::core::lexer::{OutlineItem, OutlineKind, outline};
+use crate::core::plugins::lsp::lsp_manager;
+use crate::core::plugins::lsp::protocol::{path_to_uri, range_to_offsets};
+use crate::core::plugins::lsp::symbols::{
+    DocumentSymbol, SymbolKind, document_symbol_params, document_symbol_support,
+    parse_document_symbols,
+};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::document_request::DocumentRequest;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// How long the text has to stay put before the outline is rebuilt.
+const OUTLINE_DELAY: f64 = 0.3;
+
+/// A symbol of the outline, in byte offsets into the text it was built from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineNode {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: Range<usize>,
+    /// Where the cursor goes when the node is clicked.
+    pub selection: Range<usize>,
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    fn from_symbol(text: &TextBuffer, symbol: DocumentSymbol) -> Self {
+        OutlineNode {
+            name: symbol.name,
+            kind: symbol.kind,
+            range: range_to_offsets(text, symbol.range),
+            selection: range_to_offsets(text, symbol.selection_range),
+            children: symbol
+                .children
+                .into_iter()
+                .map(|child| OutlineNode::from_symbol(text, child))
+                .collect(),
+        }
+    }
+
+    fn from_item(item: OutlineItem) -> Self {
+        OutlineNode {
+            name: item.name,
+            kind: match item.kind {
+                OutlineKind::Function => SymbolKind::Function,
+                OutlineKind::Struct => SymbolKind::Struct,
+                OutlineKind::Enum => SymbolKind::Enum,
+                OutlineKind::Trait => SymbolKind::Interface,
+                OutlineKind::Impl => SymbolKind::Object,
+                OutlineKind::Module => SymbolKind::Module,
+            },
+            range: item.range,
+            selection: item.name_range,
+            children: item
+                .children
+                .into_iter()
+                .map(OutlineNode::from_item)
+                .collect(),
+        }
+    }
+}
+
+/// A line of the outline panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineRow {
+    pub depth: usize,
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: Range<usize>,
+    pub selection: Range<usize>,
+    /// The innermost symbol under the cursor.
+    pub active: bool,
+    /// The active symbol changed; the panel scrolls to it.
+    pub scroll_to: bool,
+}
+
+/// The symbols of a document.
+pub struct Outline {
+    pub path: PathBuf,
+    revision: u64,
+    from_server: bool,
+    nodes: Vec<OutlineNode>,
+}
+
+store! {
+    pub struct OutlineInteractionsStore {
+        outline: Option<Outline> = None,
+        request: DocumentRequest = DocumentRequest::new(OUTLINE_DELAY),
+        filter: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        cursor: Option<(PathBuf, usize)> = None,
+        // rows scrolled into view in the editor
+        visible: Option<(PathBuf, Range<usize>)> = None,
+        // range of the last active node
+        active: Option<Range<usize>> = None,
+    }
+
+    on_cursor(&self, ctx: &egui::Context, path: &Path, cursor: CursorInfo) {
+        let cursor = Some((path.to_path_buf(), cursor.offset));
+        if *self.cursor.borrow() != cursor {
+            *self.reactive(ctx).cursor() = cursor;
+        }
+    }
+
+    on_visible(&self, ctx: &egui::Context, path: &Path, visible: Range<usize>) {
+        let visible = Some((path.to_path_buf(), visible));
+        if *self.visible.borrow() != visible {
+            *self.reactive(ctx).visible() = visible;
+        }
+    }
+
+    // moves the cursor to a symbol picked in the panel
+    reveal(&self, ctx: &egui::Context, selection: Range<usize>) {
+        editor_interactions_store().select_range(ctx, selection.start..selection.start);
+    }
+
+    // rebuilds the outline once the text settled; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        self.take_response(ctx);
+
+        let editor = editor_interactions_store();
+        let (Some(path), Some(text)) = (
+            editor.get_current_tab_path(ctx),
+            editor.get_current_tab_text_ref(ctx),
+        ) else {
+            return;
+        };
+        let text = text.borrow();
+        let revision = text.revision();
+
+        let server = lsp_manager()
+            .capabilities(&path)
+            .is_some_and(|capabilities| document_symbol_support(&capabilities));
+        let up_to_date = self.outline.borrow().as_ref().is_some_and(|outline| {
+            outline.path == path && outline.revision == revision && (outline.from_server || !server)
+        });
+        if up_to_date {
+            return;
+        }
+
+        // a freshly opened tab shouldn't wait for the text to settle
+        let shown = self
+            .outline
+            .borrow()
+            .as_ref()
+            .is_some_and(|outline| outline.path == path);
+        let mut request = self.request.borrow_mut();
+        if !request.is_due(ctx, &path, revision, shown) {
+            return;
+        }
+        if server {
+            let params = document_symbol_params(&path_to_uri(&path));
+            if request.send(ctx, |client| {
+                client.request("textDocument/documentSymbol", params)
+            }) {
+                return;
+            }
+            // the server is busy starting; show the lexer's outline meanwhile
+        }
+        let nodes = editor
+            .with_current_tab_tokens(outline)
+            .unwrap_or_default()
+            .into_iter()
+            .map(OutlineNode::from_item)
+            .collect();
+        *self.reactive(ctx).outline() = Some(Outline {
+            path,
+            revision,
+            from_server: false,
+            nodes,
+        });
+    }
+}
+
+impl OutlineInteractionsStore {
+    pub fn get_filter(&self) -> Rc<RefCell<TextBuffer>> {
+        self.filter.borrow().clone()
+    }
+
+    /// The outline of the current tab, flattened into indented rows. With a
+    /// filter, only matching symbols and their parents are listed.
+    pub fn get_rows(&self, ctx: &egui::Context) -> Vec<OutlineRow> {
+        let current_path = editor_interactions_store().get_current_tab_path(ctx);
+        let outline = self.outline.borrow();
+        let Some(outline) = outline
+            .as_ref()
+            .filter(|outline| Some(&outline.path) == current_path.as_ref())
+        else {
+            return vec![];
+        };
+
+        let active = self
+            .focus_offset(&outline.path)
+            .and_then(|offset| innermost(&outline.nodes, offset))
+            .map(|node| node.range.clone());
+        let mut last_active = self.active.borrow_mut();
+        let scroll_to = *last_active != active;
+        *last_active = active.clone();
+
+        let filter = self.filter.borrow().borrow().to_string().to_lowercase();
+        let mut rows = vec![];
+        flatten(&outline.nodes, 0, &filter, &mut rows);
+        for row in &mut rows {
+            row.active = active.as_ref() == Some(&row.range);
+            row.scroll_to = row.active && scroll_to;
+        }
+        rows
+    }
+
+    /// The offset whose symbol is highlighted: the cursor while it is in
+    /// view, otherwise the top of the view, so the highlight follows
+    /// scrolling.
+    fn focus_offset(&self, path: &Path) -> Option<usize> {
+        let cursor = self
+            .cursor
+            .borrow()
+            .as_ref()
+            .filter(|(cursor_path, _)| cursor_path == path)
+            .map(|(_, offset)| *offset);
+        let visible = self
+            .visible
+            .borrow()
+            .as_ref()
+            .filter(|(visible_path, _)| visible_path == path)
+            .map(|(_, visible)| visible.clone());
+        match (cursor, visible) {
+            (Some(cursor), Some(visible)) if !visible.contains(&cursor) => Some(visible.start),
+            (cursor, visible) => cursor.or(visible.map(|visible| visible.start)),
+        }
+    }
+
+    fn take_response(&self, ctx: &egui::Context) {
+        let response = self.request.borrow_mut().take_response("Document symbols");
+        let Some((path, revision, result)) = response else {
+            return;
+        };
+        let editor = editor_interactions_store();
+        if editor.get_current_tab_path(ctx).as_ref() != Some(&path) {
+            return;
+        }
+        let Some(text) = editor.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let text = text.borrow();
+        let nodes = parse_document_symbols(&result)
+            .into_iter()
+            .map(|symbol| OutlineNode::from_symbol(&text, symbol))
+            .collect();
+        *self.reactive(ctx).outline() = Some(Outline {
+            path,
+            revision,
+            from_server: true,
+            nodes,
+        });
+    }
+}
+
+/// The deepest node whose range holds `offset`.
+fn innermost(nodes: &[OutlineNode], offset: usize) -> Option<&OutlineNode> {
+    let node = nodes
+        .iter()
+        .find(|node| node.range.start <= offset && offset <= node.range.end)?;
+    innermost(&node.children, offset).or(Some(node))
+}
+
+/// Appends the rows of `nodes` that match `filter`; returns whether any did.
+fn flatten(nodes: &[OutlineNode], depth: usize, filter: &str, rows: &mut Vec<OutlineRow>) -> bool {
+    let mut any = false;
+    for node in nodes {
+        let at = rows.len();
+        rows.push(OutlineRow {
+            depth,
+            name: node.name.clone(),
+            kind: node.kind,
+            range: node.range.clone(),
+            selection: node.selection.clone(),
+            active: false,
+            scroll_to: false,
+        });
+        let matches = node.name.to_lowercase().contains(filter);
+        if flatten(&node.children, depth + 1, filter, rows) || matches {
+            any = true;
+        } else {
+            rows.truncate(at);
+        }
+    }
+    any
+}
+
+pub fn outline_store() -> std::cell::Ref<'static, OutlineInteractionsStore> {
+    OutlineInteractionsStore::instance()
+}
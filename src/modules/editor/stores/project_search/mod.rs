@@ -0,0 +1,3 @@
+pub mod project_search_interactions;
+
+pub use project_search_interactions::{ProjectSearchInteractionsStore, project_search_store};
@@ -0,0 +1,595 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+use crate::core::enums::enums::Hotkeys;
+use crate::core::search::{
+    FileMatches, Matcher, ProjectQuery, SearchQuery, search_project, text_hash,
+};
+use crate::modules::editor::stores::{FindOption, editor_interactions_store};
+use crate::store;
+
+/// Id of the query input of the search panel.
+pub const SEARCH_INPUT: &str = "project_search_query";
+
+/// How long the inputs have to stay put before searching again.
+const SEARCH_DELAY: f64 = 0.3;
+/// Matches after which a search stops, so a common word doesn't flood the
+/// panel.
+const MAX_MATCHES: usize = 2000;
+
+enum SearchMessage {
+    File(FileMatches),
+    Done(Result<(), String>),
+}
+
+/// A search walking the folder on a thread of its own.
+pub struct RunningSearch {
+    receiver: Receiver<SearchMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl RunningSearch {
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A match as the panel shows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchRow {
+    pub path: PathBuf,
+    pub range: Range<usize>,
+    pub line: usize,
+    /// The line, with the match replaced when there is a replacement to
+    /// preview.
+    pub preview: String,
+    /// Whether replacing all replaces it.
+    pub included: bool,
+}
+
+/// A file of the results, with its matches unless it is collapsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRow {
+    pub path: PathBuf,
+    pub count: usize,
+    pub collapsed: bool,
+    pub matches: Vec<MatchRow>,
+}
+
+#[derive(Clone, Copy)]
+enum SearchCommand {
+    Open,
+    Search,
+    Close,
+}
+
+store! {
+    pub struct ProjectSearchInteractionsStore {
+        // folder searched, the one the explorer shows
+        root: PathBuf = PathBuf::from("."),
+        open: bool = false,
+        // select the query input on the next frame
+        focus: bool = false,
+        query: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        replacement: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        include: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        exclude: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        case_sensitive: bool = false,
+        whole_word: bool = false,
+        regex: bool = false,
+        preserve_case: bool = false,
+        // the inputs as last seen, and when they last changed
+        typed: ProjectQuery = ProjectQuery::default(),
+        changed_at: Option<f64> = None,
+        // the query of the results, ready to preview replacements
+        searched: Option<Matcher> = None,
+        running: Option<RunningSearch> = None,
+        results: Vec<FileMatches> = vec![],
+        error: Option<String> = None,
+        truncated: bool = false,
+        // matches left out of replacing, by file and start
+        excluded: HashSet<(PathBuf, usize)> = HashSet::new(),
+        collapsed: HashSet<PathBuf> = HashSet::new(),
+        // replacing all waits for a confirmation
+        confirm: bool = false,
+        // what the last replacement did
+        replaced: Option<String> = None,
+    }
+
+    // Cmd+Shift+F opens the panel; Enter in the query searches without
+    // waiting, Escape closes it
+    handle_keys(&self, ctx: &egui::Context) {
+        let in_query = ctx.memory(|m| m.has_focus(egui::Id::new(SEARCH_INPUT)));
+        let none = egui::Modifiers::NONE;
+        let key_command = ctx.input_mut(|i| {
+            if i.consume_shortcut(&Hotkeys::FindInFiles.shortcut()) {
+                Some(SearchCommand::Open)
+            } else if in_query && i.consume_key(none, egui::Key::Enter) {
+                Some(SearchCommand::Search)
+            } else if in_query && i.consume_key(none, egui::Key::Escape) {
+                Some(SearchCommand::Close)
+            } else {
+                None
+            }
+        });
+        match key_command {
+            Some(SearchCommand::Open) => self.open_panel(ctx),
+            Some(SearchCommand::Search) => self.search(ctx),
+            Some(SearchCommand::Close) => self.close(ctx),
+            None => {}
+        }
+    }
+
+    // searches again once the inputs settle, and takes the files the
+    // search thread found; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        if !*self.open.borrow() {
+            return;
+        }
+        let query = self.project_query();
+        let now = ctx.input(|i| i.time);
+        if *self.typed.borrow() != query {
+            *self.typed.borrow_mut() = query;
+            *self.changed_at.borrow_mut() = Some(now);
+        }
+        let changed_at = *self.changed_at.borrow();
+        if let Some(changed_at) = changed_at {
+            let waited = now - changed_at;
+            if waited < SEARCH_DELAY {
+                ctx.request_repaint_after(Duration::from_secs_f64(SEARCH_DELAY - waited));
+            } else {
+                self.search(ctx);
+            }
+        }
+        self.take_results(ctx);
+    }
+
+    // opens the panel with the query selected, taking the selected text as
+    // the query when it is on one line
+    open_panel(&self, ctx: &egui::Context) {
+        if let Some(text) = editor_interactions_store().get_current_tab_text_ref(ctx) {
+            let text = text.borrow();
+            let selected = text.slice(text.selections().primary().range());
+            if !selected.is_empty() && !selected.contains('\n') {
+                self.query.borrow().borrow_mut().set_text(&selected);
+            }
+        }
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = true;
+        *reactive.focus() = true;
+    }
+
+    close(&self, ctx: &egui::Context) {
+        if let Some(running) = self.running.borrow_mut().take() {
+            running.cancel();
+        }
+        let mut reactive = self.reactive(ctx);
+        *reactive.open() = false;
+        *reactive.confirm() = false;
+    }
+
+    toggle(&self, ctx: &egui::Context, option: FindOption) {
+        let mut reactive = self.reactive(ctx);
+        let mut value = match option {
+            FindOption::CaseSensitive => reactive.case_sensitive(),
+            FindOption::WholeWord => reactive.whole_word(),
+            FindOption::Regex => reactive.regex(),
+            FindOption::PreserveCase => reactive.preserve_case(),
+        };
+        *value = !*value;
+    }
+
+    // starts searching for the inputs now, stopping the search before;
+    // open tabs are searched as they are in the editor
+    search(&self, ctx: &egui::Context) {
+        *self.changed_at.borrow_mut() = None;
+        if let Some(running) = self.running.borrow_mut().take() {
+            running.cancel();
+        }
+        let query = self.project_query();
+        let mut reactive = self.reactive(ctx);
+        reactive.results().clear();
+        reactive.excluded().clear();
+        *reactive.error() = None;
+        *reactive.truncated() = false;
+        *reactive.confirm() = false;
+        *reactive.replaced() = None;
+        *reactive.searched() = None;
+        drop(reactive);
+        if query.query.text.is_empty() {
+            return;
+        }
+        let matcher = match Matcher::new(&query.query) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                *self.reactive(ctx).error() = Some(format!("Invalid regex: {}", e));
+                return;
+            }
+        };
+        *self.searched.borrow_mut() = Some(matcher);
+
+        let texts: HashMap<PathBuf, Arc<str>> = editor_interactions_store()
+            .tabs
+            .borrow()
+            .iter()
+            .map(|tab| (tab.path.clone(), tab.content.borrow().contents()))
+            .collect();
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = cancel.clone();
+        let thread_ctx = ctx.clone();
+        let root = self.root.borrow().clone();
+        std::thread::spawn(move || {
+            // closing the panel or searching again cancels, so files
+            // without matches aren't read for nothing either
+            let cancelled = || thread_cancel.load(Ordering::Relaxed);
+            let result = search_project(&root, &query, &texts, cancelled, |file| {
+                let sent = sender.send(SearchMessage::File(file)).is_ok();
+                thread_ctx.request_repaint();
+                sent
+            });
+            let _ = sender.send(SearchMessage::Done(result.map_err(|e| e.to_string())));
+            thread_ctx.request_repaint();
+        });
+        *self.running.borrow_mut() = Some(RunningSearch { receiver, cancel });
+    }
+
+    // leaves a match out of replacing all, or puts it back
+    toggle_match(&self, ctx: &egui::Context, path: PathBuf, start: usize) {
+        let mut reactive = self.reactive(ctx);
+        let mut excluded = reactive.excluded();
+        let key = (path, start);
+        if !excluded.remove(&key) {
+            excluded.insert(key);
+        }
+    }
+
+    toggle_file(&self, ctx: &egui::Context, path: PathBuf) {
+        let mut reactive = self.reactive(ctx);
+        let mut collapsed = reactive.collapsed();
+        if !collapsed.remove(&path) {
+            collapsed.insert(path);
+        }
+    }
+
+    // opens the file of a match and selects it, unless the file changed
+    // since it was searched
+    open_match(&self, ctx: &egui::Context, path: PathBuf, range: Range<usize>) {
+        let hash = self
+            .results
+            .borrow()
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.hash);
+        let editor = editor_interactions_store();
+        editor.open_tab(ctx, path.clone());
+        let Some(text) = editor.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let mut text = text.borrow_mut();
+        if Some(text_hash(&text.contents())) != hash {
+            drop(text);
+            self.search(ctx);
+            return;
+        }
+        text.unfold_around(range.clone());
+        editor.select_range(ctx, range);
+    }
+
+    // asks to confirm replacing the included matches
+    request_replace(&self, ctx: &egui::Context) {
+        let any = self.results.borrow().iter().any(|file| {
+            file.matches
+                .iter()
+                .any(|m| !self.excluded.borrow().contains(&(file.path.clone(), m.range.start)))
+        });
+        if any && self.running.borrow().is_none() {
+            *self.reactive(ctx).confirm() = true;
+        }
+    }
+
+    cancel_replace(&self, ctx: &egui::Context) {
+        *self.reactive(ctx).confirm() = false;
+    }
+
+    // replaces the included matches: open tabs are changed in place as one
+    // undo step each, other files are rewritten on disk. Files changed since
+    // they were searched are left alone
+    replace_all(&self, ctx: &egui::Context) {
+        *self.reactive(ctx).confirm() = false;
+        let searched = self.searched.borrow();
+        let Some(matcher) = searched.as_ref() else {
+            return;
+        };
+        let replacement = self.replacement.borrow().borrow().to_string();
+        let preserve = *self.preserve_case.borrow();
+        let editor = editor_interactions_store();
+
+        let files = std::mem::take(&mut *self.results.borrow_mut());
+        let mut kept = vec![];
+        let (mut count, mut changed_files, mut stale) = (0, 0, 0);
+        for file in files {
+            let included: Vec<Range<usize>> = file
+                .matches
+                .iter()
+                .map(|m| m.range.clone())
+                .filter(|range| !self.excluded.borrow().contains(&(file.path.clone(), range.start)))
+                .collect();
+            if included.is_empty() {
+                kept.push(file);
+                continue;
+            }
+            let index = editor.tabs.borrow().iter().position(|tab| tab.path == file.path);
+            let text: Arc<str> = match index {
+                Some(index) => editor.tabs.borrow()[index].content.borrow().contents(),
+                None => std::fs::read_to_string(&file.path).unwrap_or_default().into(),
+            };
+            if text_hash(&text) != file.hash {
+                stale += 1;
+                kept.push(file);
+                continue;
+            }
+            // from the last one, so the ranges before stay where they are
+            let replacements: Vec<(Range<usize>, String)> = included
+                .iter()
+                .rev()
+                .map(|range| {
+                    let new = matcher.replacement(&text, range.clone(), &replacement, preserve);
+                    (range.clone(), new)
+                })
+                .collect();
+            let replacements: Vec<(Range<usize>, &str)> = replacements
+                .iter()
+                .map(|(range, new)| (range.clone(), new.as_str()))
+                .collect();
+            match index {
+                Some(index) => editor.replace_in_tab(ctx, index, &replacements),
+                None => {
+                    let mut after = text.to_string();
+                    for (range, new) in &replacements {
+                        after.replace_range(range.clone(), new);
+                    }
+                    if let Err(e) = std::fs::write(&file.path, &after) {
+                        eprintln!("Failed to write {}: {}", file.path.display(), e);
+                        kept.push(file);
+                        continue;
+                    }
+                }
+            }
+            count += included.len();
+            changed_files += 1;
+        }
+
+        let mut status = format!("Replaced {} matches in {} files", count, changed_files);
+        if stale > 0 {
+            status.push_str(&format!(", {} changed since the search were skipped", stale));
+        }
+        let mut reactive = self.reactive(ctx);
+        *reactive.results() = kept;
+        *reactive.replaced() = Some(status);
+    }
+}
+
+impl ProjectSearchInteractionsStore {
+    pub fn set_root(&self, root: PathBuf) {
+        *self.root.borrow_mut() = root;
+    }
+
+    /// The query input and whether it should take focus, while the panel
+    /// is open.
+    pub fn get_input(&self) -> Option<(Rc<RefCell<TextBuffer>>, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let focus = std::mem::take(&mut *self.focus.borrow_mut());
+        Some((self.query.borrow().clone(), focus))
+    }
+
+    pub fn get_replacement(&self) -> Rc<RefCell<TextBuffer>> {
+        self.replacement.borrow().clone()
+    }
+
+    pub fn get_include(&self) -> Rc<RefCell<TextBuffer>> {
+        self.include.borrow().clone()
+    }
+
+    pub fn get_exclude(&self) -> Rc<RefCell<TextBuffer>> {
+        self.exclude.borrow().clone()
+    }
+
+    pub fn is_on(&self, option: FindOption) -> bool {
+        let value = match option {
+            FindOption::CaseSensitive => &self.case_sensitive,
+            FindOption::WholeWord => &self.whole_word,
+            FindOption::Regex => &self.regex,
+            FindOption::PreserveCase => &self.preserve_case,
+        };
+        *value.borrow()
+    }
+
+    pub fn is_confirming(&self) -> bool {
+        *self.confirm.borrow()
+    }
+
+    /// Number of included matches and the files they are in.
+    pub fn get_included_count(&self) -> (usize, usize) {
+        let excluded = self.excluded.borrow();
+        let mut files = 0;
+        let mut count = 0;
+        for file in self.results.borrow().iter() {
+            let included = file
+                .matches
+                .iter()
+                .filter(|m| !excluded.contains(&(file.path.clone(), m.range.start)))
+                .count();
+            if included > 0 {
+                files += 1;
+                count += included;
+            }
+        }
+        (count, files)
+    }
+
+    /// One line about the search: its error, its progress or its results.
+    pub fn get_status(&self) -> String {
+        if let Some(error) = self.error.borrow().as_ref() {
+            return error.clone();
+        }
+        if let Some(replaced) = self.replaced.borrow().as_ref() {
+            return replaced.clone();
+        }
+        let results = self.results.borrow();
+        let running = self.running.borrow().is_some();
+        if results.is_empty() {
+            return match (running, self.searched.borrow().is_some()) {
+                (true, _) => "Searching…".to_string(),
+                (false, true) => "No results".to_string(),
+                (false, false) => String::new(),
+            };
+        }
+        let count: usize = results.iter().map(|file| file.matches.len()).sum();
+        let mut status = format!("{} results in {} files", count, results.len());
+        if *self.truncated.borrow() {
+            status.push_str(&format!(", stopped at {}", MAX_MATCHES));
+        } else if running {
+            status.push('…');
+        }
+        status
+    }
+
+    /// The results, grouped by file, with the replacement previewed in each
+    /// match while there is one or it waits for a confirmation.
+    pub fn get_rows(&self) -> Vec<FileRow> {
+        let searched = self.searched.borrow();
+        let replacement = self.replacement.borrow().borrow().to_string();
+        let previewed = searched
+            .as_ref()
+            .filter(|_| !replacement.is_empty() || *self.confirm.borrow());
+        let preserve = *self.preserve_case.borrow();
+        let excluded = self.excluded.borrow();
+        let collapsed = self.collapsed.borrow();
+
+        self.results
+            .borrow()
+            .iter()
+            .map(|file| {
+                let is_collapsed = collapsed.contains(&file.path);
+                let matches = if is_collapsed {
+                    vec![]
+                } else {
+                    file.matches
+                        .iter()
+                        .map(|m| {
+                            let preview = match previewed {
+                                Some(matcher) => {
+                                    let range = m.preview_range.clone();
+                                    let new = matcher.replacement(
+                                        &m.preview,
+                                        range.clone(),
+                                        &replacement,
+                                        preserve,
+                                    );
+                                    format!(
+                                        "{}{} → {}{}",
+                                        &m.preview[..range.start],
+                                        &m.preview[range.clone()],
+                                        new,
+                                        &m.preview[range.end..]
+                                    )
+                                }
+                                None => m.preview.clone(),
+                            };
+                            MatchRow {
+                                path: file.path.clone(),
+                                range: m.range.clone(),
+                                line: m.line,
+                                preview,
+                                included: !excluded.contains(&(file.path.clone(), m.range.start)),
+                            }
+                        })
+                        .collect()
+                };
+                FileRow {
+                    path: file.path.clone(),
+                    count: file.matches.len(),
+                    collapsed: is_collapsed,
+                    matches,
+                }
+            })
+            .collect()
+    }
+
+    fn project_query(&self) -> ProjectQuery {
+        ProjectQuery {
+            query: SearchQuery {
+                text: self.query.borrow().borrow().to_string(),
+                case_sensitive: *self.case_sensitive.borrow(),
+                whole_word: *self.whole_word.borrow(),
+                regex: *self.regex.borrow(),
+            },
+            include: ProjectQuery::globs(&self.include.borrow().borrow().to_string()),
+            exclude: ProjectQuery::globs(&self.exclude.borrow().borrow().to_string()),
+        }
+    }
+
+    /// Moves what the search thread found into the results, stopping it
+    /// past `MAX_MATCHES`.
+    fn take_results(&self, ctx: &egui::Context) {
+        let mut found = vec![];
+        let mut done = None;
+        if let Some(running) = self.running.borrow().as_ref() {
+            loop {
+                match running.receiver.try_recv() {
+                    Ok(SearchMessage::File(file)) => found.push(file),
+                    Ok(SearchMessage::Done(result)) => {
+                        done = Some(result);
+                        break;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        done = Some(Ok(()));
+                        break;
+                    }
+                }
+            }
+        }
+        if found.is_empty() && done.is_none() {
+            return;
+        }
+        let mut reactive = self.reactive(ctx);
+        let mut results = reactive.results();
+        let mut count: usize = results.iter().map(|file| file.matches.len()).sum();
+        for file in found {
+            if count >= MAX_MATCHES {
+                *reactive.truncated() = true;
+                done.get_or_insert(Ok(()));
+                break;
+            }
+            count += file.matches.len();
+            results.push(file);
+        }
+        drop(results);
+        if let Some(result) = done {
+            if let Some(running) = reactive.running().take() {
+                running.cancel();
+            }
+            if let Err(e) = result {
+                *reactive.error() = Some(e);
+            }
+        }
+    }
+}
+
+pub fn project_search_store() -> std::cell::Ref<'static, ProjectSearchInteractionsStore> {
+    ProjectSearchInteractionsStore::instance()
+}
@@ -0,0 +1,3 @@
+pub mod rename_interactions;
+
+pub use rename_interactions::{RenameInteractionsStore, RenameSummary, rename_store};
@@ -0,0 +1,367 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+
+use crate::core::plugins::lsp::protocol::{
+    Position, TextEdit, offset_to_position, parse_workspace_edit, path_to_uri, range_to_offsets,
+    text_document_position,
+};
+use crate::core::plugins::lsp::rename::{
+    PrepareRename, parse_prepare_rename, rename_params, rename_support,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::modules::editor::stores::editor::editor_interactions::EditedFile;
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+pub enum RenameStage {
+    /// Waiting for `textDocument/prepareRename`.
+    Preparing(RequestHandle),
+    /// The input box is open.
+    Editing,
+    /// Waiting for the `WorkspaceEdit` of `textDocument/rename`.
+    Renaming(RequestHandle),
+}
+
+/// A rename from F2 until the server's edits are applied.
+pub struct RenameSession {
+    pub path: PathBuf,
+    position: Position,
+    /// Byte range of the symbol in the tab, once known.
+    range: Range<usize>,
+    /// Below the cursor; where the input box opens.
+    pub anchor: egui::Pos2,
+    /// The new name, pre-filled with the old one.
+    pub input: Rc<RefCell<TextBuffer>>,
+    old_name: String,
+    stage: RenameStage,
+    // frame the input box opened in; it grabs focus on that one
+    opened_frame: u64,
+}
+
+/// What the last rename changed, until it is undone or dismissed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameSummary {
+    pub old_name: String,
+    pub new_name: String,
+    /// The undo step of the workspace the rename made.
+    pub step: Option<u64>,
+    pub files: Vec<EditedFile>,
+}
+
+impl RenameSummary {
+    pub fn edit_count(&self) -> usize {
+        self.files.iter().map(|file| file.edits).sum()
+    }
+}
+
+store! {
+    pub struct RenameInteractionsStore {
+        // where the cursor of the focused editor was last seen
+        cursor: Option<(PathBuf, CursorInfo)> = None,
+        session: Option<RenameSession> = None,
+        summary: Option<RenameSummary> = None,
+        // why the last rename didn't happen, shown in place of a summary
+        message: Option<String> = None,
+    }
+
+    // called by the editor every frame while it has focus; clicking back
+    // into the editor abandons the input box
+    on_cursor(&self, ctx: &egui::Context, path: &Path, cursor: CursorInfo) {
+        *self.cursor.borrow_mut() = Some((path.to_path_buf(), cursor));
+
+        let abandoned = self.session.borrow().as_ref().is_some_and(|session| {
+            matches!(session.stage, RenameStage::Editing)
+                && ctx.cumulative_frame_nr() > session.opened_frame + 1
+        });
+        if abandoned {
+            self.close(ctx);
+        }
+    }
+
+    // F2 starts a rename; Enter confirms the new name, Escape cancels
+    handle_keys(&self, ctx: &egui::Context) {
+        let editing = matches!(
+            self.session.borrow().as_ref().map(|session| &session.stage),
+            Some(RenameStage::Editing)
+        );
+        if editing {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)) {
+                self.submit(ctx);
+            } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+                self.close(ctx);
+            }
+        } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F2)) {
+            self.start(ctx);
+        }
+    }
+
+    // picks up the server's responses; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        let response = match self.session.borrow_mut().as_mut().map(|session| &mut session.stage) {
+            Some(RenameStage::Preparing(request) | RenameStage::Renaming(request)) => {
+                request.try_take()
+            }
+            _ => None,
+        };
+        let Some(response) = response else {
+            return;
+        };
+        let result = match response {
+            Ok(result) => result,
+            Err(e) => {
+                // e.g. "cannot rename a keyword"
+                self.close(ctx);
+                self.report(ctx, format!("Rename failed: {}", e));
+                return;
+            }
+        };
+
+        let preparing = matches!(
+            self.session.borrow().as_ref().map(|session| &session.stage),
+            Some(RenameStage::Preparing(_))
+        );
+        if preparing {
+            self.prepared(ctx, parse_prepare_rename(&result));
+        } else {
+            self.renamed(ctx, parse_workspace_edit(&result));
+        }
+    }
+
+    close(&self, ctx: &egui::Context) {
+        let Some(session) = self.reactive(ctx).session().take() else {
+            return;
+        };
+        if let RenameStage::Preparing(request) | RenameStage::Renaming(request) = session.stage {
+            lsp_manager().cancel(&session.path, request);
+        }
+    }
+
+    // undoes the last rename like Cmd+Z in one of its tabs would
+    undo(&self, ctx: &egui::Context) {
+        let Some(summary) = self.reactive(ctx).summary().take() else {
+            return;
+        };
+        if let Some(step) = summary.step {
+            editor_interactions_store().undo_workspace_step(ctx, step);
+        }
+    }
+
+    close_summary(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.summary() = None;
+        *reactive.message() = None;
+    }
+
+    // shows `message` in the rename panel instead of the last summary
+    report(&self, ctx: &egui::Context, message: String) {
+        let mut reactive = self.reactive(ctx);
+        *reactive.summary() = None;
+        *reactive.message() = Some(message);
+    }
+}
+
+impl RenameInteractionsStore {
+    /// The input box: where it opens, its text and whether it should grab
+    /// focus (with everything selected) this frame.
+    pub fn get_input(
+        &self,
+        ctx: &egui::Context,
+    ) -> Option<(egui::Pos2, Rc<RefCell<TextBuffer>>, bool)> {
+        let session = self.session.borrow();
+        let session = session.as_ref()?;
+        if matches!(session.stage, RenameStage::Preparing(_)) {
+            return None;
+        }
+        let focus = ctx.cumulative_frame_nr() == session.opened_frame;
+        Some((session.anchor, session.input.clone(), focus))
+    }
+
+    pub fn get_summary(&self) -> Option<RenameSummary> {
+        self.summary.borrow().clone()
+    }
+
+    pub fn get_message(&self) -> Option<String> {
+        self.message.borrow().clone()
+    }
+
+    fn start(&self, ctx: &egui::Context) {
+        let Some((path, cursor)) = self.cursor.borrow().clone() else {
+            return;
+        };
+        let editor = editor_interactions_store();
+        if editor.get_current_tab_path(ctx).as_ref() != Some(&path) {
+            return;
+        }
+        let Some(text) = editor.get_current_tab_text_ref(ctx) else {
+            return;
+        };
+        let text = text.borrow();
+
+        let Some(prepare) = lsp_manager()
+            .capabilities(&path)
+            .and_then(|capabilities| rename_support(&capabilities))
+        else {
+            let path = editor.display_path(&path);
+            self.report(
+                ctx,
+                format!("No language server can rename in {}", path.display()),
+            );
+            return;
+        };
+        self.close(ctx);
+
+        let position = offset_to_position(&*text, cursor.offset);
+        let mut session = RenameSession {
+            path: path.clone(),
+            position,
+            range: text.word_at(cursor.offset),
+            anchor: cursor.rect.left_bottom(),
+            input: Rc::new(RefCell::new(TextBuffer::new())),
+            old_name: String::new(),
+            stage: RenameStage::Editing,
+            opened_frame: ctx.cumulative_frame_nr(),
+        };
+
+        if prepare {
+            let params = text_document_position(&path_to_uri(&path), position);
+            let Some(request) = lsp_manager().request(ctx, &path, |client| {
+                client.request("textDocument/prepareRename", params)
+            }) else {
+                return;
+            };
+            session.stage = RenameStage::Preparing(request);
+        } else if session.range.is_empty() {
+            self.report(ctx, "Nothing to rename here".to_string());
+            return;
+        } else {
+            session.old_name = text.slice(session.range.clone()).into_owned();
+            session.input.borrow_mut().set_text(&session.old_name);
+        }
+        *self.reactive(ctx).session() = Some(session);
+    }
+
+    /// Opens the input box for what `textDocument/prepareRename` allowed.
+    fn prepared(&self, ctx: &egui::Context, prepare: Option<PrepareRename>) {
+        let mut reactive = self.reactive(ctx);
+        let mut session_slot = reactive.session();
+        let Some(session) = session_slot.as_mut() else {
+            return;
+        };
+        // the tab may have been closed meanwhile
+        let Some(text) = editor_interactions_store().get_tab_text_ref(&session.path) else {
+            *session_slot = None;
+            return;
+        };
+        let text = text.borrow();
+
+        let placeholder = match prepare {
+            Some(PrepareRename::Range { range, placeholder }) => {
+                session.range = range_to_offsets(&*text, range);
+                placeholder
+            }
+            Some(PrepareRename::Word) => None,
+            None => {
+                *session_slot = None;
+                self.report(ctx, "Nothing to rename here".to_string());
+                return;
+            }
+        };
+        if session.range.end > text.len() || session.range.is_empty() {
+            *session_slot = None;
+            return;
+        }
+
+        session.old_name = text.slice(session.range.clone()).into_owned();
+        session
+            .input
+            .borrow_mut()
+            .set_text(&placeholder.unwrap_or_else(|| session.old_name.clone()));
+        session.stage = RenameStage::Editing;
+        session.opened_frame = ctx.cumulative_frame_nr();
+    }
+
+    /// Sends `textDocument/rename` with the typed name.
+    fn submit(&self, ctx: &egui::Context) {
+        let mut session = self.session.borrow_mut();
+        let Some(session_ref) = session.as_mut() else {
+            return;
+        };
+
+        let new_name = session_ref.input.borrow().to_string().trim().to_string();
+        if new_name.is_empty() || new_name == session_ref.old_name {
+            drop(session);
+            self.close(ctx);
+            return;
+        }
+
+        let params = rename_params(
+            &path_to_uri(&session_ref.path),
+            session_ref.position,
+            &new_name,
+        );
+        match lsp_manager().request(ctx, &session_ref.path, |client| {
+            client.request("textDocument/rename", params)
+        }) {
+            Some(request) => session_ref.stage = RenameStage::Renaming(request),
+            None => {
+                drop(session);
+                self.close(ctx);
+            }
+        }
+    }
+
+    /// Applies the server's edits and keeps them for the summary.
+    fn renamed(&self, ctx: &egui::Context, changes: Vec<(PathBuf, Vec<TextEdit>)>) {
+        let Some(session) = self.reactive(ctx).session().take() else {
+            return;
+        };
+        let new_name = session.input.borrow().to_string().trim().to_string();
+
+        let editor = editor_interactions_store();
+        // where the renamed symbol ends up in the tab
+        let cursor = editor
+            .get_tab_text_ref(&session.path)
+            .zip(changes.iter().find(|(path, _)| *path == session.path))
+            .map_or(session.range.start, |(text, (_, edits))| {
+                shift_offset(&text.borrow(), edits, session.range.start)
+            });
+        let applied = editor.apply_workspace_edit(ctx, changes);
+
+        if editor.get_current_tab_path(ctx).as_ref() == Some(&session.path) {
+            editor.select_range(ctx, cursor..cursor + new_name.len());
+        }
+
+        let mut reactive = self.reactive(ctx);
+        *reactive.summary() = Some(RenameSummary {
+            old_name: session.old_name,
+            new_name,
+            step: applied.step,
+            files: applied.files,
+        });
+        *reactive.message() = None;
+    }
+}
+
+/// Where `offset` of `text` ends up once `edits` are applied; edits at or
+/// after it don't move it.
+fn shift_offset(text: &TextBuffer, edits: &[TextEdit], offset: usize) -> usize {
+    let mut shifted = offset as isize;
+    for edit in edits {
+        let range = range_to_offsets(text, edit.range);
+        if range.end <= offset && range.start < offset {
+            shifted += edit.new_text.len() as isize - range.len() as isize;
+        }
+    }
+    shifted.max(0) as usize
+}
+
+pub fn rename_store() -> std::cell::Ref<'static, RenameInteractionsStore> {
+    RenameInteractionsStore::instance()
+}
@@ -0,0 +1,3 @@
+pub mod signature_help_interactions;
+
+pub use signature_help_interactions::{SignatureHelpInteractionsStore, signature_help_store};
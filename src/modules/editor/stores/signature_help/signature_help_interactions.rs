@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eframe::egui;
+use serde_json::{Value, json};
+
+use crate::core::buffer::TextBuffer;
+use crate::core::plugins::lsp::hover::{SignatureHelp, parse_signature_help};
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::protocol::{
+    Position, offset_to_position, path_to_uri, text_document_position,
+};
+use crate::core::plugins::lsp::{RequestHandle, lsp_manager};
+use crate::core::ui::ui_kit::text_edit::CursorInfo;
+use crate::store;
+
+/// Typing settles for this long before the server is asked (again).
+const SIGNATURE_DELAY: f64 = 0.15;
+
+/// Signature help for a call, from the `(` that opened it until the server
+/// says the cursor left it.
+pub struct SignatureState {
+    pub path: PathBuf,
+    offset: usize,
+    position: Position,
+    /// Screen position above the cursor line; the popup opens upwards.
+    pub anchor: egui::Pos2,
+    trigger: Option<char>,
+    since: f64,
+    sent: bool,
+    request: Option<RequestHandle>,
+    /// Kept while a new request is in flight so the popup doesn't flicker.
+    pub help: Option<SignatureHelp>,
+}
+
+store! {
+    pub struct SignatureHelpInteractionsStore {
+        state: Option<SignatureState> = None,
+    }
+
+    // called by the editor every frame while it has focus
+    on_cursor(&self, ctx: &egui::Context, path: &Path, text: &TextBuffer, cursor: CursorInfo) {
+        let typed = text.char_before(cursor.offset).filter(|_| cursor.changed);
+        let (active, moved) = match &*self.state.borrow() {
+            Some(state) => (state.path == path, state.offset != cursor.offset),
+            None => (false, false),
+        };
+        if !active && self.state.borrow().is_some() {
+            self.close(ctx);
+        }
+
+        let triggered = typed.is_some_and(|c| trigger_characters(path).contains(&c));
+        if !(triggered || active && moved) {
+            return;
+        }
+
+        let mut state = self.state.borrow_mut();
+        let help = state.take().and_then(|mut old| {
+            if let Some(request) = old.request.take() {
+                lsp_manager().cancel(&old.path, request);
+            }
+            old.help
+        });
+        *state = Some(SignatureState {
+            path: path.to_path_buf(),
+            offset: cursor.offset,
+            position: offset_to_position(text, cursor.offset),
+            anchor: cursor.rect.left_top(),
+            trigger: typed.filter(|_| triggered),
+            since: ctx.input(|i| i.time),
+            sent: false,
+            request: None,
+            help,
+        });
+        ctx.request_repaint_after(Duration::from_secs_f64(SIGNATURE_DELAY));
+    }
+
+    // sends the (debounced) request and picks up the response; called once
+    // per frame
+    poll(&self, ctx: &egui::Context) {
+        let mut closed = false;
+        {
+            let mut state = self.state.borrow_mut();
+            let Some(state) = state.as_mut() else {
+                return;
+            };
+
+            if !state.sent {
+                let waited = ctx.input(|i| i.time) - state.since;
+                if waited < SIGNATURE_DELAY {
+                    ctx.request_repaint_after(Duration::from_secs_f64(SIGNATURE_DELAY - waited));
+                    return;
+                }
+                state.sent = true;
+                let params = signature_help_params(state);
+                state.request = lsp_manager().request(ctx, &state.path, |client| {
+                    client.request("textDocument/signatureHelp", params)
+                });
+                if state.request.is_none() {
+                    closed = true;
+                }
+            } else if let Some(response) = state.request.as_mut().and_then(|r| r.try_take()) {
+                state.request = None;
+                match response {
+                    Ok(result) => {
+                        // `null` once the cursor left the call
+                        state.help = parse_signature_help(&result);
+                        closed = state.help.is_none();
+                        ctx.request_repaint();
+                    }
+                    Err(e) => {
+                        if !is_cancelled(&e) {
+                            eprintln!("Signature help failed: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+        if closed {
+            self.close(ctx);
+        }
+    }
+
+    // Escape hides the popup
+    handle_keys(&self, ctx: &egui::Context) {
+        if self.is_visible()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+        {
+            self.close(ctx);
+        }
+    }
+
+    close(&self, ctx: &egui::Context) {
+        let Some(mut state) = self.reactive(ctx).state().take() else {
+            return;
+        };
+        if let Some(request) = state.request.take() {
+            lsp_manager().cancel(&state.path, request);
+        }
+    }
+}
+
+impl SignatureHelpInteractionsStore {
+    pub fn is_visible(&self) -> bool {
+        self.state
+            .borrow()
+            .as_ref()
+            .is_some_and(|state| state.help.is_some())
+    }
+
+    /// Where to show the popup and what it says.
+    pub fn get_help(&self) -> Option<(egui::Pos2, SignatureHelp)> {
+        let state = self.state.borrow();
+        let state = state.as_ref()?;
+        Some((state.anchor, state.help.clone()?))
+    }
+}
+
+fn signature_help_params(state: &SignatureState) -> Value {
+    let mut params = text_document_position(&path_to_uri(&state.path), state.position);
+    // `triggerKind`: 2 = trigger character, 3 = content change
+    params["context"] = match state.trigger {
+        Some(c) => json!({
+            "triggerKind": 2,
+            "triggerCharacter": c.to_string(),
+            "isRetrigger": state.help.is_some(),
+        }),
+        None => json!({ "triggerKind": 3, "isRetrigger": state.help.is_some() }),
+    };
+    params
+}
+
+/// `signatureHelpProvider.triggerCharacters` of the server for `path`.
+fn trigger_characters(path: &Path) -> Vec<char> {
+    let Some(capabilities) = lsp_manager().capabilities(path) else {
+        return Vec::new();
+    };
+    let provider = &capabilities["signatureHelpProvider"];
+    ["triggerCharacters", "retriggerCharacters"]
+        .iter()
+        .flat_map(|key| provider[key].as_array().cloned().unwrap_or_default())
+        .filter_map(|c| c.as_str()?.chars().next())
+        .collect()
+}
+
+pub fn signature_help_store() -> std::cell::Ref<'static, SignatureHelpInteractionsStore> {
+    SignatureHelpInteractionsStore::instance()
+}
@@ -1,3 +1,6 @@
+use crate::core::plugins::lsp::protocol::Severity;
+use crate::core::ui::highlighter::SyntaxPalette;
+use crate::core::ui::ui_kit::GutterColors;
 use crate::core::ui::ui_kit::style::Style;
 use eframe::egui;
 use std::rc::Rc;
@@ -29,6 +32,23 @@ store! {
         warning: egui::Color32 = egui::Color32::from_rgb(255, 152, 0),
         success: egui::Color32 = egui::Color32::from_rgb(76, 175, 80),
         info: egui::Color32 = egui::Color32::from_rgb(33, 150, 243),
+
+        syntax_keyword: egui::Color32 = egui::Color32::from_rgb(86, 156, 214),
+        syntax_ident: egui::Color32 = egui::Color32::from_rgb(156, 220, 254),
+        syntax_string: egui::Color32 = egui::Color32::from_rgb(206, 145, 120),
+        syntax_char: egui::Color32 = egui::Color32::from_rgb(215, 186, 125),
+        syntax_number: egui::Color32 = egui::Color32::from_rgb(181, 206, 168),
+        syntax_comment: egui::Color32 = egui::Color32::from_rgb(106, 153, 85),
+        syntax_doc_comment: egui::Color32 = egui::Color32::from_rgb(87, 166, 74),
+        syntax_lifetime: egui::Color32 = egui::Color32::from_rgb(78, 201, 176),
+        syntax_punct: egui::Color32 = egui::Color32::from_rgb(212, 212, 212),
+        syntax_operator: egui::Color32 = egui::Color32::from_rgb(212, 212, 212),
+        syntax_type: egui::Color32 = egui::Color32::from_rgb(78, 201, 176),
+        syntax_function: egui::Color32 = egui::Color32::from_rgb(220, 220, 170),
+        syntax_macro: egui::Color32 = egui::Color32::from_rgb(79, 193, 255),
+
+        search_match: egui::Color32 = egui::Color32::from_rgba_unmultiplied(234, 92, 0, 60),
+        search_match_current: egui::Color32 = egui::Color32::from_rgba_unmultiplied(234, 92, 0, 140),
     }
 }
 
@@ -54,6 +74,48 @@ impl ThemeInteractionsStore {
     pub fn text_primary_style(&self, ctx: &egui::Context) -> Rc<Style> {
         Rc::new(Style::new().color(self.bg_hover.get(ctx)))
     }
+
+    // SYNTAX
+    pub fn syntax_palette(&self, ctx: &egui::Context) -> SyntaxPalette {
+        SyntaxPalette {
+            plain: self.text_primary.get(ctx),
+            keyword: self.syntax_keyword.get(ctx),
+            ident: self.syntax_ident.get(ctx),
+            string: self.syntax_string.get(ctx),
+            char: self.syntax_char.get(ctx),
+            number: self.syntax_number.get(ctx),
+            comment: self.syntax_comment.get(ctx),
+            doc_comment: self.syntax_doc_comment.get(ctx),
+            lifetime: self.syntax_lifetime.get(ctx),
+            punct: self.syntax_punct.get(ctx),
+            operator: self.syntax_operator.get(ctx),
+            type_name: self.syntax_type.get(ctx),
+            function: self.syntax_function.get(ctx),
+            macro_name: self.syntax_macro.get(ctx),
+        }
+    }
+
+    // GUTTER
+    pub fn gutter_colors(&self, ctx: &egui::Context) -> GutterColors {
+        GutterColors {
+            number: self.text_tertiary.get(ctx),
+            current_number: self.text_primary.get(ctx),
+            current_line: self.bg_main_300.get(ctx),
+            breakpoint: self.error.get(ctx),
+            added: self.success.get(ctx),
+            modified: self.info.get(ctx),
+            deleted: self.error.get(ctx),
+        }
+    }
+
+    // DIAGNOSTICS
+    pub fn severity_color(&self, ctx: &egui::Context, severity: Severity) -> egui::Color32 {
+        match severity {
+            Severity::Error => self.error.get(ctx),
+            Severity::Warning => self.warning.get(ctx),
+            Severity::Information | Severity::Hint => self.info.get(ctx),
+        }
+    }
 }
 
 pub fn theme_store() -> std::cell::Ref<'static, ThemeInteractionsStore> {
@@ -0,0 +1,5 @@
+pub mod workspace_symbols_interactions;
+
+pub use workspace_symbols_interactions::{
+    WorkspaceSymbolsInteractionsStore, workspace_symbols_store,
+};
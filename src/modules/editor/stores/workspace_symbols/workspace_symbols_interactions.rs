@@ -0,0 +1,221 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use eframe::egui;
+
+use crate::core::buffer::TextBuffer;
+
+use crate::core::plugins::lsp::manager::is_cancelled;
+use crate::core::plugins::lsp::symbols::{
+    WorkspaceSymbol, parse_workspace_symbols, workspace_symbol_params, workspace_symbol_support,
+};
+use crate::core::plugins::lsp::{RequestHandle, ServerKey, ServerStatus, lsp_manager};
+use crate::modules::editor::stores::editor_interactions_store;
+use crate::store;
+
+/// More matches than anyone scrolls through; the rest is dropped.
+const MAX_RESULTS: usize = 500;
+
+/// The query in flight and what the servers answered so far.
+#[derive(Default)]
+pub struct SymbolSearch {
+    query: String,
+    requests: Vec<(ServerKey, RequestHandle)>,
+    pub results: Vec<WorkspaceSymbol>,
+}
+
+impl SymbolSearch {
+    fn cancel(&mut self) {
+        for (server, request) in self.requests.drain(..) {
+            lsp_manager().cancel_on(&server, request);
+        }
+    }
+
+    fn extend(&mut self, value: &serde_json::Value) {
+        let room = MAX_RESULTS.saturating_sub(self.results.len());
+        self.results
+            .extend(parse_workspace_symbols(value).into_iter().take(room));
+    }
+}
+
+store! {
+    pub struct WorkspaceSymbolsInteractionsStore {
+        open: bool = false,
+        input: Rc<RefCell<TextBuffer>> = Rc::new(RefCell::new(TextBuffer::new())),
+        // select the input on the next frame
+        focus: bool = false,
+        search: SymbolSearch = SymbolSearch::default(),
+        selected: usize = 0,
+        // the selection moved; the list scrolls to it
+        scroll_to_selected: bool = false,
+    }
+
+    // Cmd+T opens the overlay; the arrows, Enter and Escape drive it
+    handle_keys(&self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::T)) {
+            if *self.open.borrow() {
+                self.close(ctx);
+            } else {
+                let mut reactive = self.reactive(ctx);
+                *reactive.open() = true;
+                *reactive.focus() = true;
+            }
+            return;
+        }
+        if !*self.open.borrow() {
+            return;
+        }
+
+        let consume = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        if consume(egui::Key::ArrowDown) {
+            self.move_selection(ctx, 1);
+        } else if consume(egui::Key::ArrowUp) {
+            self.move_selection(ctx, -1);
+        } else if consume(egui::Key::Enter) {
+            let selected = *self.selected.borrow();
+            self.open_result(ctx, selected);
+        } else if consume(egui::Key::Escape) {
+            self.close(ctx);
+        }
+    }
+
+    // sends the query whenever the input changed and collects the matches as
+    // they stream in; called once per frame
+    poll(&self, ctx: &egui::Context) {
+        if !*self.open.borrow() {
+            return;
+        }
+        let query = self.input.borrow().borrow().to_string().trim().to_string();
+
+        if self.search.borrow().query != query {
+            let mut reactive = self.reactive(ctx);
+            let mut search = reactive.search();
+            search.cancel();
+            *search = SymbolSearch {
+                query: query.clone(),
+                ..SymbolSearch::default()
+            };
+            if !query.is_empty() {
+                search.requests =
+                    lsp_manager().request_all(ctx, workspace_symbol_support, |client| {
+                        let partial_results = client.partial_results();
+                        let params = workspace_symbol_params(&query, partial_results.token());
+                        client
+                            .request("workspace/symbol", params)
+                            .with_partial_results(partial_results)
+                    });
+            }
+            drop(search);
+            *reactive.selected() = 0;
+            *reactive.scroll_to_selected() = true;
+            return;
+        }
+
+        // the panel renders after this, so no repaint is needed for what
+        // arrived
+        let mut search = self.search.borrow_mut();
+        let mut index = 0;
+        while index < search.requests.len() {
+            // the response first: what was streamed before it is in by then
+            let request = &mut search.requests[index].1;
+            let response = request.try_take();
+            let streamed = request.take_partial_results();
+            for value in streamed {
+                search.extend(&value);
+            }
+            let Some(response) = response else {
+                index += 1;
+                continue;
+            };
+            search.requests.remove(index);
+            match response {
+                // empty when everything was streamed already
+                Ok(result) => search.extend(&result),
+                Err(e) => {
+                    if !is_cancelled(&e) {
+                        eprintln!("Workspace symbols failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    // opens the file of result `index` at the symbol and closes the overlay
+    open_result(&self, ctx: &egui::Context, index: usize) {
+        let Some(symbol) = self.search.borrow().results.get(index).cloned() else {
+            return;
+        };
+        self.close(ctx);
+        let editor = editor_interactions_store();
+        match symbol.range {
+            Some(range) => editor.reveal_range(ctx, symbol.path, range),
+            None => editor.open_tab(ctx, symbol.path),
+        }
+    }
+
+    close(&self, ctx: &egui::Context) {
+        let mut reactive = self.reactive(ctx);
+        reactive.search().cancel();
+        *reactive.search() = SymbolSearch::default();
+        *reactive.open() = false;
+        reactive.input().borrow_mut().set_text("");
+    }
+}
+
+impl WorkspaceSymbolsInteractionsStore {
+    /// The input and whether it should take focus, while the overlay is open.
+    pub fn get_input(&self) -> Option<(Rc<RefCell<TextBuffer>>, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let focus = std::mem::take(&mut *self.focus.borrow_mut());
+        Some((self.input.borrow().clone(), focus))
+    }
+
+    /// Matches so far, the selected one and whether to scroll to it; `None`
+    /// while the overlay is closed.
+    pub fn get_results(&self) -> Option<(Vec<WorkspaceSymbol>, usize, bool)> {
+        if !*self.open.borrow() {
+            return None;
+        }
+        let scroll = std::mem::take(&mut *self.scroll_to_selected.borrow_mut());
+        Some((
+            self.search.borrow().results.clone(),
+            *self.selected.borrow(),
+            scroll,
+        ))
+    }
+
+    /// Whether a server is still looking for matches.
+    pub fn is_searching(&self) -> bool {
+        !self.search.borrow().requests.is_empty()
+    }
+
+    /// Whether a language server runs to ask at all.
+    pub fn has_server(&self) -> bool {
+        lsp_manager()
+            .servers()
+            .iter()
+            .any(|server| server.status == ServerStatus::Running)
+    }
+
+    /// The query the results are for.
+    pub fn get_query(&self) -> String {
+        self.search.borrow().query.clone()
+    }
+
+    fn move_selection(&self, ctx: &egui::Context, delta: isize) {
+        let count = self.search.borrow().results.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let mut reactive = self.reactive(ctx);
+        let selected = *reactive.selected() as isize;
+        *reactive.selected() = (selected + delta).rem_euclid(count) as usize;
+        *reactive.scroll_to_selected() = true;
+    }
+}
+
+pub fn workspace_symbols_store() -> std::cell::Ref<'static, WorkspaceSymbolsInteractionsStore> {
+    WorkspaceSymbolsInteractionsStore::instance()
+}
@@ -0,0 +1,912 @@
+//! Drives `LspClient` against the `mock_lsp` binary.
+
+#![allow(dead_code)]
+
+#[path = "../src/core/plugins/lsp/transport.rs"]
+mod transport;
+
+#[path = "../src/core/plugins/lsp/lsp.rs"]
+mod lsp;
+
+#[path = "../src/core/plugins/lsp/protocol.rs"]
+mod protocol;
+
+#[path = "../src/core/plugins/lsp/completion.rs"]
+mod completion;
+
+#[path = "../src/core/plugins/lsp/hover.rs"]
+mod hover;
+
+#[path = "../src/core/plugins/lsp/rename.rs"]
+mod rename;
+
+#[path = "../src/core/plugins/lsp/code_action.rs"]
+mod code_action;
+
+#[path = "../src/core/plugins/lsp/formatting.rs"]
+mod formatting;
+
+#[path = "../src/core/plugins/lsp/inlay_hints.rs"]
+mod inlay_hints;
+
+#[path = "../src/core/plugins/lsp/semantic_tokens.rs"]
+mod semantic_tokens;
+
+#[path = "../src/core/plugins/lsp/symbols.rs"]
+mod symbols;
+
+#[path = "../src/core/search/fuzzy.rs"]
+mod fuzzy;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use code_action::{CodeAction, code_action_params, execute_command_params, parse_code_actions};
+use completion::{apply_completion, filter_items, parse_completion_response, request_completion};
+use formatting::{
+    formatting_options, formatting_params, parse_text_edits, range_formatting_params,
+};
+use hover::{parse_hover, parse_signature_help};
+use inlay_hints::{InlayHintKind, inlay_hint_params, parse_inlay_hints};
+use lsp::{LspClient, ResponseError, ServerMessage};
+use protocol::{
+    apply_text_edits, offset_to_position, offsets_to_range, parse_locations, parse_workspace_edit,
+    path_to_uri, range_to_offsets, text_document_position,
+};
+use rename::{PrepareRename, parse_prepare_rename, rename_params};
+use semantic_tokens::{
+    apply_semantic_tokens, decode_semantic_tokens, semantic_tokens_params, semantic_tokens_support,
+};
+use symbols::{
+    SymbolKind, WorkspaceSymbol, document_symbol_params, document_symbol_support,
+    parse_document_symbols, parse_workspace_symbols, workspace_symbol_params,
+    workspace_symbol_support,
+};
+
+const MOCK_LSP: &str = env!("CARGO_BIN_EXE_mock_lsp");
+
+fn run<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn start() -> (LspClient, UnboundedReceiver<ServerMessage>) {
+    LspClient::start(MOCK_LSP, &[]).await.unwrap()
+}
+
+async fn next_message(messages: &mut UnboundedReceiver<ServerMessage>) -> ServerMessage {
+    tokio::time::timeout(Duration::from_secs(5), messages.recv())
+        .await
+        .expect("timed out waiting for the server")
+        .expect("server closed the connection")
+}
+
+#[test]
+fn test_initialize_handshake() {
+    run(async {
+        let (client, _messages) = start().await;
+
+        let result = client
+            .request(
+                "initialize",
+                json!({ "processId": null, "capabilities": {} }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["serverInfo"]["name"], "mock-lsp");
+        client.notify("initialized", json!({})).unwrap();
+
+        assert_eq!(
+            client.request("shutdown", json!(null)).await.unwrap(),
+            json!(null)
+        );
+        client.notify("exit", json!(null)).unwrap();
+    });
+}
+
+#[test]
+fn test_responses_are_matched_by_id() {
+    run(async {
+        let (client, _messages) = start().await;
+
+        let first = client.request("mock/split", json!("first"));
+        let second = client.request("mock/echo", json!({ "n": 2 }));
+        let third = client.request("mock/echo", json!([3]));
+        assert_ne!(first.id(), second.id());
+
+        // await out of order
+        assert_eq!(third.await.unwrap(), json!([3]));
+        assert_eq!(first.await.unwrap(), json!("first"));
+        assert_eq!(second.await.unwrap(), json!({ "n": 2 }));
+    });
+}
+
+#[test]
+fn test_error_responses() {
+    run(async {
+        let (client, _messages) = start().await;
+
+        let error = client.request("mock/error", json!(null)).await.unwrap_err();
+        assert!(error.to_string().contains("mock failure"));
+
+        let error = client
+            .request("no/such/method", json!(null))
+            .await
+            .unwrap_err();
+        let error = error.downcast::<lsp::ResponseError>().unwrap();
+        assert_eq!(error.code, lsp::ResponseError::METHOD_NOT_FOUND);
+    });
+}
+
+#[test]
+fn test_notifications_are_dispatched() {
+    run(async {
+        let (client, mut messages) = start().await;
+
+        client
+            .request("mock/notify", json!({ "a": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            ServerMessage::Notification {
+                method: "mock/notification".to_string(),
+                params: json!({ "a": 1 }),
+            }
+        );
+
+        // notification and response arriving in the same read
+        assert_eq!(
+            client.request("mock/batch", json!(2)).await.unwrap(),
+            json!(2)
+        );
+        assert_eq!(
+            next_message(&mut messages).await,
+            ServerMessage::Notification {
+                method: "mock/notification".to_string(),
+                params: json!(2),
+            }
+        );
+    });
+}
+
+#[test]
+fn test_server_requests_can_be_answered() {
+    run(async {
+        let (client, mut messages) = start().await;
+
+        let ask = client.request("mock/ask", json!("ready?"));
+        let ServerMessage::Request { id, method, params } = next_message(&mut messages).await
+        else {
+            panic!("expected a request from the server");
+        };
+        assert_eq!(method, "mock/question");
+        assert_eq!(params, json!("ready?"));
+
+        client.respond(id, Ok(json!("yes"))).unwrap();
+        assert_eq!(ask.await.unwrap(), json!("yes"));
+    });
+}
+
+#[test]
+fn test_pending_requests_fail_when_server_exits() {
+    run(async {
+        let (client, _messages) = start().await;
+
+        client.notify("exit", json!(null)).unwrap();
+        let error = client.request("mock/echo", json!(1)).await.unwrap_err();
+        assert!(error.to_string().contains("closed"));
+        assert!(!client.is_running());
+    });
+}
+
+#[test]
+fn test_completion_end_to_end() {
+    run(async {
+        let (client, _messages) = start().await;
+        let result = client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        assert_eq!(
+            result["capabilities"]["completionProvider"]["triggerCharacters"],
+            json!(["."])
+        );
+        client.notify("initialized", json!({})).unwrap();
+
+        let uri = "file:///tmp/main.rs";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": "fn main() {\n    pr\n}\n" } }),
+            )
+            .unwrap();
+        // type `i` after `pr`
+        client
+            .notify(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [{
+                        "range": { "start": { "line": 1, "character": 6 }, "end": { "line": 1, "character": 6 } },
+                        "text": "i"
+                    }]
+                }),
+            )
+            .unwrap();
+
+        let text = "fn main() {\n    pri\n}\n";
+        let document = client
+            .request("mock/document", json!({ "uri": uri }))
+            .await
+            .unwrap();
+        assert_eq!(document, json!(text));
+
+        let cursor = text.find("pri").unwrap() + 3;
+        let word = cursor - 3..cursor;
+        let response = request_completion(&client, uri, offset_to_position(text, cursor), None)
+            .await
+            .unwrap();
+        let list = parse_completion_response(&response);
+
+        let score = |query: &str, text: &str| Some(fuzzy::fuzzy_match(query, text)?.score);
+        let labels: Vec<&str> = filter_items(&list.items, "pri", score)
+            .into_iter()
+            .map(|i| list.items[i].label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["print!", "println!"]);
+
+        // snippet: the placeholder is selected first, `$0` comes last
+        let println = list.items.iter().find(|i| i.label == "println!").unwrap();
+        let applied = apply_completion(text, println, word.clone(), cursor);
+        assert_eq!(applied.text, "fn main() {\n    println!(\"text\")\n}\n");
+        assert_eq!(&applied.text[applied.tabstops[0].clone()], "text");
+        assert_eq!(
+            applied.tabstops[1].start,
+            applied.text.find(")\n}").unwrap() + 1
+        );
+
+        // additional edits shift the completion
+        let hash_map = list.items.iter().find(|i| i.label == "HashMap").unwrap();
+        let applied = apply_completion(text, hash_map, word, cursor);
+        assert_eq!(
+            applied.text,
+            "use std::collections::HashMap;\nfn main() {\n    HashMap\n}\n"
+        );
+        let end = applied.text.find("HashMap\n}").unwrap() + "HashMap".len();
+        assert_eq!(applied.tabstops, vec![end..end]);
+
+        let response =
+            request_completion(&client, uri, offset_to_position(text, cursor), Some("."))
+                .await
+                .unwrap();
+        let list = parse_completion_response(&response);
+        assert_eq!(list.items[0].label, "len");
+    });
+}
+
+#[test]
+fn test_definition_and_references() {
+    run(async {
+        let (client, _messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        let path = std::path::Path::new("/tmp/riff-mock/lib.rs");
+        let uri = path_to_uri(path);
+        let text = "fn helper() {}\n\nfn main() {\n    helper();\n    helper();\n}\n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let call = text.find("helper();").unwrap() + 2;
+        let params = text_document_position(&uri, offset_to_position(text, call));
+
+        let result = client
+            .request("textDocument/definition", params.clone())
+            .await
+            .unwrap();
+        let definitions = parse_locations(&result);
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].path, path);
+        // the link's selection range is the name, not the whole item
+        assert_eq!(range_to_offsets(text, definitions[0].range), 3..9);
+
+        let result = client
+            .request("textDocument/references", params)
+            .await
+            .unwrap();
+        let references: Vec<_> = parse_locations(&result)
+            .into_iter()
+            .map(|location| &text[range_to_offsets(text, location.range)])
+            .collect();
+        assert_eq!(references, vec!["helper"; 3]);
+
+        let nowhere = text_document_position(&uri, offset_to_position(text, 1));
+        let result = client
+            .request("textDocument/definition", nowhere)
+            .await
+            .unwrap();
+        assert!(parse_locations(&result).is_empty());
+    });
+}
+
+#[test]
+fn test_hover_and_signature_help() {
+    run(async {
+        let (client, _messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        let uri = "file:///tmp/main.rs";
+        let text = "fn main() {\n    helper(1, \n}\n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let on_helper = text_document_position(
+            uri,
+            offset_to_position(text, text.find("helper").unwrap() + 1),
+        );
+        let result = client
+            .request("textDocument/hover", on_helper)
+            .await
+            .unwrap();
+        let contents = parse_hover(&result).unwrap();
+        assert!(contents.starts_with("```rust\nfn helper()"));
+
+        let on_space =
+            text_document_position(uri, offset_to_position(text, text.find("  ").unwrap() + 1));
+        let result = client
+            .request("textDocument/hover", on_space)
+            .await
+            .unwrap();
+        assert_eq!(parse_hover(&result), None);
+
+        // first argument: the label is given as UTF-16 offsets
+        let in_first =
+            text_document_position(uri, offset_to_position(text, text.find("1,").unwrap()));
+        let result = client
+            .request("textDocument/signatureHelp", in_first)
+            .await
+            .unwrap();
+        let help = parse_signature_help(&result).unwrap();
+        assert_eq!(&help.label[help.active_parameter.unwrap()], "a: i32");
+
+        // second argument: the label is given as a substring
+        let in_second = text_document_position(
+            uri,
+            offset_to_position(text, text.find(", \n").unwrap() + 2),
+        );
+        let result = client
+            .request("textDocument/signatureHelp", in_second)
+            .await
+            .unwrap();
+        let help = parse_signature_help(&result).unwrap();
+        assert_eq!(&help.label[help.active_parameter.unwrap()], "b: &str");
+        assert_eq!(help.documentation.as_deref(), Some("Helps."));
+
+        let outside = text_document_position(uri, offset_to_position(text, 3));
+        let result = client
+            .request("textDocument/signatureHelp", outside)
+            .await
+            .unwrap();
+        assert_eq!(parse_signature_help(&result), None);
+    });
+}
+
+#[test]
+fn test_cancel_request() {
+    run(async {
+        let (client, _messages) = start().await;
+
+        let slow = client.request("mock/slow", json!({}));
+        let id = slow.id();
+        client.cancel(id).unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), slow)
+            .await
+            .expect("cancelled request still pending")
+            .unwrap_err();
+        let error = error.downcast_ref::<ResponseError>().unwrap();
+        assert_eq!(error.code, ResponseError::REQUEST_CANCELLED);
+
+        // the server saw the notification, and its late answer was dropped
+        let cancelled = client.request("mock/cancelled", json!({})).await.unwrap();
+        assert_eq!(cancelled, json!([id]));
+        let echo = client
+            .request("mock/echo", json!({ "still": "alive" }))
+            .await
+            .unwrap();
+        assert_eq!(echo["still"], "alive");
+    });
+}
+
+#[test]
+fn test_prepare_rename_and_rename() {
+    run(async {
+        let (client, _messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        let main = (
+            "file:///tmp/main.rs",
+            "fn main() {\n    let total = count(1);\n}\n",
+        );
+        let lib = (
+            "file:///tmp/lib.rs",
+            "fn count(n: i32) -> i32 {\n    n\n}\n// counter\n",
+        );
+        for (uri, text) in [main, lib] {
+            client
+                .notify(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+                )
+                .unwrap();
+        }
+
+        let (uri, text) = main;
+        let on_count = offset_to_position(text, text.find("count").unwrap() + 2);
+        let result = client
+            .request(
+                "textDocument/prepareRename",
+                text_document_position(uri, on_count),
+            )
+            .await
+            .unwrap();
+        match parse_prepare_rename(&result) {
+            Some(PrepareRename::Range { range, placeholder }) => {
+                assert_eq!(&text[range_to_offsets(text, range)], "count");
+                assert_eq!(placeholder.as_deref(), Some("count"));
+            }
+            other => panic!("unexpected prepareRename result {:?}", other),
+        }
+
+        let on_keyword = offset_to_position(text, 1);
+        let result = client
+            .request(
+                "textDocument/prepareRename",
+                text_document_position(uri, on_keyword),
+            )
+            .await
+            .unwrap();
+        assert_eq!(parse_prepare_rename(&result), None);
+
+        let result = client
+            .request("textDocument/rename", rename_params(uri, on_count, "tally"))
+            .await
+            .unwrap();
+        let mut changes = parse_workspace_edit(&result);
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        let renamed: Vec<(PathBuf, String)> = changes
+            .into_iter()
+            .map(|(path, edits)| {
+                let text = if path.ends_with("main.rs") {
+                    main.1
+                } else {
+                    lib.1
+                };
+                (path, apply_text_edits(text, &edits))
+            })
+            .collect();
+        assert_eq!(
+            renamed,
+            vec![
+                (
+                    PathBuf::from("/tmp/lib.rs"),
+                    "fn tally(n: i32) -> i32 {\n    n\n}\n// counter\n".to_string()
+                ),
+                (
+                    PathBuf::from("/tmp/main.rs"),
+                    "fn main() {\n    let total = tally(1);\n}\n".to_string()
+                ),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_code_actions_resolve_and_execute_command() {
+    run(async {
+        let (client, mut messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        let uri = "file:///tmp/main.rs";
+        let text = "fn main() {\n    let unused = 1;\n}\n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let start = text.find("unused").unwrap();
+        let range = offsets_to_range(text, start..start + "unused".len());
+        let diagnostic = json!({ "range": range, "severity": 2, "message": "unused variable" });
+        let result = client
+            .request(
+                "textDocument/codeAction",
+                code_action_params(uri, range, vec![diagnostic]),
+            )
+            .await
+            .unwrap();
+        let actions = parse_code_actions(&result);
+        let titles: Vec<&str> = actions.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Remove `unused`", "Uppercase `unused`", "Insert TODO"]
+        );
+
+        // the preferred fix comes with its edit
+        let changes = parse_workspace_edit(actions[0].edit.as_ref().unwrap());
+        assert_eq!(
+            apply_text_edits(text, &changes[0].1),
+            "fn main() {\n    let  = 1;\n}\n"
+        );
+
+        // the other one has to be resolved first
+        assert!(actions[1].needs_resolve());
+        let result = client
+            .request("codeAction/resolve", actions[1].raw.clone())
+            .await
+            .unwrap();
+        let resolved = CodeAction::from_value(&result).unwrap();
+        let changes = parse_workspace_edit(resolved.edit.as_ref().unwrap());
+        assert_eq!(
+            apply_text_edits(text, &changes[0].1),
+            "fn main() {\n    let UNUSED = 1;\n}\n"
+        );
+
+        // the command edits through `workspace/applyEdit` before answering
+        let command = actions[2].command.as_ref().unwrap();
+        let execute = client.request("workspace/executeCommand", execute_command_params(command));
+        let ServerMessage::Request { id, method, params } = next_message(&mut messages).await
+        else {
+            panic!("expected a request from the server");
+        };
+        assert_eq!(method, "workspace/applyEdit");
+        let changes = parse_workspace_edit(&params["edit"]);
+        assert_eq!(changes[0].0, PathBuf::from("/tmp/main.rs"));
+        assert_eq!(
+            apply_text_edits(text, &changes[0].1),
+            format!("// TODO\n{}", text)
+        );
+
+        client.respond(id, Ok(json!({ "applied": true }))).unwrap();
+        assert_eq!(execute.await.unwrap(), json!(true));
+    });
+}
+
+#[test]
+fn test_formatting_and_range_formatting() {
+    run(async {
+        let (client, _messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        let uri = "file:///tmp/main.rs";
+        let text = "fn main() {  \n    let a = 1;\t\n}  \n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let result = client
+            .request(
+                "textDocument/formatting",
+                formatting_params(uri, formatting_options(4, true)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply_text_edits(text, &parse_text_edits(&result)),
+            "fn main() {\n    let a = 1;\n}\n"
+        );
+
+        // only the second line
+        let start = text.find("    let").unwrap();
+        let range = offsets_to_range(text, start..start + 4);
+        let result = client
+            .request(
+                "textDocument/rangeFormatting",
+                range_formatting_params(uri, range, formatting_options(4, true)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            apply_text_edits(text, &parse_text_edits(&result)),
+            "fn main() {  \n    let a = 1;\n}  \n"
+        );
+    });
+}
+
+#[test]
+fn test_inlay_hints_and_semantic_tokens() {
+    run(async {
+        let (client, _messages) = start().await;
+        let capabilities = client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap()["capabilities"]
+            .clone();
+        client.notify("initialized", json!({})).unwrap();
+
+        let uri = "file:///tmp/main.rs";
+        let text = "fn main() {\n    let a = 1;\n    let b = a;\n}\n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let result = client
+            .request(
+                "textDocument/inlayHint",
+                inlay_hint_params(uri, offsets_to_range(text, 0..text.len())),
+            )
+            .await
+            .unwrap();
+        let hints = parse_inlay_hints(&result);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].label, ": i32");
+        assert_eq!(hints[0].kind, Some(InlayHintKind::Type));
+        assert_eq!(
+            hints[0].position,
+            offset_to_position(text, text.find("a =").unwrap() + 1)
+        );
+
+        let (legend, delta) = semantic_tokens_support(&capabilities).unwrap();
+        assert!(delta);
+        let result = client
+            .request(
+                "textDocument/semanticTokens/full",
+                semantic_tokens_params(uri, None),
+            )
+            .await
+            .unwrap();
+        let tokens = apply_semantic_tokens(None, &result).unwrap();
+        let named = |text: &str, data: &[u32]| -> Vec<(String, String)> {
+            decode_semantic_tokens(text, data)
+                .into_iter()
+                .map(|token| {
+                    (
+                        text[token.range].to_string(),
+                        legend.token_type(token.token_type).unwrap().to_string(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(
+            named(text, &tokens.data)[..3],
+            [
+                ("fn".to_string(), "keyword".to_string()),
+                ("main".to_string(), "function".to_string()),
+                ("let".to_string(), "keyword".to_string()),
+            ]
+        );
+
+        // the delta applies to the previous result
+        let changed = "fn main() {\n    let a = 1;\n    let bb = a;\n}\n";
+        client
+            .notify(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [{ "text": changed }]
+                }),
+            )
+            .unwrap();
+        let result = client
+            .request(
+                "textDocument/semanticTokens/full/delta",
+                semantic_tokens_params(uri, tokens.result_id.as_deref()),
+            )
+            .await
+            .unwrap();
+        assert!(result["edits"].is_array());
+        let updated = apply_semantic_tokens(Some(&tokens), &result).unwrap();
+        assert_ne!(updated.result_id, tokens.result_id);
+        let words: Vec<String> = named(changed, &updated.data)
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect();
+        assert_eq!(words, ["fn", "main", "let", "a", "let", "bb", "a"]);
+    });
+}
+
+#[test]
+fn test_document_symbols() {
+    run(async {
+        let (client, _messages) = start().await;
+        let capabilities = client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap()["capabilities"]
+            .clone();
+        client.notify("initialized", json!({})).unwrap();
+        assert!(document_symbol_support(&capabilities));
+
+        let uri = "file:///tmp/main.rs";
+        let text = "fn helper() {}\n\nfn main() {\n    let a = 1;\n    let b = a;\n}\n";
+        client
+            .notify(
+                "textDocument/didOpen",
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+            )
+            .unwrap();
+
+        let result = client
+            .request("textDocument/documentSymbol", document_symbol_params(uri))
+            .await
+            .unwrap();
+        let symbols = parse_document_symbols(&result);
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["helper", "main"]);
+        assert_eq!(symbols[1].kind, SymbolKind::Function);
+        assert_eq!(
+            &text[range_to_offsets(text, symbols[1].range)],
+            "fn main() {\n    let a = 1;\n    let b = a;\n}"
+        );
+        let bindings: Vec<(&str, SymbolKind)> = symbols[1]
+            .children
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            bindings,
+            [("a", SymbolKind::Variable), ("b", SymbolKind::Variable)]
+        );
+        assert_eq!(
+            &text[range_to_offsets(text, symbols[1].children[1].selection_range)],
+            "b"
+        );
+    });
+}
+
+#[test]
+fn test_workspace_symbols_stream_as_partial_results() {
+    run(async {
+        let (client, mut messages) = start().await;
+        let capabilities = client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap()["capabilities"]
+            .clone();
+        client.notify("initialized", json!({})).unwrap();
+        assert!(workspace_symbol_support(&capabilities));
+
+        for (uri, text) in [
+            ("file:///tmp/lib.rs", "fn parse_args() {}\nfn run() {}\n"),
+            ("file:///tmp/main.rs", "fn main() { run(); }\nfn parse_line() {}\n"),
+        ] {
+            client
+                .notify(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+                )
+                .unwrap();
+        }
+
+        // every match arrives on its own before the (empty) response, the
+        // last one right before it; none of them as a server message
+        let mut partial_results = client.partial_results();
+        let params = workspace_symbol_params("PARSE", partial_results.token());
+        let response = client.request("workspace/symbol", params);
+        assert_eq!(response.await.unwrap(), json!([]));
+        let streamed: Vec<WorkspaceSymbol> = partial_results
+            .take()
+            .iter()
+            .flat_map(parse_workspace_symbols)
+            .collect();
+        assert!(messages.try_recv().is_err());
+
+        // a token that is no longer collected falls back to the messages
+        drop(partial_results);
+        let response = client.request("workspace/symbol", workspace_symbol_params("run", "t1"));
+        assert_eq!(response.await.unwrap(), json!([]));
+        match next_message(&mut messages).await {
+            ServerMessage::Notification { method, params } => {
+                assert_eq!(method, "$/progress");
+                assert_eq!(params["token"], "t1");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let found: Vec<(&str, Option<&str>, Option<&str>)> = streamed
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.container.as_deref(),
+                    symbol.path.file_name().and_then(|name| name.to_str()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("parse_args", Some("lib"), Some("lib.rs")),
+                ("parse_line", Some("main"), Some("main.rs")),
+            ]
+        );
+        assert_eq!(streamed[1].kind, SymbolKind::Function);
+        assert_eq!(streamed[1].range.unwrap().start.line, 1);
+    });
+}
+
+#[test]
+fn test_shutdown_and_exit() {
+    run(async {
+        let (client, _messages) = start().await;
+        client
+            .request("initialize", json!({ "capabilities": {} }))
+            .await
+            .unwrap();
+        client.notify("initialized", json!({})).unwrap();
+
+        // answers `shutdown`, then exits on `exit` before the timeout
+        client.shutdown(Duration::from_secs(5)).await.unwrap();
+    });
+}
+
+#[test]
+fn test_spawn_with_env_and_crash() {
+    run(async {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let mut command = Command::new(MOCK_LSP);
+        command.env("MOCK_GREETING", "hello").current_dir(&dir);
+        let (client, _messages) = LspClient::spawn(command).await.unwrap();
+
+        let env = client
+            .request("mock/env", json!({ "name": "MOCK_GREETING" }))
+            .await
+            .unwrap();
+        assert_eq!(env["value"], "hello");
+        assert_eq!(env["cwd"], json!(dir));
+
+        assert!(client.is_running());
+        let crashed = client.request("mock/crash", json!({})).await;
+        assert!(crashed.is_err());
+        // the reader notices the closed stdout right after failing the request
+        for _ in 0..100 {
+            if !client.is_running() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!client.is_running());
+    });
+}